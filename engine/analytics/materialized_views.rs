@@ -1,34 +1,81 @@
-use crate::execution::tuple::Tuple;
-use crate::language::ast::Statement;
+use crate::execution::engine::ExecutionEngine;
+use crate::execution::tuple::{Tuple, Value};
+use crate::language::ast::{BinaryOperator, Expression, Literal, Statement, TableReference, UnaryOperator};
+use crate::language::catalog::Catalog;
+use crate::planner::logical::LogicalPlanner;
+use crate::planner::physical::PhysicalPlanner;
+use crate::storage::StorageBackend;
 use anyhow::Result;
 use std::collections::HashMap;
 use std::sync::Arc;
 use tokio::sync::RwLock;
 
+/// A single base-table row change to propagate into dependent views.
+/// `sign = 1` is an insert, `sign = -1` is a delete; an update is modeled
+/// as a delete of the old row followed by an insert of the new one.
+#[derive(Debug, Clone)]
+pub struct TupleDelta {
+    pub tuple: Tuple,
+    pub sign: i8,
+}
+
+impl TupleDelta {
+    pub fn insert(tuple: Tuple) -> Self {
+        Self { tuple, sign: 1 }
+    }
+
+    pub fn delete(tuple: Tuple) -> Self {
+        Self { tuple, sign: -1 }
+    }
+}
+
+/// Per-group membership for incremental aggregate maintenance. Rather than
+/// maintaining running sums/mins/maxes directly (which can't be undone
+/// when a delta removes the current min/max), we keep the live set of base
+/// rows belonging to the group and recompute that group's aggregates from
+/// it — still proportional to the group's size, not the base table's.
+struct GroupAccumulator {
+    key_values: Vec<Value>,
+    members: Vec<Tuple>,
+}
+
 pub struct MaterializedView {
     pub name: String,
     pub query: Statement,
+    /// Base tables this view reads from, walked out of `query` at creation
+    /// time so a change feed can route deltas to the right views without
+    /// re-inspecting the query on every write.
+    pub base_tables: Vec<String>,
     pub data: Vec<Tuple>,
     pub last_refresh: std::time::SystemTime,
+    group_state: HashMap<Vec<String>, GroupAccumulator>,
 }
 
 pub struct MaterializedViewManager {
     views: Arc<RwLock<HashMap<String, MaterializedView>>>,
+    storage: Arc<dyn StorageBackend>,
+    catalog: Arc<RwLock<Catalog>>,
 }
 
 impl MaterializedViewManager {
-    pub fn new() -> Self {
+    pub fn new(storage: Arc<dyn StorageBackend>, catalog: Arc<RwLock<Catalog>>) -> Self {
         Self {
             views: Arc::new(RwLock::new(HashMap::new())),
+            storage,
+            catalog,
         }
     }
 
     pub async fn create_view(&self, name: String, query: Statement) -> Result<()> {
+        let base_tables = Self::base_tables_of(&query);
+
         let view = MaterializedView {
             name: name.clone(),
             query,
+            base_tables,
             data: Vec::new(),
             last_refresh: std::time::SystemTime::now(),
+            group_state: HashMap::new(),
         };
 
         let mut views = self.views.write().await;
@@ -36,35 +83,96 @@ impl MaterializedViewManager {
         Ok(())
     }
 
+    /// Fully recomputes `name` by planning and executing its stored query
+    /// through the normal query pipeline, replacing `data` wholesale.
     pub async fn refresh_view(&self, name: &str) -> Result<()> {
+        let statement = {
+            let views = self.views.read().await;
+            let view = views
+                .get(name)
+                .ok_or_else(|| anyhow::anyhow!("Materialized view not found: {}", name))?;
+            view.query.clone()
+        };
+
+        let logical_planner = LogicalPlanner::new(self.catalog.read().await.clone());
+        let logical_plan = logical_planner.plan(&statement)?;
+
+        let physical_planner = PhysicalPlanner::new(self.storage.as_ref(), self.catalog.read().await.clone());
+        let physical_plan = physical_planner.plan(&logical_plan).await?;
+
+        let mut execution_engine = ExecutionEngine::new(self.storage.as_ref(), self.catalog.clone());
+        let rows = execution_engine.execute(physical_plan).await?;
+
         let mut views = self.views.write().await;
-        
-        if let Some(view) = views.get_mut(name) {
+        let view = views
+            .get_mut(name)
+            .ok_or_else(|| anyhow::anyhow!("Materialized view not found: {}", name))?;
+        view.data = rows;
+        view.group_state.clear();
+        view.last_refresh = std::time::SystemTime::now();
+
+        Ok(())
+    }
+
+    /// Propagates a batch of `table`'s row changes into every view that
+    /// depends on it, without rescanning any base table: a filter just
+    /// tests the predicate on each delta, a projection maps it, and a
+    /// `GROUP BY` view updates only the touched group's membership.
+    pub async fn apply_deltas(&self, table: &str, deltas: Vec<TupleDelta>) -> Result<()> {
+        let mut views = self.views.write().await;
+
+        for view in views.values_mut() {
+            if !view.base_tables.iter().any(|t| t == table) {
+                continue;
+            }
+
+            let retrieve = match &view.query {
+                Statement::Retrieve(retrieve) => retrieve.clone(),
+                // Incremental maintenance only understands SELECT-shaped
+                // views; anything else needs a full `refresh_view`.
+                _ => continue,
+            };
+
+            for delta in &deltas {
+                if let Some(filter) = &retrieve.filter {
+                    if !Self::eval_predicate(filter, &delta.tuple)? {
+                        continue;
+                    }
+                }
+
+                if retrieve.group_by.is_empty() {
+                    Self::apply_delta_to_rows(&mut view.data, &retrieve.projection, delta)?;
+                } else {
+                    Self::apply_delta_to_group(
+                        &mut view.group_state,
+                        &mut view.data,
+                        &retrieve.group_by,
+                        &retrieve.projection,
+                        delta,
+                    )?;
+                }
+            }
+
             view.last_refresh = std::time::SystemTime::now();
-            Ok(())
-        } else {
-            anyhow::bail!("Materialized view not found: {}", name)
         }
+
+        Ok(())
     }
 
     pub async fn query_view(&self, name: &str) -> Result<Vec<Tuple>> {
         let views = self.views.read().await;
-        
-        if let Some(view) = views.get(name) {
-            Ok(view.data.clone())
-        } else {
-            anyhow::bail!("Materialized view not found: {}", name)
-        }
+        let view = views
+            .get(name)
+            .ok_or_else(|| anyhow::anyhow!("Materialized view not found: {}", name))?;
+        Ok(view.data.clone())
     }
 
     pub async fn drop_view(&self, name: &str) -> Result<()> {
         let mut views = self.views.write().await;
-        
-        if views.remove(name).is_some() {
-            Ok(())
-        } else {
-            anyhow::bail!("Materialized view not found: {}", name)
-        }
+        views
+            .remove(name)
+            .ok_or_else(|| anyhow::anyhow!("Materialized view not found: {}", name))?;
+        Ok(())
     }
 
     pub async fn list_views(&self) -> Vec<String> {
@@ -73,18 +181,318 @@ impl MaterializedViewManager {
     }
 
     pub async fn auto_refresh_loop(self: Arc<Self>) {
-        let mut interval = tokio::time::interval(tokio::time::Duration::from_secs(300));
+        let mut interval = tokio::time::interval(std::time::Duration::from_secs(300));
 
         loop {
             interval.tick().await;
 
-            let view_names = self.list_views().await;
-            
-            for name in view_names {
+            for name in self.list_views().await {
                 if let Err(e) = self.refresh_view(&name).await {
                     tracing::error!("Failed to refresh view {}: {}", name, e);
                 }
             }
         }
     }
+
+    fn base_tables_of(statement: &Statement) -> Vec<String> {
+        match statement {
+            Statement::Retrieve(retrieve) => {
+                let mut tables = vec![Self::table_name(&retrieve.from)];
+                tables.extend(retrieve.joins.iter().map(|join| Self::table_name(&join.table)));
+                tables
+            }
+            _ => Vec::new(),
+        }
+    }
+
+    fn table_name(reference: &TableReference) -> String {
+        match reference {
+            TableReference::Table(name) => name.clone(),
+            TableReference::Alias { table, .. } => table.clone(),
+        }
+    }
+
+    fn apply_delta_to_rows(rows: &mut Vec<Tuple>, projection: &[Expression], delta: &TupleDelta) -> Result<()> {
+        let projected = Self::project(projection, &delta.tuple)?;
+
+        match delta.sign {
+            1 => rows.push(projected),
+            -1 => {
+                if let Some(pos) = rows.iter().position(|row| Self::tuple_eq(row, &projected)) {
+                    rows.remove(pos);
+                }
+            }
+            _ => {}
+        }
+
+        Ok(())
+    }
+
+    fn apply_delta_to_group(
+        group_state: &mut HashMap<Vec<String>, GroupAccumulator>,
+        data: &mut Vec<Tuple>,
+        group_by: &[Expression],
+        projection: &[Expression],
+        delta: &TupleDelta,
+    ) -> Result<()> {
+        let key_values: Vec<Value> = group_by
+            .iter()
+            .map(|expr| Self::eval_expr(expr, &delta.tuple))
+            .collect::<Result<_>>()?;
+        let key: Vec<String> = key_values.iter().map(Self::tag_value).collect();
+
+        match delta.sign {
+            1 => {
+                let accumulator = group_state.entry(key).or_insert_with(|| GroupAccumulator {
+                    key_values,
+                    members: Vec::new(),
+                });
+                accumulator.members.push(delta.tuple.clone());
+            }
+            -1 => {
+                if let Some(accumulator) = group_state.get_mut(&key) {
+                    if let Some(pos) = accumulator.members.iter().position(|m| Self::tuple_eq(m, &delta.tuple)) {
+                        accumulator.members.remove(pos);
+                    }
+                    if accumulator.members.is_empty() {
+                        group_state.remove(&key);
+                    }
+                }
+            }
+            _ => {}
+        }
+
+        *data = group_state
+            .values()
+            .map(|accumulator| Self::finalize_group(group_by, &accumulator.key_values, projection, &accumulator.members))
+            .collect::<Result<Vec<_>>>()?;
+
+        Ok(())
+    }
+
+    fn finalize_group(
+        group_by: &[Expression],
+        key_values: &[Value],
+        projection: &[Expression],
+        members: &[Tuple],
+    ) -> Result<Tuple> {
+        let mut output = Tuple::new();
+
+        for (idx, expr) in projection.iter().enumerate() {
+            let value = match expr {
+                Expression::FunctionCall { name, args } => Self::eval_aggregate(name, args, members)?,
+                _ => match group_by.iter().position(|g| Self::expr_key(g) == Self::expr_key(expr)) {
+                    Some(pos) => key_values[pos].clone(),
+                    None => match members.first() {
+                        Some(member) => Self::eval_expr(expr, member)?,
+                        None => Value::Null,
+                    },
+                },
+            };
+
+            output.insert(Self::column_name_for(expr, idx), value);
+        }
+
+        Ok(output)
+    }
+
+    fn eval_aggregate(name: &str, args: &[Expression], members: &[Tuple]) -> Result<Value> {
+        let function = name.to_lowercase();
+
+        if function == "count" && matches!(args.first(), None | Some(Expression::Star)) {
+            return Ok(Value::Integer(members.len() as i64));
+        }
+
+        let arg = args
+            .first()
+            .ok_or_else(|| anyhow::anyhow!("{} requires an argument", function))?;
+        let values = members
+            .iter()
+            .map(|member| Self::eval_expr(arg, member))
+            .collect::<Result<Vec<_>>>()?;
+        let non_null: Vec<&Value> = values.iter().filter(|v| !v.is_null()).collect();
+
+        Ok(match function.as_str() {
+            "count" => Value::Integer(non_null.len() as i64),
+            "sum" => Self::sum_values(&non_null),
+            "avg" => {
+                if non_null.is_empty() {
+                    Value::Null
+                } else {
+                    let count = non_null.len() as f64;
+                    match Self::sum_values(&non_null) {
+                        Value::Integer(i) => Value::Float(i as f64 / count),
+                        Value::Float(f) => Value::Float(f / count),
+                        other => other,
+                    }
+                }
+            }
+            "min" => non_null
+                .into_iter()
+                .cloned()
+                .reduce(|a, b| if Self::value_lt(&b, &a) { b } else { a })
+                .unwrap_or(Value::Null),
+            "max" => non_null
+                .into_iter()
+                .cloned()
+                .reduce(|a, b| if Self::value_lt(&a, &b) { b } else { a })
+                .unwrap_or(Value::Null),
+            other => anyhow::bail!("unsupported aggregate function in materialized view: {}", other),
+        })
+    }
+
+    fn project(projection: &[Expression], tuple: &Tuple) -> Result<Tuple> {
+        let mut output = Tuple::new();
+
+        for (idx, expr) in projection.iter().enumerate() {
+            if matches!(expr, Expression::Star) {
+                for (column, value) in &tuple.values {
+                    output.insert(column.clone(), value.clone());
+                }
+                continue;
+            }
+
+            let value = Self::eval_expr(expr, tuple)?;
+            output.insert(Self::column_name_for(expr, idx), value);
+        }
+
+        Ok(output)
+    }
+
+    fn column_name_for(expr: &Expression, idx: usize) -> String {
+        match expr {
+            Expression::Column(name) => name.clone(),
+            Expression::QualifiedColumn { column, .. } => column.clone(),
+            _ => format!("col_{}", idx),
+        }
+    }
+
+    fn eval_predicate(expr: &Expression, tuple: &Tuple) -> Result<bool> {
+        match Self::eval_expr(expr, tuple)? {
+            Value::Boolean(b) => Ok(b),
+            Value::Null => Ok(false),
+            other => anyhow::bail!("filter expression did not evaluate to a boolean: {:?}", other),
+        }
+    }
+
+    fn eval_expr(expr: &Expression, tuple: &Tuple) -> Result<Value> {
+        match expr {
+            Expression::Column(name) => Ok(tuple.get(name).cloned().unwrap_or(Value::Null)),
+            Expression::QualifiedColumn { column, .. } => Ok(tuple.get(column).cloned().unwrap_or(Value::Null)),
+            Expression::Literal(lit) => Ok(Self::literal_value(lit)),
+            Expression::BinaryOp { op, left, right } => {
+                let left = Self::eval_expr(left, tuple)?;
+                let right = Self::eval_expr(right, tuple)?;
+                Self::eval_binary_op(op, &left, &right)
+            }
+            Expression::UnaryOp { op, operand } => {
+                let value = Self::eval_expr(operand, tuple)?;
+                Self::eval_unary_op(op, &value)
+            }
+            Expression::FunctionCall { .. } => {
+                anyhow::bail!("aggregate functions are only valid in a GROUP BY view's projection")
+            }
+            Expression::Cast { .. } => anyhow::bail!("CAST is not supported in incremental view maintenance"),
+            Expression::Placeholder(_) => anyhow::bail!("materialized view queries cannot reference placeholders"),
+            Expression::Star => anyhow::bail!("`*` is not a scalar expression"),
+        }
+    }
+
+    fn eval_binary_op(op: &BinaryOperator, left: &Value, right: &Value) -> Result<Value> {
+        Ok(match op {
+            BinaryOperator::Add => Self::numeric_op(left, right, |a, b| a + b, |a, b| a + b),
+            BinaryOperator::Subtract => Self::numeric_op(left, right, |a, b| a - b, |a, b| a - b),
+            BinaryOperator::Multiply => Self::numeric_op(left, right, |a, b| a * b, |a, b| a * b),
+            BinaryOperator::Divide => Self::numeric_op(left, right, |a, b| a / b, |a, b| a / b),
+            BinaryOperator::Equals => Value::Boolean(Self::tag_value(left) == Self::tag_value(right)),
+            BinaryOperator::NotEquals => Value::Boolean(Self::tag_value(left) != Self::tag_value(right)),
+            BinaryOperator::LessThan => Value::Boolean(Self::value_lt(left, right)),
+            BinaryOperator::LessThanOrEqual => Value::Boolean(!Self::value_lt(right, left)),
+            BinaryOperator::GreaterThan => Value::Boolean(Self::value_lt(right, left)),
+            BinaryOperator::GreaterThanOrEqual => Value::Boolean(!Self::value_lt(left, right)),
+            BinaryOperator::And => Value::Boolean(Self::truthy(left)? && Self::truthy(right)?),
+            BinaryOperator::Or => Value::Boolean(Self::truthy(left)? || Self::truthy(right)?),
+        })
+    }
+
+    fn eval_unary_op(op: &UnaryOperator, value: &Value) -> Result<Value> {
+        match op {
+            UnaryOperator::Not => Ok(Value::Boolean(!Self::truthy(value)?)),
+            UnaryOperator::Negate => Ok(match value {
+                Value::Integer(i) => Value::Integer(-i),
+                Value::Float(f) => Value::Float(-f),
+                other => anyhow::bail!("cannot negate {:?}", other),
+            }),
+        }
+    }
+
+    fn truthy(value: &Value) -> Result<bool> {
+        match value {
+            Value::Boolean(b) => Ok(*b),
+            Value::Null => Ok(false),
+            other => anyhow::bail!("expected a boolean, found {:?}", other),
+        }
+    }
+
+    fn literal_value(literal: &Literal) -> Value {
+        match literal {
+            Literal::Null => Value::Null,
+            Literal::Boolean(b) => Value::Boolean(*b),
+            Literal::Integer(i) => Value::Integer(*i),
+            Literal::Float(f) => Value::Float(*f),
+            Literal::String(s) => Value::String(s.clone()),
+        }
+    }
+
+    fn numeric_op(left: &Value, right: &Value, int_op: impl Fn(i64, i64) -> i64, float_op: impl Fn(f64, f64) -> f64) -> Value {
+        match (left, right) {
+            (Value::Integer(a), Value::Integer(b)) => Value::Integer(int_op(*a, *b)),
+            (Value::Integer(a), Value::Float(b)) => Value::Float(float_op(*a as f64, *b)),
+            (Value::Float(a), Value::Integer(b)) => Value::Float(float_op(*a, *b as f64)),
+            (Value::Float(a), Value::Float(b)) => Value::Float(float_op(*a, *b)),
+            _ => Value::Null,
+        }
+    }
+
+    fn sum_values(values: &[&Value]) -> Value {
+        values
+            .iter()
+            .fold(Value::Integer(0), |acc, v| Self::numeric_op(&acc, v, |a, b| a + b, |a, b| a + b))
+    }
+
+    fn value_lt(a: &Value, b: &Value) -> bool {
+        match (a, b) {
+            (Value::Integer(x), Value::Integer(y)) => x < y,
+            (Value::Float(x), Value::Float(y)) => x < y,
+            (Value::Integer(x), Value::Float(y)) => (*x as f64) < *y,
+            (Value::Float(x), Value::Integer(y)) => *x < (*y as f64),
+            (Value::String(x), Value::String(y)) => x < y,
+            (Value::Boolean(x), Value::Boolean(y)) => !x & y,
+            _ => false,
+        }
+    }
+
+    /// Tags a value with its variant so e.g. the integer `1` and the
+    /// string `"1"` never collide when used as (part of) a group key.
+    fn tag_value(value: &Value) -> String {
+        match value {
+            Value::Null => "null:".to_string(),
+            Value::Boolean(b) => format!("bool:{}", b),
+            Value::Integer(i) => format!("int:{}", i),
+            Value::Float(f) => format!("float:{}", f),
+            Value::String(s) => format!("str:{}", s),
+        }
+    }
+
+    fn tuple_eq(a: &Tuple, b: &Tuple) -> bool {
+        let mut a_pairs: Vec<(String, String)> = a.values.iter().map(|(k, v)| (k.clone(), Self::tag_value(v))).collect();
+        let mut b_pairs: Vec<(String, String)> = b.values.iter().map(|(k, v)| (k.clone(), Self::tag_value(v))).collect();
+        a_pairs.sort();
+        b_pairs.sort();
+        a_pairs == b_pairs
+    }
+
+    fn expr_key(expr: &Expression) -> String {
+        format!("{:?}", expr)
+    }
 }
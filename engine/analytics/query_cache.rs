@@ -1,102 +1,290 @@
 use crate::execution::tuple::Tuple;
 use anyhow::Result;
+use std::collections::hash_map::DefaultHasher;
 use std::collections::HashMap;
+use std::hash::{Hash, Hasher};
 use std::sync::Arc;
 use std::time::{Duration, SystemTime};
 use tokio::sync::RwLock;
 
-#[derive(Clone)]
+/// Shard count for `QueryCache`. Each shard has its own lock and its own
+/// recency list, so two queries that hash to different shards never
+/// contend — the same reasoning `TransactionManager` applies by using a
+/// `DashMap` instead of one global lock.
+const SHARD_COUNT: usize = 16;
+
+/// A node in a shard's intrusive recency list, stored in `CacheState::nodes`.
+/// The list threads through the slab via `prev`/`next` indices rather than a
+/// pointer-based structure, so a shard stays behind one lock with no unsafe
+/// code; `key` lets an eviction at the tail find the shard's `HashMap` entry
+/// to remove without the map having to store the reverse mapping too.
+struct Node {
+    key: String,
+    prev: Option<usize>,
+    next: Option<usize>,
+}
+
 struct CacheEntry {
     results: Vec<Tuple>,
     created_at: SystemTime,
-    access_count: u64,
+    node: usize,
+    /// The table names this query's plan touched, so `invalidate_table` can
+    /// drop exactly the entries that read a table instead of guessing from
+    /// the query text.
+    tables: Vec<String>,
 }
 
-pub struct QueryCache {
-    cache: Arc<RwLock<HashMap<String, CacheEntry>>>,
+/// One shard's `HashMap` plus its intrusive doubly linked recency list over
+/// a `Vec` slab: `head` is the most recently used entry, `tail` the least.
+/// Freed slots go on `free` so a long-running shard doesn't grow its slab
+/// forever under repeated eviction.
+struct CacheState {
+    entries: HashMap<String, CacheEntry>,
+    nodes: Vec<Node>,
+    free: Vec<usize>,
+    head: Option<usize>,
+    tail: Option<usize>,
+    hits: u64,
+    misses: u64,
+}
+
+impl CacheState {
+    fn new() -> Self {
+        Self {
+            entries: HashMap::new(),
+            nodes: Vec::new(),
+            free: Vec::new(),
+            head: None,
+            tail: None,
+            hits: 0,
+            misses: 0,
+        }
+    }
+
+    /// Unlinks `index` from the recency list without freeing its slot —
+    /// used both to evict and to pull a node out before re-splicing it to
+    /// the head on a fresh access.
+    fn unlink(&mut self, index: usize) {
+        let (prev, next) = (self.nodes[index].prev, self.nodes[index].next);
+
+        match prev {
+            Some(prev) => self.nodes[prev].next = next,
+            None => self.head = next,
+        }
+
+        match next {
+            Some(next) => self.nodes[next].prev = prev,
+            None => self.tail = prev,
+        }
+
+        self.nodes[index].prev = None;
+        self.nodes[index].next = None;
+    }
+
+    fn push_front(&mut self, index: usize) {
+        self.nodes[index].prev = None;
+        self.nodes[index].next = self.head;
+
+        if let Some(head) = self.head {
+            self.nodes[head].prev = Some(index);
+        }
+
+        self.head = Some(index);
+        if self.tail.is_none() {
+            self.tail = Some(index);
+        }
+    }
+
+    /// Moves an already-linked node to the head, marking it most recently
+    /// used.
+    fn touch(&mut self, index: usize) {
+        if self.head == Some(index) {
+            return;
+        }
+
+        self.unlink(index);
+        self.push_front(index);
+    }
+
+    /// Allocates a slab slot for `key`, reusing a freed one where possible,
+    /// and links it at the head.
+    fn alloc_front(&mut self, key: String) -> usize {
+        let index = match self.free.pop() {
+            Some(index) => {
+                self.nodes[index] = Node {
+                    key,
+                    prev: None,
+                    next: None,
+                };
+                index
+            }
+            None => {
+                self.nodes.push(Node {
+                    key,
+                    prev: None,
+                    next: None,
+                });
+                self.nodes.len() - 1
+            }
+        };
+
+        self.push_front(index);
+        index
+    }
+
+    /// Removes the least recently used entry, both from the recency list and
+    /// the lookup map.
+    fn evict_lru(&mut self) {
+        if let Some(tail) = self.tail {
+            let key = self.nodes[tail].key.clone();
+            self.unlink(tail);
+            self.free.push(tail);
+            self.entries.remove(&key);
+        }
+    }
+
+    fn remove(&mut self, key: &str) {
+        if let Some(entry) = self.entries.remove(key) {
+            self.unlink(entry.node);
+            self.free.push(entry.node);
+        }
+    }
+}
+
+/// One independent slice of the cache: its own lock, its own recency list,
+/// and its own share of `max_size` (split evenly across `SHARD_COUNT`
+/// shards), so contention on one shard never blocks lookups against another.
+struct Shard {
+    state: RwLock<CacheState>,
     max_size: usize,
+}
+
+pub struct QueryCache {
+    shards: Vec<Shard>,
     ttl: Duration,
 }
 
 impl QueryCache {
     pub fn new(max_size: usize, ttl: Duration) -> Self {
-        Self {
-            cache: Arc::new(RwLock::new(HashMap::new())),
-            max_size,
-            ttl,
-        }
+        let per_shard_max = (max_size / SHARD_COUNT).max(1);
+        let shards = (0..SHARD_COUNT)
+            .map(|_| Shard {
+                state: RwLock::new(CacheState::new()),
+                max_size: per_shard_max,
+            })
+            .collect();
+
+        Self { shards, ttl }
+    }
+
+    fn shard_for(&self, query: &str) -> &Shard {
+        let mut hasher = DefaultHasher::new();
+        query.hash(&mut hasher);
+        &self.shards[(hasher.finish() as usize) % self.shards.len()]
     }
 
     pub async fn get(&self, query: &str) -> Option<Vec<Tuple>> {
-        let mut cache = self.cache.write().await;
+        let shard = self.shard_for(query);
+        let mut state = shard.state.write().await;
 
-        if let Some(entry) = cache.get_mut(query) {
-            let age = SystemTime::now().duration_since(entry.created_at).ok()?;
+        let node = match state.entries.get(query) {
+            Some(entry) => {
+                let age = SystemTime::now().duration_since(entry.created_at).ok()?;
+                if age >= self.ttl {
+                    None
+                } else {
+                    Some(entry.node)
+                }
+            }
+            None => None,
+        };
 
-            if age < self.ttl {
-                entry.access_count += 1;
-                return Some(entry.results.clone());
-            } else {
-                cache.remove(query);
+        match node {
+            Some(node) => {
+                state.touch(node);
+                state.hits += 1;
+                Some(state.entries.get(query).unwrap().results.clone())
+            }
+            None => {
+                state.remove(query);
+                state.misses += 1;
+                None
             }
         }
-
-        None
     }
 
-    pub async fn put(&self, query: String, results: Vec<Tuple>) -> Result<()> {
-        let mut cache = self.cache.write().await;
+    /// Caches `results` for `query`, recording `tables` (the table names its
+    /// plan read) so `invalidate_table` can find it later.
+    pub async fn put(&self, query: String, results: Vec<Tuple>, tables: Vec<String>) -> Result<()> {
+        let shard = self.shard_for(&query);
+        let mut state = shard.state.write().await;
 
-        if cache.len() >= self.max_size {
-            self.evict_lru(&mut cache);
+        state.remove(&query);
+
+        if state.entries.len() >= shard.max_size {
+            state.evict_lru();
         }
 
-        cache.insert(
+        let node = state.alloc_front(query.clone());
+        state.entries.insert(
             query,
             CacheEntry {
                 results,
                 created_at: SystemTime::now(),
-                access_count: 0,
+                node,
+                tables,
             },
         );
 
         Ok(())
     }
 
-    pub async fn invalidate(&self, pattern: &str) -> Result<()> {
-        let mut cache = self.cache.write().await;
+    /// Drops every cached query whose recorded `tables` includes `table`,
+    /// instead of the old substring match against query text (which could
+    /// drop unrelated queries that merely mentioned the pattern).
+    pub async fn invalidate_table(&self, table: &str) -> Result<()> {
+        for shard in &self.shards {
+            let mut state = shard.state.write().await;
+
+            let matching: Vec<String> = state
+                .entries
+                .iter()
+                .filter(|(_, entry)| entry.tables.iter().any(|t| t == table))
+                .map(|(key, _)| key.clone())
+                .collect();
 
-        cache.retain(|key, _| !key.contains(pattern));
+            for key in matching {
+                state.remove(&key);
+            }
+        }
 
         Ok(())
     }
 
     pub async fn clear(&self) {
-        let mut cache = self.cache.write().await;
-        cache.clear();
+        for shard in &self.shards {
+            let mut state = shard.state.write().await;
+            *state = CacheState::new();
+        }
     }
 
     pub async fn stats(&self) -> CacheStats {
-        let cache = self.cache.read().await;
-
-        let total_entries = cache.len();
-        let total_accesses: u64 = cache.values().map(|e| e.access_count).sum();
+        let mut stats = CacheStats {
+            entries: 0,
+            max_size: 0,
+            hits: 0,
+            misses: 0,
+        };
 
-        CacheStats {
-            entries: total_entries,
-            total_accesses,
-            max_size: self.max_size,
+        for shard in &self.shards {
+            let state = shard.state.read().await;
+            stats.entries += state.entries.len();
+            stats.max_size += shard.max_size;
+            stats.hits += state.hits;
+            stats.misses += state.misses;
         }
-    }
 
-    fn evict_lru(&self, cache: &mut HashMap<String, CacheEntry>) {
-        if let Some(lru_key) = cache
-            .iter()
-            .min_by_key(|(_, entry)| entry.access_count)
-            .map(|(k, _)| k.clone())
-        {
-            cache.remove(&lru_key);
-        }
+        stats
     }
 
     pub async fn cleanup_loop(self: Arc<Self>) {
@@ -104,15 +292,26 @@ impl QueryCache {
 
         loop {
             interval.tick().await;
-
-            let mut cache = self.cache.write().await;
             let now = SystemTime::now();
 
-            cache.retain(|_, entry| {
-                now.duration_since(entry.created_at)
-                    .map(|age| age < self.ttl)
-                    .unwrap_or(false)
-            });
+            for shard in &self.shards {
+                let mut state = shard.state.write().await;
+
+                let expired: Vec<String> = state
+                    .entries
+                    .iter()
+                    .filter(|(_, entry)| {
+                        now.duration_since(entry.created_at)
+                            .map(|age| age >= self.ttl)
+                            .unwrap_or(true)
+                    })
+                    .map(|(key, _)| key.clone())
+                    .collect();
+
+                for key in expired {
+                    state.remove(&key);
+                }
+            }
         }
     }
 }
@@ -120,6 +319,20 @@ impl QueryCache {
 #[derive(Debug, Clone)]
 pub struct CacheStats {
     pub entries: usize,
-    pub total_accesses: u64,
     pub max_size: usize,
+    pub hits: u64,
+    pub misses: u64,
+}
+
+impl CacheStats {
+    /// The fraction of lookups that hit, in `[0.0, 1.0]`; `0.0` (rather than
+    /// `NaN`) when the cache has never been queried.
+    pub fn hit_ratio(&self) -> f64 {
+        let total = self.hits + self.misses;
+        if total == 0 {
+            0.0
+        } else {
+            self.hits as f64 / total as f64
+        }
+    }
 }
@@ -1,41 +1,166 @@
 use crate::execution::tuple::{Tuple, Value};
 use anyhow::Result;
+use std::collections::HashMap;
 
 const VECTOR_SIZE: usize = 1024;
 
+/// One field's storage in a `VectorBatch`: a dense value array plus a
+/// parallel validity bitmap, so a null is a bit flip rather than a
+/// `Value::Null` tag every reader has to branch on, and a whole column can
+/// be scanned contiguously instead of hopping between `Tuple`s.
+struct Column {
+    values: Vec<Value>,
+    validity: Vec<bool>,
+}
+
+impl Column {
+    fn with_capacity(capacity: usize) -> Self {
+        Self {
+            values: Vec::with_capacity(capacity),
+            validity: Vec::with_capacity(capacity),
+        }
+    }
+
+    fn push(&mut self, value: Option<&Value>) {
+        match value {
+            Some(Value::Null) | None => {
+                self.values.push(Value::Null);
+                self.validity.push(false);
+            }
+            Some(value) => {
+                self.values.push(value.clone());
+                self.validity.push(true);
+            }
+        }
+    }
+
+    fn clear(&mut self) {
+        self.values.clear();
+        self.validity.clear();
+    }
+}
+
+/// A columnar batch of up to `VECTOR_SIZE` rows: one `Column` per field
+/// instead of `Tuple`'s row-oriented `HashMap<String, Value>`.
+/// `column_order` pins field order (a `HashMap`'s iteration order isn't
+/// stable), so two batches built from the same schema expose the same
+/// column at the same position.
 pub struct VectorBatch {
-    tuples: Vec<Tuple>,
+    column_order: Vec<String>,
+    columns: HashMap<String, Column>,
+    num_rows: usize,
 }
 
 impl VectorBatch {
-    pub fn new() -> Self {
+    pub fn new(column_order: Vec<String>) -> Self {
+        let columns = column_order
+            .iter()
+            .map(|name| (name.clone(), Column::with_capacity(VECTOR_SIZE)))
+            .collect();
+
         Self {
-            tuples: Vec::with_capacity(VECTOR_SIZE),
+            column_order,
+            columns,
+            num_rows: 0,
         }
     }
 
-    pub fn add(&mut self, tuple: Tuple) -> bool {
-        if self.tuples.len() >= VECTOR_SIZE {
+    pub fn add(&mut self, tuple: &Tuple) -> bool {
+        if self.is_full() {
             return false;
         }
-        self.tuples.push(tuple);
+
+        for name in &self.column_order {
+            let column = self.columns.get_mut(name).expect("column_order and columns stay in sync");
+            column.push(tuple.get(name));
+        }
+
+        self.num_rows += 1;
         true
     }
 
     pub fn is_full(&self) -> bool {
-        self.tuples.len() >= VECTOR_SIZE
+        self.num_rows >= VECTOR_SIZE
     }
 
     pub fn len(&self) -> usize {
-        self.tuples.len()
+        self.num_rows
     }
 
-    pub fn iter(&self) -> impl Iterator<Item = &Tuple> {
-        self.tuples.iter()
+    pub fn column_names(&self) -> &[String] {
+        &self.column_order
+    }
+
+    /// The full `0..len()` selection vector — every row, in order. The
+    /// starting point a fresh batch hands to `filter_batch`.
+    pub fn full_selection(&self) -> Vec<u32> {
+        (0..self.num_rows as u32).collect()
     }
 
     pub fn clear(&mut self) {
-        self.tuples.clear();
+        for column in self.columns.values_mut() {
+            column.clear();
+        }
+        self.num_rows = 0;
+    }
+
+    /// Reads `column`'s value at `row`, or `None` if the column doesn't
+    /// exist or the row's validity bit is unset.
+    pub fn get_value(&self, column: &str, row: usize) -> Option<&Value> {
+        let column = self.columns.get(column)?;
+        if *column.validity.get(row)? {
+            column.values.get(row)
+        } else {
+            None
+        }
+    }
+
+    fn row_tuple(&self, row: usize) -> Tuple {
+        let mut tuple = Tuple::new();
+        for name in &self.column_order {
+            if let Some(value) = self.get_value(name, row) {
+                tuple.insert(name.clone(), value.clone());
+            }
+        }
+        tuple
+    }
+
+    /// Builds a batch from row-oriented tuples — the bridge that lets the
+    /// existing row-based executor feed a `VectorizedExecutor`. The column
+    /// order is taken from the first tuple; an empty `tuples` produces an
+    /// empty, column-less batch.
+    pub fn from_tuples(tuples: &[Tuple]) -> Self {
+        let column_order = tuples.first().map(|t| t.columns()).unwrap_or_default();
+        let mut batch = Self::new(column_order);
+
+        for tuple in tuples {
+            if !batch.add(tuple) {
+                break;
+            }
+        }
+
+        batch
+    }
+
+    /// Drains rows back out as `Tuple`s, honoring `selection` if given (only
+    /// those row indices, in that order) instead of every row in the batch.
+    pub fn to_tuples(&self, selection: Option<&[u32]>) -> Vec<Tuple> {
+        match selection {
+            Some(selection) => selection.iter().map(|&row| self.row_tuple(row as usize)).collect(),
+            None => (0..self.num_rows).map(|row| self.row_tuple(row)).collect(),
+        }
+    }
+}
+
+/// A join key rendered through the same ad hoc `Debug`-string convention
+/// `execution::operators::join`'s `composite_key` uses, since `Value` holds
+/// a `Float` and so can't derive `Eq`/`Hash` itself.
+#[derive(PartialEq, Eq, Hash)]
+struct HashKey(String);
+
+impl From<&Value> for HashKey {
+    fn from(value: &Value) -> Self {
+        HashKey(format!("{:?}", value))
     }
 }
 
@@ -46,55 +171,58 @@ impl VectorizedExecutor {
         Self
     }
 
+    /// Evaluates `predicate` over every row `selection` names (or every row
+    /// in the batch, if `None`) and returns the surviving row indices as a
+    /// new selection vector — no tuple is copied to do this.
     pub fn filter_batch(
         &self,
         batch: &VectorBatch,
-        predicate: impl Fn(&Tuple) -> bool,
-    ) -> VectorBatch {
-        let mut result = VectorBatch::new();
-
-        for tuple in batch.iter() {
-            if predicate(tuple) {
-                result.add(tuple.clone());
-            }
-        }
+        selection: Option<&[u32]>,
+        predicate: impl Fn(&VectorBatch, u32) -> bool,
+    ) -> Vec<u32> {
+        let rows: Box<dyn Iterator<Item = u32>> = match selection {
+            Some(selection) => Box::new(selection.iter().copied()),
+            None => Box::new(0..batch.len() as u32),
+        };
 
-        result
+        rows.filter(|&row| predicate(batch, row)).collect()
     }
 
-    pub fn project_batch(&self, batch: &VectorBatch, columns: &[String]) -> VectorBatch {
-        let mut result = VectorBatch::new();
-
-        for tuple in batch.iter() {
-            let mut projected = Tuple::new();
-            for col in columns {
-                if let Some(val) = tuple.get(col) {
-                    projected.insert(col.clone(), val.clone());
-                }
-            }
-            result.add(projected);
+    /// A zero-copy reordering: `columns` names which fields of `batch` to
+    /// expose and in what order, without cloning a single `Value` — readers
+    /// resolve a column/row through the returned view straight into `batch`.
+    pub fn project_batch<'a>(&self, batch: &'a VectorBatch, selection: &[u32], columns: &[String]) -> ProjectedBatch<'a> {
+        ProjectedBatch {
+            batch,
+            selection: selection.to_vec(),
+            columns: columns.to_vec(),
         }
-
-        result
     }
 
-    pub fn aggregate_batch(&self, batch: &VectorBatch, column: &str) -> Result<Value> {
+    /// Averages `column` over `selection` (or the whole batch), skipping rows
+    /// where the value is absent or the validity bitmap marks it null —
+    /// exactly what SQL's `AVG` does with nulls; `Value::Null` when no row
+    /// contributes a value.
+    pub fn aggregate_batch(&self, batch: &VectorBatch, column: &str, selection: Option<&[u32]>) -> Result<Value> {
+        let rows: Box<dyn Iterator<Item = u32>> = match selection {
+            Some(selection) => Box::new(selection.iter().copied()),
+            None => Box::new(0..batch.len() as u32),
+        };
+
         let mut sum = 0.0;
         let mut count = 0;
 
-        for tuple in batch.iter() {
-            if let Some(value) = tuple.get(column) {
-                match value {
-                    Value::Integer(i) => {
-                        sum += *i as f64;
-                        count += 1;
-                    }
-                    Value::Float(f) => {
-                        sum += f;
-                        count += 1;
-                    }
-                    _ => {}
+        for row in rows {
+            match batch.get_value(column, row as usize) {
+                Some(Value::Integer(i)) => {
+                    sum += *i as f64;
+                    count += 1;
+                }
+                Some(Value::Float(f)) => {
+                    sum += f;
+                    count += 1;
                 }
+                _ => {}
             }
         }
 
@@ -105,42 +233,89 @@ impl VectorizedExecutor {
         }
     }
 
-    pub fn join_batches(
-        &self,
-        left: &VectorBatch,
-        right: &VectorBatch,
-        left_key: &str,
-        right_key: &str,
-    ) -> VectorBatch {
-        let mut result = VectorBatch::new();
-
-        for left_tuple in left.iter() {
-            for right_tuple in right.iter() {
-                if let (Some(left_val), Some(right_val)) =
-                    (left_tuple.get(left_key), right_tuple.get(right_key))
-                {
-                    if self.values_equal(left_val, right_val) {
-                        let mut joined = left_tuple.clone();
-                        for (k, v) in &right_tuple.values {
-                            joined.insert(k.clone(), v.clone());
-                        }
-                        if !result.add(joined) {
-                            break;
-                        }
-                    }
+    /// Hash-joins `left` and `right` on `left_key`/`right_key`: builds a hash
+    /// table over the smaller side's key column, then probes it with the
+    /// larger side's selection vector, which is O(n + m) rather than the
+    /// O(n * m) nested-loop scan this used to do.
+    pub fn join_batches(&self, left: &VectorBatch, right: &VectorBatch, left_key: &str, right_key: &str) -> VectorBatch {
+        let left_is_build = left.len() <= right.len();
+        let (build, probe, build_key, probe_key) = if left_is_build {
+            (left, right, left_key, right_key)
+        } else {
+            (right, left, right_key, left_key)
+        };
+
+        let mut table: HashMap<HashKey, Vec<u32>> = HashMap::new();
+        for row in 0..build.len() as u32 {
+            if let Some(value) = build.get_value(build_key, row as usize) {
+                table.entry(HashKey::from(value)).or_default().push(row);
+            }
+        }
+
+        let mut results = Vec::new();
+
+        for probe_row in probe.full_selection() {
+            let Some(probe_value) = probe.get_value(probe_key, probe_row as usize) else {
+                continue;
+            };
+            let Some(build_rows) = table.get(&HashKey::from(probe_value)) else {
+                continue;
+            };
+
+            for &build_row in build_rows {
+                let (left_row, left_batch, right_row, right_batch) = if left_is_build {
+                    (build_row, build, probe_row, probe)
+                } else {
+                    (probe_row, probe, build_row, build)
+                };
+
+                let mut joined = left_batch.row_tuple(left_row as usize);
+                for (column, value) in right_batch.row_tuple(right_row as usize).values {
+                    joined.insert(column, value);
                 }
+                results.push(joined);
             }
         }
 
-        result
+        VectorBatch::from_tuples(&results)
     }
+}
 
-    fn values_equal(&self, a: &Value, b: &Value) -> bool {
-        match (a, b) {
-            (Value::Integer(x), Value::Integer(y)) => x == y,
-            (Value::String(x), Value::String(y)) => x == y,
-            (Value::Boolean(x), Value::Boolean(y)) => x == y,
-            _ => false,
-        }
+/// A zero-copy, reordered/narrowed view over a `VectorBatch`'s columns:
+/// holds a column subset and a selection vector rather than a copy of the
+/// underlying `Value`s.
+pub struct ProjectedBatch<'a> {
+    batch: &'a VectorBatch,
+    selection: Vec<u32>,
+    columns: Vec<String>,
+}
+
+impl<'a> ProjectedBatch<'a> {
+    pub fn len(&self) -> usize {
+        self.selection.len()
+    }
+
+    pub fn column_names(&self) -> &[String] {
+        &self.columns
+    }
+
+    pub fn get(&self, column: &str, row: usize) -> Option<&Value> {
+        let batch_row = *self.selection.get(row)? as usize;
+        self.batch.get_value(column, batch_row)
+    }
+
+    pub fn to_tuples(&self) -> Vec<Tuple> {
+        self.selection
+            .iter()
+            .map(|&row| {
+                let mut tuple = Tuple::new();
+                for column in &self.columns {
+                    if let Some(value) = self.batch.get_value(column, row as usize) {
+                        tuple.insert(column.clone(), value.clone());
+                    }
+                }
+                tuple
+            })
+            .collect()
     }
 }
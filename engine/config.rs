@@ -1,5 +1,7 @@
+use crate::protocol::auth::AuthProviderConfig;
 use anyhow::{Context, Result};
 use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
 use std::env;
 use std::path::PathBuf;
 
@@ -13,34 +15,185 @@ pub struct Config {
     pub wal_buffer_size: usize,
     pub deterministic: bool,
     pub num_shards: usize,
+    pub auth: AuthProviderConfig,
+    pub admin_metrics_port: u16,
 }
 
 impl Config {
+    /// Builds a `Config` by layering, from lowest to highest precedence:
+    /// hardcoded defaults, `MINSQL_*` environment variable fallbacks, the
+    /// base section of a `--config` TOML file (if given), the `[env.<name>]`
+    /// section named by `--env`/`MINSQL_ENV`, and finally explicit CLI
+    /// flags. This lets operators keep a single versioned config file and
+    /// only pass CLI flags for the handful of values that differ per run.
     pub fn from_args() -> Result<Self> {
         let args: Vec<String> = env::args().collect();
 
-        let mut node_id = 1;
-        let mut data_dir = "./data".to_string();
-        let mut port = 5433;
-        let mut peers = Vec::new();
+        let mut overlay = ConfigOverlay::from_env_vars();
+
+        if let Some(path) = Self::find_flag(&args, "--config") {
+            let file = ConfigFile::from_path(&path)?;
+            overlay.merge(file.base);
+
+            let env_name = Self::find_flag(&args, "--env").or_else(|| env::var("MINSQL_ENV").ok());
+            if let Some(name) = env_name {
+                if let Some(section) = file.env.get(&name) {
+                    overlay.merge(section.clone());
+                } else {
+                    tracing::warn!("config: no [env.{}] section found, ignoring --env", name);
+                }
+            }
+        }
+
+        overlay.merge(ConfigOverlay::from_cli_args(&args)?);
+
+        Ok(overlay.finish())
+    }
+
+    /// Parses a standalone TOML document (no `--env` layering) into a fully
+    /// resolved `Config`, for callers that already know which environment
+    /// they want rather than reading it off argv.
+    pub fn from_file(path: &str) -> Result<Self> {
+        let file = ConfigFile::from_path(path)?;
+        Ok(ConfigOverlay::defaults().merged_with(file.base).finish())
+    }
+
+    fn find_flag(args: &[String], flag: &str) -> Option<String> {
+        args.iter()
+            .position(|a| a == flag)
+            .and_then(|i| args.get(i + 1))
+            .cloned()
+    }
+
+    pub fn data_path(&self) -> PathBuf {
+        PathBuf::from(&self.data_dir)
+    }
+}
+
+/// The TOML document shape: a base table of config keys plus any number of
+/// named `[env.<name>]` override sections.
+#[derive(Debug, Clone, Default, Deserialize)]
+struct ConfigFile {
+    #[serde(flatten)]
+    base: ConfigOverlay,
+    #[serde(default)]
+    env: HashMap<String, ConfigOverlay>,
+}
+
+impl ConfigFile {
+    fn from_path(path: &str) -> Result<Self> {
+        let contents = std::fs::read_to_string(path)
+            .with_context(|| format!("Failed to read config file: {}", path))?;
+        toml::from_str(&contents).with_context(|| format!("Failed to parse config file: {}", path))
+    }
+}
+
+/// Every `Config` field as an `Option`, so a layer can be merged on top of
+/// another without clobbering values it doesn't mention.
+#[derive(Debug, Clone, Default, Deserialize)]
+struct ConfigOverlay {
+    node_id: Option<u32>,
+    data_dir: Option<String>,
+    port: Option<u16>,
+    peers: Option<Vec<String>>,
+    buffer_pool_size: Option<usize>,
+    wal_buffer_size: Option<usize>,
+    deterministic: Option<bool>,
+    num_shards: Option<usize>,
+    auth: Option<AuthProviderConfig>,
+    admin_metrics_port: Option<u16>,
+}
+
+impl ConfigOverlay {
+    fn defaults() -> Self {
+        Self {
+            node_id: Some(1),
+            data_dir: Some("./data".to_string()),
+            port: Some(5433),
+            peers: Some(Vec::new()),
+            buffer_pool_size: Some(1024),
+            wal_buffer_size: Some(65536),
+            deterministic: Some(false),
+            num_shards: Some(16),
+            auth: Some(AuthProviderConfig::default()),
+            admin_metrics_port: Some(9090),
+        }
+    }
+
+    /// `MINSQL_*` variables are the lowest-precedence override above the
+    /// hardcoded defaults, letting deployment tooling set individual keys
+    /// without templating a config file.
+    fn from_env_vars() -> Self {
+        let mut overlay = Self::defaults();
+
+        if let Some(v) = non_empty(env::var("MINSQL_NODE_ID").ok()) {
+            overlay.node_id = v.parse().ok();
+        }
+        if let Some(v) = non_empty(env::var("MINSQL_DATA_DIR").ok()) {
+            overlay.data_dir = Some(v);
+        }
+        if let Some(v) = non_empty(env::var("MINSQL_PORT").ok()) {
+            overlay.port = v.parse().ok();
+        }
+        if let Some(v) = non_empty(env::var("MINSQL_PEERS").ok()) {
+            overlay.peers = Some(split_peers(&v));
+        }
+        if let Some(v) = non_empty(env::var("MINSQL_NUM_SHARDS").ok()) {
+            overlay.num_shards = v.parse().ok();
+        }
+        if let Some(v) = non_empty(env::var("MINSQL_ADMIN_METRICS_PORT").ok()) {
+            overlay.admin_metrics_port = v.parse().ok();
+        }
+
+        overlay
+    }
+
+    fn from_cli_args(args: &[String]) -> Result<Self> {
+        let mut overlay = Self::default();
 
         let mut i = 1;
         while i < args.len() {
             match args[i].as_str() {
                 "--node-id" => {
-                    node_id = args[i + 1].parse().context("Invalid node-id")?;
+                    overlay.node_id = Some(args[i + 1].parse().context("Invalid node-id")?);
                     i += 2;
                 }
                 "--data-dir" => {
-                    data_dir = args[i + 1].clone();
+                    overlay.data_dir = non_empty(Some(args[i + 1].clone()));
                     i += 2;
                 }
                 "--port" => {
-                    port = args[i + 1].parse().context("Invalid port")?;
+                    overlay.port = Some(args[i + 1].parse().context("Invalid port")?);
                     i += 2;
                 }
                 "--peers" => {
-                    peers = args[i + 1].split(',').map(|s| s.to_string()).collect();
+                    overlay.peers = non_empty(Some(args[i + 1].clone())).map(|p| split_peers(&p));
+                    i += 2;
+                }
+                "--buffer-pool-size" => {
+                    overlay.buffer_pool_size = Some(args[i + 1].parse().context("Invalid buffer-pool-size")?);
+                    i += 2;
+                }
+                "--wal-buffer-size" => {
+                    overlay.wal_buffer_size = Some(args[i + 1].parse().context("Invalid wal-buffer-size")?);
+                    i += 2;
+                }
+                "--deterministic" => {
+                    overlay.deterministic = Some(true);
+                    i += 1;
+                }
+                "--num-shards" => {
+                    overlay.num_shards = Some(args[i + 1].parse().context("Invalid num-shards")?);
+                    i += 2;
+                }
+                "--admin-metrics-port" => {
+                    overlay.admin_metrics_port =
+                        Some(args[i + 1].parse().context("Invalid admin-metrics-port")?);
+                    i += 2;
+                }
+                // "--config" and "--env" are consumed by `Config::from_args`
+                // before CLI parsing starts; skip their value here too.
+                "--config" | "--env" => {
                     i += 2;
                 }
                 _ => {
@@ -49,19 +202,74 @@ impl Config {
             }
         }
 
-        Ok(Config {
-            node_id,
-            data_dir,
-            port,
-            peers,
-            buffer_pool_size: 1024,
-            wal_buffer_size: 65536,
-            deterministic: false,
-            num_shards: 16,
-        })
+        Ok(overlay)
     }
 
-    pub fn data_path(&self) -> PathBuf {
-        PathBuf::from(&self.data_dir)
+    /// Overlays `other` on top of `self`, with `other`'s `Some` values
+    /// taking precedence.
+    fn merge(&mut self, other: ConfigOverlay) {
+        if other.node_id.is_some() {
+            self.node_id = other.node_id;
+        }
+        if other.data_dir.is_some() {
+            self.data_dir = other.data_dir;
+        }
+        if other.port.is_some() {
+            self.port = other.port;
+        }
+        if other.peers.is_some() {
+            self.peers = other.peers;
+        }
+        if other.buffer_pool_size.is_some() {
+            self.buffer_pool_size = other.buffer_pool_size;
+        }
+        if other.wal_buffer_size.is_some() {
+            self.wal_buffer_size = other.wal_buffer_size;
+        }
+        if other.deterministic.is_some() {
+            self.deterministic = other.deterministic;
+        }
+        if other.num_shards.is_some() {
+            self.num_shards = other.num_shards;
+        }
+        if other.auth.is_some() {
+            self.auth = other.auth;
+        }
+        if other.admin_metrics_port.is_some() {
+            self.admin_metrics_port = other.admin_metrics_port;
+        }
+    }
+
+    fn merged_with(mut self, other: ConfigOverlay) -> Self {
+        self.merge(other);
+        self
+    }
+
+    fn finish(self) -> Config {
+        let defaults = Self::defaults();
+
+        Config {
+            node_id: self.node_id.or(defaults.node_id).unwrap(),
+            data_dir: self.data_dir.or(defaults.data_dir).unwrap(),
+            port: self.port.or(defaults.port).unwrap(),
+            peers: self.peers.or(defaults.peers).unwrap(),
+            buffer_pool_size: self.buffer_pool_size.or(defaults.buffer_pool_size).unwrap(),
+            wal_buffer_size: self.wal_buffer_size.or(defaults.wal_buffer_size).unwrap(),
+            deterministic: self.deterministic.or(defaults.deterministic).unwrap(),
+            num_shards: self.num_shards.or(defaults.num_shards).unwrap(),
+            auth: self.auth.or(defaults.auth).unwrap(),
+            admin_metrics_port: self.admin_metrics_port.or(defaults.admin_metrics_port).unwrap(),
+        }
     }
 }
+
+/// An empty string is treated the same as an unset value for optional
+/// overrides (e.g. `--data-dir ""` or `MINSQL_PEERS=""`), so tooling that
+/// always passes a flag doesn't have to conditionally omit it.
+fn non_empty(value: Option<String>) -> Option<String> {
+    value.filter(|s| !s.is_empty())
+}
+
+fn split_peers(value: &str) -> Vec<String> {
+    value.split(',').map(|s| s.to_string()).filter(|s| !s.is_empty()).collect()
+}
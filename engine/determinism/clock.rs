@@ -1,11 +1,14 @@
 use serde::{Deserialize, Serialize};
-use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Mutex, MutexGuard};
 use std::time::{SystemTime, UNIX_EPOCH};
 
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+/// A hybrid-logical-clock timestamp: `physical` is declared before `logical`
+/// so the derived `Ord` compares physical time first and only falls back to
+/// the logical counter to break ties at the same physical instant.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
 pub struct LogicalTime {
-    pub logical: u64,
     pub physical: u64,
+    pub logical: u64,
 }
 
 impl LogicalTime {
@@ -26,40 +29,86 @@ pub enum ClockMode {
     Deterministic { frozen_physical: u64 },
 }
 
+/// A Hybrid Logical Clock: `state` packs `(l, c)`, the greatest physical
+/// time this clock has observed and the logical counter that breaks ties
+/// within that same physical instant, per Kulkarni et al.'s HLC algorithm.
 pub struct HybridLogicalClock {
     mode: ClockMode,
-    logical_counter: AtomicU64,
+    state: Mutex<(u64, u64)>,
 }
 
 impl HybridLogicalClock {
     pub fn new_realtime() -> Self {
         Self {
             mode: ClockMode::Realtime,
-            logical_counter: AtomicU64::new(0),
+            state: Mutex::new((0, 0)),
         }
     }
 
     pub fn new_deterministic(frozen_physical: u64) -> Self {
         Self {
             mode: ClockMode::Deterministic { frozen_physical },
-            logical_counter: AtomicU64::new(0),
+            state: Mutex::new((0, 0)),
         }
     }
 
-    pub fn now(&self) -> LogicalTime {
-        let physical = match &self.mode {
-            ClockMode::Realtime => {
-                SystemTime::now()
-                    .duration_since(UNIX_EPOCH)
-                    .unwrap()
-                    .as_micros() as u64
-            }
+    fn physical_time(&self) -> u64 {
+        match &self.mode {
+            ClockMode::Realtime => SystemTime::now()
+                .duration_since(UNIX_EPOCH)
+                .unwrap()
+                .as_micros() as u64,
             ClockMode::Deterministic { frozen_physical } => *frozen_physical,
-        };
+        }
+    }
+
+    fn lock(&self) -> MutexGuard<'_, (u64, u64)> {
+        self.state.lock().expect("HybridLogicalClock mutex poisoned")
+    }
 
-        let logical = self.logical_counter.fetch_add(1, Ordering::SeqCst);
+    /// The local-event half of the HLC algorithm: advances `(l, c)` past the
+    /// current physical time, resetting the logical counter whenever
+    /// physical time has actually moved forward.
+    pub fn now(&self) -> LogicalTime {
+        let pt = self.physical_time();
+        let mut state = self.lock();
+        let (l, c) = *state;
 
-        LogicalTime { logical, physical }
+        let new_l = l.max(pt);
+        let new_c = if new_l == l { c + 1 } else { 0 };
+
+        *state = (new_l, new_c);
+        LogicalTime {
+            physical: new_l,
+            logical: new_c,
+        }
+    }
+
+    /// The message-receipt half of the HLC algorithm: merges a `remote`
+    /// timestamp into this clock's state so that every event the local
+    /// clock produces afterward is ordered after both its own history and
+    /// whatever the remote side had already observed.
+    pub fn update(&self, remote: LogicalTime) -> LogicalTime {
+        let pt = self.physical_time();
+        let mut state = self.lock();
+        let (l, c) = *state;
+
+        let new_l = l.max(remote.physical).max(pt);
+        let new_c = if new_l == l && new_l == remote.physical {
+            c.max(remote.logical) + 1
+        } else if new_l == l {
+            c + 1
+        } else if new_l == remote.physical {
+            remote.logical + 1
+        } else {
+            0
+        };
+
+        *state = (new_l, new_c);
+        LogicalTime {
+            physical: new_l,
+            logical: new_c,
+        }
     }
 
     pub fn advance(&self) -> LogicalTime {
@@ -67,6 +116,7 @@ impl HybridLogicalClock {
     }
 
     pub fn advance_by(&self, delta: u64) {
-        self.logical_counter.fetch_add(delta, Ordering::SeqCst);
+        let mut state = self.lock();
+        state.1 += delta;
     }
 }
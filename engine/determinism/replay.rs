@@ -1,5 +1,5 @@
 use crate::determinism::clock::{HybridLogicalClock, LogicalTime};
-use crate::ffi::storage::StorageEngine;
+use crate::storage::StorageBackend;
 use anyhow::Result;
 
 pub struct ReplayEngine {
@@ -13,8 +13,8 @@ impl ReplayEngine {
         }
     }
 
-    pub fn replay_wal(&self, storage: &StorageEngine) -> Result<()> {
-        storage.wal_replay()?;
+    pub fn replay_wal(&self, storage: &dyn StorageBackend) -> Result<()> {
+        storage.recover()?;
         Ok(())
     }
 
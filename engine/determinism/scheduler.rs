@@ -1,6 +1,8 @@
+use futures::stream::{FuturesUnordered, StreamExt};
+use std::cmp::Reverse;
 use std::collections::BTreeMap;
 use std::sync::Arc;
-use tokio::sync::Mutex;
+use tokio::sync::{Mutex, RwLock};
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
 pub struct TaskId(pub u64);
@@ -11,15 +13,29 @@ pub struct Task {
     pub work: Arc<dyn Fn() + Send + Sync>,
 }
 
+/// Orders the ready queue by priority (higher first), falling back to
+/// `TaskId` — and so insertion order — as a FIFO tiebreak between tasks of
+/// equal priority. Wrapping `priority` in `Reverse` makes ascending `BTreeMap`
+/// order put the highest priority first.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+struct ReadyKey {
+    priority: Reverse<u8>,
+    id: TaskId,
+}
+
 pub struct DeterministicScheduler {
-    ready_queue: Arc<Mutex<BTreeMap<TaskId, Task>>>,
+    /// Read-mostly: `is_empty`/length probes only need a read lock and so
+    /// don't contend with each other or with `schedule`/`execute_next`'s
+    /// quick write-lock sections, following the read-mostly locking pattern
+    /// used in OpenEthereum's epoch cache.
+    ready_queue: Arc<RwLock<BTreeMap<ReadyKey, Task>>>,
     next_task_id: Arc<Mutex<u64>>,
 }
 
 impl DeterministicScheduler {
     pub fn new() -> Self {
         Self {
-            ready_queue: Arc::new(Mutex::new(BTreeMap::new())),
+            ready_queue: Arc::new(RwLock::new(BTreeMap::new())),
             next_task_id: Arc::new(Mutex::new(0)),
         }
     }
@@ -36,25 +52,63 @@ impl DeterministicScheduler {
             work,
         };
 
-        let mut queue = self.ready_queue.lock().await;
-        queue.insert(task_id, task);
+        let key = ReadyKey { priority: Reverse(priority), id: task_id };
+
+        let mut queue = self.ready_queue.write().await;
+        queue.insert(key, task);
 
         task_id
     }
 
+    /// Pops and runs the highest-priority ready task (FIFO among equal
+    /// priorities) on the caller's own task, blocking until it returns.
     pub async fn execute_next(&self) -> Option<TaskId> {
-        let mut queue = self.ready_queue.lock().await;
-        
-        if let Some((task_id, task)) = queue.pop_first() {
-            drop(queue);
-            (task.work)();
-            Some(task_id)
-        } else {
-            None
+        let task = self.pop_ready().await?;
+        let task_id = task.id;
+        (task.work)();
+        Some(task_id)
+    }
+
+    async fn pop_ready(&self) -> Option<Task> {
+        let mut queue = self.ready_queue.write().await;
+        queue.pop_first().map(|(_, task)| task)
+    }
+
+    /// Drains the ready queue, dispatching up to `concurrency` tasks at a
+    /// time onto the Tokio runtime's blocking pool. Tasks are always
+    /// *dispatched* in deterministic priority order; with `concurrency == 1`
+    /// they instead run one at a time on the caller's own task (as with
+    /// `execute_next`) and so also *complete* in that order,
+    /// which is what reproducible single-threaded tests should use. Higher
+    /// concurrency trades that completion-order determinism for throughput,
+    /// since multiple tasks then race on the runtime's thread pool.
+    pub async fn run_until_empty(&self, concurrency: usize) {
+        let concurrency = concurrency.max(1);
+
+        if concurrency == 1 {
+            while self.execute_next().await.is_some() {}
+            return;
+        }
+
+        let mut in_flight = FuturesUnordered::new();
+
+        loop {
+            while in_flight.len() < concurrency {
+                match self.pop_ready().await {
+                    Some(task) => in_flight.push(tokio::task::spawn_blocking(move || (task.work)())),
+                    None => break,
+                }
+            }
+
+            match in_flight.next().await {
+                Some(Ok(())) => {}
+                Some(Err(e)) => tracing::error!("scheduled task panicked: {}", e),
+                None => break,
+            }
         }
     }
 
     pub async fn is_empty(&self) -> bool {
-        self.ready_queue.lock().await.is_empty()
+        self.ready_queue.read().await.is_empty()
     }
 }
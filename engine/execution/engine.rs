@@ -1,27 +1,70 @@
-use crate::execution::expression::ExpressionEvaluator;
-use crate::execution::operators::scan::SeqScan;
+use crate::execution::operators::aggregate::HashAggregate;
+use crate::execution::operators::exec_node::{ExecNode, FilterNode, LimitNode, MaterializedNode, ProjectNode};
+use crate::execution::operators::join::{HashJoin, IndexSemiJoin, NestedLoopJoin};
+use crate::execution::operators::scan::{IndexScan, SeqScan};
+use crate::execution::operators::sort::{DistinctNode, SortNode};
 use crate::execution::sandbox::{QueryLimits, Sandbox};
 use crate::execution::tuple::Tuple;
-use crate::ffi::storage::StorageEngine;
-use crate::planner::physical::PhysicalPlan;
 use crate::language::ast::ColumnDefinition;
+use crate::language::catalog::Catalog;
+use crate::planner::physical::{Partitioning, PhysicalPlan};
+use crate::sharding::keyspace::{Keyspace, ShardId};
+use crate::storage::StorageBackend;
+use crate::transactions::time_travel::TimeTravelManager;
 use anyhow::Result;
 use futures::future::BoxFuture;
 use futures::FutureExt;
-use std::collections::HashMap;
 use serde_json::json;
+use std::collections::HashMap;
+use std::sync::Arc;
+use tokio::sync::RwLock;
 
 pub struct ExecutionEngine<'a> {
-    storage: &'a StorageEngine,
-    evaluator: ExpressionEvaluator,
+    storage: &'a dyn StorageBackend,
+    catalog: Arc<RwLock<Catalog>>,
+    role: Option<String>,
 }
 
 impl<'a> ExecutionEngine<'a> {
-    pub fn new(storage: &'a StorageEngine) -> Self {
-        Self {
-            storage,
-            evaluator: ExpressionEvaluator::new(),
+    pub fn new(storage: &'a dyn StorageBackend, catalog: Arc<RwLock<Catalog>>) -> Self {
+        Self { storage, catalog, role: None }
+    }
+
+    /// Attaches the role a query is running as, so `Catalog`'s `RLSManager`
+    /// filters scan results through any row-level security policy
+    /// registered for it. Callers that don't represent an authenticated
+    /// user-facing query (replicated fragment execution, materialized view
+    /// refresh, Raft log application, ...) leave this unset and see every
+    /// row, same as before RLS existed.
+    pub fn with_role(mut self, role: impl Into<String>) -> Self {
+        self.role = Some(role.into());
+        self
+    }
+
+    /// Filters `node`'s rows through any row-level security policy
+    /// `catalog` has registered for `table` and the current `role`, leaving
+    /// `node` untouched (so scans stay zero-buffer) when there's no role or
+    /// no matching policy. A scan with an active policy has to be
+    /// materialized first since `RLSManager::apply_policies` filters a whole
+    /// result set rather than one row at a time.
+    async fn apply_rls<'b>(&'b self, table: &str, node: Box<dyn ExecNode + 'b>) -> Result<Box<dyn ExecNode + 'b>> {
+        let role = match &self.role {
+            Some(role) => role,
+            None => return Ok(node),
+        };
+
+        if self.catalog.read().await.rls().get_policies(table, role).is_empty() {
+            return Ok(node);
         }
+
+        let mut node = node;
+        let mut tuples = Vec::new();
+        while let Some(tuple) = node.next().await? {
+            tuples.push(tuple);
+        }
+
+        let filtered = self.catalog.read().await.rls().apply_policies(table, role, tuples)?;
+        Ok(Box::new(MaterializedNode::new(filtered)) as Box<dyn ExecNode>)
     }
 
     pub async fn execute(&mut self, plan: PhysicalPlan) -> Result<Vec<Tuple>> {
@@ -29,67 +72,183 @@ impl<'a> ExecutionEngine<'a> {
         self.execute_with_sandbox(plan, sandbox).await
     }
 
-    fn execute_with_sandbox<'b>(&'b mut self, plan: PhysicalPlan, sandbox: Sandbox) -> BoxFuture<'b, Result<Vec<Tuple>>> {
-        async move {
-            let mut sandbox = sandbox;
-            sandbox.check()?;
-
-            match plan {
-                PhysicalPlan::SeqScan { table, columns } => {
-                    let mut scan = SeqScan::new(table, columns);
-                    let mut results = Vec::new();
+    /// Like `execute`, but takes a caller-owned `Sandbox` instead of
+    /// constructing a fresh one, so the caller can keep a clone of it around
+    /// and call `Sandbox::cancel` to abort the query while it's running.
+    pub async fn execute_cancellable(&mut self, plan: PhysicalPlan, sandbox: Sandbox) -> Result<Vec<Tuple>> {
+        self.execute_with_sandbox(plan, sandbox).await
+    }
 
-                    while let Some(tuple) = scan.next()? {
-                        sandbox.check()?;
-                        results.push(tuple);
-                    }
+    /// Computes which shard a tuple's hash-partition key belongs to, using
+    /// the same `hash(key) % num_shards` scheme as `Keyspace::lookup`.
+    fn partition_shard(tuple: &Tuple, keys: &[String], keyspace: &Keyspace) -> ShardId {
+        let mut key_bytes = Vec::new();
+        for key in keys {
+            if let Some(value) = tuple.get(key) {
+                key_bytes.extend(format!("{:?}", value).into_bytes());
+            }
+        }
+        keyspace.lookup(&key_bytes)
+    }
 
-                    Ok(results)
+    /// Builds a pull-based operator tree for the streaming (non-mutation)
+    /// half of `PhysicalPlan`: each node's `ExecNode::next` pulls exactly
+    /// one tuple at a time from its child, so `Filter`/`Project` never
+    /// buffer anything and `Limit` stops pulling as soon as it has enough
+    /// rows. `HashJoin`/`NestedLoopJoin`/`HashAggregate`/`Sort`/`Distinct`/
+    /// `Exchange` are inherent pipeline breakers that must see all of their
+    /// input before producing a first row, so their child is drained to a
+    /// `Vec<Tuple>` via `drain_node` first; `HashJoin`/`NestedLoopJoin`/
+    /// `HashAggregate`/`SortNode`/`DistinctNode` then implement `ExecNode`
+    /// directly over their own result buffer, while `Exchange`'s
+    /// repartitioned output is handed out through `MaterializedNode`.
+    fn build_node<'b>(&'b self, plan: PhysicalPlan, sandbox: Sandbox) -> BoxFuture<'b, Result<Box<dyn ExecNode + 'b>>> {
+        async move {
+            match plan {
+                PhysicalPlan::SeqScan { table, columns, time_travel, key_range } => {
+                    let as_of = time_travel.as_ref().map(|tt| TimeTravelManager::new().resolve_window(tt)).transpose()?;
+                    let scan = Box::new(SeqScan::new(table.clone(), columns, key_range, as_of)?) as Box<dyn ExecNode>;
+                    self.apply_rls(&table, scan).await
+                }
+                PhysicalPlan::IndexScan { table, index, columns, predicate, time_travel, key_range } => {
+                    let as_of = time_travel.as_ref().map(|tt| TimeTravelManager::new().resolve_window(tt)).transpose()?;
+                    let scan = Box::new(IndexScan::new(table.clone(), index, columns, predicate, key_range, as_of)?) as Box<dyn ExecNode>;
+                    self.apply_rls(&table, scan).await
                 }
                 PhysicalPlan::Filter { predicate, input } => {
-                    let tuples = self.execute_with_sandbox(*input, sandbox).await?;
-                    let mut results = Vec::new();
+                    let child = self.build_node(*input, sandbox).await?;
+                    Ok(Box::new(FilterNode::new(child, predicate)) as Box<dyn ExecNode>)
+                }
+                PhysicalPlan::Project { columns, input } => {
+                    let child = self.build_node(*input, sandbox).await?;
+                    Ok(Box::new(ProjectNode::new(child, columns)) as Box<dyn ExecNode>)
+                }
+                PhysicalPlan::Limit { count, offset, input } => {
+                    let child = self.build_node(*input, sandbox).await?;
+                    Ok(Box::new(LimitNode::new(child, count, offset)) as Box<dyn ExecNode>)
+                }
+                PhysicalPlan::HashJoin {
+                    join_type,
+                    left,
+                    right,
+                    condition,
+                } => {
+                    let left_tuples = self.drain_node(*left, sandbox.clone()).await?;
+                    let right_tuples = self.drain_node(*right, sandbox).await?;
+                    let join = HashJoin::new(left_tuples, right_tuples, join_type, condition)?;
+                    Ok(Box::new(join) as Box<dyn ExecNode>)
+                }
+                PhysicalPlan::NestedLoopJoin {
+                    join_type,
+                    left,
+                    right,
+                    condition,
+                } => {
+                    let left_tuples = self.drain_node(*left, sandbox.clone()).await?;
+                    let right_tuples = self.drain_node(*right, sandbox).await?;
+                    let join = NestedLoopJoin::new(left_tuples, right_tuples, join_type, condition)?;
+                    Ok(Box::new(join) as Box<dyn ExecNode>)
+                }
+                PhysicalPlan::IndexSemiJoin {
+                    join_type,
+                    outer,
+                    inner_table,
+                    inner_index: _,
+                    inner_columns,
+                    condition,
+                } => {
+                    let outer_tuples = self.drain_node(*outer, sandbox).await?;
+                    let join = IndexSemiJoin::new(outer_tuples, inner_table, inner_columns, join_type, condition)?;
+                    Ok(Box::new(join) as Box<dyn ExecNode>)
+                }
+                PhysicalPlan::HashAggregate {
+                    group_by,
+                    aggregates,
+                    input,
+                } => {
+                    let tuples = self.drain_node(*input, sandbox).await?;
+                    let aggregate = HashAggregate::new(tuples, group_by, aggregates)?;
+                    Ok(Box::new(aggregate) as Box<dyn ExecNode>)
+                }
+                PhysicalPlan::Sort { order_by, input } => {
+                    let tuples = self.drain_node(*input, sandbox).await?;
+                    let sort = SortNode::new(tuples, order_by)?;
+                    Ok(Box::new(sort) as Box<dyn ExecNode>)
+                }
+                PhysicalPlan::Distinct { input } => {
+                    let tuples = self.drain_node(*input, sandbox).await?;
+                    Ok(Box::new(DistinctNode::new(tuples)) as Box<dyn ExecNode>)
+                }
+                PhysicalPlan::Exchange { partitioning, input } => {
+                    let tuples = self.drain_node(*input, sandbox).await?;
 
-                    for tuple in tuples {
-                        if self.evaluator.evaluate_filter(&predicate, &tuple)? {
-                            results.push(tuple);
+                    let keys = match partitioning {
+                        Partitioning::Single => {
+                            return Ok(Box::new(MaterializedNode::new(tuples)) as Box<dyn ExecNode>)
                         }
-                    }
+                        Partitioning::HashPartition(keys) => keys,
+                    };
 
-                    Ok(results)
-                }
-                PhysicalPlan::Project { columns, input } => {
-                    let tuples = self.execute_with_sandbox(*input, sandbox).await?;
-                    let mut results = Vec::new();
+                    // Repartition by hash(key) % num_shards and route each
+                    // partition to the node owning that shard. In a
+                    // single-process deployment every shard is local, so the
+                    // coordinator's merge step is just reassembling the
+                    // partitions it just routed; a multi-node deployment
+                    // would ship each partition to its owner via
+                    // `replication::fragment::ShardTransport` instead.
+                    let keyspace = Keyspace::new(16);
+                    let mut partitions: HashMap<ShardId, Vec<Tuple>> = HashMap::new();
 
                     for tuple in tuples {
-                        let mut projected = Tuple::new();
-                        
-                        for col_intent in &columns {
-                            match col_intent {
-                                crate::language::intent::ColumnIntent::Named(name) => {
-                                    if let Some(val) = tuple.get(name) {
-                                        projected.insert(name.clone(), val.clone());
-                                    }
-                                }
-                                crate::language::intent::ColumnIntent::All => {
-                                    for (k, v) in &tuple.values {
-                                        projected.insert(k.clone(), v.clone());
-                                    }
-                                }
-                                _ => {}
-                            }
-                        }
+                        let shard_id = Self::partition_shard(&tuple, &keys, &keyspace);
+                        partitions.entry(shard_id).or_default().push(tuple);
+                    }
 
-                        results.push(projected);
+                    let mut merged = Vec::new();
+                    for (shard_id, mut partition) in partitions {
+                        tracing::debug!(
+                            "exchange: routed {} tuples to shard {:?}",
+                            partition.len(),
+                            shard_id
+                        );
+                        merged.append(&mut partition);
                     }
 
-                    Ok(results)
-                }
-                PhysicalPlan::Limit { count, offset, input } => {
-                    let tuples = self.execute_with_sandbox(*input, sandbox).await?;
-                    Ok(tuples.into_iter().skip(offset).take(count).collect())
+                    Ok(Box::new(MaterializedNode::new(merged)) as Box<dyn ExecNode>)
                 }
+                _ => anyhow::bail!("Unsupported plan type"),
+            }
+        }
+        .boxed()
+    }
+
+    /// Builds the operator tree for `plan` and pulls it dry into a
+    /// `Vec<Tuple>`, checking `sandbox` after every produced row. Used both
+    /// as the top-level entry point for query plans and internally by
+    /// pipeline-breaking operators that need their entire input materialized
+    /// before they can run.
+    fn drain_node<'b>(&'b self, plan: PhysicalPlan, sandbox: Sandbox) -> BoxFuture<'b, Result<Vec<Tuple>>> {
+        async move {
+            let mut sandbox = sandbox;
+            let mut node = self.build_node(plan, sandbox.clone()).await?;
+            let mut results = Vec::new();
+
+            while let Some(tuple) = node.next().await? {
+                sandbox.check()?;
+                results.push(tuple);
+            }
+
+            Ok(results)
+        }
+        .boxed()
+    }
+
+    fn execute_with_sandbox<'b>(&'b mut self, plan: PhysicalPlan, sandbox: Sandbox) -> BoxFuture<'b, Result<Vec<Tuple>>> {
+        async move {
+            let mut sandbox = sandbox;
+            sandbox.check()?;
+
+            match plan {
                 PhysicalPlan::Insert { table, columns, values } => {
                     tracing::info!("INSERT into {} with {} rows", table, values.len());
                     
@@ -165,6 +324,25 @@ impl<'a> ExecutionEngine<'a> {
                     tracing::info!("Successfully deleted {} rows from {}", deleted_count, table);
                     Ok(vec![])
                 }
+                PhysicalPlan::CreateIndex { name, table, columns } => {
+                    tracing::info!("CREATE INDEX {} ON {} ({} columns)", name, table, columns.len());
+
+                    // The C storage layer has no key->row-id index structure
+                    // to build yet (see `TableProvider::scan`'s placeholder
+                    // stats), so this registers the index in the in-memory
+                    // catalog only; the physical planner consults it to
+                    // choose `IndexScan`/`IndexSemiJoin` over a full scan.
+                    self.catalog.write().await.register_index(&name, &table, columns);
+
+                    tracing::info!("Successfully created index: {}", name);
+                    Ok(vec![])
+                }
+                PhysicalPlan::DropIndex { name } => {
+                    tracing::info!("DROP INDEX {}", name);
+                    self.catalog.write().await.drop_index(&name);
+                    tracing::info!("Successfully dropped index: {}", name);
+                    Ok(vec![])
+                }
                 PhysicalPlan::CreateTable { name, columns } => {
                     tracing::info!("CREATE TABLE {} with {} columns", name, columns.len());
                     
@@ -179,18 +357,30 @@ impl<'a> ExecutionEngine<'a> {
                         schema.insert(col.name.clone(), col_info);
                     }
                     
-                    let schema_json = serde_json::to_string_pretty(&schema)?; 
+                    let schema_json = serde_json::to_string_pretty(&schema)?;
                     tracing::debug!("Creating table with schema: {}", schema_json);
                     self.storage.create_table(&name, &schema_json)?;
                     self.storage.wal_flush()?;
                     self.storage.checkpoint()?;
-                    
+                    self.catalog.write().await.register_table(&name, columns);
+
                     tracing::info!("Successfully created table: {}", name);
                     Ok(vec![])
-                }                _ => {
-                    anyhow::bail!("Unsupported plan type")
                 }
+                PhysicalPlan::CreatePolicy { policy_name, table, roles, filter } => {
+                    tracing::info!("CREATE POLICY {} ON {}", policy_name, table);
+                    self.catalog.write().await.add_policy(&policy_name, &table, roles, filter);
+                    tracing::info!("Successfully created policy: {}", policy_name);
+                    Ok(vec![])
+                }
+                PhysicalPlan::DropPolicy { policy_name, table } => {
+                    tracing::info!("DROP POLICY {} ON {}", policy_name, table);
+                    self.catalog.write().await.drop_policy(&table, &policy_name);
+                    tracing::info!("Successfully dropped policy: {}", policy_name);
+                    Ok(vec![])
+                }
+                plan => self.drain_node(plan, sandbox).await,
             }
         }.boxed()
     }
-          }
+}
@@ -1,6 +1,7 @@
 use crate::execution::tuple::{Tuple, Value};
 use crate::language::intent::*;
 use anyhow::Result;
+use chrono::{DateTime, NaiveDateTime, Utc};
 
 pub struct ExpressionEvaluator;
 
@@ -22,13 +23,100 @@ impl ExpressionEvaluator {
                 self.eval_arithmetic(op, &left_val, &right_val)
             }
             ExpressionIntent::Function { name, args } => self.eval_function(name, args, tuple),
+            ExpressionIntent::Cast { target, inner, .. } => {
+                let value = self.evaluate(inner, tuple)?;
+                self.eval_cast(target, &value)
+            }
+            ExpressionIntent::Placeholder(index) => {
+                anyhow::bail!("Unbound placeholder ${} reached execution; bind parameters before executing", index)
+            }
+        }
+    }
+
+    fn eval_cast(&self, target: &ConversionKind, value: &Value) -> Result<Value> {
+        if value.is_null() {
+            return Ok(Value::Null);
+        }
+
+        match target {
+            ConversionKind::Bytes => Ok(Value::String(self.value_to_string(value))),
+            ConversionKind::Integer => match value {
+                Value::Integer(i) => Ok(Value::Integer(*i)),
+                Value::Float(f) => Ok(Value::Integer(*f as i64)),
+                Value::Boolean(b) => Ok(Value::Integer(*b as i64)),
+                Value::String(s) => s
+                    .parse::<i64>()
+                    .map(Value::Integer)
+                    .map_err(|_| anyhow::anyhow!("Cannot convert '{}' to int", s)),
+                Value::Null => unreachable!(),
+            },
+            ConversionKind::Float => match value {
+                Value::Integer(i) => Ok(Value::Float(*i as f64)),
+                Value::Float(f) => Ok(Value::Float(*f)),
+                Value::String(s) => s
+                    .parse::<f64>()
+                    .map(Value::Float)
+                    .map_err(|_| anyhow::anyhow!("Cannot convert '{}' to float", s)),
+                _ => anyhow::bail!("Cannot convert {:?} to float", value),
+            },
+            ConversionKind::Boolean => match value {
+                Value::Boolean(b) => Ok(Value::Boolean(*b)),
+                Value::Integer(i) => Ok(Value::Boolean(*i != 0)),
+                Value::String(s) => match s.to_lowercase().as_str() {
+                    "true" | "t" | "1" => Ok(Value::Boolean(true)),
+                    "false" | "f" | "0" => Ok(Value::Boolean(false)),
+                    _ => anyhow::bail!("Cannot convert '{}' to bool", s),
+                },
+                _ => anyhow::bail!("Cannot convert {:?} to bool", value),
+            },
+            // Epoch microseconds, matching the unit `HybridLogicalClock`
+            // stamps its physical component in.
+            ConversionKind::Timestamp => {
+                let s = self.value_to_string(value);
+                DateTime::parse_from_rfc3339(&s)
+                    .map(|dt| Value::Integer(dt.timestamp_micros()))
+                    .map_err(|_| anyhow::anyhow!("Cannot convert '{}' to timestamp: expected RFC3339", s))
+            }
+            ConversionKind::TimestampFmt(fmt) => {
+                let s = self.value_to_string(value);
+                NaiveDateTime::parse_from_str(&s, fmt)
+                    .map(|dt| Value::Integer(dt.and_utc().timestamp_micros()))
+                    .map_err(|_| anyhow::anyhow!("Cannot convert '{}' to timestamp using format '{}'", s, fmt))
+            }
+            ConversionKind::TimestampTZFmt(fmt) => {
+                let s = self.value_to_string(value);
+                DateTime::parse_from_str(&s, fmt)
+                    .map(|dt| Value::Integer(dt.with_timezone(&Utc).timestamp_micros()))
+                    .map_err(|_| anyhow::anyhow!("Cannot convert '{}' to timestamp using format '{}'", s, fmt))
+            }
+        }
+    }
+
+    fn value_to_string(&self, value: &Value) -> String {
+        match value {
+            Value::Null => String::new(),
+            Value::Boolean(b) => b.to_string(),
+            Value::Integer(i) => i.to_string(),
+            Value::Float(f) => f.to_string(),
+            Value::String(s) => s.clone(),
         }
     }
 
+    /// Evaluates `filter` to a plain `bool`, collapsing SQL's UNKNOWN (a
+    /// `NULL`-tainted comparison) to `false` the way a `WHERE` clause does:
+    /// a row is only kept when its predicate is definitely `true`.
     pub fn evaluate_filter(&self, filter: &FilterIntent, tuple: &Tuple) -> Result<bool> {
+        Ok(self.evaluate_filter_kleene(filter, tuple)?.unwrap_or(false))
+    }
+
+    /// Three-valued (Kleene) evaluation of `filter`: `None` is SQL's
+    /// UNKNOWN, produced by a comparison against a `NULL` operand and
+    /// propagated through `AND`/`OR`/`NOT` per the standard truth tables
+    /// instead of being treated as an error.
+    fn evaluate_filter_kleene(&self, filter: &FilterIntent, tuple: &Tuple) -> Result<Option<bool>> {
         match filter {
-            FilterIntent::Always => Ok(true),
-            FilterIntent::Never => Ok(false),
+            FilterIntent::Always => Ok(Some(true)),
+            FilterIntent::Never => Ok(Some(false)),
             FilterIntent::Comparison { op, left, right } => {
                 let left_val = self.evaluate(left, tuple)?;
                 let right_val = self.evaluate(right, tuple)?;
@@ -36,32 +124,53 @@ impl ExpressionEvaluator {
             }
             FilterIntent::Logical { op, operands } => match op {
                 LogicalOp::And => {
+                    let mut result = Some(true);
                     for operand in operands {
-                        if !self.evaluate_filter(operand, tuple)? {
-                            return Ok(false);
-                        }
+                        result = Self::kleene_and(result, self.evaluate_filter_kleene(operand, tuple)?);
                     }
-                    Ok(true)
+                    Ok(result)
                 }
                 LogicalOp::Or => {
+                    let mut result = Some(false);
                     for operand in operands {
-                        if self.evaluate_filter(operand, tuple)? {
-                            return Ok(true);
-                        }
+                        result = Self::kleene_or(result, self.evaluate_filter_kleene(operand, tuple)?);
                     }
-                    Ok(false)
+                    Ok(result)
                 }
                 LogicalOp::Not => {
                     if operands.len() != 1 {
                         anyhow::bail!("NOT expects exactly one operand");
                     }
-                    Ok(!self.evaluate_filter(&operands[0], tuple)?)
+                    Ok(self.evaluate_filter_kleene(&operands[0], tuple)?.map(|b| !b))
                 }
             },
         }
     }
 
+    fn kleene_and(a: Option<bool>, b: Option<bool>) -> Option<bool> {
+        match (a, b) {
+            (Some(false), _) | (_, Some(false)) => Some(false),
+            (Some(true), Some(true)) => Some(true),
+            _ => None,
+        }
+    }
+
+    fn kleene_or(a: Option<bool>, b: Option<bool>) -> Option<bool> {
+        match (a, b) {
+            (Some(true), _) | (_, Some(true)) => Some(true),
+            (Some(false), Some(false)) => Some(false),
+            _ => None,
+        }
+    }
+
+    /// SQL arithmetic: a `NULL` operand makes the whole expression `NULL`
+    /// rather than an error, and a mixed `Integer`/`Float` pair coerces the
+    /// integer side to `Float` instead of rejecting the combination.
     fn eval_arithmetic(&self, op: &ArithmeticOp, left: &Value, right: &Value) -> Result<Value> {
+        if left.is_null() || right.is_null() {
+            return Ok(Value::Null);
+        }
+
         match (left, right) {
             (Value::Integer(l), Value::Integer(r)) => {
                 let result = match op {
@@ -77,46 +186,66 @@ impl ExpressionEvaluator {
                 };
                 Ok(Value::Integer(result))
             }
-            (Value::Float(l), Value::Float(r)) => {
-                let result = match op {
-                    ArithmeticOp::Add => l + r,
-                    ArithmeticOp::Subtract => l - r,
-                    ArithmeticOp::Multiply => l * r,
-                    ArithmeticOp::Divide => l / r,
-                };
-                Ok(Value::Float(result))
-            }
+            (Value::Float(l), Value::Float(r)) => self.eval_float_arithmetic(op, *l, *r),
+            (Value::Integer(l), Value::Float(r)) => self.eval_float_arithmetic(op, *l as f64, *r),
+            (Value::Float(l), Value::Integer(r)) => self.eval_float_arithmetic(op, *l, *r as f64),
             _ => anyhow::bail!("Type mismatch in arithmetic operation"),
         }
     }
 
-    fn eval_comparison(&self, op: &ComparisonOp, left: &Value, right: &Value) -> Result<bool> {
-        match (left, right) {
-            (Value::Integer(l), Value::Integer(r)) => Ok(match op {
+    fn eval_float_arithmetic(&self, op: &ArithmeticOp, l: f64, r: f64) -> Result<Value> {
+        let result = match op {
+            ArithmeticOp::Add => l + r,
+            ArithmeticOp::Subtract => l - r,
+            ArithmeticOp::Multiply => l * r,
+            ArithmeticOp::Divide => l / r,
+        };
+        Ok(Value::Float(result))
+    }
+
+    /// SQL comparison: `Ok(None)` is UNKNOWN, produced whenever either side
+    /// is `NULL`, per `evaluate_filter_kleene`'s three-valued logic. A mixed
+    /// `Integer`/`Float` pair coerces the integer side to `Float` the same
+    /// way `eval_arithmetic` does.
+    fn eval_comparison(&self, op: &ComparisonOp, left: &Value, right: &Value) -> Result<Option<bool>> {
+        if left.is_null() || right.is_null() {
+            return Ok(None);
+        }
+
+        let result = match (left, right) {
+            (Value::Integer(l), Value::Integer(r)) => match op {
                 ComparisonOp::Equal => l == r,
                 ComparisonOp::NotEqual => l != r,
                 ComparisonOp::LessThan => l < r,
                 ComparisonOp::LessThanOrEqual => l <= r,
                 ComparisonOp::GreaterThan => l > r,
                 ComparisonOp::GreaterThanOrEqual => l >= r,
-            }),
-            (Value::Float(l), Value::Float(r)) => Ok(match op {
-                ComparisonOp::Equal => (l - r).abs() < f64::EPSILON,
-                ComparisonOp::NotEqual => (l - r).abs() >= f64::EPSILON,
-                ComparisonOp::LessThan => l < r,
-                ComparisonOp::LessThanOrEqual => l <= r,
-                ComparisonOp::GreaterThan => l > r,
-                ComparisonOp::GreaterThanOrEqual => l >= r,
-            }),
-            (Value::String(l), Value::String(r)) => Ok(match op {
+            },
+            (Value::Float(l), Value::Float(r)) => Self::compare_floats(op, *l, *r),
+            (Value::Integer(l), Value::Float(r)) => Self::compare_floats(op, *l as f64, *r),
+            (Value::Float(l), Value::Integer(r)) => Self::compare_floats(op, *l, *r as f64),
+            (Value::String(l), Value::String(r)) => match op {
                 ComparisonOp::Equal => l == r,
                 ComparisonOp::NotEqual => l != r,
                 ComparisonOp::LessThan => l < r,
                 ComparisonOp::LessThanOrEqual => l <= r,
                 ComparisonOp::GreaterThan => l > r,
                 ComparisonOp::GreaterThanOrEqual => l >= r,
-            }),
+            },
             _ => anyhow::bail!("Type mismatch in comparison"),
+        };
+
+        Ok(Some(result))
+    }
+
+    fn compare_floats(op: &ComparisonOp, l: f64, r: f64) -> bool {
+        match op {
+            ComparisonOp::Equal => (l - r).abs() < f64::EPSILON,
+            ComparisonOp::NotEqual => (l - r).abs() >= f64::EPSILON,
+            ComparisonOp::LessThan => l < r,
+            ComparisonOp::LessThanOrEqual => l <= r,
+            ComparisonOp::GreaterThan => l > r,
+            ComparisonOp::GreaterThanOrEqual => l >= r,
         }
     }
 
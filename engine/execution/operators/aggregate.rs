@@ -1,13 +1,16 @@
+use crate::execution::expression::ExpressionEvaluator;
+use crate::execution::operators::exec_node::ExecNode;
 use crate::execution::tuple::{Tuple, Value};
 use crate::language::intent::{AggregateIntent, ExpressionIntent};
 use anyhow::Result;
-use std::collections::HashMap;
+use futures::future::BoxFuture;
+use futures::FutureExt;
+use std::collections::{HashMap, HashSet};
 
 pub struct HashAggregate {
-    input: Vec<Tuple>,
     group_by: Vec<ExpressionIntent>,
     aggregates: Vec<AggregateIntent>,
-    groups: HashMap<String, AggregateState>,
+    groups: HashMap<Vec<String>, GroupState>,
     finalized: bool,
     result_iter: std::vec::IntoIter<Tuple>,
 }
@@ -17,23 +20,25 @@ impl HashAggregate {
         input: Vec<Tuple>,
         group_by: Vec<ExpressionIntent>,
         aggregates: Vec<AggregateIntent>,
-    ) -> Self {
-        let mut groups = HashMap::new();
+    ) -> Result<Self> {
+        let evaluator = ExpressionEvaluator::new();
+        let mut groups: HashMap<Vec<String>, GroupState> = HashMap::new();
 
         for tuple in &input {
-            let group_key = Self::compute_group_key(&group_by, tuple);
-            let state = groups.entry(group_key).or_insert_with(AggregateState::new);
-            state.accumulate(&aggregates, tuple);
+            let group_key = Self::compute_group_key(&group_by, tuple, &evaluator)?;
+            let state = groups
+                .entry(group_key)
+                .or_insert_with(|| GroupState::new(aggregates.len()));
+            state.accumulate(&aggregates, tuple, &evaluator)?;
         }
 
-        Self {
-            input,
+        Ok(Self {
             group_by,
             aggregates,
             groups,
             finalized: false,
             result_iter: Vec::new().into_iter(),
-        }
+        })
     }
 
     pub fn next(&mut self) -> Result<Option<Tuple>> {
@@ -48,12 +53,16 @@ impl HashAggregate {
     fn finalize(&mut self) -> Result<()> {
         let mut results = Vec::new();
 
-        for (_group_key, state) in &self.groups {
+        for (group_key, state) in &self.groups {
             let mut tuple = Tuple::new();
 
-            for agg in &self.aggregates {
-                let value = state.finalize(&agg.function);
-                let col_name = agg.alias.as_ref().unwrap_or(&agg.function).clone();
+            for (idx, expr) in self.group_by.iter().enumerate() {
+                let col_name = Self::group_column_name(expr, idx);
+                tuple.insert(col_name, Self::untag(&group_key[idx]));
+            }
+
+            for (agg, value) in self.aggregates.iter().zip(state.finalize(&self.aggregates)) {
+                let col_name = agg.alias.clone().unwrap_or_else(|| agg.function.clone());
                 tuple.insert(col_name, value);
             }
 
@@ -64,55 +73,204 @@ impl HashAggregate {
         Ok(())
     }
 
-    fn compute_group_key(_group_by: &[ExpressionIntent], _tuple: &Tuple) -> String {
-        String::from("default_group")
+    /// Evaluates every `GROUP BY` expression against `tuple` and serializes
+    /// the resulting values into a composite key. Each part is tagged with
+    /// its `Value` variant so e.g. the integer `1` and the string `"1"`
+    /// never collide.
+    fn compute_group_key(
+        group_by: &[ExpressionIntent],
+        tuple: &Tuple,
+        evaluator: &ExpressionEvaluator,
+    ) -> Result<Vec<String>> {
+        group_by
+            .iter()
+            .map(|expr| Ok(Self::tag(&evaluator.evaluate(expr, tuple)?)))
+            .collect()
+    }
+
+    pub(crate) fn tag(value: &Value) -> String {
+        match value {
+            Value::Null => "null:".to_string(),
+            Value::Boolean(b) => format!("bool:{}", b),
+            Value::Integer(i) => format!("int:{}", i),
+            Value::Float(f) => format!("float:{}", f),
+            Value::String(s) => format!("str:{}", s),
+        }
+    }
+
+    /// Recovers a `Value` from a tagged group-key component, so the key
+    /// columns can be emitted alongside the aggregate columns.
+    fn untag(tagged: &str) -> Value {
+        match tagged.split_once(':') {
+            Some(("null", "")) => Value::Null,
+            Some(("bool", rest)) => Value::Boolean(rest == "true"),
+            Some(("int", rest)) => rest.parse().map(Value::Integer).unwrap_or(Value::Null),
+            Some(("float", rest)) => rest.parse().map(Value::Float).unwrap_or(Value::Null),
+            Some(("str", rest)) => Value::String(rest.to_string()),
+            _ => Value::Null,
+        }
+    }
+
+    fn group_column_name(expr: &ExpressionIntent, idx: usize) -> String {
+        match expr {
+            ExpressionIntent::Column(name) => name.clone(),
+            ExpressionIntent::QualifiedColumn { column, .. } => column.clone(),
+            _ => format!("group_{}", idx),
+        }
     }
 }
 
-struct AggregateState {
+impl ExecNode for HashAggregate {
+    fn next<'a>(&'a mut self) -> BoxFuture<'a, Result<Option<Tuple>>> {
+        async move { self.next() }.boxed()
+    }
+}
+
+/// Per-group accumulator state, one `Accumulator` per `AggregateIntent` in
+/// the query.
+struct GroupState {
+    accumulators: Vec<Accumulator>,
+}
+
+impl GroupState {
+    fn new(num_aggregates: usize) -> Self {
+        Self {
+            accumulators: (0..num_aggregates).map(|_| Accumulator::new()).collect(),
+        }
+    }
+
+    fn accumulate(
+        &mut self,
+        aggregates: &[AggregateIntent],
+        tuple: &Tuple,
+        evaluator: &ExpressionEvaluator,
+    ) -> Result<()> {
+        for (acc, agg) in self.accumulators.iter_mut().zip(aggregates) {
+            let function = agg.function.to_lowercase();
+
+            // `COUNT(*)` is represented as a `Column("*")` argument: it
+            // counts rows regardless of whether any column is null, unlike
+            // `COUNT(col)` which must skip nulls.
+            if function == "count" && Self::is_star(&agg.argument) {
+                acc.count += 1;
+                continue;
+            }
+
+            let value = evaluator.evaluate(&agg.argument, tuple)?;
+            acc.accumulate(&function, &value);
+        }
+
+        Ok(())
+    }
+
+    fn finalize(&self, aggregates: &[AggregateIntent]) -> Vec<Value> {
+        self.accumulators
+            .iter()
+            .zip(aggregates)
+            .map(|(acc, agg)| acc.finalize(&agg.function.to_lowercase()))
+            .collect()
+    }
+
+    fn is_star(expr: &ExpressionIntent) -> bool {
+        matches!(expr, ExpressionIntent::Column(name) if name == "*")
+    }
+}
+
+struct Accumulator {
     count: i64,
-    sum: f64,
-    min: Option<f64>,
-    max: Option<f64>,
+    sum: Value,
+    min: Option<Value>,
+    max: Option<Value>,
+    distinct: HashSet<String>,
 }
 
-impl AggregateState {
+impl Accumulator {
     fn new() -> Self {
         Self {
             count: 0,
-            sum: 0.0,
+            sum: Value::Integer(0),
             min: None,
             max: None,
+            distinct: HashSet::new(),
         }
     }
 
-    fn accumulate(&mut self, _aggregates: &[AggregateIntent], _tuple: &Tuple) {
-        self.count += 1;
-        self.sum += 1.0;
-
-        if self.min.is_none() {
-            self.min = Some(1.0);
-        }
-
-        if self.max.is_none() {
-            self.max = Some(1.0);
+    fn accumulate(&mut self, function: &str, value: &Value) {
+        match function {
+            "count" => {
+                if !value.is_null() {
+                    self.count += 1;
+                }
+            }
+            "count_distinct" => {
+                if !value.is_null() {
+                    self.distinct.insert(HashAggregate::tag(value));
+                }
+            }
+            "sum" | "avg" => {
+                if !value.is_null() {
+                    self.count += 1;
+                    self.sum = Self::add(&self.sum, value);
+                }
+            }
+            "min" => {
+                if !value.is_null() && self.min.as_ref().map_or(true, |m| Self::lt(value, m)) {
+                    self.min = Some(value.clone());
+                }
+            }
+            "max" => {
+                if !value.is_null() && self.max.as_ref().map_or(true, |m| Self::lt(m, value)) {
+                    self.max = Some(value.clone());
+                }
+            }
+            _ => {}
         }
     }
 
     fn finalize(&self, function: &str) -> Value {
-        match function.to_lowercase().as_str() {
+        match function {
             "count" => Value::Integer(self.count),
-            "sum" => Value::Float(self.sum),
+            "count_distinct" => Value::Integer(self.distinct.len() as i64),
+            "sum" => self.sum.clone(),
             "avg" => {
-                if self.count > 0 {
-                    Value::Float(self.sum / self.count as f64)
-                } else {
+                if self.count == 0 {
                     Value::Null
+                } else {
+                    match &self.sum {
+                        Value::Integer(i) => Value::Float(*i as f64 / self.count as f64),
+                        Value::Float(f) => Value::Float(f / self.count as f64),
+                        _ => Value::Null,
+                    }
                 }
             }
-            "min" => self.min.map(Value::Float).unwrap_or(Value::Null),
-            "max" => self.max.map(Value::Float).unwrap_or(Value::Null),
+            "min" => self.min.clone().unwrap_or(Value::Null),
+            "max" => self.max.clone().unwrap_or(Value::Null),
             _ => Value::Null,
         }
     }
+
+    /// The running `SUM`/`AVG` total stays integer as long as every
+    /// contributing value is an integer, and widens to float the moment a
+    /// float value appears; `AVG` itself always finalizes to a float.
+    fn add(a: &Value, b: &Value) -> Value {
+        match (a, b) {
+            (Value::Integer(x), Value::Integer(y)) => Value::Integer(x + y),
+            (Value::Integer(x), Value::Float(y)) => Value::Float(*x as f64 + y),
+            (Value::Float(x), Value::Integer(y)) => Value::Float(x + *y as f64),
+            (Value::Float(x), Value::Float(y)) => Value::Float(x + y),
+            _ => a.clone(),
+        }
+    }
+
+    fn lt(a: &Value, b: &Value) -> bool {
+        match (a, b) {
+            (Value::Integer(x), Value::Integer(y)) => x < y,
+            (Value::Float(x), Value::Float(y)) => x < y,
+            (Value::Integer(x), Value::Float(y)) => (*x as f64) < *y,
+            (Value::Float(x), Value::Integer(y)) => *x < (*y as f64),
+            (Value::String(x), Value::String(y)) => x < y,
+            (Value::Boolean(x), Value::Boolean(y)) => !x & y,
+            _ => false,
+        }
+    }
 }
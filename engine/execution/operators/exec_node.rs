@@ -0,0 +1,163 @@
+use crate::execution::expression::ExpressionEvaluator;
+use crate::execution::tuple::Tuple;
+use crate::language::intent::{ColumnIntent, FilterIntent};
+use anyhow::Result;
+use futures::future::BoxFuture;
+use futures::FutureExt;
+
+/// A pull-based (Volcano-style) query operator: a parent calls `next` to
+/// pull one tuple at a time from its child instead of the child eagerly
+/// materializing its entire output up front. `Scan`/`Filter`/`Project`/
+/// `Limit` implement this with zero buffering of their own; a pipeline
+/// breaker (`HashJoin`, `HashAggregate`, sort-merge) still has to see all
+/// of its input before it can produce a first row, so it materializes once
+/// internally (`HashJoin`/`NestedLoopJoin`/`HashAggregate` implement this
+/// trait directly over their own already-built `results` buffer) while
+/// `MaterializedNode` adapts a plain `Vec<Tuple>` onto the same interface.
+pub trait ExecNode: Send {
+    fn next<'a>(&'a mut self) -> BoxFuture<'a, Result<Option<Tuple>>>;
+}
+
+/// Zero-buffer pass-through: pulls from `child` one row at a time, only
+/// forwarding rows that satisfy `predicate`.
+pub struct FilterNode {
+    child: Box<dyn ExecNode>,
+    predicate: FilterIntent,
+    evaluator: ExpressionEvaluator,
+}
+
+impl FilterNode {
+    pub fn new(child: Box<dyn ExecNode>, predicate: FilterIntent) -> Self {
+        Self {
+            child,
+            predicate,
+            evaluator: ExpressionEvaluator::new(),
+        }
+    }
+}
+
+impl ExecNode for FilterNode {
+    fn next<'a>(&'a mut self) -> BoxFuture<'a, Result<Option<Tuple>>> {
+        async move {
+            while let Some(tuple) = self.child.next().await? {
+                if self.evaluator.evaluate_filter(&self.predicate, &tuple)? {
+                    return Ok(Some(tuple));
+                }
+            }
+            Ok(None)
+        }
+        .boxed()
+    }
+}
+
+/// Zero-buffer pass-through: reshapes each tuple pulled from `child`
+/// according to `columns` without ever holding more than one row at a time.
+pub struct ProjectNode {
+    child: Box<dyn ExecNode>,
+    columns: Vec<ColumnIntent>,
+}
+
+impl ProjectNode {
+    pub fn new(child: Box<dyn ExecNode>, columns: Vec<ColumnIntent>) -> Self {
+        Self { child, columns }
+    }
+
+    fn project(columns: &[ColumnIntent], tuple: &Tuple) -> Tuple {
+        let mut projected = Tuple::new();
+
+        for col_intent in columns {
+            match col_intent {
+                ColumnIntent::Named(name) => {
+                    if let Some(val) = tuple.get(name) {
+                        projected.insert(name.clone(), val.clone());
+                    }
+                }
+                ColumnIntent::All => {
+                    for (k, v) in &tuple.values {
+                        projected.insert(k.clone(), v.clone());
+                    }
+                }
+                _ => {}
+            }
+        }
+
+        projected
+    }
+}
+
+impl ExecNode for ProjectNode {
+    fn next<'a>(&'a mut self) -> BoxFuture<'a, Result<Option<Tuple>>> {
+        async move {
+            match self.child.next().await? {
+                Some(tuple) => Ok(Some(Self::project(&self.columns, &tuple))),
+                None => Ok(None),
+            }
+        }
+        .boxed()
+    }
+}
+
+/// Stops pulling from `child` once `count` rows past `offset` have been
+/// produced, so a `LIMIT 10` over a huge input only ever drains as much of
+/// its child as it needs to.
+pub struct LimitNode {
+    child: Box<dyn ExecNode>,
+    remaining_offset: usize,
+    remaining_count: usize,
+}
+
+impl LimitNode {
+    pub fn new(child: Box<dyn ExecNode>, count: usize, offset: usize) -> Self {
+        Self {
+            child,
+            remaining_offset: offset,
+            remaining_count: count,
+        }
+    }
+}
+
+impl ExecNode for LimitNode {
+    fn next<'a>(&'a mut self) -> BoxFuture<'a, Result<Option<Tuple>>> {
+        async move {
+            if self.remaining_count == 0 {
+                return Ok(None);
+            }
+
+            while let Some(tuple) = self.child.next().await? {
+                if self.remaining_offset > 0 {
+                    self.remaining_offset -= 1;
+                    continue;
+                }
+
+                self.remaining_count -= 1;
+                return Ok(Some(tuple));
+            }
+
+            self.remaining_count = 0;
+            Ok(None)
+        }
+        .boxed()
+    }
+}
+
+/// Adapts an already fully materialized result set into the pull
+/// interface, for results that are built in one piece rather than by an
+/// operator of their own (e.g. `Exchange`'s repartitioned output or an
+/// RLS-filtered scan): this just hands them out one tuple at a time.
+pub struct MaterializedNode {
+    tuples: std::vec::IntoIter<Tuple>,
+}
+
+impl MaterializedNode {
+    pub fn new(tuples: Vec<Tuple>) -> Self {
+        Self {
+            tuples: tuples.into_iter(),
+        }
+    }
+}
+
+impl ExecNode for MaterializedNode {
+    fn next<'a>(&'a mut self) -> BoxFuture<'a, Result<Option<Tuple>>> {
+        async move { Ok(self.tuples.next()) }.boxed()
+    }
+}
@@ -1,110 +1,561 @@
-use crate::execution::tuple::Tuple;
-use crate::language::intent::FilterIntent;
+use crate::execution::expression::ExpressionEvaluator;
+use crate::execution::operators::exec_node::ExecNode;
+use crate::execution::operators::scan::probe_mock_index;
+use crate::execution::tuple::{Tuple, Value};
+use crate::language::ast::JoinType;
+use crate::language::intent::{ComparisonOp, ConstantValue, ExpressionIntent, FilterIntent, LogicalOp};
 use anyhow::Result;
+use futures::future::BoxFuture;
+use futures::FutureExt;
 use std::collections::HashMap;
 
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+struct CompositeKey(Vec<String>);
+
+/// Build/probe hash join. The build side is whichever input has fewer rows;
+/// matched build rows are tracked so `Left`/`Right`/`Full` can pad in the
+/// unmatched rows with `Value::Null` once the probe side is exhausted.
 pub struct HashJoin {
-    left: Vec<Tuple>,
-    right: Vec<Tuple>,
-    condition: FilterIntent,
-    hash_table: HashMap<String, Vec<Tuple>>,
-    left_pos: usize,
-    right_pos: usize,
+    results: Vec<Tuple>,
+    position: usize,
 }
 
 impl HashJoin {
-    pub fn new(left: Vec<Tuple>, right: Vec<Tuple>, condition: FilterIntent) -> Self {
-        let mut hash_table: HashMap<String, Vec<Tuple>> = HashMap::new();
+    pub fn new(
+        left: Vec<Tuple>,
+        right: Vec<Tuple>,
+        join_type: JoinType,
+        condition: FilterIntent,
+    ) -> Result<Self> {
+        let evaluator = ExpressionEvaluator::new();
+        let (equi_pairs, residual) = split_equi_join(&condition);
 
-        for tuple in &right {
-            let key = Self::extract_join_key(tuple);
-            hash_table.entry(key).or_default().push(tuple.clone());
-        }
+        let build_is_left = left.len() <= right.len();
+        let (build, probe) = if build_is_left {
+            (left, right)
+        } else {
+            (right, left)
+        };
 
-        Self {
-            left,
-            right,
-            condition,
-            hash_table,
-            left_pos: 0,
-            right_pos: 0,
+        let (build_keys, probe_keys) = orient_keys(&equi_pairs, build.first());
+
+        let mut hash_table: HashMap<CompositeKey, Vec<usize>> = HashMap::new();
+        for (idx, tuple) in build.iter().enumerate() {
+            let key = composite_key(&build_keys, tuple, &evaluator)?;
+            hash_table.entry(key).or_default().push(idx);
         }
-    }
 
-    pub fn next(&mut self) -> Result<Option<Tuple>> {
-        while self.left_pos < self.left.len() {
-            let left_tuple = &self.left[self.left_pos];
-            let key = Self::extract_join_key(left_tuple);
+        let mut matched = vec![false; build.len()];
+        let mut results = Vec::new();
+
+        let probe_is_outer = match join_type {
+            JoinType::Full => true,
+            JoinType::Left => !build_is_left,
+            JoinType::Right => build_is_left,
+            JoinType::Inner => false,
+        };
+        let build_is_outer = match join_type {
+            JoinType::Full => true,
+            JoinType::Left => build_is_left,
+            JoinType::Right => !build_is_left,
+            JoinType::Inner => false,
+        };
 
-            if let Some(matches) = self.hash_table.get(&key) {
-                if self.right_pos < matches.len() {
-                    let right_tuple = &matches[self.right_pos];
-                    self.right_pos += 1;
+        for probe_tuple in &probe {
+            let key = composite_key(&probe_keys, probe_tuple, &evaluator)?;
+            let mut any_match = false;
 
-                    let mut joined = left_tuple.clone();
+            if let Some(indices) = hash_table.get(&key) {
+                for &idx in indices {
+                    let build_tuple = &build[idx];
+                    let (left_tuple, right_tuple) = if build_is_left {
+                        (build_tuple, probe_tuple)
+                    } else {
+                        (probe_tuple, build_tuple)
+                    };
+
+                    let mut candidate = left_tuple.clone();
                     for (k, v) in &right_tuple.values {
-                        joined.insert(k.clone(), v.clone());
+                        candidate.insert(k.clone(), v.clone());
                     }
 
-                    return Ok(Some(joined));
+                    if evaluator.evaluate_filter(&residual, &candidate)? {
+                        matched[idx] = true;
+                        any_match = true;
+                        results.push(candidate);
+                    }
                 }
             }
 
-            self.left_pos += 1;
-            self.right_pos = 0;
+            if !any_match && probe_is_outer {
+                results.push(pad(probe_tuple, build.first()));
+            }
+        }
+
+        if build_is_outer {
+            for (idx, build_tuple) in build.iter().enumerate() {
+                if !matched[idx] {
+                    results.push(pad(build_tuple, probe.first()));
+                }
+            }
         }
 
-        Ok(None)
+        Ok(Self {
+            results,
+            position: 0,
+        })
     }
 
-    fn extract_join_key(tuple: &Tuple) -> String {
-        tuple
-            .get("id")
-            .map(|v| format!("{:?}", v))
-            .unwrap_or_default()
+    pub fn next(&mut self) -> Result<Option<Tuple>> {
+        if self.position >= self.results.len() {
+            return Ok(None);
+        }
+
+        let tuple = self.results[self.position].clone();
+        self.position += 1;
+        Ok(Some(tuple))
     }
 }
 
-pub struct NestedLoopJoin {
-    left: Vec<Tuple>,
-    right: Vec<Tuple>,
-    condition: FilterIntent,
-    left_pos: usize,
-    right_pos: usize,
+impl ExecNode for HashJoin {
+    fn next<'a>(&'a mut self) -> BoxFuture<'a, Result<Option<Tuple>>> {
+        async move { self.next() }.boxed()
+    }
 }
 
-impl NestedLoopJoin {
-    pub fn new(left: Vec<Tuple>, right: Vec<Tuple>, condition: FilterIntent) -> Self {
-        Self {
+/// Pads `tuple` with `Value::Null` for every column on the other side that
+/// it doesn't already have, using `other_side_sample` to learn that side's
+/// column set.
+fn pad(tuple: &Tuple, other_side_sample: Option<&Tuple>) -> Tuple {
+    let mut padded = tuple.clone();
+    if let Some(sample) = other_side_sample {
+        for column in sample.columns() {
+            padded.values.entry(column).or_insert(Value::Null);
+        }
+    }
+    padded
+}
+
+/// Splits an AND-conjunction condition into its equi-join column pairs
+/// (used to build/probe a hash table, or to sort/merge two inputs) and
+/// whatever residual predicate is left over, which is evaluated per
+/// candidate pair after the equi-join match is found.
+fn split_equi_join(condition: &FilterIntent) -> (Vec<(ExpressionIntent, ExpressionIntent)>, FilterIntent) {
+    match condition {
+        FilterIntent::Logical {
+            op: LogicalOp::And,
+            operands,
+        } => {
+            let mut equi = Vec::new();
+            let mut residual = Vec::new();
+
+            for operand in operands {
+                let (op_equi, op_residual) = split_equi_join(operand);
+                equi.extend(op_equi);
+                if !matches!(op_residual, FilterIntent::Always) {
+                    residual.push(op_residual);
+                }
+            }
+
+            let residual_filter = match residual.len() {
+                0 => FilterIntent::Always,
+                1 => residual.into_iter().next().unwrap(),
+                _ => FilterIntent::Logical {
+                    op: LogicalOp::And,
+                    operands: residual,
+                },
+            };
+
+            (equi, residual_filter)
+        }
+        FilterIntent::Comparison {
+            op: ComparisonOp::Equal,
             left,
             right,
-            condition,
-            left_pos: 0,
-            right_pos: 0,
+        } if is_column_ref(left) && is_column_ref(right) => {
+            (vec![(left.clone(), right.clone())], FilterIntent::Always)
         }
+        other => (vec![], other.clone()),
+    }
+}
+
+fn is_column_ref(expr: &ExpressionIntent) -> bool {
+    matches!(
+        expr,
+        ExpressionIntent::Column(_) | ExpressionIntent::QualifiedColumn { .. }
+    )
+}
+
+/// Tuples carry no table qualifier at runtime, so which side of each
+/// equi-join pair belongs to `first_side` is resolved by checking which
+/// expression's columns `first_side_sample` actually has.
+fn orient_keys(
+    equi_pairs: &[(ExpressionIntent, ExpressionIntent)],
+    first_side_sample: Option<&Tuple>,
+) -> (Vec<ExpressionIntent>, Vec<ExpressionIntent>) {
+    let mut first_side_keys = Vec::with_capacity(equi_pairs.len());
+    let mut other_side_keys = Vec::with_capacity(equi_pairs.len());
+
+    for (a, b) in equi_pairs {
+        let a_is_first_side = first_side_sample
+            .map(|t| references_tuple(a, t))
+            .unwrap_or(true);
+
+        if a_is_first_side {
+            first_side_keys.push(a.clone());
+            other_side_keys.push(b.clone());
+        } else {
+            first_side_keys.push(b.clone());
+            other_side_keys.push(a.clone());
+        }
+    }
+
+    (first_side_keys, other_side_keys)
+}
+
+fn references_tuple(expr: &ExpressionIntent, tuple: &Tuple) -> bool {
+    match expr {
+        ExpressionIntent::Column(name) => tuple.get(name).is_some(),
+        ExpressionIntent::QualifiedColumn { column, .. } => tuple.get(column).is_some(),
+        ExpressionIntent::Constant(_) => true,
+        ExpressionIntent::Arithmetic { left, right, .. } => {
+            references_tuple(left, tuple) && references_tuple(right, tuple)
+        }
+        ExpressionIntent::Function { args, .. } => args.iter().all(|a| references_tuple(a, tuple)),
+        ExpressionIntent::Cast { inner, .. } => references_tuple(inner, tuple),
+        ExpressionIntent::Placeholder(_) => true,
+    }
+}
+
+fn composite_key(keys: &[ExpressionIntent], tuple: &Tuple, evaluator: &ExpressionEvaluator) -> Result<CompositeKey> {
+    let mut parts = Vec::with_capacity(keys.len());
+    for key in keys {
+        let value = evaluator.evaluate(key, tuple)?;
+        parts.push(format!("{:?}", value));
+    }
+    Ok(CompositeKey(parts))
+}
+
+/// Classic sort-merge join: sorts both sides on their equi-join keys
+/// (skipping the sort for a side that is already in key order, e.g. from
+/// an indexed scan), then walks them with two cursors,
+/// advancing whichever side has the smaller key and emitting the
+/// cross-product of each run of equal keys when the cursors agree.
+/// O(n log n + m log m) to sort, O(n + m) to merge, versus `HashJoin`'s
+/// materialized build side.
+pub struct MergeJoin {
+    results: Vec<Tuple>,
+    position: usize,
+}
+
+impl MergeJoin {
+    pub fn new(
+        left: Vec<Tuple>,
+        right: Vec<Tuple>,
+        join_type: JoinType,
+        condition: FilterIntent,
+    ) -> Result<Self> {
+        let evaluator = ExpressionEvaluator::new();
+        let (equi_pairs, residual) = split_equi_join(&condition);
+
+        if equi_pairs.is_empty() {
+            anyhow::bail!("MergeJoin requires at least one equi-join condition");
+        }
+
+        let (left_keys, right_keys) = orient_keys(&equi_pairs, left.first());
+
+        let mut left = left;
+        let mut right = right;
+        Self::sort_by_keys(&mut left, &left_keys, &evaluator)?;
+        Self::sort_by_keys(&mut right, &right_keys, &evaluator)?;
+
+        let mut results = Vec::new();
+        let mut left_matched = vec![false; left.len()];
+        let mut right_matched = vec![false; right.len()];
+
+        let mut l = 0;
+        let mut r = 0;
+
+        while l < left.len() && r < right.len() {
+            let left_key = composite_key(&left_keys, &left[l], &evaluator)?;
+            let right_key = composite_key(&right_keys, &right[r], &evaluator)?;
+
+            match left_key.cmp(&right_key) {
+                std::cmp::Ordering::Less => l += 1,
+                std::cmp::Ordering::Greater => r += 1,
+                std::cmp::Ordering::Equal => {
+                    let left_start = l;
+                    while l < left.len() && composite_key(&left_keys, &left[l], &evaluator)? == left_key {
+                        l += 1;
+                    }
+                    let left_end = l;
+
+                    let right_start = r;
+                    while r < right.len() && composite_key(&right_keys, &right[r], &evaluator)? == right_key {
+                        r += 1;
+                    }
+                    let right_end = r;
+
+                    for li in left_start..left_end {
+                        for ri in right_start..right_end {
+                            let mut candidate = left[li].clone();
+                            for (k, v) in &right[ri].values {
+                                candidate.insert(k.clone(), v.clone());
+                            }
+
+                            if evaluator.evaluate_filter(&residual, &candidate)? {
+                                left_matched[li] = true;
+                                right_matched[ri] = true;
+                                results.push(candidate);
+                            }
+                        }
+                    }
+                }
+            }
+        }
+
+        if matches!(join_type, JoinType::Left | JoinType::Full) {
+            for (idx, tuple) in left.iter().enumerate() {
+                if !left_matched[idx] {
+                    results.push(pad(tuple, right.first()));
+                }
+            }
+        }
+
+        if matches!(join_type, JoinType::Right | JoinType::Full) {
+            for (idx, tuple) in right.iter().enumerate() {
+                if !right_matched[idx] {
+                    results.push(pad(tuple, left.first()));
+                }
+            }
+        }
+
+        Ok(Self {
+            results,
+            position: 0,
+        })
+    }
+
+    /// Sorts `tuples` by their join key unless they're already in that
+    /// order, so a caller that already has sorted input (e.g. from an
+    /// indexed scan) doesn't pay for a redundant sort.
+    fn sort_by_keys(tuples: &mut [Tuple], keys: &[ExpressionIntent], evaluator: &ExpressionEvaluator) -> Result<()> {
+        let mut keyed = Vec::with_capacity(tuples.len());
+        for tuple in tuples.iter() {
+            keyed.push(composite_key(keys, tuple, evaluator)?);
+        }
+
+        if keyed.windows(2).all(|w| w[0] <= w[1]) {
+            return Ok(());
+        }
+
+        let mut indexed: Vec<usize> = (0..tuples.len()).collect();
+        indexed.sort_by(|&a, &b| keyed[a].cmp(&keyed[b]));
+
+        let sorted: Vec<Tuple> = indexed.into_iter().map(|i| tuples[i].clone()).collect();
+        tuples.clone_from_slice(&sorted);
+
+        Ok(())
     }
 
     pub fn next(&mut self) -> Result<Option<Tuple>> {
-        if self.left_pos >= self.left.len() {
+        if self.position >= self.results.len() {
             return Ok(None);
         }
 
-        if self.right_pos < self.right.len() {
-            let left_tuple = &self.left[self.left_pos];
-            let right_tuple = &self.right[self.right_pos];
+        let tuple = self.results[self.position].clone();
+        self.position += 1;
+        Ok(Some(tuple))
+    }
+}
+
+impl ExecNode for MergeJoin {
+    fn next<'a>(&'a mut self) -> BoxFuture<'a, Result<Option<Tuple>>> {
+        async move { self.next() }.boxed()
+    }
+}
+
+/// Join against an indexed inner table: rather than materializing and
+/// hash-building the inner side, `outer` is streamed and each row's
+/// equi-join key is turned into an equality predicate that's looked up
+/// against the inner side's mock index data directly (`probe_mock_index`),
+/// the same way `IndexScan` evaluates a pushed-down predicate. Only
+/// `Inner`/`Left` are supported, since `Right`/`Full` need every unmatched
+/// row on the indexed side, which would mean scanning it in full anyway.
+pub struct IndexSemiJoin {
+    results: Vec<Tuple>,
+    position: usize,
+}
+
+impl IndexSemiJoin {
+    pub fn new(
+        outer: Vec<Tuple>,
+        inner_table: String,
+        inner_columns: Vec<String>,
+        join_type: JoinType,
+        condition: FilterIntent,
+    ) -> Result<Self> {
+        if matches!(join_type, JoinType::Right | JoinType::Full) {
+            anyhow::bail!("IndexSemiJoin only supports Inner/Left joins; Right/Full require materializing the indexed side");
+        }
+
+        let evaluator = ExpressionEvaluator::new();
+        let (equi_pairs, residual) = split_equi_join(&condition);
+
+        if equi_pairs.is_empty() {
+            anyhow::bail!("IndexSemiJoin requires at least one equi-join condition");
+        }
+
+        let (outer_keys, inner_keys) = orient_keys(&equi_pairs, outer.first());
+
+        let mut results = Vec::new();
 
-            let mut joined = left_tuple.clone();
-            for (k, v) in &right_tuple.values {
-                joined.insert(k.clone(), v.clone());
+        for outer_tuple in &outer {
+            let mut terms = Vec::with_capacity(inner_keys.len());
+            for (inner_key, outer_key) in inner_keys.iter().zip(outer_keys.iter()) {
+                let value = evaluator.evaluate(outer_key, outer_tuple)?;
+                terms.push(FilterIntent::Comparison {
+                    op: ComparisonOp::Equal,
+                    left: inner_key.clone(),
+                    right: ExpressionIntent::Constant(value_to_constant(&value)),
+                });
             }
 
-            self.right_pos += 1;
-            return Ok(Some(joined));
+            let lookup_predicate = match terms.len() {
+                1 => terms.into_iter().next().unwrap(),
+                _ => FilterIntent::Logical {
+                    op: LogicalOp::And,
+                    operands: terms,
+                },
+            };
+
+            let inner_matches = probe_mock_index(&inner_table, &inner_columns, &lookup_predicate)?;
+            let mut any_match = false;
+
+            for inner_tuple in &inner_matches {
+                let mut candidate = outer_tuple.clone();
+                for (k, v) in &inner_tuple.values {
+                    candidate.insert(k.clone(), v.clone());
+                }
+
+                if evaluator.evaluate_filter(&residual, &candidate)? {
+                    any_match = true;
+                    results.push(candidate);
+                }
+            }
+
+            if !any_match && matches!(join_type, JoinType::Left) {
+                let mut padded = outer_tuple.clone();
+                for column in &inner_columns {
+                    padded.values.entry(column.clone()).or_insert(Value::Null);
+                }
+                results.push(padded);
+            }
+        }
+
+        Ok(Self {
+            results,
+            position: 0,
+        })
+    }
+
+    pub fn next(&mut self) -> Result<Option<Tuple>> {
+        if self.position >= self.results.len() {
+            return Ok(None);
         }
 
-        self.left_pos += 1;
-        self.right_pos = 0;
+        let tuple = self.results[self.position].clone();
+        self.position += 1;
+        Ok(Some(tuple))
+    }
+}
+
+impl ExecNode for IndexSemiJoin {
+    fn next<'a>(&'a mut self) -> BoxFuture<'a, Result<Option<Tuple>>> {
+        async move { self.next() }.boxed()
+    }
+}
 
-        self.next()
+/// Converts a runtime `Value` back into the `ConstantValue` the language
+/// layer's `FilterIntent` expressions carry, so a probe row's join-key value
+/// can be folded into a lookup predicate for `probe_mock_index`.
+fn value_to_constant(value: &Value) -> ConstantValue {
+    match value {
+        Value::Null => ConstantValue::Null,
+        Value::Boolean(b) => ConstantValue::Boolean(*b),
+        Value::Integer(i) => ConstantValue::Integer(*i),
+        Value::Float(f) => ConstantValue::Float(*f),
+        Value::String(s) => ConstantValue::String(s.clone()),
     }
+}
+
+/// Brute-force join for conditions `HashJoin`/`MergeJoin` can't turn into an
+/// equi-join (e.g. a range predicate): evaluates `condition` against every
+/// left/right pair, and honors `join_type` the same way the other
+/// operators do by padding unmatched outer rows with `Value::Null`.
+pub struct NestedLoopJoin {
+    results: Vec<Tuple>,
+    position: usize,
+}
+
+impl NestedLoopJoin {
+    pub fn new(left: Vec<Tuple>, right: Vec<Tuple>, join_type: JoinType, condition: FilterIntent) -> Result<Self> {
+        let evaluator = ExpressionEvaluator::new();
+
+        let mut results = Vec::new();
+        let mut left_matched = vec![false; left.len()];
+        let mut right_matched = vec![false; right.len()];
+
+        for (li, left_tuple) in left.iter().enumerate() {
+            for (ri, right_tuple) in right.iter().enumerate() {
+                let mut candidate = left_tuple.clone();
+                for (k, v) in &right_tuple.values {
+                    candidate.insert(k.clone(), v.clone());
+                }
+
+                if evaluator.evaluate_filter(&condition, &candidate)? {
+                    left_matched[li] = true;
+                    right_matched[ri] = true;
+                    results.push(candidate);
+                }
+            }
+        }
+
+        if matches!(join_type, JoinType::Left | JoinType::Full) {
+            for (idx, tuple) in left.iter().enumerate() {
+                if !left_matched[idx] {
+                    results.push(pad(tuple, right.first()));
+                }
+            }
+        }
+
+        if matches!(join_type, JoinType::Right | JoinType::Full) {
+            for (idx, tuple) in right.iter().enumerate() {
+                if !right_matched[idx] {
+                    results.push(pad(tuple, left.first()));
+                }
             }
+        }
+
+        Ok(Self {
+            results,
+            position: 0,
+        })
+    }
+
+    pub fn next(&mut self) -> Result<Option<Tuple>> {
+        if self.position >= self.results.len() {
+            return Ok(None);
+        }
+
+        let tuple = self.results[self.position].clone();
+        self.position += 1;
+        Ok(Some(tuple))
+    }
+}
+
+impl ExecNode for NestedLoopJoin {
+    fn next<'a>(&'a mut self) -> BoxFuture<'a, Result<Option<Tuple>>> {
+        async move { self.next() }.boxed()
+    }
+}
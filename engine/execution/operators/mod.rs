@@ -2,8 +2,12 @@ pub mod scan;
 pub mod join;
 pub mod aggregate;
 pub mod mutate;
+pub mod exec_node;
+pub mod sort;
 
 pub use scan::*;
 pub use join::*;
 pub use aggregate::*;
 pub use mutate::*;
+pub use exec_node::*;
+pub use sort::*;
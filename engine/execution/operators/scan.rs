@@ -1,5 +1,13 @@
+use crate::determinism::clock::LogicalTime;
+use crate::execution::expression::ExpressionEvaluator;
+use crate::execution::operators::exec_node::ExecNode;
 use crate::execution::tuple::{Tuple, Value};
+use crate::language::intent::{FilterIntent, LogicalOp};
+use crate::planner::logical::KeyRange;
+use crate::transactions::time_travel::TimeTravelWindow;
 use anyhow::Result;
+use futures::future::BoxFuture;
+use futures::FutureExt;
 
 pub struct SeqScan {
     table: String,
@@ -9,15 +17,30 @@ pub struct SeqScan {
 }
 
 impl SeqScan {
-    pub fn new(table: String, columns: Vec<String>) -> Self {
-        let data = Self::generate_mock_data(&table, &columns);
-        
-        Self {
+    /// `key_range`, when present, is the range the optimizer proved the
+    /// scan's key column is bounded to (see `Optimizer::push_down_range_predicates`);
+    /// it's converted to a predicate and evaluated per row with
+    /// `ExpressionEvaluator`, the same mechanism `IndexScan` already uses,
+    /// since there's no real index structure below the mock data layer to
+    /// seek on instead.
+    pub fn new(
+        table: String,
+        columns: Vec<String>,
+        key_range: Option<KeyRange>,
+        as_of: Option<TimeTravelWindow>,
+    ) -> Result<Self> {
+        let predicate = key_range.map(|range| range.to_filter());
+        let data = mock_rows(&table, as_of.as_ref(), predicate.as_ref())?
+            .into_iter()
+            .map(|row| project(&row, &columns))
+            .collect();
+
+        Ok(Self {
             table,
             columns,
             position: 0,
             data,
-        }
+        })
     }
 
     pub fn next(&mut self) -> Result<Option<Tuple>> {
@@ -29,26 +52,11 @@ impl SeqScan {
         self.position += 1;
         Ok(Some(tuple))
     }
+}
 
-    fn generate_mock_data(table: &str, columns: &[String]) -> Vec<Tuple> {
-        let mut data = Vec::new();
-
-        for i in 0..10 {
-            let mut tuple = Tuple::new();
-            
-            for col in columns {
-                match col.as_str() {
-                    "id" => tuple.insert(col.clone(), Value::Integer(i as i64)),
-                    "name" => tuple.insert(col.clone(), Value::String(format!("user_{}", i))),
-                    "age" => tuple.insert(col.clone(), Value::Integer(20 + i as i64)),
-                    _ => tuple.insert(col.clone(), Value::Null),
-                }
-            }
-
-            data.push(tuple);
-        }
-
-        data
+impl ExecNode for SeqScan {
+    fn next<'a>(&'a mut self) -> BoxFuture<'a, Result<Option<Tuple>>> {
+        async move { self.next() }.boxed()
     }
 }
 
@@ -57,19 +65,140 @@ pub struct IndexScan {
     index: String,
     columns: Vec<String>,
     position: usize,
+    data: Vec<Tuple>,
 }
 
 impl IndexScan {
-    pub fn new(table: String, index: String, columns: Vec<String>) -> Self {
-        Self {
+    /// `predicate`, when present, is the pushed-down comparison the
+    /// optimizer proved the index covers (see `PhysicalPlanner::plan_local`);
+    /// `key_range`, when present, is the bound the range-pushdown rule proved
+    /// on the scan's key column (see `Optimizer::push_down_range_predicates`)
+    /// and is ANDed in alongside it. Both are evaluated per row with
+    /// `ExpressionEvaluator`, the same mechanism `FilterNode` uses, since
+    /// there's no real index structure below the mock data layer to probe
+    /// instead.
+    pub fn new(
+        table: String,
+        index: String,
+        columns: Vec<String>,
+        predicate: Option<FilterIntent>,
+        key_range: Option<KeyRange>,
+        as_of: Option<TimeTravelWindow>,
+    ) -> Result<Self> {
+        let predicate = Self::combine(key_range, predicate);
+        let data = mock_rows(&table, as_of.as_ref(), predicate.as_ref())?
+            .into_iter()
+            .map(|row| project(&row, &columns))
+            .collect();
+
+        Ok(Self {
             table,
             index,
             columns,
             position: 0,
+            data,
+        })
+    }
+
+    fn combine(key_range: Option<KeyRange>, predicate: Option<FilterIntent>) -> Option<FilterIntent> {
+        match (key_range.map(|range| range.to_filter()), predicate) {
+            (Some(range_filter), Some(predicate)) => Some(FilterIntent::Logical {
+                op: LogicalOp::And,
+                operands: vec![range_filter, predicate],
+            }),
+            (Some(range_filter), None) => Some(range_filter),
+            (None, predicate) => predicate,
         }
     }
 
     pub fn next(&mut self) -> Result<Option<Tuple>> {
-        Ok(None)
+        if self.position >= self.data.len() {
+            return Ok(None);
+        }
+
+        let tuple = self.data[self.position].clone();
+        self.position += 1;
+        Ok(Some(tuple))
+    }
+}
+
+impl ExecNode for IndexScan {
+    fn next<'a>(&'a mut self) -> BoxFuture<'a, Result<Option<Tuple>>> {
+        async move { self.next() }.boxed()
+    }
+}
+
+/// The full (`id`, `name`, `age`) mock row for index `i`, shared by
+/// `SeqScan`/`IndexScan`/`IndexSemiJoin` so a pushed-down predicate can be
+/// evaluated against columns that weren't necessarily requested in the
+/// projection.
+fn full_mock_row(i: u32) -> (Tuple, LogicalTime, Option<LogicalTime>) {
+    let commit_time = LogicalTime::new(0, i as u64 * 1_000_000);
+    let delete_time = if i % 3 == 2 {
+        Some(LogicalTime::new(0, (i as u64 + 1) * 1_000_000))
+    } else {
+        None
+    };
+
+    let mut tuple = Tuple::new();
+    tuple.insert("id".to_string(), Value::Integer(i as i64));
+    tuple.insert("name".to_string(), Value::String(format!("user_{}", i)));
+    tuple.insert("age".to_string(), Value::Integer(20 + i as i64));
+
+    (tuple, commit_time, delete_time)
+}
+
+/// The shared mock-data source for `table`: 10 full rows, filtered by
+/// `as_of` (if this is a time-travel scan) and `predicate` (if the optimizer
+/// pushed one down to an `IndexScan`/`IndexSemiJoin`). `table` isn't
+/// consulted yet since every table shares the same mock fixture.
+fn mock_rows(
+    _table: &str,
+    as_of: Option<&TimeTravelWindow>,
+    predicate: Option<&FilterIntent>,
+) -> Result<Vec<Tuple>> {
+    let evaluator = ExpressionEvaluator::new();
+    let mut rows = Vec::new();
+
+    for i in 0..10 {
+        let (tuple, commit_time, delete_time) = full_mock_row(i);
+
+        if let Some(window) = as_of {
+            if !window.includes(commit_time, delete_time) {
+                continue;
+            }
+        }
+
+        if let Some(predicate) = predicate {
+            if !evaluator.evaluate_filter(predicate, &tuple)? {
+                continue;
+            }
+        }
+
+        rows.push(tuple);
+    }
+
+    Ok(rows)
+}
+
+/// One-shot equivalent of `IndexScan` used by `IndexSemiJoin`: looks up the
+/// mock rows on `table` matching `predicate` (typically an equality
+/// comparison on the probe row's join key) without any scan-position state,
+/// since the join operator calls this once per outer row rather than
+/// pulling from an `ExecNode`.
+pub fn probe_mock_index(table: &str, columns: &[String], predicate: &FilterIntent) -> Result<Vec<Tuple>> {
+    Ok(mock_rows(table, None, Some(predicate))?
+        .iter()
+        .map(|row| project(row, columns))
+        .collect())
+}
+
+/// Projects `row` down to `columns`, inserting `Value::Null` for any column
+/// this mock fixture doesn't know about.
+fn project(row: &Tuple, columns: &[String]) -> Tuple {
+    let mut tuple = Tuple::new();
+    for col in columns {
+        tuple.insert(col.clone(), row.get(col).cloned().unwrap_or(Value::Null));
     }
+    tuple
 }
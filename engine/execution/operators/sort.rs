@@ -0,0 +1,140 @@
+use crate::execution::expression::ExpressionEvaluator;
+use crate::execution::operators::aggregate::HashAggregate;
+use crate::execution::operators::exec_node::ExecNode;
+use crate::execution::tuple::{Tuple, Value};
+use crate::language::intent::{NullsOrder, OrderIntent};
+use anyhow::Result;
+use futures::future::BoxFuture;
+use futures::FutureExt;
+use std::cmp::Ordering;
+
+/// `ORDER BY` is an inherent pipeline breaker like `HashAggregate`: it must
+/// see all of its input before it can produce a first row, so it sorts
+/// `input` once up front and hands the result out one tuple at a time.
+pub struct SortNode {
+    result_iter: std::vec::IntoIter<Tuple>,
+}
+
+impl SortNode {
+    pub fn new(input: Vec<Tuple>, order_by: Vec<OrderIntent>) -> Result<Self> {
+        let evaluator = ExpressionEvaluator::new();
+
+        let mut keyed = input
+            .into_iter()
+            .map(|tuple| {
+                let key = order_by
+                    .iter()
+                    .map(|order| evaluator.evaluate(&order.expr, &tuple))
+                    .collect::<Result<Vec<Value>>>()?;
+                Ok((key, tuple))
+            })
+            .collect::<Result<Vec<(Vec<Value>, Tuple)>>>()?;
+
+        keyed.sort_by(|(a, _), (b, _)| {
+            for (idx, order) in order_by.iter().enumerate() {
+                let ordering = Self::compare(&a[idx], &b[idx], order);
+                if ordering != Ordering::Equal {
+                    return ordering;
+                }
+            }
+            Ordering::Equal
+        });
+
+        Ok(Self {
+            result_iter: keyed.into_iter().map(|(_, tuple)| tuple).collect::<Vec<_>>().into_iter(),
+        })
+    }
+
+    /// Orders two evaluations of one `ORDER BY` key: a null sorts per
+    /// `order.nulls` if given, else the SQL-standard default (NULLS LAST for
+    /// ascending, NULLS FIRST for descending); everything else compares by
+    /// value and is reversed when `order.ascending` is false.
+    fn compare(a: &Value, b: &Value, order: &OrderIntent) -> Ordering {
+        if a.is_null() || b.is_null() {
+            let nulls_first = match order.nulls {
+                Some(NullsOrder::First) => true,
+                Some(NullsOrder::Last) => false,
+                None => !order.ascending,
+            };
+
+            return match (a.is_null(), b.is_null()) {
+                (true, true) => Ordering::Equal,
+                (true, false) => if nulls_first { Ordering::Less } else { Ordering::Greater },
+                (false, true) => if nulls_first { Ordering::Greater } else { Ordering::Less },
+                (false, false) => unreachable!(),
+            };
+        }
+
+        let ordering = Self::compare_non_null(a, b);
+        if order.ascending {
+            ordering
+        } else {
+            ordering.reverse()
+        }
+    }
+
+    fn compare_non_null(a: &Value, b: &Value) -> Ordering {
+        match (a, b) {
+            (Value::Integer(x), Value::Integer(y)) => x.cmp(y),
+            (Value::Float(x), Value::Float(y)) => x.partial_cmp(y).unwrap_or(Ordering::Equal),
+            (Value::Integer(x), Value::Float(y)) => (*x as f64).partial_cmp(y).unwrap_or(Ordering::Equal),
+            (Value::Float(x), Value::Integer(y)) => x.partial_cmp(&(*y as f64)).unwrap_or(Ordering::Equal),
+            (Value::String(x), Value::String(y)) => x.cmp(y),
+            (Value::Boolean(x), Value::Boolean(y)) => x.cmp(y),
+            _ => Ordering::Equal,
+        }
+    }
+}
+
+impl ExecNode for SortNode {
+    fn next<'a>(&'a mut self) -> BoxFuture<'a, Result<Option<Tuple>>> {
+        async move { Ok(self.result_iter.next()) }.boxed()
+    }
+}
+
+/// `DISTINCT` is also an inherent pipeline breaker: a row can only be
+/// dropped as a duplicate once every row before it has been seen, so this
+/// dedupes `input` once up front (keeping the first occurrence of each
+/// distinct row) and hands the result out one tuple at a time.
+pub struct DistinctNode {
+    result_iter: std::vec::IntoIter<Tuple>,
+}
+
+impl DistinctNode {
+    pub fn new(input: Vec<Tuple>) -> Self {
+        let mut seen = std::collections::HashSet::new();
+        let mut deduped = Vec::with_capacity(input.len());
+
+        for tuple in input {
+            if seen.insert(Self::row_key(&tuple)) {
+                deduped.push(tuple);
+            }
+        }
+
+        Self {
+            result_iter: deduped.into_iter(),
+        }
+    }
+
+    /// Serializes every column, sorted by name so two tuples with the same
+    /// columns in different `HashMap` iteration order still produce the
+    /// same key, tagged by `Value` variant the same way `HashAggregate`
+    /// tags its group keys so e.g. the integer `1` and the string `"1"`
+    /// never collide.
+    fn row_key(tuple: &Tuple) -> String {
+        let mut columns: Vec<&String> = tuple.values.keys().collect();
+        columns.sort();
+
+        columns
+            .into_iter()
+            .map(|col| format!("{}={}", col, HashAggregate::tag(&tuple.values[col])))
+            .collect::<Vec<_>>()
+            .join("|")
+    }
+}
+
+impl ExecNode for DistinctNode {
+    fn next<'a>(&'a mut self) -> BoxFuture<'a, Result<Option<Tuple>>> {
+        async move { Ok(self.result_iter.next()) }.boxed()
+    }
+}
@@ -1,3 +1,7 @@
+use std::fmt;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::thread::ThreadId;
 use std::time::{Duration, Instant};
 
 #[derive(Debug, Clone)]
@@ -17,10 +21,131 @@ impl Default for QueryLimits {
     }
 }
 
+/// Which limit a `Sandbox` tripped, so the protocol layer can report a
+/// specific client-facing reason instead of a generic failure.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LimitKind {
+    WallTime,
+    CpuTime,
+    Memory,
+}
+
+impl fmt::Display for LimitKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            LimitKind::WallTime => write!(f, "wall time"),
+            LimitKind::CpuTime => write!(f, "CPU time"),
+            LimitKind::Memory => write!(f, "memory"),
+        }
+    }
+}
+
+/// Distinguishes a resource limit trip from a cooperative cancellation, so
+/// callers can map each to its own response rather than a generic bail.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SandboxError {
+    LimitExceeded { kind: LimitKind },
+    Cancelled,
+}
+
+impl fmt::Display for SandboxError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SandboxError::LimitExceeded { kind } => write!(f, "query exceeded {} limit", kind),
+            SandboxError::Cancelled => write!(f, "query was cancelled"),
+        }
+    }
+}
+
+impl std::error::Error for SandboxError {}
+
+/// Reads the calling thread's CPU time via `clock_gettime(CLOCK_THREAD_CPUTIME_ID, ...)`.
+/// Returns `None` on platforms where that clock isn't available, so `Sandbox`
+/// can fall back to wall time instead.
+#[cfg(unix)]
+fn thread_cpu_time() -> Option<Duration> {
+    #[repr(C)]
+    struct Timespec {
+        tv_sec: i64,
+        tv_nsec: i64,
+    }
+
+    extern "C" {
+        fn clock_gettime(clk_id: i32, tp: *mut Timespec) -> i32;
+    }
+
+    const CLOCK_THREAD_CPUTIME_ID: i32 = 3;
+
+    let mut ts = Timespec { tv_sec: 0, tv_nsec: 0 };
+    let rc = unsafe { clock_gettime(CLOCK_THREAD_CPUTIME_ID, &mut ts) };
+    if rc == 0 {
+        Some(Duration::new(ts.tv_sec.max(0) as u64, ts.tv_nsec.max(0) as u32))
+    } else {
+        None
+    }
+}
+
+#[cfg(not(unix))]
+fn thread_cpu_time() -> Option<Duration> {
+    None
+}
+
+/// `CLOCK_THREAD_CPUTIME_ID` is scoped to whichever OS thread reads it, so a
+/// single reading is only meaningful relative to another reading taken on
+/// the *same* thread. `Sandbox::check` is called repeatedly across
+/// `.await` points, and on the default multi-threaded Tokio runtime a task
+/// can resume on a different worker thread after an await — so this tracks
+/// a running total instead of one start/now subtraction, only folding a
+/// reading into that total when it was taken on the same thread as the
+/// previous one; a migration's gap is charged in wall time instead, since
+/// there's no way to diff two unrelated thread clocks.
+#[derive(Clone)]
+struct CpuUsage {
+    accumulated: Duration,
+    last_sample: Option<(ThreadId, Duration)>,
+    last_check: Instant,
+}
+
+impl CpuUsage {
+    fn new() -> Self {
+        Self {
+            accumulated: Duration::ZERO,
+            last_sample: thread_cpu_time().map(|reading| (std::thread::current().id(), reading)),
+            last_check: Instant::now(),
+        }
+    }
+
+    /// Folds the CPU time consumed since the previous call into
+    /// `accumulated` and returns the new running total, or `None` if this
+    /// platform has no thread CPU clock (per `thread_cpu_time`'s contract),
+    /// so the caller can fall back to wall time entirely.
+    fn sample(&mut self) -> Option<Duration> {
+        let now = Instant::now();
+        let reading = thread_cpu_time()?;
+        let thread_id = std::thread::current().id();
+
+        match self.last_sample {
+            Some((last_thread, last_reading)) if last_thread == thread_id => {
+                self.accumulated += reading.saturating_sub(last_reading);
+            }
+            _ => {
+                self.accumulated += now.saturating_duration_since(self.last_check);
+            }
+        }
+
+        self.last_sample = Some((thread_id, reading));
+        self.last_check = now;
+        Some(self.accumulated)
+    }
+}
+
+#[derive(Clone)]
 pub struct Sandbox {
     limits: QueryLimits,
     start_time: Instant,
+    cpu: CpuUsage,
     memory_used: usize,
+    cancelled: Arc<AtomicBool>,
 }
 
 impl Sandbox {
@@ -28,19 +153,33 @@ impl Sandbox {
         Self {
             limits,
             start_time: Instant::now(),
+            cpu: CpuUsage::new(),
             memory_used: 0,
+            cancelled: Arc::new(AtomicBool::new(false)),
         }
     }
 
-    pub fn check(&self) -> anyhow::Result<()> {
-        let elapsed = self.start_time.elapsed();
-        
-        if elapsed > self.limits.max_wall_time {
-            anyhow::bail!("Query exceeded wall time limit");
+    /// Executors should call this at loop boundaries (per scanned row,
+    /// per join probe, ...) and bail out as soon as it returns `Err`.
+    pub fn check(&mut self) -> Result<(), SandboxError> {
+        if self.cancelled.load(Ordering::Relaxed) {
+            return Err(SandboxError::Cancelled);
+        }
+
+        let wall_elapsed = self.start_time.elapsed();
+        if wall_elapsed > self.limits.max_wall_time {
+            return Err(SandboxError::LimitExceeded { kind: LimitKind::WallTime });
+        }
+
+        // Falls back to wall time on a platform without a thread CPU clock,
+        // per `thread_cpu_time`'s contract.
+        let cpu_elapsed = self.cpu.sample().unwrap_or(wall_elapsed);
+        if cpu_elapsed > self.limits.max_cpu_time {
+            return Err(SandboxError::LimitExceeded { kind: LimitKind::CpuTime });
         }
 
         if self.memory_used > self.limits.max_memory {
-            anyhow::bail!("Query exceeded memory limit");
+            return Err(SandboxError::LimitExceeded { kind: LimitKind::Memory });
         }
 
         Ok(())
@@ -53,4 +192,16 @@ impl Sandbox {
     pub fn elapsed(&self) -> Duration {
         self.start_time.elapsed()
     }
+
+    /// Requests cooperative cancellation. Takes effect the next time any
+    /// clone of this sandbox calls `check()`, so the `Lifecycle`/server
+    /// layer can abort a running query (client disconnect, admin request)
+    /// by holding on to a cloned `Sandbox` and calling this.
+    pub fn cancel(&self) {
+        self.cancelled.store(true, Ordering::Relaxed);
+    }
+
+    pub fn is_cancelled(&self) -> bool {
+        self.cancelled.load(Ordering::Relaxed)
+    }
 }
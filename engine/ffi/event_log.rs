@@ -0,0 +1,179 @@
+use anyhow::Result;
+use std::ffi::CString;
+use std::os::raw::c_char;
+
+extern "C" {
+    fn event_log_open(data_dir: *const c_char) -> *mut std::ffi::c_void;
+    fn event_log_close(handle: *mut std::ffi::c_void);
+    fn event_log_append(handle: *mut std::ffi::c_void, record: *const u8, record_len: usize) -> i32;
+    fn event_log_read_all(
+        handle: *mut std::ffi::c_void,
+        buf_out: *mut *mut u8,
+        buf_len_out: *mut usize,
+    ) -> i32;
+    fn event_log_free_buffer(buf: *mut u8, buf_len: usize);
+    fn event_log_compact(handle: *mut std::ffi::c_void, records: *const u8, records_len: usize) -> i32;
+    fn event_log_write_snapshot(
+        handle: *mut std::ffi::c_void,
+        aggregate_id: *const c_char,
+        data: *const u8,
+        data_len: usize,
+    ) -> i32;
+    fn event_log_read_snapshot(
+        handle: *mut std::ffi::c_void,
+        aggregate_id: *const c_char,
+        buf_out: *mut *mut u8,
+        buf_len_out: *mut usize,
+    ) -> i32;
+}
+
+/// The native, page-manager-backed counterpart to `ffi::storage::StorageEngine`,
+/// but for `streams::event_sourcing::EventStore`: events are appended as
+/// length-prefixed records to a dedicated WAL segment instead of the table
+/// storage, and snapshots are written through the same page manager so a
+/// crash between two appends never loses more than the unflushed tail.
+pub struct EventLogEngine {
+    handle: *mut std::ffi::c_void,
+}
+
+unsafe impl Send for EventLogEngine {}
+unsafe impl Sync for EventLogEngine {}
+
+impl EventLogEngine {
+    pub fn open(data_dir: &str) -> Result<Self> {
+        let c_dir = CString::new(data_dir)?;
+        let handle = unsafe { event_log_open(c_dir.as_ptr()) };
+
+        if handle.is_null() {
+            anyhow::bail!("Failed to open event log at '{}'", data_dir);
+        }
+
+        Ok(Self { handle })
+    }
+
+    /// Appends `payload` as one length-prefixed record. The length prefix is
+    /// written here rather than left to the native layer so `read_all` can
+    /// split the log back into records without the native side having to
+    /// understand anything about `Event`'s shape.
+    pub fn append(&self, payload: &[u8]) -> Result<()> {
+        let record = Self::frame(payload);
+
+        let result = unsafe { event_log_append(self.handle, record.as_ptr(), record.len()) };
+        if result != 0 {
+            anyhow::bail!("Failed to append event record: error code {}", result);
+        }
+        Ok(())
+    }
+
+    /// Every record currently in the log, oldest first: the records the
+    /// last `compact` rewrote it to, followed by everything appended since.
+    pub fn read_all(&self) -> Result<Vec<Vec<u8>>> {
+        let mut buf: *mut u8 = std::ptr::null_mut();
+        let mut buf_len: usize = 0;
+
+        let result = unsafe { event_log_read_all(self.handle, &mut buf, &mut buf_len) };
+        if result != 0 {
+            anyhow::bail!("Failed to read event log: error code {}", result);
+        }
+
+        if buf.is_null() || buf_len == 0 {
+            return Ok(Vec::new());
+        }
+
+        let bytes = unsafe { std::slice::from_raw_parts(buf, buf_len) }.to_vec();
+        unsafe { event_log_free_buffer(buf, buf_len) };
+
+        Self::unframe(&bytes)
+    }
+
+    /// Rewrites the log to contain exactly `records`, then fsyncs and swaps
+    /// it in for the previous segment, the same "write a new segment, flush,
+    /// swap" shape `StorageEngine::checkpoint` follows for table storage.
+    pub fn compact(&self, records: &[Vec<u8>]) -> Result<()> {
+        let mut framed = Vec::new();
+        for record in records {
+            framed.extend_from_slice(&Self::frame(record));
+        }
+
+        let result = unsafe { event_log_compact(self.handle, framed.as_ptr(), framed.len()) };
+        if result != 0 {
+            anyhow::bail!("Failed to compact event log: error code {}", result);
+        }
+        Ok(())
+    }
+
+    pub fn write_snapshot(&self, aggregate_id: &str, data: &[u8]) -> Result<()> {
+        let c_aggregate_id = CString::new(aggregate_id)?;
+
+        let result = unsafe {
+            event_log_write_snapshot(self.handle, c_aggregate_id.as_ptr(), data.as_ptr(), data.len())
+        };
+        if result != 0 {
+            anyhow::bail!(
+                "Failed to write snapshot for aggregate '{}': error code {}",
+                aggregate_id,
+                result
+            );
+        }
+        Ok(())
+    }
+
+    pub fn read_snapshot(&self, aggregate_id: &str) -> Result<Option<Vec<u8>>> {
+        let c_aggregate_id = CString::new(aggregate_id)?;
+        let mut buf: *mut u8 = std::ptr::null_mut();
+        let mut buf_len: usize = 0;
+
+        let result = unsafe {
+            event_log_read_snapshot(self.handle, c_aggregate_id.as_ptr(), &mut buf, &mut buf_len)
+        };
+        if result != 0 {
+            anyhow::bail!(
+                "Failed to read snapshot for aggregate '{}': error code {}",
+                aggregate_id,
+                result
+            );
+        }
+
+        if buf.is_null() || buf_len == 0 {
+            return Ok(None);
+        }
+
+        let bytes = unsafe { std::slice::from_raw_parts(buf, buf_len) }.to_vec();
+        unsafe { event_log_free_buffer(buf, buf_len) };
+        Ok(Some(bytes))
+    }
+
+    fn frame(payload: &[u8]) -> Vec<u8> {
+        let mut record = Vec::with_capacity(4 + payload.len());
+        record.extend_from_slice(&(payload.len() as u32).to_le_bytes());
+        record.extend_from_slice(payload);
+        record
+    }
+
+    fn unframe(bytes: &[u8]) -> Result<Vec<Vec<u8>>> {
+        let mut records = Vec::new();
+        let mut offset = 0;
+
+        while offset + 4 <= bytes.len() {
+            let len = u32::from_le_bytes(bytes[offset..offset + 4].try_into().unwrap()) as usize;
+            offset += 4;
+
+            if offset + len > bytes.len() {
+                anyhow::bail!("corrupt event log: truncated record at offset {}", offset);
+            }
+
+            records.push(bytes[offset..offset + len].to_vec());
+            offset += len;
+        }
+
+        Ok(records)
+    }
+}
+
+impl Drop for EventLogEngine {
+    fn drop(&mut self) {
+        if !self.handle.is_null() {
+            unsafe { event_log_close(self.handle) };
+        }
+    }
+}
@@ -0,0 +1,2 @@
+pub mod event_log;
+pub mod storage;
@@ -1,4 +1,6 @@
+use crate::storage::{StorageBackend, TableProvider, TableStatistics};
 use anyhow::Result;
+use std::collections::HashMap;
 use std::ffi::CString;
 use std::os::raw::c_char;
 
@@ -231,3 +233,65 @@ impl Drop for StorageEngine {
         }
     }
 }
+
+impl StorageBackend for StorageEngine {
+    fn create_table(&self, table_name: &str, schema: &str) -> Result<()> {
+        StorageEngine::create_table(self, table_name, schema)
+    }
+
+    fn insert_row(&self, table_name: &str, data: &[u8]) -> Result<u64> {
+        StorageEngine::insert_row(self, table_name, data)
+    }
+
+    fn update_rows(&self, table_name: &str, predicate: &str, data: &[u8]) -> Result<usize> {
+        StorageEngine::update_rows(self, table_name, predicate, data)
+    }
+
+    fn delete_rows(&self, table_name: &str, predicate: &str) -> Result<usize> {
+        StorageEngine::delete_rows(self, table_name, predicate)
+    }
+
+    fn checkpoint(&self) -> Result<()> {
+        StorageEngine::checkpoint(self)
+    }
+
+    fn wal_flush(&self) -> Result<()> {
+        StorageEngine::wal_flush(self)
+    }
+
+    fn recover(&self) -> Result<()> {
+        StorageEngine::recover(self)
+    }
+}
+
+impl TableProvider for StorageEngine {
+    fn scan(
+        &self,
+        table: &str,
+        columns: &[String],
+    ) -> std::pin::Pin<Box<dyn std::future::Future<Output = Result<TableStatistics>> + Send + '_>>
+    {
+        let table = table.to_string();
+        let columns = columns.to_vec();
+
+        Box::pin(async move {
+            // The C storage layer doesn't expose a catalog/statistics query
+            // yet, so this reports conservative placeholder stats rather
+            // than blocking the planner on a real scan. Once
+            // `storage_table_stats` lands in the extern block above, this is
+            // where it gets called (behind a `spawn_blocking`, since the FFI
+            // call is synchronous).
+            tracing::debug!(
+                "gathering statistics for table '{}' ({} columns requested)",
+                table,
+                columns.len()
+            );
+
+            Ok(TableStatistics {
+                row_count: 1000,
+                columns: HashMap::new(),
+                indexes: Vec::new(),
+            })
+        })
+    }
+}
@@ -1,16 +1,57 @@
+use crate::execution::engine::ExecutionEngine;
 use crate::execution::tuple::Tuple;
-use crate::graphql::schema::GraphQLSchema;
+use crate::graphql::schema::{GraphQLArgument, GraphQLQuery, GraphQLSchema, SchemaGenerator};
+use crate::language::ast::{Expression, Literal, Statement};
+use crate::language::catalog::Catalog;
+use crate::language::parser::Parser;
+use crate::planner::logical::LogicalPlanner;
+use crate::planner::physical::PhysicalPlanner;
+use crate::storage::StorageBackend;
 use anyhow::Result;
+use serde::Deserialize;
 use serde_json::Value;
 use std::collections::HashMap;
+use std::sync::Arc;
+use tokio::io::{AsyncBufReadExt, AsyncWriteExt, BufReader};
+use tokio::net::tcp::OwnedWriteHalf;
+use tokio::net::TcpStream;
+use tokio::sync::RwLock;
 
 pub struct GraphQLResolver {
     schema: GraphQLSchema,
+    storage: Arc<dyn StorageBackend>,
+    catalog: Arc<RwLock<Catalog>>,
+}
+
+/// One `@defer`d field's worth of data, keyed by the JSON path (mixing
+/// field-name and list-index segments, e.g. `["listWidgets", 0, "price"]`)
+/// it fills into the client's already-received initial payload.
+#[derive(Debug, Clone)]
+pub struct DeferredPatch {
+    pub path: Vec<Value>,
+    pub data: Value,
+}
+
+/// A row from `resolve_query_deferred`'s primary fetch that still has
+/// `deferred` fields owed to it: `id` lets `resolve_deferred_patches` match
+/// its second query's rows back to this one, and `base_path` is where those
+/// fields' patches should point.
+#[derive(Debug, Clone)]
+pub struct PendingDeferred {
+    base_path: Vec<Value>,
+    id: Value,
+}
+
+#[derive(Debug, Deserialize)]
+struct DeferredRequest {
+    query: String,
+    #[serde(default)]
+    arguments: HashMap<String, Value>,
 }
 
 impl GraphQLResolver {
-    pub fn new(schema: GraphQLSchema) -> Self {
-        Self { schema }
+    pub fn new(schema: GraphQLSchema, storage: Arc<dyn StorageBackend>, catalog: Arc<RwLock<Catalog>>) -> Self {
+        Self { schema, storage, catalog }
     }
 
     pub async fn resolve_query(
@@ -21,26 +62,590 @@ impl GraphQLResolver {
         let query = self.schema.queries.get(query_name)
             .ok_or_else(|| anyhow::anyhow!("Query not found: {}", query_name))?;
 
-        let sql = self.build_sql(&query.sql_template, &arguments)?;
+        if query.return_type.ends_with("Connection") {
+            return self.resolve_connection_query(query, arguments).await;
+        }
+
+        let (rows, is_list, type_name) = self.resolve_rows(query, &arguments).await?;
+
+        let results = rows
+            .iter()
+            .map(|tuple| self.tuple_to_json(tuple, &type_name))
+            .collect::<Result<Vec<_>>>()?;
+
+        if is_list {
+            Ok(Value::Array(results))
+        } else {
+            Ok(results.into_iter().next().unwrap_or(Value::Null))
+        }
+    }
+
+    /// Resolves `query_name` like `resolve_query`, but fetches only the
+    /// fields the schema doesn't mark `deferred` up front and returns that
+    /// primary payload plus one `PendingDeferred` (result path and row id)
+    /// per resolved row, which `resolve_deferred_patches` takes to fetch and
+    /// deliver the deferred fields in a second, later query. This is what makes `deferred` fields actually lazy: the
+    /// primary payload only waits on the cheap columns, and a caller that
+    /// never asks for the patches never pays for the expensive ones at all.
+    pub async fn resolve_query_deferred(
+        &self,
+        query_name: &str,
+        arguments: &HashMap<String, Value>,
+    ) -> Result<(Value, Vec<PendingDeferred>)> {
+        let query = self.schema.queries.get(query_name)
+            .ok_or_else(|| anyhow::anyhow!("Query not found: {}", query_name))?;
+        anyhow::ensure!(
+            !query.return_type.ends_with("Connection"),
+            "@defer is not supported for connection-paginated queries: {}",
+            query_name
+        );
+
+        let is_list = query.return_type.starts_with('[');
+        let type_name = query.return_type.trim_start_matches('[').trim_end_matches(']').to_string();
+        let gql_type = self.schema.types.get(&type_name)
+            .ok_or_else(|| anyhow::anyhow!("Type not found: {}", type_name))?;
+        let id_column = Self::id_column(gql_type);
+
+        if !gql_type.fields.iter().any(|field| field.deferred) {
+            let (rows, is_list, type_name) = self.resolve_rows(query, arguments).await?;
+            let results = rows.iter().map(|tuple| self.tuple_to_json(tuple, &type_name)).collect::<Result<Vec<_>>>()?;
+            let data = if is_list { Value::Array(results) } else { results.into_iter().next().unwrap_or(Value::Null) };
+            return Ok((data, Vec::new()));
+        }
+
+        let mut primary_columns: Vec<String> = gql_type.fields.iter()
+            .filter(|field| !field.deferred)
+            .map(|field| field.column_mapping.clone().unwrap_or_else(|| field.name.clone()))
+            .collect();
+        if !primary_columns.iter().any(|col| col == &id_column) {
+            primary_columns.push(id_column.clone());
+        }
+
+        let rows = self.resolve_rows_with_projection(query, arguments, &primary_columns).await?;
+
+        let mut primary_results = Vec::with_capacity(rows.len());
+        let mut pending = Vec::with_capacity(rows.len());
+
+        for (index, tuple) in rows.iter().enumerate() {
+            primary_results.push(self.tuple_to_json(tuple, &type_name)?);
+
+            if let Some(id) = tuple.get(&id_column) {
+                let base_path = if is_list {
+                    vec![Value::String(query_name.to_string()), Value::from(index)]
+                } else {
+                    vec![Value::String(query_name.to_string())]
+                };
+                pending.push(PendingDeferred { base_path, id: Self::value_to_json(id) });
+            }
+        }
+
+        let data = if is_list {
+            Value::Array(primary_results)
+        } else {
+            primary_results.into_iter().next().unwrap_or(Value::Null)
+        };
+
+        Ok((data, pending))
+    }
+
+    /// Runs the second, later query a `pending` list from
+    /// `resolve_query_deferred` needs: fetches only the `deferred` columns
+    /// (plus `id` to match rows back up), then emits one `DeferredPatch` per
+    /// deferred field per pending row. Callers control when this runs, so
+    /// the expensive columns aren't computed until the primary payload is
+    /// already on the wire.
+    async fn resolve_deferred_patches(
+        &self,
+        query_name: &str,
+        arguments: &HashMap<String, Value>,
+        pending: Vec<PendingDeferred>,
+    ) -> Result<Vec<DeferredPatch>> {
+        if pending.is_empty() {
+            return Ok(Vec::new());
+        }
+
+        let query = self.schema.queries.get(query_name)
+            .ok_or_else(|| anyhow::anyhow!("Query not found: {}", query_name))?;
+        let type_name = query.return_type.trim_start_matches('[').trim_end_matches(']').to_string();
+        let gql_type = self.schema.types.get(&type_name)
+            .ok_or_else(|| anyhow::anyhow!("Type not found: {}", type_name))?;
+        let id_column = Self::id_column(gql_type);
+        let deferred_fields: Vec<_> = gql_type.fields.iter().filter(|field| field.deferred).collect();
+
+        let mut columns: Vec<String> = deferred_fields.iter()
+            .map(|field| field.column_mapping.clone().unwrap_or_else(|| field.name.clone()))
+            .collect();
+        if !columns.iter().any(|col| col == &id_column) {
+            columns.push(id_column.clone());
+        }
+
+        let rows = self.resolve_rows_with_projection(query, arguments, &columns).await?;
+        let by_id: HashMap<String, &Tuple> = rows
+            .iter()
+            .filter_map(|tuple| tuple.get(&id_column).map(|id| (Self::value_to_json(id).to_string(), tuple)))
+            .collect();
+
+        let mut patches = Vec::new();
+        for item in &pending {
+            let Some(tuple) = by_id.get(&item.id.to_string()) else { continue };
+
+            for field in &deferred_fields {
+                let column = field.column_mapping.as_ref().unwrap_or(&field.name);
+                if let Some(value) = tuple.get(column) {
+                    let mut path = item.base_path.clone();
+                    path.push(Value::String(field.name.clone()));
+                    patches.push(DeferredPatch { path, data: Self::value_to_json(value) });
+                }
+            }
+        }
+
+        Ok(patches)
+    }
+
+    /// The column backing a type's `id` field, falling back to the
+    /// conventional `"id"` column name for types (like `PageInfo`) that
+    /// don't declare one.
+    fn id_column(gql_type: &crate::graphql::schema::GraphQLType) -> String {
+        gql_type.fields.iter()
+            .find(|field| field.name == "id")
+            .and_then(|field| field.column_mapping.clone())
+            .unwrap_or_else(|| "id".to_string())
+    }
+
+    /// Runs `query.sql_template` bound against `arguments` and returns its
+    /// rows plus the return-type bookkeeping (`is_list`, the bare type name)
+    /// `resolve_query`/`resolve_query_deferred` both need afterward.
+    async fn resolve_rows(
+        &self,
+        query: &GraphQLQuery,
+        arguments: &HashMap<String, Value>,
+    ) -> Result<(Vec<Tuple>, bool, String)> {
+        let statement = Self::bind_statement(&query.sql_template, &query.arguments, arguments)?;
+
+        let logical_planner = LogicalPlanner::new(self.catalog.read().await.clone());
+        let logical_plan = logical_planner.plan(&statement)?;
+
+        let physical_planner = PhysicalPlanner::new(self.storage.as_ref(), self.catalog.read().await.clone());
+        let physical_plan = physical_planner.plan(&logical_plan).await?;
+
+        let mut execution_engine = ExecutionEngine::new(self.storage.as_ref(), self.catalog.clone());
+        let rows = execution_engine.execute(physical_plan).await?;
+
+        let is_list = query.return_type.starts_with('[');
+        let type_name = query.return_type.trim_start_matches('[').trim_end_matches(']').to_string();
+
+        Ok((rows, is_list, type_name))
+    }
+
+    /// Like `resolve_rows`, but replaces the template's `*` projection with
+    /// `columns` before planning it, so the query only has to do the work of
+    /// fetching the columns a caller actually asked for.
+    async fn resolve_rows_with_projection(
+        &self,
+        query: &GraphQLQuery,
+        arguments: &HashMap<String, Value>,
+        columns: &[String],
+    ) -> Result<Vec<Tuple>> {
+        let mut statement = Self::bind_statement(&query.sql_template, &query.arguments, arguments)?;
+
+        if let Statement::Retrieve(retrieve) = &mut statement {
+            retrieve.projection = columns.iter().cloned().map(Expression::Column).collect();
+        }
+
+        let logical_planner = LogicalPlanner::new(self.catalog.read().await.clone());
+        let logical_plan = logical_planner.plan(&statement)?;
+
+        let physical_planner = PhysicalPlanner::new(self.storage.as_ref(), self.catalog.read().await.clone());
+        let physical_plan = physical_planner.plan(&logical_plan).await?;
+
+        let mut execution_engine = ExecutionEngine::new(self.storage.as_ref(), self.catalog.clone());
+        execution_engine.execute(physical_plan).await
+    }
+
+    /// Serves one `@defer`-aware query request over `stream`: reads a single
+    /// newline-delimited JSON `{ "query": ..., "arguments": ... }` request
+    /// line, writes the initial `{ data, hasNext }` response as soon as the
+    /// cheap columns are ready, then only fetches the `deferred` columns
+    /// (one patch per field per row) after that, streaming each as a
+    /// `{ path, data, hasNext }` line.
+    ///
+    /// This reuses `graphql::subscriptions::SubscriptionRuntime::serve_ws`'s
+    /// newline-delimited-JSON-over-`TcpStream` wire convention rather than a
+    /// `multipart/mixed` HTTP response, since this crate has no HTTP layer
+    /// to produce one from.
+    pub async fn serve_deferred(&self, stream: TcpStream) -> Result<()> {
+        let (read_half, mut write_half) = stream.into_split();
+        let mut reader = BufReader::new(read_half);
+
+        let mut line = String::new();
+        let bytes_read = reader.read_line(&mut line).await?;
+        if bytes_read == 0 {
+            return Ok(());
+        }
+
+        let request: DeferredRequest = serde_json::from_str(line.trim_end())?;
+        let (data, pending) = self.resolve_query_deferred(&request.query, &request.arguments).await?;
+
+        Self::write_line(
+            &mut write_half,
+            &serde_json::json!({ "data": data, "hasNext": !pending.is_empty() }),
+        )
+        .await?;
+
+        let patches = self.resolve_deferred_patches(&request.query, &request.arguments, pending).await?;
+
+        for (index, patch) in patches.iter().enumerate() {
+            let has_next = index + 1 < patches.len();
+            Self::write_line(
+                &mut write_half,
+                &serde_json::json!({
+                    "path": patch.path,
+                    "data": patch.data,
+                    "hasNext": has_next,
+                }),
+            )
+            .await?;
+        }
+
+        Ok(())
+    }
+
+    async fn write_line(writer: &mut OwnedWriteHalf, value: &Value) -> Result<()> {
+        let mut line = serde_json::to_string(value)?;
+        line.push('\n');
+        writer.write_all(line.as_bytes()).await?;
+        Ok(())
+    }
+
+    /// Resolves a `{Type}Connection`-returning query per the GraphQL Cursor
+    /// Connections spec, dispatching to the forward (`first`/`after`) or
+    /// backward (`last`/`before`) half depending on which the caller asked
+    /// for; the spec treats mixing both as a client error.
+    async fn resolve_connection_query(
+        &self,
+        query: &GraphQLQuery,
+        arguments: HashMap<String, Value>,
+    ) -> Result<Value> {
+        let type_name = query.return_type.trim_end_matches("Connection").to_string();
 
-        Ok(Value::Null)
+        let first = arguments.get("first").and_then(Value::as_i64);
+        let last = arguments.get("last").and_then(Value::as_i64);
+        anyhow::ensure!(
+            !(first.is_some() && last.is_some()),
+            "Cannot specify both `first` and `last` on a connection query"
+        );
+
+        if last.is_some() || arguments.get("before").is_some() {
+            self.resolve_connection_query_backward(query, arguments, &type_name, last.unwrap_or(10).max(0)).await
+        } else {
+            self.resolve_connection_query_forward(query, arguments, &type_name, first.unwrap_or(10).max(0)).await
+        }
     }
 
-    fn build_sql(&self, template: &str, arguments: &HashMap<String, Value>) -> Result<String> {
-        let mut sql = template.to_string();
+    /// Fetches one row past `first` to determine `hasNextPage` without a
+    /// second round-trip, then trims it off before building `edges`/`pageInfo`.
+    async fn resolve_connection_query_forward(
+        &self,
+        query: &GraphQLQuery,
+        arguments: HashMap<String, Value>,
+        type_name: &str,
+        first: i64,
+    ) -> Result<Value> {
+        let after = arguments
+            .get("after")
+            .and_then(Value::as_str)
+            .map(SchemaGenerator::decode_cursor)
+            .transpose()?;
+
+        let mut bound_arguments = arguments;
+        bound_arguments.insert("first".to_string(), Value::from(first + 1));
+
+        // Without a cursor the template's `$after` placeholder has nothing
+        // safe to bind to (the engine has no `IS NULL` operator to fall back
+        // on), so the unbounded first page drops that clause entirely
+        // instead of comparing against a sentinel.
+        let sql_template = match &after {
+            Some(cursor) => {
+                bound_arguments.insert("after".to_string(), Value::String(cursor.clone()));
+                query.sql_template.clone()
+            }
+            None => query.sql_template.replace("where id > $after ", ""),
+        };
+
+        let mut rows = self.execute_connection_statement(&sql_template, query, &bound_arguments).await?;
+
+        let has_next_page = rows.len() > first as usize;
+        rows.truncate(first as usize);
+
+        let (edges, start_cursor, end_cursor) = self.build_edges(&rows, type_name)?;
+
+        Ok(serde_json::json!({
+            "edges": edges,
+            "pageInfo": {
+                "hasNextPage": has_next_page,
+                "hasPreviousPage": after.is_some(),
+                "startCursor": start_cursor,
+                "endCursor": end_cursor,
+            },
+        }))
+    }
+
+    /// Mirrors `resolve_connection_query_forward` for `last`/`before`: scans
+    /// backward from the cursor in descending `id` order, fetching one row
+    /// past `last` to determine `hasPreviousPage`, then reverses the page
+    /// back into the spec's ascending edge order before building `edges`.
+    async fn resolve_connection_query_backward(
+        &self,
+        query: &GraphQLQuery,
+        arguments: HashMap<String, Value>,
+        type_name: &str,
+        last: i64,
+    ) -> Result<Value> {
+        let before = arguments
+            .get("before")
+            .and_then(Value::as_str)
+            .map(SchemaGenerator::decode_cursor)
+            .transpose()?;
+
+        let mut bound_arguments = arguments;
+        bound_arguments.insert("last".to_string(), Value::from(last + 1));
 
-        for (key, value) in arguments {
-            let placeholder = format!("${}", key);
-            let value_str = match value {
-                Value::String(s) => format!("'{}'", s),
-                Value::Number(n) => n.to_string(),
-                Value::Bool(b) => b.to_string(),
-                _ => value.to_string(),
+        let reordered = query.sql_template.replace("order by id limit $first", "order by id desc limit $last");
+        let sql_template = match &before {
+            Some(cursor) => {
+                bound_arguments.insert("before".to_string(), Value::String(cursor.clone()));
+                reordered.replace("where id > $after ", "where id < $before ")
+            }
+            None => reordered.replace("where id > $after ", ""),
+        };
+
+        let mut rows = self.execute_connection_statement(&sql_template, query, &bound_arguments).await?;
+
+        let has_previous_page = rows.len() > last as usize;
+        rows.truncate(last as usize);
+        rows.reverse();
+
+        let (edges, start_cursor, end_cursor) = self.build_edges(&rows, type_name)?;
+
+        Ok(serde_json::json!({
+            "edges": edges,
+            "pageInfo": {
+                "hasNextPage": before.is_some(),
+                "hasPreviousPage": has_previous_page,
+                "startCursor": start_cursor,
+                "endCursor": end_cursor,
+            },
+        }))
+    }
+
+    async fn execute_connection_statement(
+        &self,
+        sql_template: &str,
+        query: &GraphQLQuery,
+        bound_arguments: &HashMap<String, Value>,
+    ) -> Result<Vec<Tuple>> {
+        let statement = Self::bind_statement(sql_template, &query.arguments, bound_arguments)?;
+
+        let logical_planner = LogicalPlanner::new(self.catalog.read().await.clone());
+        let logical_plan = logical_planner.plan(&statement)?;
+
+        let physical_planner = PhysicalPlanner::new(self.storage.as_ref(), self.catalog.read().await.clone());
+        let physical_plan = physical_planner.plan(&logical_plan).await?;
+
+        let mut execution_engine = ExecutionEngine::new(self.storage.as_ref(), self.catalog.clone());
+        execution_engine.execute(physical_plan).await
+    }
+
+    fn build_edges(&self, rows: &[Tuple], type_name: &str) -> Result<(Vec<Value>, Value, Value)> {
+        let mut edges = Vec::with_capacity(rows.len());
+        for tuple in rows {
+            let node = self.tuple_to_json(tuple, type_name)?;
+            let cursor = SchemaGenerator::encode_cursor(&Self::row_position(tuple));
+            edges.push(serde_json::json!({ "node": node, "cursor": cursor }));
+        }
+
+        let start_cursor = edges.first().map(|edge| edge["cursor"].clone()).unwrap_or(Value::Null);
+        let end_cursor = edges.last().map(|edge| edge["cursor"].clone()).unwrap_or(Value::Null);
+
+        Ok((edges, start_cursor, end_cursor))
+    }
+
+    /// The stable position key a cursor is derived from: currently always
+    /// the row's `id` column.
+    fn row_position(tuple: &Tuple) -> String {
+        match tuple.get("id") {
+            Some(crate::execution::tuple::Value::Integer(i)) => i.to_string(),
+            Some(crate::execution::tuple::Value::String(s)) => s.clone(),
+            _ => String::new(),
+        }
+    }
+
+    /// Parses `template` (with its named `$arg` placeholders rewritten to
+    /// the parser's positional `$n` syntax) into a `Statement`, then
+    /// replaces every placeholder in the parsed AST with the bound argument
+    /// as a typed literal — so the value reaches the planner as an ordinary
+    /// constant rather than ever touching the SQL text.
+    fn bind_statement(
+        template: &str,
+        declared_arguments: &[GraphQLArgument],
+        arguments: &HashMap<String, Value>,
+    ) -> Result<Statement> {
+        let (sql, placeholder_names) = Self::positionalize(template);
+
+        let mut bound = Vec::with_capacity(placeholder_names.len());
+        for name in &placeholder_names {
+            let argument = declared_arguments
+                .iter()
+                .find(|arg| &arg.name == name)
+                .ok_or_else(|| anyhow::anyhow!("Query template references undeclared argument: {}", name))?;
+
+            let value = arguments.get(name).filter(|v| !v.is_null());
+
+            let literal = match value {
+                Some(value) => Self::coerce_literal(name, &argument.arg_type, value)?,
+                None if argument.required => anyhow::bail!("Missing required argument: {}", name),
+                None => Literal::Null,
             };
-            sql = sql.replace(&placeholder, &value_str);
+
+            bound.push(literal);
+        }
+
+        let mut parser = Parser::new();
+        let mut statement = parser.parse(&sql)?;
+        Self::substitute_statement(&mut statement, &bound);
+        Ok(statement)
+    }
+
+    /// Scans `template` for `$name`-style placeholders, rewriting each to
+    /// the lexer's `$n` positional form (reusing the same index for every
+    /// occurrence of a repeated name), and returns the rewritten SQL plus
+    /// the ordered list of names so the caller can bind them by position.
+    fn positionalize(template: &str) -> (String, Vec<String>) {
+        let chars: Vec<char> = template.chars().collect();
+        let mut sql = String::with_capacity(template.len());
+        let mut names: Vec<String> = Vec::new();
+        let mut i = 0;
+
+        while i < chars.len() {
+            let starts_name = chars[i] == '$' && chars.get(i + 1).map_or(false, |c| c.is_alphabetic() || *c == '_');
+
+            if starts_name {
+                let start = i + 1;
+                let mut end = start;
+                while end < chars.len() && (chars[end].is_alphanumeric() || chars[end] == '_') {
+                    end += 1;
+                }
+
+                let name: String = chars[start..end].iter().collect();
+                let index = match names.iter().position(|n| n == &name) {
+                    Some(pos) => pos + 1,
+                    None => {
+                        names.push(name);
+                        names.len()
+                    }
+                };
+
+                sql.push('$');
+                sql.push_str(&index.to_string());
+                i = end;
+            } else {
+                sql.push(chars[i]);
+                i += 1;
+            }
+        }
+
+        (sql, names)
+    }
+
+    fn coerce_literal(name: &str, arg_type: &str, value: &Value) -> Result<Literal> {
+        match (arg_type, value) {
+            ("Int", Value::Number(n)) => n
+                .as_i64()
+                .map(Literal::Integer)
+                .ok_or_else(|| anyhow::anyhow!("Argument {} must be an integer", name)),
+            ("Float", Value::Number(n)) => n
+                .as_f64()
+                .map(Literal::Float)
+                .ok_or_else(|| anyhow::anyhow!("Argument {} must be a number", name)),
+            ("Boolean", Value::Bool(b)) => Ok(Literal::Boolean(*b)),
+            ("ID", Value::String(s)) => Ok(Literal::String(s.clone())),
+            ("ID", Value::Number(n)) => Ok(Literal::String(n.to_string())),
+            ("String", Value::String(s)) => Ok(Literal::String(s.clone())),
+            _ => anyhow::bail!(
+                "Argument {} does not match declared type {}: {}",
+                name,
+                arg_type,
+                value
+            ),
         }
+    }
+
+    fn substitute_statement(statement: &mut Statement, bound: &[Literal]) {
+        match statement {
+            Statement::Retrieve(retrieve) => {
+                for expr in &mut retrieve.projection {
+                    Self::substitute_expr(expr, bound);
+                }
+                for join in &mut retrieve.joins {
+                    Self::substitute_expr(&mut join.on, bound);
+                }
+                if let Some(filter) = &mut retrieve.filter {
+                    Self::substitute_expr(filter, bound);
+                }
+                for expr in &mut retrieve.group_by {
+                    Self::substitute_expr(expr, bound);
+                }
+                if let Some(having) = &mut retrieve.having {
+                    Self::substitute_expr(having, bound);
+                }
+                for order in &mut retrieve.order_by {
+                    Self::substitute_expr(&mut order.expr, bound);
+                }
+            }
+            Statement::Insert(insert) => {
+                for row in &mut insert.values {
+                    for expr in row {
+                        Self::substitute_expr(expr, bound);
+                    }
+                }
+            }
+            Statement::Update(update) => {
+                for assignment in &mut update.assignments {
+                    Self::substitute_expr(&mut assignment.value, bound);
+                }
+                if let Some(filter) = &mut update.filter {
+                    Self::substitute_expr(filter, bound);
+                }
+            }
+            Statement::Delete(delete) => {
+                if let Some(filter) = &mut delete.filter {
+                    Self::substitute_expr(filter, bound);
+                }
+            }
+            _ => {}
+        }
+    }
 
-        Ok(sql)
+    fn substitute_expr(expr: &mut Expression, bound: &[Literal]) {
+        match expr {
+            Expression::Placeholder(index) => {
+                if let Some(value) = bound.get(*index - 1) {
+                    *expr = Expression::Literal(value.clone());
+                }
+            }
+            Expression::BinaryOp { left, right, .. } => {
+                Self::substitute_expr(left, bound);
+                Self::substitute_expr(right, bound);
+            }
+            Expression::UnaryOp { operand, .. } => Self::substitute_expr(operand, bound),
+            Expression::FunctionCall { args, .. } => {
+                for arg in args {
+                    Self::substitute_expr(arg, bound);
+                }
+            }
+            Expression::Cast { inner, .. } => Self::substitute_expr(inner, bound),
+            _ => {}
+        }
     }
 
     pub fn tuple_to_json(&self, tuple: &Tuple, type_name: &str) -> Result<Value> {
@@ -51,23 +656,26 @@ impl GraphQLResolver {
 
         for field in &gql_type.fields {
             let column_name = field.column_mapping.as_ref().unwrap_or(&field.name);
-            
+
             if let Some(value) = tuple.get(column_name) {
-                let json_value = match value {
-                    crate::execution::tuple::Value::Null => Value::Null,
-                    crate::execution::tuple::Value::Boolean(b) => Value::Bool(*b),
-                    crate::execution::tuple::Value::Integer(i) => Value::Number((*i).into()),
-                    crate::execution::tuple::Value::Float(f) => {
-                        serde_json::Number::from_f64(*f)
-                            .map(Value::Number)
-                            .unwrap_or(Value::Null)
-                    }
-                    crate::execution::tuple::Value::String(s) => Value::String(s.clone()),
-                };
-                obj.insert(field.name.clone(), json_value);
+                obj.insert(field.name.clone(), Self::value_to_json(value));
             }
         }
 
         Ok(Value::Object(obj))
     }
+
+    fn value_to_json(value: &crate::execution::tuple::Value) -> Value {
+        match value {
+            crate::execution::tuple::Value::Null => Value::Null,
+            crate::execution::tuple::Value::Boolean(b) => Value::Bool(*b),
+            crate::execution::tuple::Value::Integer(i) => Value::Number((*i).into()),
+            crate::execution::tuple::Value::Float(f) => {
+                serde_json::Number::from_f64(*f)
+                    .map(Value::Number)
+                    .unwrap_or(Value::Null)
+            }
+            crate::execution::tuple::Value::String(s) => Value::String(s.clone()),
+        }
+    }
 }
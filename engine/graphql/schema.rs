@@ -23,6 +23,13 @@ pub struct GraphQLField {
     pub field_type: String,
     pub nullable: bool,
     pub column_mapping: Option<String>,
+    /// Whether this field is expensive enough that a `@defer`-aware
+    /// resolution (`GraphQLResolver::resolve_query_deferred`) should hold it
+    /// back from the initial response and deliver it as a follow-up patch
+    /// instead of blocking the whole row on it. Ignored by `resolve_query`,
+    /// which always resolves every field eagerly.
+    #[serde(default)]
+    pub deferred: bool,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -55,10 +62,28 @@ pub struct GraphQLArgument {
     pub required: bool,
 }
 
+/// Whether `list{Type}s` queries paginate by `limit`/`offset` or by the
+/// GraphQL Cursor Connections spec. Offset pagination is simple but drifts
+/// under concurrent inserts; connections use keyset pagination on `id`
+/// instead, so a cursor always names the same row regardless of what's been
+/// inserted around it.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PaginationMode {
+    Offset,
+    Connection,
+}
+
 pub struct SchemaGenerator;
 
 impl SchemaGenerator {
     pub fn generate_from_tables(tables: Vec<String>) -> Result<GraphQLSchema> {
+        Self::generate_from_tables_with_mode(tables, PaginationMode::Offset)
+    }
+
+    pub fn generate_from_tables_with_mode(
+        tables: Vec<String>,
+        mode: PaginationMode,
+    ) -> Result<GraphQLSchema> {
         let mut types = HashMap::new();
         let mut queries = HashMap::new();
 
@@ -72,6 +97,7 @@ impl SchemaGenerator {
                     field_type: "ID".to_string(),
                     nullable: false,
                     column_mapping: Some("id".to_string()),
+                    deferred: false,
                 }],
                 table_mapping: Some(table.clone()),
             };
@@ -92,26 +118,67 @@ impl SchemaGenerator {
                 },
             );
 
-            queries.insert(
-                format!("list{}s", type_name),
-                GraphQLQuery {
-                    name: format!("list{}s", type_name),
-                    return_type: format!("[{}]", type_name),
-                    arguments: vec![
-                        GraphQLArgument {
-                            name: "limit".to_string(),
-                            arg_type: "Int".to_string(),
-                            required: false,
+            match mode {
+                PaginationMode::Offset => {
+                    queries.insert(
+                        format!("list{}s", type_name),
+                        GraphQLQuery {
+                            name: format!("list{}s", type_name),
+                            return_type: format!("[{}]", type_name),
+                            arguments: vec![
+                                GraphQLArgument {
+                                    name: "limit".to_string(),
+                                    arg_type: "Int".to_string(),
+                                    required: false,
+                                },
+                                GraphQLArgument {
+                                    name: "offset".to_string(),
+                                    arg_type: "Int".to_string(),
+                                    required: false,
+                                },
+                            ],
+                            sql_template: format!("retrieve * from {} limit $limit offset $offset", table),
                         },
-                        GraphQLArgument {
-                            name: "offset".to_string(),
-                            arg_type: "Int".to_string(),
-                            required: false,
+                    );
+                }
+                PaginationMode::Connection => {
+                    Self::add_connection_type(&mut types, &type_name);
+
+                    queries.insert(
+                        format!("list{}s", type_name),
+                        GraphQLQuery {
+                            name: format!("list{}s", type_name),
+                            return_type: format!("{}Connection", type_name),
+                            arguments: vec![
+                                GraphQLArgument {
+                                    name: "first".to_string(),
+                                    arg_type: "Int".to_string(),
+                                    required: false,
+                                },
+                                GraphQLArgument {
+                                    name: "after".to_string(),
+                                    arg_type: "ID".to_string(),
+                                    required: false,
+                                },
+                                GraphQLArgument {
+                                    name: "last".to_string(),
+                                    arg_type: "Int".to_string(),
+                                    required: false,
+                                },
+                                GraphQLArgument {
+                                    name: "before".to_string(),
+                                    arg_type: "ID".to_string(),
+                                    required: false,
+                                },
+                            ],
+                            sql_template: format!(
+                                "retrieve * from {} where id > $after order by id limit $first",
+                                table
+                            ),
                         },
-                    ],
-                    sql_template: format!("retrieve * from {} limit $limit offset $offset", table),
-                },
-            );
+                    );
+                }
+            }
         }
 
         Ok(GraphQLSchema {
@@ -122,6 +189,111 @@ impl SchemaGenerator {
         })
     }
 
+    /// Registers `{TypeName}Connection`/`{TypeName}Edge`, plus the shared
+    /// `PageInfo` type (inserted once, idempotently), into `types` per the
+    /// GraphQL Cursor Connections spec.
+    fn add_connection_type(types: &mut HashMap<String, GraphQLType>, type_name: &str) {
+        types.entry("PageInfo".to_string()).or_insert_with(|| GraphQLType {
+            name: "PageInfo".to_string(),
+            fields: vec![
+                GraphQLField {
+                    name: "hasNextPage".to_string(),
+                    field_type: "Boolean".to_string(),
+                    nullable: false,
+                    column_mapping: None,
+                    deferred: false,
+                },
+                GraphQLField {
+                    name: "hasPreviousPage".to_string(),
+                    field_type: "Boolean".to_string(),
+                    nullable: false,
+                    column_mapping: None,
+                    deferred: false,
+                },
+                GraphQLField {
+                    name: "startCursor".to_string(),
+                    field_type: "String".to_string(),
+                    nullable: true,
+                    column_mapping: None,
+                    deferred: false,
+                },
+                GraphQLField {
+                    name: "endCursor".to_string(),
+                    field_type: "String".to_string(),
+                    nullable: true,
+                    column_mapping: None,
+                    deferred: false,
+                },
+            ],
+            table_mapping: None,
+        });
+
+        let edge_name = format!("{}Edge", type_name);
+        types.insert(
+            edge_name.clone(),
+            GraphQLType {
+                name: edge_name.clone(),
+                fields: vec![
+                    GraphQLField {
+                        name: "node".to_string(),
+                        field_type: type_name.to_string(),
+                        nullable: false,
+                        column_mapping: None,
+                        deferred: false,
+                    },
+                    GraphQLField {
+                        name: "cursor".to_string(),
+                        field_type: "String".to_string(),
+                        nullable: false,
+                        column_mapping: None,
+                        deferred: false,
+                    },
+                ],
+                table_mapping: None,
+            },
+        );
+
+        types.insert(
+            format!("{}Connection", type_name),
+            GraphQLType {
+                name: format!("{}Connection", type_name),
+                fields: vec![
+                    GraphQLField {
+                        name: "edges".to_string(),
+                        field_type: format!("[{}]", edge_name),
+                        nullable: false,
+                        column_mapping: None,
+                        deferred: false,
+                    },
+                    GraphQLField {
+                        name: "pageInfo".to_string(),
+                        field_type: "PageInfo".to_string(),
+                        nullable: false,
+                        column_mapping: None,
+                        deferred: false,
+                    },
+                ],
+                table_mapping: None,
+            },
+        );
+    }
+
+    /// Opaque cursor for a row: base64 of its stable position key (currently
+    /// always the `id` column), so clients must treat it as an identifier
+    /// rather than a value they can construct or sort on themselves.
+    pub fn encode_cursor(position: &str) -> String {
+        use base64::Engine;
+        base64::engine::general_purpose::STANDARD.encode(position)
+    }
+
+    pub fn decode_cursor(cursor: &str) -> Result<String> {
+        use base64::Engine;
+        let bytes = base64::engine::general_purpose::STANDARD
+            .decode(cursor)
+            .map_err(|_| anyhow::anyhow!("Invalid cursor: {}", cursor))?;
+        String::from_utf8(bytes).map_err(|_| anyhow::anyhow!("Invalid cursor: {}", cursor))
+    }
+
     fn to_pascal_case(s: &str) -> String {
         s.split('_')
             .map(|word| {
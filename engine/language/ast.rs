@@ -9,18 +9,26 @@ pub enum Statement {
     CreateTable(CreateTableStatement),
     CreateIndex(CreateIndexStatement),
     DropTable(DropTableStatement),
+    DropIndex(DropIndexStatement),
     BeginTransaction(BeginTransactionStatement),
     Commit,
     Rollback,
+    Savepoint(SavepointStatement),
+    ReleaseSavepoint(ReleaseSavepointStatement),
+    RollbackToSavepoint(RollbackToSavepointStatement),
+    CreatePolicy(CreatePolicyStatement),
+    DropPolicy(DropPolicyStatement),
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct RetrieveStatement {
+    pub distinct: bool,
     pub projection: Vec<Expression>,
     pub from: TableReference,
     pub joins: Vec<JoinClause>,
     pub filter: Option<Expression>,
     pub group_by: Vec<Expression>,
+    pub having: Option<Expression>,
     pub order_by: Vec<OrderByClause>,
     pub limit: Option<usize>,
     pub offset: Option<usize>,
@@ -91,12 +99,49 @@ pub struct DropTableStatement {
     pub name: String,
 }
 
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DropIndexStatement {
+    pub name: String,
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct BeginTransactionStatement {
     pub deterministic: bool,
     pub at_timestamp: Option<String>,
 }
 
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SavepointStatement {
+    pub name: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ReleaseSavepointStatement {
+    pub name: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RollbackToSavepointStatement {
+    pub name: String,
+}
+
+/// `CREATE POLICY <name> ON <table> FOR ROLE <role>, ... USING (<predicate>)`.
+/// `roles` empty means the policy applies to every role, matching
+/// `RLSManager::get_policies`'s treatment of an empty role list as global.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CreatePolicyStatement {
+    pub policy_name: String,
+    pub table: String,
+    pub roles: Vec<String>,
+    pub predicate: Expression,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DropPolicyStatement {
+    pub policy_name: String,
+    pub table: String,
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub enum TableReference {
     Table(String),
@@ -122,6 +167,15 @@ pub enum JoinType {
 pub struct OrderByClause {
     pub expr: Expression,
     pub ascending: bool,
+    /// `NULLS FIRST`/`NULLS LAST`, or `None` if the query didn't specify one
+    /// and the executor should fall back to its own default.
+    pub nulls: Option<NullsOrder>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub enum NullsOrder {
+    First,
+    Last,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -145,6 +199,12 @@ pub enum Expression {
         name: String,
         args: Vec<Expression>,
     },
+    Cast {
+        inner: Box<Expression>,
+        conversion: String,
+        format: Option<String>,
+    },
+    Placeholder(usize),
     Star,
 }
 
@@ -0,0 +1,129 @@
+use crate::language::ast::ColumnDefinition;
+use crate::language::intent::FilterIntent;
+use crate::security::row_level_security::{RLSManager, RowLevelSecurityPolicy};
+use std::collections::HashMap;
+
+/// The columns and indexes known for a single table, as registered by
+/// `CREATE TABLE`/`CREATE INDEX`. This is the schema `SemanticAnalyzer`
+/// resolves names and types against.
+#[derive(Debug, Clone, Default)]
+pub struct TableSchema {
+    pub columns: Vec<ColumnDefinition>,
+    pub indexes: Vec<String>,
+}
+
+impl TableSchema {
+    pub fn column(&self, name: &str) -> Option<&ColumnDefinition> {
+        self.columns.iter().find(|c| c.name == name)
+    }
+
+    pub fn has_column(&self, name: &str) -> bool {
+        self.column(name).is_some()
+    }
+}
+
+/// A `CREATE INDEX name ON table(cols...)` registration, tracked by name so
+/// `DROP INDEX` can find and remove it again.
+#[derive(Debug, Clone)]
+pub struct IndexDefinition {
+    pub name: String,
+    pub table: String,
+    pub columns: Vec<String>,
+}
+
+/// The set of tables a `SemanticAnalyzer` can validate statements against.
+/// A fresh `Catalog` knows no tables; callers register schemas as
+/// `CREATE TABLE`/`CREATE INDEX` statements are executed, so later queries
+/// in the same session can be checked against them.
+#[derive(Debug, Clone, Default)]
+pub struct Catalog {
+    tables: HashMap<String, TableSchema>,
+    indexes: HashMap<String, IndexDefinition>,
+    rls: RLSManager,
+}
+
+impl Catalog {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn register_table(&mut self, name: &str, columns: Vec<ColumnDefinition>) {
+        self.tables.insert(
+            name.to_string(),
+            TableSchema {
+                columns,
+                indexes: Vec::new(),
+            },
+        );
+    }
+
+    pub fn register_index(&mut self, name: &str, table: &str, columns: Vec<String>) {
+        if let Some(schema) = self.tables.get_mut(table) {
+            for column in &columns {
+                if !schema.indexes.iter().any(|c| c == column) {
+                    schema.indexes.push(column.clone());
+                }
+            }
+        }
+
+        self.indexes.insert(
+            name.to_string(),
+            IndexDefinition {
+                name: name.to_string(),
+                table: table.to_string(),
+                columns,
+            },
+        );
+    }
+
+    pub fn drop_index(&mut self, name: &str) {
+        if let Some(definition) = self.indexes.remove(name) {
+            if let Some(schema) = self.tables.get_mut(&definition.table) {
+                schema.indexes.retain(|c| !definition.columns.contains(c));
+            }
+        }
+    }
+
+    pub fn drop_table(&mut self, name: &str) {
+        self.tables.remove(name);
+        self.indexes.retain(|_, definition| definition.table != name);
+    }
+
+    pub fn table(&self, name: &str) -> Option<&TableSchema> {
+        self.tables.get(name)
+    }
+
+    pub fn has_table(&self, name: &str) -> bool {
+        self.tables.contains_key(name)
+    }
+
+    pub fn index(&self, name: &str) -> Option<&IndexDefinition> {
+        self.indexes.get(name)
+    }
+
+    /// The first registered index covering `column` on `table`, if any —
+    /// used by the physical planner to decide whether a `Filter` over a
+    /// `Scan` can be lowered to an `IndexScan`/`IndexSemiJoin` instead.
+    pub fn index_on_column(&self, table: &str, column: &str) -> Option<&IndexDefinition> {
+        self.indexes
+            .values()
+            .find(|definition| definition.table == table && definition.columns.iter().any(|c| c == column))
+    }
+
+    pub fn add_policy(&mut self, policy_name: &str, table: &str, roles: Vec<String>, filter: FilterIntent) {
+        self.rls.add_policy(RowLevelSecurityPolicy {
+            table: table.to_string(),
+            policy_name: policy_name.to_string(),
+            filter,
+            roles,
+        });
+    }
+
+    pub fn drop_policy(&mut self, table: &str, policy_name: &str) {
+        self.rls.remove_policy(table, policy_name);
+    }
+
+    pub fn rls(&self) -> &RLSManager {
+        &self.rls
+    }
+}
@@ -0,0 +1,176 @@
+use crate::language::ast::{BinaryOperator, Expression, Literal, UnaryOperator};
+
+/// Folds constant subtrees of `expr` bottom-up so the executor has less to
+/// evaluate at runtime — e.g. `1 + 2` becomes the literal `3`, and `x AND
+/// false` becomes the literal `false` without even looking at `x`. Pure and
+/// idempotent: running it again on its own output is a no-op.
+pub fn optimize(expr: Expression) -> Expression {
+    match expr {
+        Expression::BinaryOp { op, left, right } => {
+            let left = optimize(*left);
+            let right = optimize(*right);
+            fold_binary_op(op, left, right)
+        }
+        Expression::UnaryOp { op, operand } => {
+            let operand = optimize(*operand);
+            fold_unary_op(op, operand)
+        }
+        Expression::FunctionCall { name, args } => Expression::FunctionCall {
+            name,
+            args: args.into_iter().map(optimize).collect(),
+        },
+        Expression::Cast { inner, conversion, format } => Expression::Cast {
+            inner: Box::new(optimize(*inner)),
+            conversion,
+            format,
+        },
+        other => other,
+    }
+}
+
+fn fold_binary_op(op: BinaryOperator, left: Expression, right: Expression) -> Expression {
+    if let Some(folded) = fold_short_circuit(&op, &left, &right) {
+        return folded;
+    }
+
+    match (&left, &right) {
+        (Expression::Literal(left), Expression::Literal(right)) => {
+            match fold_literal_binary_op(&op, left, right) {
+                Some(literal) => Expression::Literal(literal),
+                None => rebuild_binary_op(op, left.clone(), right.clone()),
+            }
+        }
+        _ => Expression::BinaryOp {
+            op,
+            left: Box::new(left),
+            right: Box::new(right),
+        },
+    }
+}
+
+fn rebuild_binary_op(op: BinaryOperator, left: Literal, right: Literal) -> Expression {
+    Expression::BinaryOp {
+        op,
+        left: Box::new(Expression::Literal(left)),
+        right: Box::new(Expression::Literal(right)),
+    }
+}
+
+/// `AND`/`OR` identities that hold even when only one side is constant, so
+/// e.g. `x AND false` folds to `false` without needing `x` to be a literal
+/// too.
+fn fold_short_circuit(op: &BinaryOperator, left: &Expression, right: &Expression) -> Option<Expression> {
+    match op {
+        BinaryOperator::And => {
+            if let Expression::Literal(Literal::Boolean(value)) = left {
+                return Some(if *value { right.clone() } else { Expression::Literal(Literal::Boolean(false)) });
+            }
+            if let Expression::Literal(Literal::Boolean(value)) = right {
+                return Some(if *value { left.clone() } else { Expression::Literal(Literal::Boolean(false)) });
+            }
+            None
+        }
+        BinaryOperator::Or => {
+            if let Expression::Literal(Literal::Boolean(value)) = left {
+                return Some(if *value { Expression::Literal(Literal::Boolean(true)) } else { right.clone() });
+            }
+            if let Expression::Literal(Literal::Boolean(value)) = right {
+                return Some(if *value { Expression::Literal(Literal::Boolean(true)) } else { left.clone() });
+            }
+            None
+        }
+        _ => None,
+    }
+}
+
+/// Evaluates `op` over two literals, or `None` if the combination can't be
+/// folded safely — mismatched operand types (leave it for the runtime to
+/// raise its own error) or division by a zero literal (leave the node intact
+/// so the runtime raises the proper division-by-zero error instead of this
+/// pass guessing at one).
+fn fold_literal_binary_op(op: &BinaryOperator, left: &Literal, right: &Literal) -> Option<Literal> {
+    use BinaryOperator::*;
+
+    match (left, right) {
+        (Literal::Integer(left), Literal::Integer(right)) => match op {
+            Add => Some(Literal::Integer(left.checked_add(*right)?)),
+            Subtract => Some(Literal::Integer(left.checked_sub(*right)?)),
+            Multiply => Some(Literal::Integer(left.checked_mul(*right)?)),
+            Divide => {
+                if *right == 0 {
+                    None
+                } else {
+                    Some(Literal::Integer(left.checked_div(*right)?))
+                }
+            }
+            Equals => Some(Literal::Boolean(left == right)),
+            NotEquals => Some(Literal::Boolean(left != right)),
+            LessThan => Some(Literal::Boolean(left < right)),
+            LessThanOrEqual => Some(Literal::Boolean(left <= right)),
+            GreaterThan => Some(Literal::Boolean(left > right)),
+            GreaterThanOrEqual => Some(Literal::Boolean(left >= right)),
+            And | Or => None,
+        },
+        (Literal::Float(left), Literal::Float(right)) => fold_float_binary_op(op, *left, *right),
+        (Literal::Integer(left), Literal::Float(right)) => fold_float_binary_op(op, *left as f64, *right),
+        (Literal::Float(left), Literal::Integer(right)) => fold_float_binary_op(op, *left, *right as f64),
+        (Literal::Boolean(left), Literal::Boolean(right)) => match op {
+            Equals => Some(Literal::Boolean(left == right)),
+            NotEquals => Some(Literal::Boolean(left != right)),
+            And => Some(Literal::Boolean(*left && *right)),
+            Or => Some(Literal::Boolean(*left || *right)),
+            _ => None,
+        },
+        (Literal::String(left), Literal::String(right)) => match op {
+            Equals => Some(Literal::Boolean(left == right)),
+            NotEquals => Some(Literal::Boolean(left != right)),
+            LessThan => Some(Literal::Boolean(left < right)),
+            LessThanOrEqual => Some(Literal::Boolean(left <= right)),
+            GreaterThan => Some(Literal::Boolean(left > right)),
+            GreaterThanOrEqual => Some(Literal::Boolean(left >= right)),
+            _ => None,
+        },
+        _ => None,
+    }
+}
+
+fn fold_float_binary_op(op: &BinaryOperator, left: f64, right: f64) -> Option<Literal> {
+    use BinaryOperator::*;
+
+    match op {
+        Add => Some(Literal::Float(left + right)),
+        Subtract => Some(Literal::Float(left - right)),
+        Multiply => Some(Literal::Float(left * right)),
+        Divide => {
+            if right == 0.0 {
+                None
+            } else {
+                Some(Literal::Float(left / right))
+            }
+        }
+        Equals => Some(Literal::Boolean(left == right)),
+        NotEquals => Some(Literal::Boolean(left != right)),
+        LessThan => Some(Literal::Boolean(left < right)),
+        LessThanOrEqual => Some(Literal::Boolean(left <= right)),
+        GreaterThan => Some(Literal::Boolean(left > right)),
+        GreaterThanOrEqual => Some(Literal::Boolean(left >= right)),
+        And | Or => None,
+    }
+}
+
+fn fold_unary_op(op: UnaryOperator, operand: Expression) -> Expression {
+    match (&op, &operand) {
+        (UnaryOperator::Negate, Expression::Literal(Literal::Integer(value))) => {
+            Expression::Literal(Literal::Integer(-value))
+        }
+        (UnaryOperator::Negate, Expression::Literal(Literal::Float(value))) => {
+            Expression::Literal(Literal::Float(-value))
+        }
+        (UnaryOperator::Not, Expression::Literal(Literal::Boolean(value))) => {
+            Expression::Literal(Literal::Boolean(!value))
+        }
+        // `NOT NOT x` cancels to `x`, regardless of whether `x` is constant.
+        (UnaryOperator::Not, Expression::UnaryOp { op: UnaryOperator::Not, operand: inner }) => (**inner).clone(),
+        _ => Expression::UnaryOp { op, operand: Box::new(operand) },
+    }
+}
@@ -0,0 +1,194 @@
+use crate::language::ast::DataType;
+use crate::language::catalog::TableSchema;
+use crate::language::intent::*;
+use std::collections::HashMap;
+
+/// The type reported for a result column or a bound parameter. Mirrors
+/// `DataType` but adds `Unknown`, the sentinel returned for a placeholder
+/// whose type couldn't be inferred from its surrounding expression.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TypeKind {
+    Boolean,
+    Integer,
+    Float,
+    Text,
+    Timestamp,
+    Unknown,
+}
+
+impl From<&DataType> for TypeKind {
+    fn from(data_type: &DataType) -> Self {
+        match data_type {
+            DataType::Boolean => TypeKind::Boolean,
+            DataType::Integer | DataType::BigInt => TypeKind::Integer,
+            DataType::Real | DataType::Double => TypeKind::Float,
+            DataType::Text => TypeKind::Text,
+            DataType::Timestamp => TypeKind::Timestamp,
+        }
+    }
+}
+
+/// The result of describing a statement without executing it: the shape of
+/// its output columns and the inferred type of each positional placeholder,
+/// so a client can prepare the statement and bind parameters safely.
+#[derive(Debug, Clone)]
+pub struct Describe {
+    pub columns: Vec<(String, TypeKind)>,
+    pub params: Vec<TypeKind>,
+}
+
+/// Walks an intent tree bottom-up to infer expression types, interning each
+/// distinct sub-expression (by its `Debug` rendering, the same ad hoc key
+/// the hash join uses for composite keys) into a dense id and memoizing its
+/// type in `memo` so an identical subtree appearing twice is only typed
+/// once. Placeholder types aren't inferred from the placeholder itself —
+/// they're back-propagated from whichever operand they're compared or
+/// assigned against.
+pub(super) struct TypeInferer {
+    ids: HashMap<String, usize>,
+    memo: Vec<Option<TypeKind>>,
+    params: HashMap<usize, TypeKind>,
+}
+
+impl TypeInferer {
+    pub(super) fn new() -> Self {
+        Self {
+            ids: HashMap::new(),
+            memo: Vec::new(),
+            params: HashMap::new(),
+        }
+    }
+
+    fn intern(&mut self, expr: &ExpressionIntent) -> usize {
+        let key = format!("{:?}", expr);
+        if let Some(&id) = self.ids.get(&key) {
+            return id;
+        }
+
+        let id = self.memo.len();
+        self.ids.insert(key, id);
+        self.memo.push(None);
+        id
+    }
+
+    /// Infers `expr`'s type against `scope` (the tables a column name can
+    /// resolve against), returning `Unknown` rather than erroring when no
+    /// evidence is available — describe reports best-effort types, it
+    /// doesn't reject a statement the way `SemanticAnalyzer::analyze` does.
+    pub(super) fn infer_expression(&mut self, expr: &ExpressionIntent, scope: &[(&str, &TableSchema)]) -> TypeKind {
+        let id = self.intern(expr);
+        if let Some(ty) = self.memo[id] {
+            return ty;
+        }
+
+        let ty = match expr {
+            ExpressionIntent::Column(name) => Self::lookup_column(scope, None, name),
+            ExpressionIntent::QualifiedColumn { table, column } => Self::lookup_column(scope, Some(table), column),
+            ExpressionIntent::Constant(ConstantValue::Boolean(_)) => TypeKind::Boolean,
+            ExpressionIntent::Constant(ConstantValue::Integer(_)) => TypeKind::Integer,
+            ExpressionIntent::Constant(ConstantValue::Float(_)) => TypeKind::Float,
+            ExpressionIntent::Constant(ConstantValue::String(_)) => TypeKind::Text,
+            ExpressionIntent::Constant(ConstantValue::Null) => TypeKind::Unknown,
+            ExpressionIntent::Arithmetic { left, right, .. } => {
+                let left_ty = self.infer_expression(left, scope);
+                let right_ty = self.infer_expression(right, scope);
+                self.bind_placeholder(left, right_ty);
+                self.bind_placeholder(right, left_ty);
+
+                match (left_ty, right_ty) {
+                    (TypeKind::Float, _) | (_, TypeKind::Float) => TypeKind::Float,
+                    (TypeKind::Integer, TypeKind::Integer) => TypeKind::Integer,
+                    _ => TypeKind::Unknown,
+                }
+            }
+            ExpressionIntent::Function { name, args } => {
+                for arg in args {
+                    self.infer_expression(arg, scope);
+                }
+                Self::aggregate_result_type(name, args, scope)
+            }
+            ExpressionIntent::Cast { target, .. } => match target {
+                ConversionKind::Bytes => TypeKind::Text,
+                ConversionKind::Integer => TypeKind::Integer,
+                ConversionKind::Float => TypeKind::Float,
+                ConversionKind::Boolean => TypeKind::Boolean,
+                ConversionKind::Timestamp | ConversionKind::TimestampFmt(_) | ConversionKind::TimestampTZFmt(_) => {
+                    TypeKind::Timestamp
+                }
+            },
+            ExpressionIntent::Placeholder(index) => self.params.get(index).copied().unwrap_or(TypeKind::Unknown),
+        };
+
+        self.memo[id] = Some(ty);
+        ty
+    }
+
+    fn aggregate_result_type(name: &str, args: &[ExpressionIntent], scope: &[(&str, &TableSchema)]) -> TypeKind {
+        match name.to_uppercase().as_str() {
+            "COUNT" => TypeKind::Integer,
+            "SUM" => match args.first().map(|arg| TypeInferer::new().infer_expression(arg, scope)) {
+                Some(TypeKind::Integer) => TypeKind::Integer,
+                _ => TypeKind::Float,
+            },
+            "AVG" => TypeKind::Float,
+            "MIN" | "MAX" => args
+                .first()
+                .map(|arg| TypeInferer::new().infer_expression(arg, scope))
+                .unwrap_or(TypeKind::Unknown),
+            _ => TypeKind::Unknown,
+        }
+    }
+
+    fn lookup_column(scope: &[(&str, &TableSchema)], table: Option<&str>, column: &str) -> TypeKind {
+        scope
+            .iter()
+            .filter(|(name, _)| table.map_or(true, |t| *name == t))
+            .find_map(|(_, schema)| schema.column(column))
+            .map(|col| TypeKind::from(&col.data_type))
+            .unwrap_or(TypeKind::Unknown)
+    }
+
+    /// If `expr` is a bare placeholder, records `ty` as its inferred type
+    /// (first evidence wins) so `finish_params` can report it.
+    fn bind_placeholder(&mut self, expr: &ExpressionIntent, ty: TypeKind) {
+        if let ExpressionIntent::Placeholder(index) = expr {
+            if ty != TypeKind::Unknown {
+                self.params.entry(*index).or_insert(ty);
+            }
+        }
+    }
+
+    pub(super) fn infer_filter(&mut self, filter: &FilterIntent, scope: &[(&str, &TableSchema)]) {
+        match filter {
+            FilterIntent::Always | FilterIntent::Never => {}
+            FilterIntent::Comparison { left, right, .. } => {
+                let left_ty = self.infer_expression(left, scope);
+                let right_ty = self.infer_expression(right, scope);
+                self.bind_placeholder(left, right_ty);
+                self.bind_placeholder(right, left_ty);
+            }
+            FilterIntent::Logical { operands, .. } => {
+                for operand in operands {
+                    self.infer_filter(operand, scope);
+                }
+            }
+        }
+    }
+
+    /// Binds an assignment's value expression against `column_type` (e.g. an
+    /// UPDATE's `SET col = ?`), the same back-propagation a comparison gets.
+    pub(super) fn infer_assignment(&mut self, value: &ExpressionIntent, column_type: TypeKind, scope: &[(&str, &TableSchema)]) {
+        self.infer_expression(value, scope);
+        self.bind_placeholder(value, column_type);
+    }
+
+    /// Flattens the sparse `params` map into a dense, 1-indexed vector
+    /// (`params[0]` is `$1`), filling any gap left by an unreferenced
+    /// placeholder index with `Unknown`.
+    pub(super) fn finish_params(&self) -> Vec<TypeKind> {
+        let max_index = self.params.keys().copied().max().unwrap_or(0);
+        (1..=max_index)
+            .map(|i| self.params.get(&i).copied().unwrap_or(TypeKind::Unknown))
+            .collect()
+    }
+}
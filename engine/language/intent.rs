@@ -5,9 +5,12 @@ use serde::{Deserialize, Serialize};
 pub enum Intent {
     Retrieve {
         columns: Vec<ColumnIntent>,
+        distinct: bool,
         source: SourceIntent,
         filter: Option<FilterIntent>,
         aggregates: Vec<AggregateIntent>,
+        grouping: Vec<ExpressionIntent>,
+        having: Option<FilterIntent>,
         ordering: Vec<OrderIntent>,
         limit: Option<usize>,
         time_travel: Option<TimeTravelIntent>,
@@ -92,6 +95,26 @@ pub enum ExpressionIntent {
         name: String,
         args: Vec<ExpressionIntent>,
     },
+    Cast {
+        target: ConversionKind,
+        format: Option<String>,
+        inner: Box<ExpressionIntent>,
+    },
+    Placeholder(usize),
+}
+
+/// The conversion set recognized by `CAST(expr AS ...)`, analogous to the
+/// classic `bytes/string`, `int`, `float`, `bool`, `timestamp` conversion
+/// names, plus format-aware timestamp variants.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub enum ConversionKind {
+    Bytes,
+    Integer,
+    Float,
+    Boolean,
+    Timestamp,
+    TimestampFmt(String),
+    TimestampTZFmt(String),
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -122,6 +145,13 @@ pub struct AggregateIntent {
 pub struct OrderIntent {
     pub expr: ExpressionIntent,
     pub ascending: bool,
+    pub nulls: Option<NullsOrder>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub enum NullsOrder {
+    First,
+    Last,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -162,6 +192,19 @@ pub enum SchemaIntent {
     DropTable {
         name: String,
     },
+    DropIndex {
+        name: String,
+    },
+    CreatePolicy {
+        policy_name: String,
+        table: String,
+        roles: Vec<String>,
+        filter: FilterIntent,
+    },
+    DropPolicy {
+        policy_name: String,
+        table: String,
+    },
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -172,4 +215,7 @@ pub enum TransactionIntent {
     },
     Commit,
     Rollback,
+    Savepoint { name: String },
+    ReleaseSavepoint { name: String },
+    RollbackToSavepoint { name: String },
 }
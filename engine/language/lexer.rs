@@ -23,20 +23,40 @@ pub enum Token {
     Commit,
     Rollback,
     Transaction,
+    Savepoint,
+    Release,
+    To,
     Deterministic,
+    Policy,
+    For,
+    Role,
+    Using,
+    Distinct,
+    Asc,
+    Desc,
+    Nulls,
+    First,
+    Last,
     Join,
     Left,
     Inner,
     Outer,
     Group,
     By,
+    Having,
     Order,
     Limit,
     Offset,
     As,
     With,
     Select,
-    
+    Cast,
+
+    /// `?` lexes as `Placeholder(None)` (its index is assigned positionally
+    /// by the parser); `$n` lexes as `Placeholder(Some(n))` with an explicit
+    /// 1-based index.
+    Placeholder(Option<usize>),
+
     Identifier(String),
     String(String),
     Integer(i64),
@@ -68,6 +88,25 @@ pub enum Token {
     Eof,
 }
 
+/// A half-open range of character offsets into the original input, `[start,
+/// end)`. Character offsets rather than byte offsets, since `Lexer` already
+/// indexes its input as `Vec<char>` — converting to byte offsets would mean
+/// re-scanning the source on every token instead of just at error time.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Span {
+    pub start: usize,
+    pub end: usize,
+}
+
+/// A `Token` paired with the span of input it was lexed from, so a parser
+/// built on `Vec<TokenWithSpan>` can report where a syntax error occurred
+/// instead of only what was found.
+#[derive(Debug, Clone, PartialEq)]
+pub struct TokenWithSpan {
+    pub token: Token,
+    pub span: Span,
+}
+
 pub struct Lexer {
     input: Vec<char>,
     position: usize,
@@ -81,7 +120,7 @@ impl Lexer {
         }
     }
 
-    pub fn tokenize(&mut self) -> Result<Vec<Token>> {
+    pub fn tokenize(&mut self) -> Result<Vec<TokenWithSpan>> {
         let mut tokens = Vec::new();
 
         loop {
@@ -101,11 +140,17 @@ impl Lexer {
                 continue;
             }
 
+            let start = self.position;
             let token = self.next_token()?;
-            tokens.push(token);
+            let span = Span { start, end: self.position };
+            tokens.push(TokenWithSpan { token, span });
         }
 
-        tokens.push(Token::Eof);
+        let eof_at = self.position;
+        tokens.push(TokenWithSpan {
+            token: Token::Eof,
+            span: Span { start: eof_at, end: eof_at },
+        });
         Ok(tokens)
     }
 
@@ -183,13 +228,40 @@ impl Lexer {
                     anyhow::bail!("Unexpected character: !")
                 }
             }
-            '\'' | '"' => self.read_string(),
+            '\'' => self.read_string(),
+            '"' | '`' => self.read_quoted_identifier(),
+            '?' => {
+                self.advance();
+                Ok(Token::Placeholder(None))
+            }
+            '$' => self.read_dollar_placeholder(),
             _ if ch.is_ascii_digit() => self.read_number(),
             _ if ch.is_ascii_alphabetic() || ch == '_' => self.read_identifier(),
             _ => anyhow::bail!("Unexpected character: {}", ch),
         }
     }
 
+    fn read_dollar_placeholder(&mut self) -> Result<Token> {
+        self.advance();
+
+        let mut digits = String::new();
+        while let Some(ch) = self.current() {
+            if ch.is_ascii_digit() {
+                digits.push(ch);
+                self.advance();
+            } else {
+                break;
+            }
+        }
+
+        if digits.is_empty() {
+            anyhow::bail!("Expected a digit after '$' in placeholder");
+        }
+
+        let index = digits.parse::<usize>().context("Invalid placeholder index")?;
+        Ok(Token::Placeholder(Some(index)))
+    }
+
     fn read_identifier(&mut self) -> Result<Token> {
         let mut ident = String::new();
 
@@ -224,19 +296,34 @@ impl Lexer {
             "commit" => Token::Commit,
             "rollback" => Token::Rollback,
             "transaction" => Token::Transaction,
+            "savepoint" => Token::Savepoint,
+            "release" => Token::Release,
+            "to" => Token::To,
             "deterministic" => Token::Deterministic,
+            "policy" => Token::Policy,
+            "for" => Token::For,
+            "role" => Token::Role,
+            "using" => Token::Using,
+            "distinct" => Token::Distinct,
+            "asc" => Token::Asc,
+            "desc" => Token::Desc,
+            "nulls" => Token::Nulls,
+            "first" => Token::First,
+            "last" => Token::Last,
             "join" => Token::Join,
             "left" => Token::Left,
             "inner" => Token::Inner,
             "outer" => Token::Outer,
             "group" => Token::Group,
             "by" => Token::By,
+            "having" => Token::Having,
             "order" => Token::Order,
             "limit" => Token::Limit,
             "offset" => Token::Offset,
             "as" => Token::As,
             "with" => Token::With,
             "select" => Token::Select,
+            "cast" => Token::Cast,
             "and" => Token::And,
             "or" => Token::Or,
             "not" => Token::Not,
@@ -273,26 +360,49 @@ impl Lexer {
     }
 
     fn read_string(&mut self) -> Result<Token> {
+        let value = self.read_delimited('\'', "string")?;
+        Ok(Token::String(value))
+    }
+
+    /// A `"..."` or `` `...` ``-delimited identifier, e.g. `"select"` names a
+    /// column called `select` rather than lexing as `Token::Select` — unlike
+    /// `read_identifier`, the contents never go through keyword lookup.
+    fn read_quoted_identifier(&mut self) -> Result<Token> {
         let quote_char = self.current().unwrap();
+        let value = self.read_delimited(quote_char, "quoted identifier")?;
+        Ok(Token::Identifier(value))
+    }
+
+    /// Reads the body between a pair of `quote_char` delimiters, where a
+    /// doubled delimiter (`''`, `""`, or `` `` ``) is an escaped literal
+    /// quote character rather than the closing delimiter — the same
+    /// doubling convention standard SQL uses for both string literals and
+    /// quoted identifiers.
+    fn read_delimited(&mut self, quote_char: char, what: &str) -> Result<String> {
         self.advance();
 
-        let mut str_val = String::new();
+        let mut value = String::new();
 
         loop {
             match self.current() {
                 Some(ch) if ch == quote_char => {
                     self.advance();
-                    break;
+                    if self.current() == Some(quote_char) {
+                        value.push(quote_char);
+                        self.advance();
+                    } else {
+                        break;
+                    }
                 }
                 Some(ch) => {
-                    str_val.push(ch);
+                    value.push(ch);
                     self.advance();
                 }
-                None => anyhow::bail!("Unterminated string"),
+                None => anyhow::bail!("Unterminated {}", what),
             }
         }
 
-        Ok(Token::String(str_val))
+        Ok(value)
     }
 
     fn skip_whitespace(&mut self) {
@@ -360,4 +470,4 @@ impl Lexer {
     fn advance(&mut self) {
         self.position += 1;
     }
-              }
+}
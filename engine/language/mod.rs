@@ -1,4 +1,7 @@
 pub mod ast;
+pub mod catalog;
+pub mod constant_fold;
+pub mod describe;
 pub mod intent;
 pub mod lexer;
 pub mod parser;
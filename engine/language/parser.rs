@@ -1,24 +1,47 @@
 use crate::language::ast::*;
-use crate::language::lexer::{Lexer, Token};
+use crate::language::constant_fold;
+use crate::language::lexer::{Lexer, Span, Token, TokenWithSpan};
 use anyhow::{Context, Result};
 
 pub struct Parser {
-    tokens: Vec<Token>,
+    tokens: Vec<TokenWithSpan>,
+    /// The input `parse` was last called with, kept around only so errors can
+    /// compute a line/column from a `Span` — tokens themselves store offsets,
+    /// not positions, to avoid rescanning on every advance.
+    input: String,
     position: usize,
+    next_placeholder: usize,
+    /// When set, `parse_update`/`parse_delete` reject a statement with no
+    /// `WHERE` clause instead of silently parsing a whole-table mutation.
+    /// Off by default so existing callers aren't surprised by it.
+    require_where_for_dml: bool,
 }
 
 impl Parser {
     pub fn new() -> Self {
         Self {
             tokens: Vec::new(),
+            input: String::new(),
             position: 0,
+            next_placeholder: 1,
+            require_where_for_dml: false,
         }
     }
 
+    /// Opts this parser instance into rejecting `UPDATE`/`DELETE` statements
+    /// that have no `WHERE` clause, so a missing filter is a parse error
+    /// instead of a silent whole-table mutation.
+    pub fn require_where_for_dml(mut self, required: bool) -> Self {
+        self.require_where_for_dml = required;
+        self
+    }
+
     pub fn parse(&mut self, input: &str) -> Result<Statement> {
         let mut lexer = Lexer::new(input);
         self.tokens = lexer.tokenize()?;
+        self.input = input.to_string();
         self.position = 0;
+        self.next_placeholder = 1;
 
         self.parse_statement()
     }
@@ -36,18 +59,28 @@ impl Parser {
                 self.advance();
                 Ok(Statement::Commit)
             }
-            Token::Rollback => {
-                self.advance();
-                Ok(Statement::Rollback)
-            }
-            _ => anyhow::bail!("Unexpected token: {:?}", self.current()?),
+            Token::Rollback => self.parse_rollback(),
+            Token::Savepoint => self.parse_savepoint(),
+            Token::Release => self.parse_release_savepoint(),
+            other => return Err(self.error_at(self.current_span(), format!("Unexpected token: {:?}", other))),
         }
     }
 
     fn parse_retrieve(&mut self) -> Result<Statement> {
         self.advance();
 
-        let projection = self.parse_projection()?;
+        let distinct = if matches!(self.current(), Ok(Token::Distinct)) {
+            self.advance();
+            true
+        } else {
+            false
+        };
+
+        let projection = self
+            .parse_projection()?
+            .into_iter()
+            .map(constant_fold::optimize)
+            .collect();
 
         self.expect(Token::From)?;
         let from = self.parse_table_reference()?;
@@ -59,7 +92,7 @@ impl Parser {
 
         let filter = if matches!(self.current(), Ok(Token::Where)) {
             self.advance();
-            Some(self.parse_expression()?)
+            Some(constant_fold::optimize(self.parse_expression()?))
         } else {
             None
         };
@@ -72,6 +105,13 @@ impl Parser {
             Vec::new()
         };
 
+        let having = if matches!(self.current(), Ok(Token::Having)) {
+            self.advance();
+            Some(constant_fold::optimize(self.parse_expression()?))
+        } else {
+            None
+        };
+
         let order_by = if matches!(self.current(), Ok(Token::Order)) {
             self.advance();
             self.expect(Token::By)?;
@@ -113,11 +153,13 @@ impl Parser {
         };
 
         Ok(Statement::Retrieve(RetrieveStatement {
+            distinct,
             projection,
             from,
             joins,
             filter,
             group_by,
+            having,
             order_by,
             limit,
             offset,
@@ -186,6 +228,10 @@ impl Parser {
             None
         };
 
+        if self.require_where_for_dml && filter.is_none() {
+            anyhow::bail!("refusing to UPDATE without WHERE; use WHERE true to override");
+        }
+
         Ok(Statement::Update(UpdateStatement {
             table,
             assignments,
@@ -206,6 +252,10 @@ impl Parser {
             None
         };
 
+        if self.require_where_for_dml && filter.is_none() {
+            anyhow::bail!("refusing to DELETE without WHERE; use WHERE true to override");
+        }
+
         Ok(Statement::Delete(DeleteStatement { table, filter }))
     }
 
@@ -215,7 +265,8 @@ impl Parser {
         match self.current()? {
             Token::Table => self.parse_create_table(),
             Token::Index => self.parse_create_index(),
-            _ => anyhow::bail!("Expected TABLE or INDEX after CREATE"),
+            Token::Policy => self.parse_create_policy(),
+            _ => Err(self.error_at(self.current_span(), "Expected TABLE, INDEX, or POLICY after CREATE".to_string())),
         }
     }
 
@@ -273,13 +324,31 @@ impl Parser {
 
     fn parse_drop(&mut self) -> Result<Statement> {
         self.advance();
-        self.expect(Token::Table)?;
+
+        match self.current()? {
+            Token::Table => self.parse_drop_table(),
+            Token::Index => self.parse_drop_index(),
+            Token::Policy => self.parse_drop_policy(),
+            _ => Err(self.error_at(self.current_span(), "Expected TABLE, INDEX, or POLICY after DROP".to_string())),
+        }
+    }
+
+    fn parse_drop_table(&mut self) -> Result<Statement> {
+        self.advance();
 
         let name = self.parse_identifier()?;
 
         Ok(Statement::DropTable(DropTableStatement { name }))
     }
 
+    fn parse_drop_index(&mut self) -> Result<Statement> {
+        self.advance();
+
+        let name = self.parse_identifier()?;
+
+        Ok(Statement::DropIndex(DropIndexStatement { name }))
+    }
+
     fn parse_begin_transaction(&mut self) -> Result<Statement> {
         self.advance();
 
@@ -306,6 +375,84 @@ impl Parser {
         }))
     }
 
+    /// `ROLLBACK` alone aborts the whole transaction; `ROLLBACK TO
+    /// [SAVEPOINT] name` instead unwinds just the statements issued since
+    /// that savepoint, leaving the enclosing transaction active.
+    fn parse_rollback(&mut self) -> Result<Statement> {
+        self.advance();
+
+        if matches!(self.current(), Ok(Token::To)) {
+            self.advance();
+
+            if matches!(self.current(), Ok(Token::Savepoint)) {
+                self.advance();
+            }
+
+            let name = self.parse_identifier()?;
+            return Ok(Statement::RollbackToSavepoint(RollbackToSavepointStatement { name }));
+        }
+
+        Ok(Statement::Rollback)
+    }
+
+    fn parse_savepoint(&mut self) -> Result<Statement> {
+        self.advance();
+        let name = self.parse_identifier()?;
+        Ok(Statement::Savepoint(SavepointStatement { name }))
+    }
+
+    fn parse_release_savepoint(&mut self) -> Result<Statement> {
+        self.advance();
+
+        if matches!(self.current(), Ok(Token::Savepoint)) {
+            self.advance();
+        }
+
+        let name = self.parse_identifier()?;
+        Ok(Statement::ReleaseSavepoint(ReleaseSavepointStatement { name }))
+    }
+
+    /// `CREATE POLICY <name> ON <table> [FOR ROLE <role>, ...] USING
+    /// (<predicate>)`. The `FOR ROLE` clause is optional; omitting it leaves
+    /// `roles` empty, which `RLSManager` treats as applying to every role.
+    fn parse_create_policy(&mut self) -> Result<Statement> {
+        self.advance();
+
+        let policy_name = self.parse_identifier()?;
+        self.expect(Token::On)?;
+        let table = self.parse_identifier()?;
+
+        let roles = if matches!(self.current(), Ok(Token::For)) {
+            self.advance();
+            self.expect(Token::Role)?;
+            self.parse_identifier_list()?
+        } else {
+            Vec::new()
+        };
+
+        self.expect(Token::Using)?;
+        self.expect(Token::LeftParen)?;
+        let predicate = self.parse_expression()?;
+        self.expect(Token::RightParen)?;
+
+        Ok(Statement::CreatePolicy(CreatePolicyStatement {
+            policy_name,
+            table,
+            roles,
+            predicate,
+        }))
+    }
+
+    fn parse_drop_policy(&mut self) -> Result<Statement> {
+        self.advance();
+
+        let policy_name = self.parse_identifier()?;
+        self.expect(Token::On)?;
+        let table = self.parse_identifier()?;
+
+        Ok(Statement::DropPolicy(DropPolicyStatement { policy_name, table }))
+    }
+
     fn parse_projection(&mut self) -> Result<Vec<Expression>> {
         if matches!(self.current(), Ok(Token::Star)) {
             self.advance();
@@ -470,6 +617,38 @@ impl Parser {
 
     fn parse_primary_expression(&mut self) -> Result<Expression> {
         match self.current()? {
+            Token::Cast => {
+                self.advance();
+                self.expect(Token::LeftParen)?;
+                let inner = self.parse_expression()?;
+                self.expect(Token::As)?;
+                let conversion = self.parse_conversion_name()?;
+
+                let format = if matches!(self.current(), Ok(Token::LeftParen)) {
+                    self.advance();
+                    let span = self.current_span();
+                    let fmt = match self.current()? {
+                        Token::String(s) => {
+                            let fmt = s.clone();
+                            self.advance();
+                            fmt
+                        }
+                        other => return Err(self.error_at(span, format!("Expected format string in CAST, found {:?}", other))),
+                    };
+                    self.expect(Token::RightParen)?;
+                    Some(fmt)
+                } else {
+                    None
+                };
+
+                self.expect(Token::RightParen)?;
+
+                Ok(Expression::Cast {
+                    inner: Box::new(inner),
+                    conversion,
+                    format,
+                })
+            }
             Token::LeftParen => {
                 self.advance();
                 let expr = self.parse_expression()?;
@@ -516,7 +695,33 @@ impl Parser {
                 self.advance();
                 Ok(Expression::Star)
             }
-            _ => anyhow::bail!("Unexpected token in expression: {:?}", self.current()?),
+            Token::Placeholder(explicit_index) => {
+                let index = explicit_index.unwrap_or(self.next_placeholder);
+                self.next_placeholder = index + 1;
+                self.advance();
+                Ok(Expression::Placeholder(index))
+            }
+            other => Err(self.error_at(self.current_span(), format!("Unexpected token in expression: {:?}", other))),
+        }
+    }
+
+    /// The type name in `CAST(expr AS <name>)`. Most conversions are plain
+    /// identifiers (`int`, `bool`, ...), but `timestamp` is its own keyword
+    /// token (shared with `AT TIMESTAMP`/column typing), so it needs its
+    /// own arm here rather than falling through to `Token::Identifier`.
+    fn parse_conversion_name(&mut self) -> Result<String> {
+        let span = self.current_span();
+        match self.current()? {
+            Token::Timestamp => {
+                self.advance();
+                Ok("timestamp".to_string())
+            }
+            Token::Identifier(name) => {
+                let name = name.clone();
+                self.advance();
+                Ok(name)
+            }
+            other => Err(self.error_at(span, format!("Expected conversion type name in CAST, found {:?}", other))),
         }
     }
 
@@ -567,9 +772,40 @@ impl Parser {
 
         loop {
             let expr = self.parse_expression()?;
-            let ascending = true;
 
-            clauses.push(OrderByClause { expr, ascending });
+            let ascending = if matches!(self.current(), Ok(Token::Desc)) {
+                self.advance();
+                false
+            } else {
+                if matches!(self.current(), Ok(Token::Asc)) {
+                    self.advance();
+                }
+                true
+            };
+
+            let nulls = if matches!(self.current(), Ok(Token::Nulls)) {
+                self.advance();
+                match self.current()? {
+                    Token::First => {
+                        self.advance();
+                        Some(NullsOrder::First)
+                    }
+                    Token::Last => {
+                        self.advance();
+                        Some(NullsOrder::Last)
+                    }
+                    other => {
+                        return Err(self.error_at(
+                            self.current_span(),
+                            format!("Expected FIRST or LAST after NULLS, found {:?}", other),
+                        ))
+                    }
+                }
+            } else {
+                None
+            };
+
+            clauses.push(OrderByClause { expr, ascending, nulls });
 
             if !matches!(self.current(), Ok(Token::Comma)) {
                 break;
@@ -596,7 +832,7 @@ impl Parser {
     }
 
     fn parse_data_type(&mut self) -> Result<DataType> {
-        let type_name = self.parse_identifier()?;
+        let (type_name, span) = self.parse_identifier_spanned()?;
 
         match type_name.to_lowercase().as_str() {
             "boolean" | "bool" => Ok(DataType::Boolean),
@@ -606,56 +842,105 @@ impl Parser {
             "double" => Ok(DataType::Double),
             "text" | "string" | "varchar" => Ok(DataType::Text),
             "timestamp" | "datetime" => Ok(DataType::Timestamp),
-            _ => anyhow::bail!("Unknown data type: {}", type_name),
+            _ => Err(self.error_at(span, format!("Unknown data type: {}", type_name))),
         }
     }
 
-    fn parse_identifier(&mut self) -> Result<String> {
+    /// Returns the identifier along with the span it was lexed from, so a
+    /// caller that needs to point a later semantic error back at the source
+    /// (e.g. "unknown column") doesn't have to re-derive a position.
+    fn parse_identifier_spanned(&mut self) -> Result<(String, Span)> {
+        let span = self.current_span();
         match self.current()? {
             Token::Identifier(name) => {
                 let id = name.clone();
                 self.advance();
-                Ok(id)
+                Ok((id, span))
             }
-            _ => anyhow::bail!("Expected identifier, got {:?}", self.current()?),
+            other => Err(self.error_at(span, format!("Expected identifier, found {:?}", other))),
         }
     }
 
+    fn parse_identifier(&mut self) -> Result<String> {
+        self.parse_identifier_spanned().map(|(id, _)| id)
+    }
+
     fn parse_string(&mut self) -> Result<String> {
+        let span = self.current_span();
         match self.current()? {
             Token::String(s) => {
                 let val = s.clone();
                 self.advance();
                 Ok(val)
             }
-            _ => anyhow::bail!("Expected string, got {:?}", self.current()?),
+            other => Err(self.error_at(span, format!("Expected string, found {:?}", other))),
         }
     }
 
     fn parse_integer(&mut self) -> Result<i64> {
+        let span = self.current_span();
         match self.current()? {
             Token::Integer(n) => {
                 let val = *n;
                 self.advance();
                 Ok(val)
             }
-            _ => anyhow::bail!("Expected integer, got {:?}", self.current()?),
+            other => Err(self.error_at(span, format!("Expected integer, found {:?}", other))),
         }
     }
 
     fn current(&self) -> Result<&Token> {
         self.tokens
             .get(self.position)
+            .map(|t| &t.token)
             .context("Unexpected end of input")
     }
 
+    /// The current token's span, or the span just past the last token if the
+    /// cursor has run off the end — so an "unexpected end of input" error
+    /// still has somewhere to point.
+    fn current_span(&self) -> Span {
+        self.tokens
+            .get(self.position)
+            .map(|t| t.span)
+            .or_else(|| self.tokens.last().map(|t| t.span))
+            .unwrap_or(Span { start: 0, end: 0 })
+    }
+
+    /// Converts a character offset into the original input into a 1-based
+    /// `(line, column)` pair, scanning for newlines up to that offset. Only
+    /// called when building an error message, so paying for the scan there
+    /// (rather than tracking line/column on every token) is the cheaper
+    /// trade-off.
+    fn line_col(&self, offset: usize) -> (usize, usize) {
+        let mut line = 1;
+        let mut column = 1;
+
+        for ch in self.input.chars().take(offset) {
+            if ch == '\n' {
+                line += 1;
+                column = 1;
+            } else {
+                column += 1;
+            }
+        }
+
+        (line, column)
+    }
+
+    fn error_at(&self, span: Span, message: String) -> anyhow::Error {
+        let (line, column) = self.line_col(span.start);
+        anyhow::anyhow!("{} at line {}, column {}", message, line, column)
+    }
+
     fn advance(&mut self) {
         self.position += 1;
     }
 
     fn expect(&mut self, expected: Token) -> Result<()> {
+        let span = self.current_span();
         let current = self.current()?.clone();
-        
+
         let matches = match (&expected, &current) {
             (Token::Identifier(_), Token::Identifier(_)) => true,
             (Token::String(_), Token::String(_)) => true,
@@ -668,7 +953,7 @@ impl Parser {
             self.advance();
             Ok(())
         } else {
-            anyhow::bail!("Expected {:?}, got {:?}", expected, current)
+            Err(self.error_at(span, format!("Expected {:?}, found {:?}", expected, current)))
         }
     }
-  }
+}
@@ -1,12 +1,25 @@
 use crate::language::ast::*;
+use crate::language::catalog::{Catalog, TableSchema};
+use crate::language::describe::{Describe, TypeInferer, TypeKind};
 use crate::language::intent::*;
 use anyhow::Result;
 
-pub struct SemanticAnalyzer;
+/// A table as it's visible inside one statement: its catalog name and
+/// schema. An alias the statement gives it is not kept
+/// (`extract_table_name` resolves it to the table name), so a qualified
+/// column reference resolves against the catalog name.
+struct ScopeEntry<'a> {
+    name: String,
+    schema: &'a TableSchema,
+}
+
+pub struct SemanticAnalyzer {
+    catalog: Catalog,
+}
 
 impl SemanticAnalyzer {
-    pub fn new() -> Self {
-        Self
+    pub fn new(catalog: Catalog) -> Self {
+        Self { catalog }
     }
 
     pub fn analyze(&self, statement: &Statement) -> Result<Intent> {
@@ -18,6 +31,7 @@ impl SemanticAnalyzer {
             Statement::CreateTable(stmt) => self.analyze_create_table(stmt),
             Statement::CreateIndex(stmt) => self.analyze_create_index(stmt),
             Statement::DropTable(stmt) => self.analyze_drop_table(stmt),
+            Statement::DropIndex(stmt) => self.analyze_drop_index(stmt),
             Statement::BeginTransaction(stmt) => self.analyze_begin_transaction(stmt),
             Statement::Commit => Ok(Intent::Transaction {
                 operation: TransactionIntent::Commit,
@@ -25,33 +39,249 @@ impl SemanticAnalyzer {
             Statement::Rollback => Ok(Intent::Transaction {
                 operation: TransactionIntent::Rollback,
             }),
+            Statement::Savepoint(stmt) => Ok(Intent::Transaction {
+                operation: TransactionIntent::Savepoint { name: stmt.name.clone() },
+            }),
+            Statement::ReleaseSavepoint(stmt) => Ok(Intent::Transaction {
+                operation: TransactionIntent::ReleaseSavepoint { name: stmt.name.clone() },
+            }),
+            Statement::RollbackToSavepoint(stmt) => Ok(Intent::Transaction {
+                operation: TransactionIntent::RollbackToSavepoint { name: stmt.name.clone() },
+            }),
+            Statement::CreatePolicy(stmt) => self.analyze_create_policy(stmt),
+            Statement::DropPolicy(stmt) => self.analyze_drop_policy(stmt),
+        }
+    }
+
+    fn lookup_table(&self, name: &str) -> Result<&TableSchema> {
+        self.catalog
+            .table(name)
+            .ok_or_else(|| anyhow::anyhow!("Unknown table: {}", name))
+    }
+
+    /// Describes a statement without executing it: the type of each result
+    /// column and the type inferred for each `?`/`$n` placeholder, so a
+    /// caller can prepare the statement and bind parameters up front.
+    ///
+    /// This doesn't just delegate to `analyze` because `analyze_insert`
+    /// requires every inserted value to be a literal; describing an INSERT
+    /// with placeholders needs its own path that infers a placeholder's type
+    /// from the column it's bound to instead of rejecting it outright.
+    pub fn describe(&self, statement: &Statement) -> Result<Describe> {
+        match statement {
+            Statement::Retrieve(stmt) => self.describe_retrieve(stmt),
+            Statement::Insert(stmt) => self.describe_insert(stmt),
+            Statement::Update(stmt) => self.describe_update(stmt),
+            Statement::Delete(stmt) => self.describe_delete(stmt),
+            Statement::CreateTable(_)
+            | Statement::CreateIndex(_)
+            | Statement::DropTable(_)
+            | Statement::DropIndex(_)
+            | Statement::BeginTransaction(_)
+            | Statement::Commit
+            | Statement::Rollback
+            | Statement::Savepoint(_)
+            | Statement::ReleaseSavepoint(_)
+            | Statement::RollbackToSavepoint(_)
+            | Statement::CreatePolicy(_)
+            | Statement::DropPolicy(_) => Ok(Describe {
+                columns: Vec::new(),
+                params: Vec::new(),
+            }),
+        }
+    }
+
+    fn describe_retrieve(&self, stmt: &RetrieveStatement) -> Result<Describe> {
+        let primary = self.extract_table_name(&stmt.from)?;
+        let mut scope = vec![ScopeEntry {
+            name: primary.clone(),
+            schema: self.lookup_table(&primary)?,
+        }];
+
+        for join in &stmt.joins {
+            let table = self.extract_table_name(&join.table)?;
+            scope.push(ScopeEntry {
+                name: table.clone(),
+                schema: self.lookup_table(&table)?,
+            });
+        }
+
+        let type_scope: Vec<(&str, &TableSchema)> =
+            scope.iter().map(|entry| (entry.name.as_str(), entry.schema)).collect();
+
+        let (projected_columns, _) = self.analyze_projection(&stmt.projection, &scope)?;
+
+        let mut inferer = TypeInferer::new();
+        let mut columns = Vec::new();
+        for col in &projected_columns {
+            match col {
+                ColumnIntent::Named(name) => {
+                    let ty = inferer.infer_expression(&ExpressionIntent::Column(name.clone()), &type_scope);
+                    columns.push((name.clone(), ty));
+                }
+                ColumnIntent::Qualified { table, column } => {
+                    let expr = ExpressionIntent::QualifiedColumn {
+                        table: table.clone(),
+                        column: column.clone(),
+                    };
+                    let ty = inferer.infer_expression(&expr, &type_scope);
+                    columns.push((format!("{}.{}", table, column), ty));
+                }
+                ColumnIntent::Expression { expr, alias } => {
+                    let ty = inferer.infer_expression(expr, &type_scope);
+                    let label = alias.clone().unwrap_or_else(|| format!("{:?}", expr));
+                    columns.push((label, ty));
+                }
+                ColumnIntent::All => {}
+            }
+        }
+
+        if let Some(filter) = &stmt.filter {
+            let filter_intent = self.analyze_filter(filter, &scope)?;
+            inferer.infer_filter(&filter_intent, &type_scope);
         }
+
+        if let Some(having) = &stmt.having {
+            let having_intent = self.analyze_filter(having, &scope)?;
+            inferer.infer_filter(&having_intent, &type_scope);
+        }
+
+        Ok(Describe {
+            columns,
+            params: inferer.finish_params(),
+        })
+    }
+
+    /// Doesn't route through `analyze_insert`/`extract_constant`, which
+    /// rejects any value that isn't a literal; a placeholder value instead
+    /// takes its type from the column it's being inserted into.
+    fn describe_insert(&self, stmt: &InsertStatement) -> Result<Describe> {
+        let schema = self.lookup_table(&stmt.table)?;
+        let scope = vec![ScopeEntry {
+            name: stmt.table.clone(),
+            schema,
+        }];
+        let type_scope: Vec<(&str, &TableSchema)> = vec![(stmt.table.as_str(), schema)];
+
+        let mut inferer = TypeInferer::new();
+        for row in &stmt.values {
+            for (column, expr) in stmt.columns.iter().zip(row) {
+                let value_intent = self.analyze_expression_intent(expr, &scope)?;
+                let column_type = schema
+                    .column(column)
+                    .map(|col_def| TypeKind::from(&col_def.data_type))
+                    .unwrap_or(TypeKind::Unknown);
+                inferer.infer_assignment(&value_intent, column_type, &type_scope);
+            }
+        }
+
+        Ok(Describe {
+            columns: Vec::new(),
+            params: inferer.finish_params(),
+        })
+    }
+
+    fn describe_update(&self, stmt: &UpdateStatement) -> Result<Describe> {
+        let schema = self.lookup_table(&stmt.table)?;
+        let scope = vec![ScopeEntry {
+            name: stmt.table.clone(),
+            schema,
+        }];
+        let type_scope: Vec<(&str, &TableSchema)> = vec![(stmt.table.as_str(), schema)];
+
+        let mut inferer = TypeInferer::new();
+        for assignment in &stmt.assignments {
+            let column_type = schema
+                .column(&assignment.column)
+                .map(|col_def| TypeKind::from(&col_def.data_type))
+                .unwrap_or(TypeKind::Unknown);
+            let value_intent = self.analyze_expression_intent(&assignment.value, &scope)?;
+            inferer.infer_assignment(&value_intent, column_type, &type_scope);
+        }
+
+        if let Some(filter) = &stmt.filter {
+            let filter_intent = self.analyze_filter(filter, &scope)?;
+            inferer.infer_filter(&filter_intent, &type_scope);
+        }
+
+        Ok(Describe {
+            columns: Vec::new(),
+            params: inferer.finish_params(),
+        })
+    }
+
+    fn describe_delete(&self, stmt: &DeleteStatement) -> Result<Describe> {
+        let schema = self.lookup_table(&stmt.table)?;
+        let scope = vec![ScopeEntry {
+            name: stmt.table.clone(),
+            schema,
+        }];
+        let type_scope: Vec<(&str, &TableSchema)> = vec![(stmt.table.as_str(), schema)];
+
+        let mut inferer = TypeInferer::new();
+        if let Some(filter) = &stmt.filter {
+            let filter_intent = self.analyze_filter(filter, &scope)?;
+            inferer.infer_filter(&filter_intent, &type_scope);
+        }
+
+        Ok(Describe {
+            columns: Vec::new(),
+            params: inferer.finish_params(),
+        })
     }
 
     fn analyze_retrieve(&self, stmt: &RetrieveStatement) -> Result<Intent> {
-        let columns = self.analyze_projection(&stmt.projection)?;
-        
+        let primary = self.extract_table_name(&stmt.from)?;
+        let mut scope = vec![ScopeEntry {
+            name: primary.clone(),
+            schema: self.lookup_table(&primary)?,
+        }];
+
+        for join in &stmt.joins {
+            let table = self.extract_table_name(&join.table)?;
+            scope.push(ScopeEntry {
+                name: table.clone(),
+                schema: self.lookup_table(&table)?,
+            });
+        }
+
+        let grouping = stmt
+            .group_by
+            .iter()
+            .map(|e| self.analyze_expression_intent(e, &scope))
+            .collect::<Result<Vec<_>>>()?;
+
+        let (columns, aggregates) = self.analyze_projection(&stmt.projection, &scope)?;
+
+        if !aggregates.is_empty() || !grouping.is_empty() {
+            self.check_grouping(&columns, &grouping)?;
+        }
+
         let source = SourceIntent {
-            primary: self.extract_table_name(&stmt.from)?,
+            primary,
             joins: stmt
                 .joins
                 .iter()
-                .map(|j| self.analyze_join(j))
+                .map(|j| self.analyze_join(j, &scope))
                 .collect::<Result<Vec<_>>>()?,
         };
 
         let filter = stmt
             .filter
             .as_ref()
-            .map(|f| self.analyze_filter(f))
+            .map(|f| self.analyze_filter(f, &scope))
             .transpose()?;
 
-        let aggregates = Vec::new();
+        let having = stmt
+            .having
+            .as_ref()
+            .map(|h| self.analyze_filter(h, &scope))
+            .transpose()?;
 
         let ordering = stmt
             .order_by
             .iter()
-            .map(|o| self.analyze_order_by(o))
+            .map(|o| self.analyze_order_by(o, &scope))
             .collect::<Result<Vec<_>>>()?;
 
         let time_travel = if let Some(at) = &stmt.at_timestamp {
@@ -65,9 +295,12 @@ impl SemanticAnalyzer {
 
         Ok(Intent::Retrieve {
             columns,
+            distinct: stmt.distinct,
             source,
             filter,
             aggregates,
+            grouping,
+            having,
             ordering,
             limit: stmt.limit,
             time_travel,
@@ -75,12 +308,33 @@ impl SemanticAnalyzer {
     }
 
     fn analyze_insert(&self, stmt: &InsertStatement) -> Result<Intent> {
+        let schema = self.lookup_table(&stmt.table)?;
+
+        for column in &stmt.columns {
+            if !schema.has_column(column) {
+                anyhow::bail!("Unknown column '{}' on table '{}'", column, stmt.table);
+            }
+        }
+
         let mut values = Vec::new();
 
         for row in &stmt.values {
+            if row.len() != stmt.columns.len() {
+                anyhow::bail!(
+                    "Insert into '{}' has {} columns but {} values",
+                    stmt.table,
+                    stmt.columns.len(),
+                    row.len()
+                );
+            }
+
             let mut row_values = Vec::new();
-            for expr in row {
-                row_values.push(self.extract_constant(expr)?);
+            for (column, expr) in stmt.columns.iter().zip(row) {
+                let value = self.extract_constant(expr)?;
+                if let Some(col_def) = schema.column(column) {
+                    self.check_value_type(&stmt.table, column, &col_def.data_type, &value)?;
+                }
+                row_values.push(value);
             }
             values.push(row_values);
         }
@@ -96,19 +350,35 @@ impl SemanticAnalyzer {
     }
 
     fn analyze_update(&self, stmt: &UpdateStatement) -> Result<Intent> {
+        let schema = self.lookup_table(&stmt.table)?;
+        let scope = vec![ScopeEntry {
+            name: stmt.table.clone(),
+            schema,
+        }];
+
         let assignments = stmt
             .assignments
             .iter()
-            .map(|a| Ok(AssignmentIntent {
-                column: a.column.clone(),
-                value: self.analyze_expression_intent(&a.value)?,
-            }))
+            .map(|a| {
+                let col_def = schema
+                    .column(&a.column)
+                    .ok_or_else(|| anyhow::anyhow!("Unknown column '{}' on table '{}'", a.column, stmt.table))?;
+
+                if let Expression::Literal(lit) = &a.value {
+                    self.check_value_type(&stmt.table, &a.column, &col_def.data_type, &self.convert_literal(lit))?;
+                }
+
+                Ok(AssignmentIntent {
+                    column: a.column.clone(),
+                    value: self.analyze_expression_intent(&a.value, &scope)?,
+                })
+            })
             .collect::<Result<Vec<_>>>()?;
 
         let filter = stmt
             .filter
             .as_ref()
-            .map(|f| self.analyze_filter(f))
+            .map(|f| self.analyze_filter(f, &scope))
             .transpose()?;
 
         Ok(Intent::Mutate {
@@ -119,10 +389,16 @@ impl SemanticAnalyzer {
     }
 
     fn analyze_delete(&self, stmt: &DeleteStatement) -> Result<Intent> {
+        let schema = self.lookup_table(&stmt.table)?;
+        let scope = vec![ScopeEntry {
+            name: stmt.table.clone(),
+            schema,
+        }];
+
         let filter = stmt
             .filter
             .as_ref()
-            .map(|f| self.analyze_filter(f))
+            .map(|f| self.analyze_filter(f, &scope))
             .transpose()?;
 
         Ok(Intent::Mutate {
@@ -142,6 +418,13 @@ impl SemanticAnalyzer {
     }
 
     fn analyze_create_index(&self, stmt: &CreateIndexStatement) -> Result<Intent> {
+        let schema = self.lookup_table(&stmt.table)?;
+        for column in &stmt.columns {
+            if !schema.has_column(column) {
+                anyhow::bail!("Unknown column '{}' on table '{}'", column, stmt.table);
+            }
+        }
+
         Ok(Intent::Schema {
             operation: SchemaIntent::CreateIndex {
                 name: stmt.name.clone(),
@@ -159,6 +442,46 @@ impl SemanticAnalyzer {
         })
     }
 
+    fn analyze_drop_index(&self, stmt: &DropIndexStatement) -> Result<Intent> {
+        Ok(Intent::Schema {
+            operation: SchemaIntent::DropIndex {
+                name: stmt.name.clone(),
+            },
+        })
+    }
+
+    /// The policy's `USING` predicate is analyzed against a single-table
+    /// scope, the same as a `WHERE` clause would be, so it can only
+    /// reference columns of the table the policy protects.
+    fn analyze_create_policy(&self, stmt: &CreatePolicyStatement) -> Result<Intent> {
+        let scope = vec![ScopeEntry {
+            name: stmt.table.clone(),
+            schema: self.lookup_table(&stmt.table)?,
+        }];
+
+        let filter = self.analyze_filter(&stmt.predicate, &scope)?;
+
+        Ok(Intent::Schema {
+            operation: SchemaIntent::CreatePolicy {
+                policy_name: stmt.policy_name.clone(),
+                table: stmt.table.clone(),
+                roles: stmt.roles.clone(),
+                filter,
+            },
+        })
+    }
+
+    fn analyze_drop_policy(&self, stmt: &DropPolicyStatement) -> Result<Intent> {
+        self.lookup_table(&stmt.table)?;
+
+        Ok(Intent::Schema {
+            operation: SchemaIntent::DropPolicy {
+                policy_name: stmt.policy_name.clone(),
+                table: stmt.table.clone(),
+            },
+        })
+    }
+
     fn analyze_begin_transaction(&self, stmt: &BeginTransactionStatement) -> Result<Intent> {
         Ok(Intent::Transaction {
             operation: TransactionIntent::Begin {
@@ -168,81 +491,155 @@ impl SemanticAnalyzer {
         })
     }
 
-    fn analyze_projection(&self, projection: &[Expression]) -> Result<Vec<ColumnIntent>> {
-        projection
-            .iter()
-            .map(|expr| match expr {
-                Expression::Star => Ok(ColumnIntent::All),
-                Expression::Column(name) => Ok(ColumnIntent::Named(name.clone())),
-                Expression::QualifiedColumn { table, column } => Ok(ColumnIntent::Qualified {
-                    table: table.clone(),
-                    column: column.clone(),
-                }),
-                expr => Ok(ColumnIntent::Expression {
-                    expr: self.analyze_expression_intent(expr)?,
+    /// Analyzes the projection list, splitting out aggregate function calls
+    /// (`COUNT`/`SUM`/`AVG`/`MIN`/`MAX`) into `AggregateIntent`s alongside the
+    /// plain `ColumnIntent` projection so `HashAggregate` has something to
+    /// compute.
+    fn analyze_projection(
+        &self,
+        projection: &[Expression],
+        scope: &[ScopeEntry],
+    ) -> Result<(Vec<ColumnIntent>, Vec<AggregateIntent>)> {
+        let mut columns = Vec::new();
+        let mut aggregates = Vec::new();
+
+        for expr in projection {
+            match expr {
+                Expression::Star => {
+                    for entry in scope {
+                        for col in &entry.schema.columns {
+                            columns.push(ColumnIntent::Named(col.name.clone()));
+                        }
+                    }
+                }
+                Expression::Column(name) => {
+                    self.resolve_column(name, scope)?;
+                    columns.push(ColumnIntent::Named(name.clone()));
+                }
+                Expression::QualifiedColumn { table, column } => {
+                    self.resolve_qualified_column(table, column, scope)?;
+                    columns.push(ColumnIntent::Qualified {
+                        table: table.clone(),
+                        column: column.clone(),
+                    });
+                }
+                Expression::FunctionCall { name, args } if Self::is_aggregate(name) => {
+                    let argument = match args.as_slice() {
+                        [Expression::Star] => ExpressionIntent::Constant(ConstantValue::Integer(1)),
+                        [single] => self.analyze_expression_intent(single, scope)?,
+                        _ => anyhow::bail!("Aggregate function '{}' takes exactly one argument", name),
+                    };
+
+                    let function = name.to_uppercase();
+                    aggregates.push(AggregateIntent {
+                        function: function.clone(),
+                        argument: argument.clone(),
+                        alias: None,
+                    });
+                    columns.push(ColumnIntent::Expression {
+                        expr: ExpressionIntent::Function {
+                            name: function,
+                            args: vec![argument],
+                        },
+                        alias: None,
+                    });
+                }
+                expr => columns.push(ColumnIntent::Expression {
+                    expr: self.analyze_expression_intent(expr, scope)?,
                     alias: None,
                 }),
-            })
-            .collect()
+            }
+        }
+
+        Ok((columns, aggregates))
+    }
+
+    fn is_aggregate(name: &str) -> bool {
+        matches!(name.to_uppercase().as_str(), "COUNT" | "SUM" | "AVG" | "MIN" | "MAX")
+    }
+
+    /// Enforces that every plain (non-aggregate) projected column also
+    /// appears in the GROUP BY set, the standard rule that makes a mixed
+    /// aggregate/non-aggregate projection well-defined.
+    fn check_grouping(&self, columns: &[ColumnIntent], grouping: &[ExpressionIntent]) -> Result<()> {
+        let grouping_keys: std::collections::HashSet<String> =
+            grouping.iter().map(|g| format!("{:?}", g)).collect();
+
+        for col in columns {
+            let (key, label) = match col {
+                ColumnIntent::Named(name) => {
+                    (format!("{:?}", ExpressionIntent::Column(name.clone())), name.clone())
+                }
+                ColumnIntent::Qualified { table, column } => (
+                    format!(
+                        "{:?}",
+                        ExpressionIntent::QualifiedColumn {
+                            table: table.clone(),
+                            column: column.clone(),
+                        }
+                    ),
+                    format!("{}.{}", table, column),
+                ),
+                ColumnIntent::Expression { .. } | ColumnIntent::All => continue,
+            };
+
+            if !grouping_keys.contains(&key) {
+                anyhow::bail!(
+                    "Column '{}' must appear in the GROUP BY clause or be used in an aggregate function",
+                    label
+                );
+            }
+        }
+
+        Ok(())
     }
 
-    fn analyze_join(&self, join: &JoinClause) -> Result<JoinIntent> {
+    fn analyze_join(&self, join: &JoinClause, scope: &[ScopeEntry]) -> Result<JoinIntent> {
         Ok(JoinIntent {
             join_type: join.join_type.clone(),
             table: self.extract_table_name(&join.table)?,
-            condition: self.analyze_filter(&join.on)?,
+            condition: self.analyze_filter(&join.on, scope)?,
         })
     }
 
-    fn analyze_filter(&self, expr: &Expression) -> Result<FilterIntent> {
+    fn analyze_filter(&self, expr: &Expression, scope: &[ScopeEntry]) -> Result<FilterIntent> {
         match expr {
             Expression::BinaryOp { op, left, right } => {
-                let left_intent = self.analyze_expression_intent(left)?;
-                let right_intent = self.analyze_expression_intent(right)?;
+                let left_intent = self.analyze_expression_intent(left, scope)?;
+                let right_intent = self.analyze_expression_intent(right, scope)?;
+
+                let comparison_op = match op {
+                    BinaryOperator::Equals => Some(ComparisonOp::Equal),
+                    BinaryOperator::NotEquals => Some(ComparisonOp::NotEqual),
+                    BinaryOperator::LessThan => Some(ComparisonOp::LessThan),
+                    BinaryOperator::LessThanOrEqual => Some(ComparisonOp::LessThanOrEqual),
+                    BinaryOperator::GreaterThan => Some(ComparisonOp::GreaterThan),
+                    BinaryOperator::GreaterThanOrEqual => Some(ComparisonOp::GreaterThanOrEqual),
+                    _ => None,
+                };
 
-                match op {
-                    BinaryOperator::Equals => Ok(FilterIntent::Comparison {
-                        op: ComparisonOp::Equal,
-                        left: left_intent,
-                        right: right_intent,
-                    }),
-                    BinaryOperator::NotEquals => Ok(FilterIntent::Comparison {
-                        op: ComparisonOp::NotEqual,
-                        left: left_intent,
-                        right: right_intent,
-                    }),
-                    BinaryOperator::LessThan => Ok(FilterIntent::Comparison {
-                        op: ComparisonOp::LessThan,
-                        left: left_intent,
-                        right: right_intent,
-                    }),
-                    BinaryOperator::LessThanOrEqual => Ok(FilterIntent::Comparison {
-                        op: ComparisonOp::LessThanOrEqual,
-                        left: left_intent,
-                        right: right_intent,
-                    }),
-                    BinaryOperator::GreaterThan => Ok(FilterIntent::Comparison {
-                        op: ComparisonOp::GreaterThan,
-                        left: left_intent,
-                        right: right_intent,
-                    }),
-                    BinaryOperator::GreaterThanOrEqual => Ok(FilterIntent::Comparison {
-                        op: ComparisonOp::GreaterThanOrEqual,
+                if let Some(op) = comparison_op {
+                    self.check_comparable(&left_intent, &right_intent, scope)?;
+                    return Ok(FilterIntent::Comparison {
+                        op,
                         left: left_intent,
                         right: right_intent,
-                    }),
+                    });
+                }
+
+                match op {
                     BinaryOperator::And => Ok(FilterIntent::Logical {
                         op: LogicalOp::And,
                         operands: vec![
-                            self.analyze_filter(left)?,
-                            self.analyze_filter(right)?,
+                            self.analyze_filter(left, scope)?,
+                            self.analyze_filter(right, scope)?,
                         ],
                     }),
                     BinaryOperator::Or => Ok(FilterIntent::Logical {
                         op: LogicalOp::Or,
                         operands: vec![
-                            self.analyze_filter(left)?,
-                            self.analyze_filter(right)?,
+                            self.analyze_filter(left, scope)?,
+                            self.analyze_filter(right, scope)?,
                         ],
                     }),
                     _ => anyhow::bail!("Invalid operator in filter: {:?}", op),
@@ -251,7 +648,7 @@ impl SemanticAnalyzer {
             Expression::UnaryOp { op, operand } => match op {
                 UnaryOperator::Not => Ok(FilterIntent::Logical {
                     op: LogicalOp::Not,
-                    operands: vec![self.analyze_filter(operand)?],
+                    operands: vec![self.analyze_filter(operand, scope)?],
                 }),
                 _ => anyhow::bail!("Invalid unary operator in filter"),
             },
@@ -259,10 +656,14 @@ impl SemanticAnalyzer {
         }
     }
 
-    fn analyze_expression_intent(&self, expr: &Expression) -> Result<ExpressionIntent> {
+    fn analyze_expression_intent(&self, expr: &Expression, scope: &[ScopeEntry]) -> Result<ExpressionIntent> {
         match expr {
-            Expression::Column(name) => Ok(ExpressionIntent::Column(name.clone())),
+            Expression::Column(name) => {
+                self.resolve_column(name, scope)?;
+                Ok(ExpressionIntent::Column(name.clone()))
+            }
             Expression::QualifiedColumn { table, column } => {
+                self.resolve_qualified_column(table, column, scope)?;
                 Ok(ExpressionIntent::QualifiedColumn {
                     table: table.clone(),
                     column: column.clone(),
@@ -270,8 +671,8 @@ impl SemanticAnalyzer {
             }
             Expression::Literal(lit) => Ok(ExpressionIntent::Constant(self.convert_literal(lit))),
             Expression::BinaryOp { op, left, right } => {
-                let left_intent = self.analyze_expression_intent(left)?;
-                let right_intent = self.analyze_expression_intent(right)?;
+                let left_intent = self.analyze_expression_intent(left, scope)?;
+                let right_intent = self.analyze_expression_intent(right, scope)?;
 
                 let arith_op = match op {
                     BinaryOperator::Add => ArithmeticOp::Add,
@@ -290,7 +691,7 @@ impl SemanticAnalyzer {
             Expression::FunctionCall { name, args } => {
                 let arg_intents = args
                     .iter()
-                    .map(|a| self.analyze_expression_intent(a))
+                    .map(|a| self.analyze_expression_intent(a, scope))
                     .collect::<Result<Vec<_>>>()?;
 
                 Ok(ExpressionIntent::Function {
@@ -298,14 +699,64 @@ impl SemanticAnalyzer {
                     args: arg_intents,
                 })
             }
+            Expression::Cast { inner, conversion, format } => {
+                let target = self.analyze_conversion(conversion, format.as_deref())?;
+
+                Ok(ExpressionIntent::Cast {
+                    target,
+                    format: format.clone(),
+                    inner: Box::new(self.analyze_expression_intent(inner, scope)?),
+                })
+            }
+            Expression::Placeholder(index) => Ok(ExpressionIntent::Placeholder(*index)),
             _ => anyhow::bail!("Unsupported expression type"),
         }
     }
 
-    fn analyze_order_by(&self, order_by: &OrderByClause) -> Result<OrderIntent> {
+    /// Resolves a `CAST(... AS <name>)` type name to a `ConversionKind`,
+    /// validating any accompanying strftime-style format string along the
+    /// way so a bad pattern is rejected at analysis time rather than on
+    /// first use.
+    fn analyze_conversion(&self, name: &str, format: Option<&str>) -> Result<ConversionKind> {
+        match (name.to_lowercase().as_str(), format) {
+            ("bytes", _) | ("string", _) => Ok(ConversionKind::Bytes),
+            ("int", _) | ("integer", _) => Ok(ConversionKind::Integer),
+            ("float", _) | ("double", _) => Ok(ConversionKind::Float),
+            ("bool", _) | ("boolean", _) => Ok(ConversionKind::Boolean),
+            ("timestamp", None) => Ok(ConversionKind::Timestamp),
+            ("timestamp", Some(fmt)) => {
+                self.validate_strftime_format(fmt)?;
+                Ok(ConversionKind::TimestampFmt(fmt.to_string()))
+            }
+            ("timestamptz", Some(fmt)) => {
+                self.validate_strftime_format(fmt)?;
+                Ok(ConversionKind::TimestampTZFmt(fmt.to_string()))
+            }
+            ("timestamptz", None) => {
+                anyhow::bail!("Conversion 'timestamptz' requires a format string, e.g. timestamptz('%Y-%m-%dT%H:%M:%S%z')")
+            }
+            _ => anyhow::bail!("Unknown conversion: {}", name),
+        }
+    }
+
+    fn validate_strftime_format(&self, format: &str) -> Result<()> {
+        use chrono::format::{Item, StrftimeItems};
+
+        if StrftimeItems::new(format).any(|item| matches!(item, Item::Error)) {
+            anyhow::bail!("Invalid timestamp format string: {}", format);
+        }
+
+        Ok(())
+    }
+
+    fn analyze_order_by(&self, order_by: &OrderByClause, scope: &[ScopeEntry]) -> Result<OrderIntent> {
         Ok(OrderIntent {
-            expr: self.analyze_expression_intent(&order_by.expr)?,
+            expr: self.analyze_expression_intent(&order_by.expr, scope)?,
             ascending: order_by.ascending,
+            nulls: order_by.nulls.as_ref().map(|nulls| match nulls {
+                crate::language::ast::NullsOrder::First => crate::language::intent::NullsOrder::First,
+                crate::language::ast::NullsOrder::Last => crate::language::intent::NullsOrder::Last,
+            }),
         })
     }
 
@@ -332,4 +783,119 @@ impl SemanticAnalyzer {
             Literal::String(s) => ConstantValue::String(s.clone()),
         }
     }
-          }
+
+    /// Resolves an unqualified column name to exactly one table in scope,
+    /// bailing on an unknown column or one that's ambiguous across a join.
+    fn resolve_column<'a>(&self, name: &str, scope: &'a [ScopeEntry]) -> Result<&'a ScopeEntry<'a>> {
+        let mut matches = scope.iter().filter(|entry| entry.schema.has_column(name));
+
+        let first = matches.next().ok_or_else(|| anyhow::anyhow!("Unknown column: {}", name))?;
+
+        if matches.next().is_some() {
+            anyhow::bail!("Ambiguous column reference: {}", name);
+        }
+
+        Ok(first)
+    }
+
+    fn resolve_qualified_column(&self, table: &str, column: &str, scope: &[ScopeEntry]) -> Result<()> {
+        let entry = scope
+            .iter()
+            .find(|entry| entry.name == table)
+            .ok_or_else(|| anyhow::anyhow!("Unknown table in qualified column: {}", table))?;
+
+        if !entry.schema.has_column(column) {
+            anyhow::bail!("Unknown column '{}' on table '{}'", column, table);
+        }
+
+        Ok(())
+    }
+
+    /// Type-checks the two sides of a comparison where both resolve to a
+    /// concrete column or constant type; expressions whose type can't be
+    /// inferred statically (arithmetic, function calls) are left unchecked
+    /// rather than rejected.
+    fn check_comparable(&self, left: &ExpressionIntent, right: &ExpressionIntent, scope: &[ScopeEntry]) -> Result<()> {
+        let left_type = self.expr_type(left, scope);
+        let right_type = self.expr_type(right, scope);
+
+        if let (Some(l), Some(r)) = (left_type, right_type) {
+            if !Self::types_compatible(l, r) {
+                anyhow::bail!("Type mismatch in comparison: {:?} vs {:?}", l, r);
+            }
+        }
+
+        Ok(())
+    }
+
+    fn expr_type(&self, expr: &ExpressionIntent, scope: &[ScopeEntry]) -> Option<DataType> {
+        match expr {
+            ExpressionIntent::Column(name) => self
+                .resolve_column(name, scope)
+                .ok()
+                .and_then(|entry| entry.schema.column(name))
+                .map(|col| col.data_type.clone()),
+            ExpressionIntent::QualifiedColumn { table, column } => scope
+                .iter()
+                .find(|entry| &entry.name == table)
+                .and_then(|entry| entry.schema.column(column))
+                .map(|col| col.data_type.clone()),
+            ExpressionIntent::Constant(ConstantValue::Boolean(_)) => Some(DataType::Boolean),
+            ExpressionIntent::Constant(ConstantValue::Integer(_)) => Some(DataType::Integer),
+            ExpressionIntent::Constant(ConstantValue::Float(_)) => Some(DataType::Real),
+            ExpressionIntent::Constant(ConstantValue::String(_)) => Some(DataType::Text),
+            ExpressionIntent::Constant(ConstantValue::Null) => None,
+            ExpressionIntent::Cast { target, .. } => Some(match target {
+                ConversionKind::Bytes => DataType::Text,
+                ConversionKind::Integer => DataType::Integer,
+                ConversionKind::Float => DataType::Real,
+                ConversionKind::Boolean => DataType::Boolean,
+                ConversionKind::Timestamp | ConversionKind::TimestampFmt(_) | ConversionKind::TimestampTZFmt(_) => {
+                    DataType::Timestamp
+                }
+            }),
+            ExpressionIntent::Arithmetic { .. }
+            | ExpressionIntent::Function { .. }
+            | ExpressionIntent::Placeholder(_) => None,
+        }
+    }
+
+    fn types_compatible(left: DataType, right: DataType) -> bool {
+        Self::type_group(&left) == Self::type_group(&right)
+    }
+
+    fn type_group(data_type: &DataType) -> u8 {
+        match data_type {
+            DataType::Integer | DataType::BigInt | DataType::Real | DataType::Double => 0,
+            DataType::Text => 1,
+            DataType::Boolean => 2,
+            DataType::Timestamp => 3,
+        }
+    }
+
+    fn check_value_type(&self, table: &str, column: &str, data_type: &DataType, value: &ConstantValue) -> Result<()> {
+        if matches!(value, ConstantValue::Null) {
+            return Ok(());
+        }
+
+        let value_type = match value {
+            ConstantValue::Boolean(_) => DataType::Boolean,
+            ConstantValue::Integer(_) => DataType::Integer,
+            ConstantValue::Float(_) => DataType::Real,
+            ConstantValue::String(_) => DataType::Text,
+            ConstantValue::Null => unreachable!(),
+        };
+
+        if !Self::types_compatible(value_type.clone(), data_type.clone()) {
+            anyhow::bail!(
+                "Type mismatch: column '{}.{}' is {:?} but value is {:?}",
+                table,
+                column,
+                data_type,
+                value_type
+            );
+        }
+
+        Ok(())
+    }
+}
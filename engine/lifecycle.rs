@@ -1,11 +1,14 @@
 use crate::config::Config;
 use crate::ffi::storage::StorageEngine;
+use crate::language::catalog::Catalog;
+use crate::protocol::auth::AuthManager;
 use crate::protocol::server::Server;
 use crate::replication::consensus::RaftNode;
-use crate::telemetry::metrics::MetricsRegistry;
+use crate::telemetry::metrics::{self, MetricsRegistry};
 use anyhow::Result;
 use std::sync::Arc;
 use tokio::signal;
+use tokio::sync::RwLock;
 
 pub struct Lifecycle {
     config: Config,
@@ -18,22 +21,28 @@ pub struct Lifecycle {
 impl Lifecycle {
     pub async fn new(config: Config) -> Result<Self> {
         let storage = Arc::new(StorageEngine::new(&config.data_dir)?);
-        
+
         storage.recover()?;
 
         let metrics = Arc::new(MetricsRegistry::new());
+        let catalog = Arc::new(RwLock::new(Catalog::new()));
 
         let raft_node = Arc::new(RaftNode::new(
             config.node_id,
             config.peers.clone(),
             storage.clone(),
+            catalog.clone(),
         )?);
 
+        let auth = Arc::new(AuthManager::from_config(&config.auth)?);
+
         let server = Server::new(
             config.port,
             storage.clone(),
             raft_node.clone(),
             metrics.clone(),
+            catalog,
+            auth,
         )?;
 
         Ok(Self {
@@ -69,6 +78,14 @@ impl Lifecycle {
             })
         };
 
+        let admin_metrics_handle = {
+            let metrics = self.metrics.clone();
+            let port = self.config.admin_metrics_port;
+            tokio::spawn(async move {
+                metrics::serve_admin_http(port, metrics).await
+            })
+        };
+
         tokio::select! {
             result = raft_handle => {
                 tracing::error!("Raft node exited: {:?}", result);
@@ -79,6 +96,9 @@ impl Lifecycle {
             result = metrics_handle => {
                 tracing::error!("Metrics exited: {:?}", result);
             }
+            result = admin_metrics_handle => {
+                tracing::error!("Admin metrics endpoint exited: {:?}", result);
+            }
             _ = signal::ctrl_c() => {
                 tracing::info!("Received shutdown signal");
             }
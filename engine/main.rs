@@ -10,6 +10,7 @@ mod sharding;
 mod replication;
 mod udf;
 mod ffi;
+mod storage;
 mod telemetry;
 mod analytics;
 mod security;
@@ -0,0 +1,247 @@
+use anyhow::Result;
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicI64, AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::TcpListener;
+
+/// A counter broken down by a single label (e.g. source stream, or
+/// `table:operation`), rendered as one Prometheus series per label value.
+#[derive(Default)]
+pub struct LabeledCounter {
+    values: Mutex<HashMap<String, u64>>,
+}
+
+impl LabeledCounter {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn increment(&self, label: &str) {
+        let mut values = self.values.lock().unwrap();
+        *values.entry(label.to_string()).or_insert(0) += 1;
+    }
+
+    pub(crate) fn render(&self, name: &str, help: &str, label_name: &str, out: &mut String) {
+        out.push_str(&format!("# HELP {name} {help}\n# TYPE {name} counter\n"));
+        for (label, value) in self.values.lock().unwrap().iter() {
+            out.push_str(&format!("{name}{{{label_name}=\"{label}\"}} {value}\n"));
+        }
+    }
+}
+
+pub struct Gauge {
+    value: AtomicI64,
+}
+
+impl Gauge {
+    pub fn new() -> Self {
+        Self {
+            value: AtomicI64::new(0),
+        }
+    }
+
+    pub fn set(&self, value: i64) {
+        self.value.store(value, Ordering::Relaxed);
+    }
+
+    pub fn add(&self, delta: i64) {
+        self.value.fetch_add(delta, Ordering::Relaxed);
+    }
+
+    pub fn get(&self) -> i64 {
+        self.value.load(Ordering::Relaxed)
+    }
+
+    pub(crate) fn render(&self, name: &str, help: &str, out: &mut String) {
+        out.push_str(&format!(
+            "# HELP {name} {help}\n# TYPE {name} gauge\n{name} {}\n",
+            self.get()
+        ));
+    }
+}
+
+/// A fixed-bucket histogram rendered in Prometheus's cumulative `le` form.
+pub struct Histogram {
+    buckets: Vec<f64>,
+    counts: Vec<AtomicU64>,
+    sum: Mutex<f64>,
+    count: AtomicU64,
+}
+
+impl Histogram {
+    pub fn new(buckets: Vec<f64>) -> Self {
+        let counts = buckets.iter().map(|_| AtomicU64::new(0)).collect();
+        Self {
+            buckets,
+            counts,
+            sum: Mutex::new(0.0),
+            count: AtomicU64::new(0),
+        }
+    }
+
+    pub fn observe(&self, value: f64) {
+        for (bucket, count) in self.buckets.iter().zip(&self.counts) {
+            if value <= *bucket {
+                count.fetch_add(1, Ordering::Relaxed);
+            }
+        }
+        *self.sum.lock().unwrap() += value;
+        self.count.fetch_add(1, Ordering::Relaxed);
+    }
+
+    pub(crate) fn render(&self, name: &str, help: &str, out: &mut String) {
+        out.push_str(&format!("# HELP {name} {help}\n# TYPE {name} histogram\n"));
+        for (bucket, count) in self.buckets.iter().zip(&self.counts) {
+            out.push_str(&format!(
+                "{name}_bucket{{le=\"{bucket}\"}} {}\n",
+                count.load(Ordering::Relaxed)
+            ));
+        }
+        out.push_str(&format!(
+            "{name}_bucket{{le=\"+Inf\"}} {}\n",
+            self.count.load(Ordering::Relaxed)
+        ));
+        out.push_str(&format!("{name}_sum {}\n", *self.sum.lock().unwrap()));
+        out.push_str(&format!("{name}_count {}\n", self.count.load(Ordering::Relaxed)));
+    }
+}
+
+/// Counters and gauges for the streaming subsystems (`ContinuousQueryEngine`
+/// and `ChangeDataCapture`), rendered in Prometheus text exposition format
+/// over the admin HTTP endpoint. `tracing::info!` alone can't answer "how
+/// far behind is subscriber X" or "what's our p99 window latency", which is
+/// what this exists to make queryable.
+pub struct StreamingMetrics {
+    pub tuples_ingested: LabeledCounter,
+    pub window_latency: Histogram,
+    pub windows_emitted: AtomicU64,
+    pub buffered_tuples: Gauge,
+
+    pub changes_emitted: LabeledCounter,
+    pub subscriber_send_failures: LabeledCounter,
+    subscriber_lag: Mutex<HashMap<String, i64>>,
+}
+
+impl StreamingMetrics {
+    pub fn new() -> Self {
+        Self {
+            tuples_ingested: LabeledCounter::new(),
+            window_latency: Histogram::new(vec![0.001, 0.005, 0.01, 0.05, 0.1, 0.5, 1.0, 5.0]),
+            windows_emitted: AtomicU64::new(0),
+            buffered_tuples: Gauge::new(),
+            changes_emitted: LabeledCounter::new(),
+            subscriber_send_failures: LabeledCounter::new(),
+            subscriber_lag: Mutex::new(HashMap::new()),
+        }
+    }
+
+    pub fn record_window_emitted(&self, latency_secs: f64) {
+        self.windows_emitted.fetch_add(1, Ordering::Relaxed);
+        self.window_latency.observe(latency_secs);
+    }
+
+    /// Derived from the channel's remaining send capacity: the more of the
+    /// bounded channel a subscriber has filled, the further behind it is.
+    pub fn set_subscriber_lag(&self, subscriber_id: &str, lag: i64) {
+        self.subscriber_lag
+            .lock()
+            .unwrap()
+            .insert(subscriber_id.to_string(), lag);
+    }
+
+    /// Serializes every registered metric in Prometheus text exposition
+    /// format (also valid as OpenMetrics, aside from the trailing `# EOF`
+    /// line this already includes).
+    pub fn render(&self) -> String {
+        let mut out = String::new();
+
+        self.tuples_ingested.render(
+            "minsql_stream_tuples_ingested_total",
+            "Tuples ingested per source stream",
+            "source",
+            &mut out,
+        );
+        self.window_latency.render(
+            "minsql_stream_window_latency_seconds",
+            "Continuous query window processing latency",
+            &mut out,
+        );
+        out.push_str(&format!(
+            "# HELP minsql_stream_windows_emitted_total Windows emitted by continuous queries\n# TYPE minsql_stream_windows_emitted_total counter\nminsql_stream_windows_emitted_total {}\n",
+            self.windows_emitted.load(Ordering::Relaxed)
+        ));
+        self.buffered_tuples.render(
+            "minsql_stream_buffered_tuples",
+            "Tuples currently buffered in open continuous-query windows",
+            &mut out,
+        );
+
+        self.changes_emitted.render(
+            "minsql_cdc_changes_emitted_total",
+            "CDC changes emitted per table/operation",
+            "table_operation",
+            &mut out,
+        );
+        self.subscriber_send_failures.render(
+            "minsql_cdc_subscriber_send_failures_total",
+            "CDC events that failed to deliver to a subscriber",
+            "subscriber",
+            &mut out,
+        );
+
+        out.push_str("# HELP minsql_cdc_subscriber_lag Estimated backlog depth for each CDC subscriber channel\n# TYPE minsql_cdc_subscriber_lag gauge\n");
+        for (subscriber, lag) in self.subscriber_lag.lock().unwrap().iter() {
+            out.push_str(&format!("minsql_cdc_subscriber_lag{{subscriber=\"{subscriber}\"}} {lag}\n"));
+        }
+
+        out.push_str("# EOF\n");
+        out
+    }
+}
+
+/// Serves `GET /metrics` over a raw HTTP/1.1 response on `port`, matching
+/// the admin-facing surface the rest of `monitoring` exposes (no web
+/// framework dependency, same as the hand-rolled wire protocol in
+/// `protocol::server`).
+pub async fn serve_admin_http(port: u16, metrics: Arc<StreamingMetrics>) -> Result<()> {
+    let addr = format!("0.0.0.0:{}", port);
+    let listener = TcpListener::bind(&addr).await?;
+
+    tracing::info!("Admin metrics endpoint listening on {}", addr);
+
+    loop {
+        let (stream, _) = listener.accept().await?;
+        let metrics = metrics.clone();
+
+        tokio::spawn(async move {
+            if let Err(e) = handle_admin_request(stream, metrics).await {
+                tracing::warn!("Admin HTTP connection error: {}", e);
+            }
+        });
+    }
+}
+
+async fn handle_admin_request(
+    mut stream: tokio::net::TcpStream,
+    metrics: Arc<StreamingMetrics>,
+) -> Result<()> {
+    let mut buf = [0u8; 1024];
+    let n = stream.read(&mut buf).await?;
+    let request = String::from_utf8_lossy(&buf[..n]);
+    let path = request.split_whitespace().nth(1).unwrap_or("/");
+
+    let (status, content_type, body) = if path == "/metrics" {
+        ("200 OK", "text/plain; version=0.0.4", metrics.render())
+    } else {
+        ("404 Not Found", "text/plain", "not found\n".to_string())
+    };
+
+    let response = format!(
+        "HTTP/1.1 {status}\r\nContent-Type: {content_type}\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{body}",
+        body.len()
+    );
+
+    stream.write_all(response.as_bytes()).await?;
+    Ok(())
+}
@@ -1,7 +1,9 @@
 pub mod health_check;
+pub mod metrics;
 pub mod performance;
 pub mod alerts;
 
 pub use health_check::*;
+pub use metrics::*;
 pub use performance::*;
 pub use alerts::*;
@@ -1,4 +1,7 @@
+use crate::language::intent::FilterIntent;
+use crate::planner::logical::KeyRange;
 use crate::planner::physical::PhysicalPlan;
+use crate::planner::statistics::{Statistics, DEFAULT_SELECTIVITY};
 
 pub const PAGE_SCAN_COST: f64 = 1.0;
 pub const CPU_TUPLE_COST: f64 = 0.01;
@@ -10,6 +13,11 @@ pub struct Cost {
     pub io: f64,
     pub memory: f64,
     pub network: f64,
+    /// The estimated number of rows this operator outputs, propagated up
+    /// from scans through `Filter`/`Project`/`Join`/`HashAggregate`/`Sort`/
+    /// `Limit` so a parent operator's cost can scale with its actual
+    /// expected input size instead of a flat constant.
+    pub rows: f64,
 }
 
 impl Cost {
@@ -23,103 +31,228 @@ impl Cost {
             io: 0.0,
             memory: 0.0,
             network: 0.0,
+            rows: 0.0,
         }
     }
 }
 
-pub struct CostEstimator;
+/// Scores a `PhysicalPlan`'s cost, consulting `statistics` for any table it
+/// has real row/column data for and falling back to the original flat
+/// defaults (1000 rows per scan, 100 per index scan, fixed operator
+/// fan-outs) everywhere else — an un-analyzed table never costs worse than
+/// it would have before `Statistics` existed.
+pub struct CostEstimator {
+    statistics: Statistics,
+}
 
 impl CostEstimator {
     pub fn new() -> Self {
-        Self
+        Self {
+            statistics: Statistics::new(),
+        }
+    }
+
+    pub fn with_statistics(statistics: Statistics) -> Self {
+        Self { statistics }
     }
 
     pub fn estimate(&self, plan: &PhysicalPlan) -> Cost {
         match plan {
-            PhysicalPlan::SeqScan { .. } => {
-                let estimated_rows = 1000.0;
+            PhysicalPlan::SeqScan { table, key_range, .. } => {
+                let base = self.statistics.row_count(table).unwrap_or(1000.0);
+                let rows = Self::ranged_rows(key_range, base);
                 Cost {
-                    cpu: estimated_rows * CPU_TUPLE_COST,
-                    io: estimated_rows * PAGE_SCAN_COST / 100.0,
+                    cpu: rows * CPU_TUPLE_COST,
+                    io: rows * PAGE_SCAN_COST / 100.0,
                     memory: 0.0,
                     network: 0.0,
+                    rows,
                 }
             }
-            PhysicalPlan::IndexScan { .. } => {
-                let estimated_rows = 100.0;
+            PhysicalPlan::IndexScan {
+                table,
+                predicate,
+                key_range,
+                ..
+            } => {
+                let base = self.statistics.row_count(table).map(|rows| rows.min(100.0)).unwrap_or(100.0);
+                let mut rows = Self::ranged_rows(key_range, base);
+                if let Some(predicate) = predicate {
+                    rows *= self.statistics.selectivity(table, predicate);
+                }
+                let rows = rows.max(1.0);
                 Cost {
-                    cpu: estimated_rows * CPU_TUPLE_COST,
-                    io: estimated_rows * PAGE_SCAN_COST / 100.0,
+                    cpu: rows * CPU_TUPLE_COST,
+                    io: rows * PAGE_SCAN_COST / 100.0,
                     memory: 0.0,
                     network: 0.0,
+                    rows,
                 }
             }
-            PhysicalPlan::Filter { input, .. } => {
+            PhysicalPlan::Filter { predicate, input } => {
                 let input_cost = self.estimate(input);
+                let selectivity = match Self::underlying_table(input) {
+                    Some(table) => self.statistics.selectivity(table, predicate),
+                    None => DEFAULT_SELECTIVITY,
+                };
+                let rows = (input_cost.rows * selectivity).max(1.0);
                 Cost {
-                    cpu: input_cost.cpu + 1000.0 * CPU_OPERATOR_COST,
+                    cpu: input_cost.cpu + input_cost.rows * CPU_OPERATOR_COST,
                     io: input_cost.io,
                     memory: input_cost.memory,
                     network: input_cost.network,
+                    rows,
                 }
             }
             PhysicalPlan::Project { input, .. } => {
                 let input_cost = self.estimate(input);
                 Cost {
-                    cpu: input_cost.cpu + 1000.0 * CPU_OPERATOR_COST,
+                    cpu: input_cost.cpu + input_cost.rows * CPU_OPERATOR_COST,
                     io: input_cost.io,
                     memory: input_cost.memory,
                     network: input_cost.network,
+                    rows: input_cost.rows,
                 }
             }
-            PhysicalPlan::HashJoin { left, right, .. } => {
+            PhysicalPlan::HashJoin {
+                left, right, condition, ..
+            } => {
                 let left_cost = self.estimate(left);
                 let right_cost = self.estimate(right);
+                let selectivity = self.join_selectivity(left, right, condition);
+                let rows = (left_cost.rows * right_cost.rows * selectivity).max(1.0);
                 Cost {
-                    cpu: left_cost.cpu + right_cost.cpu + 10000.0 * CPU_OPERATOR_COST,
+                    cpu: left_cost.cpu + right_cost.cpu + (left_cost.rows + right_cost.rows) * CPU_TUPLE_COST + rows * CPU_OPERATOR_COST,
                     io: left_cost.io + right_cost.io,
-                    memory: 1000.0,
+                    memory: left_cost.rows.min(right_cost.rows),
                     network: 0.0,
+                    rows,
                 }
             }
-            PhysicalPlan::NestedLoopJoin { left, right, .. } => {
+            PhysicalPlan::NestedLoopJoin {
+                left, right, condition, ..
+            } => {
                 let left_cost = self.estimate(left);
                 let right_cost = self.estimate(right);
+                let selectivity = self.join_selectivity(left, right, condition);
+                let rows = (left_cost.rows * right_cost.rows * selectivity).max(1.0);
                 Cost {
-                    cpu: left_cost.cpu + 1000.0 * right_cost.cpu,
-                    io: left_cost.io + 1000.0 * right_cost.io,
+                    cpu: left_cost.cpu + left_cost.rows * right_cost.cpu + rows * CPU_OPERATOR_COST,
+                    io: left_cost.io + left_cost.rows * right_cost.io,
                     memory: 0.0,
                     network: 0.0,
+                    rows,
                 }
             }
-            PhysicalPlan::HashAggregate { input, .. } => {
+            PhysicalPlan::IndexSemiJoin {
+                outer, inner_table, condition, ..
+            } => {
+                let outer_cost = self.estimate(outer);
+                let inner_rows = self.statistics.row_count(inner_table).map(|rows| rows.min(100.0)).unwrap_or(100.0);
+                let selectivity = self.statistics.selectivity(inner_table, condition);
+                let rows = (outer_cost.rows * selectivity).max(1.0);
+                Cost {
+                    cpu: outer_cost.cpu + inner_rows * CPU_OPERATOR_COST,
+                    io: outer_cost.io + inner_rows * PAGE_SCAN_COST / 100.0,
+                    memory: outer_cost.memory,
+                    network: outer_cost.network,
+                    rows,
+                }
+            }
+            PhysicalPlan::HashAggregate { group_by, input, .. } => {
                 let input_cost = self.estimate(input);
+                let rows = match Self::underlying_table(input).and_then(|table| self.statistics.group_cardinality(table, group_by)) {
+                    Some(rows) => rows,
+                    None if group_by.is_empty() => 1.0,
+                    None => (input_cost.rows * 0.5).max(1.0),
+                };
                 Cost {
-                    cpu: input_cost.cpu + 1000.0 * CPU_OPERATOR_COST,
+                    cpu: input_cost.cpu + input_cost.rows * CPU_OPERATOR_COST,
                     io: input_cost.io,
-                    memory: 500.0,
+                    memory: rows,
                     network: 0.0,
+                    rows,
                 }
             }
             PhysicalPlan::Sort { input, .. } => {
                 let input_cost = self.estimate(input);
+                let scaled = input_cost.rows.max(1.0) * input_cost.rows.max(1.0).log2().max(1.0);
                 Cost {
-                    cpu: input_cost.cpu + 5000.0 * CPU_OPERATOR_COST,
+                    cpu: input_cost.cpu + scaled * CPU_OPERATOR_COST,
                     io: input_cost.io,
-                    memory: 1000.0,
+                    memory: input_cost.rows,
                     network: 0.0,
+                    rows: input_cost.rows,
                 }
             }
-            PhysicalPlan::Limit { input, .. } => {
+            PhysicalPlan::Limit { count, offset, input } => {
                 let input_cost = self.estimate(input);
+                let rows = (input_cost.rows - *offset as f64).max(0.0).min(*count as f64);
+                let fraction = if input_cost.rows > 0.0 { (rows / input_cost.rows).clamp(0.0, 1.0) } else { 0.0 };
                 Cost {
-                    cpu: input_cost.cpu * 0.1,
-                    io: input_cost.io * 0.1,
+                    cpu: input_cost.cpu * fraction,
+                    io: input_cost.io * fraction,
                     memory: input_cost.memory,
                     network: input_cost.network,
+                    rows,
                 }
             }
             _ => Cost::zero(),
         }
     }
-              }
+
+    /// The flat row-count estimate `estimate` would derive for a base scan
+    /// with no statistics, exposed separately so `JoinOrderSearch` can seed
+    /// its DP memo with the same numbers and then scale them down per join
+    /// predicate with its own selectivity factors.
+    pub fn base_cardinality(plan: &PhysicalPlan) -> f64 {
+        match plan {
+            PhysicalPlan::SeqScan { key_range, .. } => Self::ranged_rows(key_range, 1000.0),
+            PhysicalPlan::IndexScan { key_range, .. } => Self::ranged_rows(key_range, 100.0),
+            _ => 1000.0,
+        }
+    }
+
+    /// The selectivity to apply to a join's cross-product cardinality:
+    /// resolved against whichever side's underlying table `statistics` has
+    /// column stats for (a join condition usually names a column from each
+    /// side, so either is a reasonable anchor — `Statistics::selectivity`
+    /// falls back to a flat default itself when the referenced column isn't
+    /// one it knows about).
+    fn join_selectivity(&self, left: &PhysicalPlan, right: &PhysicalPlan, condition: &FilterIntent) -> f64 {
+        match Self::underlying_table(left).or_else(|| Self::underlying_table(right)) {
+            Some(table) => self.statistics.selectivity(table, condition),
+            None => DEFAULT_SELECTIVITY,
+        }
+    }
+
+    /// Walks a single-input operator chain down to the table it ultimately
+    /// scans, so a `Filter`/`HashAggregate`/join side built directly over a
+    /// scan can look up that table's statistics. Returns `None` once it hits
+    /// a join or another multi-input/unrecognized node, since there's no
+    /// single table to attribute the rows to at that point.
+    fn underlying_table(plan: &PhysicalPlan) -> Option<&str> {
+        match plan {
+            PhysicalPlan::SeqScan { table, .. } | PhysicalPlan::IndexScan { table, .. } => Some(table.as_str()),
+            PhysicalPlan::Filter { input, .. }
+            | PhysicalPlan::Project { input, .. }
+            | PhysicalPlan::HashAggregate { input, .. }
+            | PhysicalPlan::Exchange { input, .. }
+            | PhysicalPlan::Sort { input, .. }
+            | PhysicalPlan::Limit { input, .. } => Self::underlying_table(input),
+            _ => None,
+        }
+    }
+
+    /// Scales a scan's flat row-count estimate down when `key_range` bounds
+    /// how much of the table it actually reads: a range with both a lower
+    /// and upper bound narrows furthest, one bound narrows less, and no
+    /// range at all leaves `base` unchanged.
+    fn ranged_rows(key_range: &Option<KeyRange>, base: f64) -> f64 {
+        match key_range {
+            Some(range) if range.lower.is_some() && range.upper.is_some() => base * 0.05,
+            Some(range) if range.lower.is_some() || range.upper.is_some() => base * 0.3,
+            _ => base,
+        }
+    }
+}
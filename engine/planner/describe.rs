@@ -0,0 +1,292 @@
+use crate::language::catalog::Catalog;
+use crate::language::describe::TypeKind;
+use crate::language::intent::*;
+use crate::language::JoinType;
+use crate::planner::logical::LogicalPlan;
+
+/// One output column's shape, as reported by `PlanDescriber::describe`: its
+/// name, inferred type, and whether it can come back `NULL` — so a caller
+/// can validate an expected result shape before running the query.
+#[derive(Debug, Clone)]
+pub struct ColumnDescription {
+    pub name: String,
+    pub type_kind: TypeKind,
+    pub nullable: bool,
+}
+
+/// The per-column state `PlanDescriber` carries up the `LogicalPlan` tree.
+/// Identical to `ColumnDescription` except it additionally remembers which
+/// table a column came from (`None` once it's passed through an `Aggregate`
+/// or an expression in a `Project`), so a `ColumnIntent::Qualified` reference
+/// can disambiguate two joined tables that share a column name.
+struct ColumnState {
+    name: String,
+    type_kind: TypeKind,
+    nullable: bool,
+    source_table: Option<String>,
+}
+
+impl From<&ColumnState> for ColumnDescription {
+    fn from(state: &ColumnState) -> Self {
+        Self {
+            name: state.name.clone(),
+            type_kind: state.type_kind,
+            nullable: state.nullable,
+        }
+    }
+}
+
+/// Infers the output schema of a `LogicalPlan` without executing it, similar
+/// to how `SemanticAnalyzer::describe` describes a prepared statement, but
+/// working over the already-planned tree so it can reason about the shape
+/// of a `Join` (the statement-level describe only sees a flat table list).
+///
+/// The key rule is that nullability propagates through outer joins: a
+/// column from the null-producing side of a `LEFT`/`RIGHT`/`FULL` join
+/// becomes nullable even when its base column is declared `NOT NULL`,
+/// because any row on that side can fail to match and come back as nulls.
+pub struct PlanDescriber {
+    catalog: Catalog,
+}
+
+impl PlanDescriber {
+    pub fn new(catalog: Catalog) -> Self {
+        Self { catalog }
+    }
+
+    pub fn describe(&self, plan: &LogicalPlan) -> Vec<ColumnDescription> {
+        self.describe_state(plan).iter().map(ColumnDescription::from).collect()
+    }
+
+    fn describe_state(&self, plan: &LogicalPlan) -> Vec<ColumnState> {
+        match plan {
+            LogicalPlan::Scan { table, columns, .. } => columns
+                .iter()
+                .map(|column| self.scan_column(table, column))
+                .collect(),
+            LogicalPlan::Filter { input, .. } => self.describe_state(input),
+            LogicalPlan::Sort { input, .. } => self.describe_state(input),
+            LogicalPlan::Limit { input, .. } => self.describe_state(input),
+            LogicalPlan::Distinct { input } => self.describe_state(input),
+            LogicalPlan::Project { columns, input } => {
+                let input_state = self.describe_state(input);
+                columns
+                    .iter()
+                    .filter_map(|column| self.project_column(column, &input_state))
+                    .collect()
+            }
+            LogicalPlan::Join {
+                join_type, left, right, ..
+            } => {
+                let mut left_state = self.describe_state(left);
+                let mut right_state = self.describe_state(right);
+
+                match join_type {
+                    JoinType::Inner => {}
+                    JoinType::Left => Self::force_nullable(&mut right_state),
+                    JoinType::Right => Self::force_nullable(&mut left_state),
+                    JoinType::Full => {
+                        Self::force_nullable(&mut left_state);
+                        Self::force_nullable(&mut right_state);
+                    }
+                }
+
+                left_state.extend(right_state);
+                left_state
+            }
+            LogicalPlan::Aggregate {
+                group_by,
+                aggregates,
+                input,
+            } => {
+                let input_state = self.describe_state(input);
+                let mut state: Vec<ColumnState> = group_by
+                    .iter()
+                    .map(|expr| {
+                        let (type_kind, nullable) = Self::infer_expression(expr, &input_state);
+                        ColumnState {
+                            name: Self::expression_label(expr),
+                            type_kind,
+                            nullable,
+                            source_table: None,
+                        }
+                    })
+                    .collect();
+
+                state.extend(aggregates.iter().map(|aggregate| Self::aggregate_column(aggregate, &input_state)));
+                state
+            }
+            // DML/DDL statements have no result columns to describe.
+            LogicalPlan::Insert { .. }
+            | LogicalPlan::Update { .. }
+            | LogicalPlan::Delete { .. }
+            | LogicalPlan::CreateTable { .. }
+            | LogicalPlan::CreateIndex { .. }
+            | LogicalPlan::DropIndex { .. }
+            | LogicalPlan::CreatePolicy { .. }
+            | LogicalPlan::DropPolicy { .. } => Vec::new(),
+        }
+    }
+
+    fn scan_column(&self, table: &str, column: &str) -> ColumnState {
+        let definition = self.catalog.table(table).and_then(|schema| schema.column(column));
+        match definition {
+            Some(definition) => ColumnState {
+                name: column.to_string(),
+                type_kind: TypeKind::from(&definition.data_type),
+                nullable: definition.nullable,
+                source_table: Some(table.to_string()),
+            },
+            None => ColumnState {
+                name: column.to_string(),
+                type_kind: TypeKind::Unknown,
+                nullable: true,
+                source_table: Some(table.to_string()),
+            },
+        }
+    }
+
+    /// Like `SemanticAnalyzer::describe_retrieve`, doesn't expand
+    /// `ColumnIntent::All` (a `Scan`'s own `columns` list never contains it
+    /// either — see `LogicalPlanner::extract_column_names`): an `All` entry
+    /// is omitted rather than guessed at. A `SELECT *` never reaches here as
+    /// `All`, since `SemanticAnalyzer::analyze_projection` already expands
+    /// `*` into one `Named` column per table column.
+    fn project_column(&self, column: &ColumnIntent, input: &[ColumnState]) -> Option<ColumnState> {
+        match column {
+            ColumnIntent::Named(name) => Self::find_column(input, None, name).or(Some(ColumnState {
+                name: name.clone(),
+                type_kind: TypeKind::Unknown,
+                nullable: true,
+                source_table: None,
+            })),
+            ColumnIntent::Qualified { table, column } => {
+                let found = Self::find_column(input, Some(table), column);
+                Some(found.unwrap_or(ColumnState {
+                    name: format!("{}.{}", table, column),
+                    type_kind: TypeKind::Unknown,
+                    nullable: true,
+                    source_table: None,
+                }))
+            }
+            ColumnIntent::Expression { expr, alias } => {
+                let (type_kind, nullable) = Self::infer_expression(expr, input);
+                let name = alias.clone().unwrap_or_else(|| Self::expression_label(expr));
+                Some(ColumnState {
+                    name,
+                    type_kind,
+                    nullable,
+                    source_table: None,
+                })
+            }
+            ColumnIntent::All => None,
+        }
+    }
+
+    fn aggregate_column(aggregate: &AggregateIntent, input: &[ColumnState]) -> ColumnState {
+        let name = aggregate
+            .alias
+            .clone()
+            .unwrap_or_else(|| format!("{}({:?})", aggregate.function, aggregate.argument));
+
+        // Per the describe contract: `COUNT` never returns `NULL` (it's 0
+        // for an empty group); every other aggregate is reported nullable,
+        // since e.g. `AVG` over an empty group or `MIN`/`MAX` over an
+        // all-null column comes back `NULL`. `SUM` keeps an integer
+        // argument's type, as `HashAggregate` does.
+        let (type_kind, nullable) = match aggregate.function.to_uppercase().as_str() {
+            "COUNT" => (TypeKind::Integer, false),
+            "SUM" => match Self::infer_expression(&aggregate.argument, input).0 {
+                TypeKind::Integer => (TypeKind::Integer, true),
+                _ => (TypeKind::Float, true),
+            },
+            "AVG" => (TypeKind::Float, true),
+            "MIN" | "MAX" => (Self::infer_expression(&aggregate.argument, input).0, true),
+            _ => (TypeKind::Unknown, true),
+        };
+
+        ColumnState {
+            name,
+            type_kind,
+            nullable,
+            source_table: None,
+        }
+    }
+
+    fn find_column(input: &[ColumnState], table: Option<&str>, name: &str) -> Option<ColumnState> {
+        input
+            .iter()
+            .find(|column| column.name == name && table.map_or(true, |t| column.source_table.as_deref() == Some(t)))
+            .map(|column| ColumnState {
+                name: column.name.clone(),
+                type_kind: column.type_kind,
+                nullable: column.nullable,
+                source_table: column.source_table.clone(),
+            })
+    }
+
+    fn infer_expression(expr: &ExpressionIntent, input: &[ColumnState]) -> (TypeKind, bool) {
+        match expr {
+            ExpressionIntent::Column(name) => Self::find_column(input, None, name)
+                .map(|column| (column.type_kind, column.nullable))
+                .unwrap_or((TypeKind::Unknown, true)),
+            ExpressionIntent::QualifiedColumn { table, column } => Self::find_column(input, Some(table), column)
+                .map(|column| (column.type_kind, column.nullable))
+                .unwrap_or((TypeKind::Unknown, true)),
+            ExpressionIntent::Constant(ConstantValue::Null) => (TypeKind::Unknown, true),
+            ExpressionIntent::Constant(ConstantValue::Boolean(_)) => (TypeKind::Boolean, false),
+            ExpressionIntent::Constant(ConstantValue::Integer(_)) => (TypeKind::Integer, false),
+            ExpressionIntent::Constant(ConstantValue::Float(_)) => (TypeKind::Float, false),
+            ExpressionIntent::Constant(ConstantValue::String(_)) => (TypeKind::Text, false),
+            ExpressionIntent::Arithmetic { left, right, .. } => {
+                let (left_ty, left_null) = Self::infer_expression(left, input);
+                let (right_ty, right_null) = Self::infer_expression(right, input);
+                let type_kind = match (left_ty, right_ty) {
+                    (TypeKind::Float, _) | (_, TypeKind::Float) => TypeKind::Float,
+                    (TypeKind::Integer, TypeKind::Integer) => TypeKind::Integer,
+                    _ => TypeKind::Unknown,
+                };
+                (type_kind, left_null || right_null)
+            }
+            ExpressionIntent::Function { name, args } => {
+                let type_kind = match name.to_uppercase().as_str() {
+                    "COUNT" => TypeKind::Integer,
+                    "SUM" => match args.first().map(|arg| Self::infer_expression(arg, input).0) {
+                        Some(TypeKind::Integer) => TypeKind::Integer,
+                        _ => TypeKind::Float,
+                    },
+                    "AVG" => TypeKind::Float,
+                    "MIN" | "MAX" => args.first().map(|arg| Self::infer_expression(arg, input).0).unwrap_or(TypeKind::Unknown),
+                    _ => TypeKind::Unknown,
+                };
+                (type_kind, true)
+            }
+            ExpressionIntent::Cast { target, inner, .. } => {
+                let (_, nullable) = Self::infer_expression(inner, input);
+                let type_kind = match target {
+                    ConversionKind::Bytes => TypeKind::Text,
+                    ConversionKind::Integer => TypeKind::Integer,
+                    ConversionKind::Float => TypeKind::Float,
+                    ConversionKind::Boolean => TypeKind::Boolean,
+                    ConversionKind::Timestamp | ConversionKind::TimestampFmt(_) | ConversionKind::TimestampTZFmt(_) => TypeKind::Timestamp,
+                };
+                (type_kind, nullable)
+            }
+            ExpressionIntent::Placeholder(_) => (TypeKind::Unknown, true),
+        }
+    }
+
+    fn expression_label(expr: &ExpressionIntent) -> String {
+        match expr {
+            ExpressionIntent::Column(name) => name.clone(),
+            ExpressionIntent::QualifiedColumn { table, column } => format!("{}.{}", table, column),
+            _ => format!("{:?}", expr),
+        }
+    }
+
+    fn force_nullable(state: &mut [ColumnState]) {
+        for column in state {
+            column.nullable = true;
+        }
+    }
+}
@@ -0,0 +1,348 @@
+use crate::language::intent::{ComparisonOp, ExpressionIntent, FilterIntent, LogicalOp};
+use crate::language::JoinType;
+use crate::planner::cost::{CostEstimator, CPU_OPERATOR_COST, CPU_TUPLE_COST};
+use crate::planner::physical::PhysicalPlan;
+use std::collections::{HashMap, HashSet};
+
+/// How many base relations `JoinOrderSearch::search` will run the full
+/// bottom-up DP over. Every subset gets tried against every way of
+/// splitting it in two, which is O(3^n), so past this count
+/// `PhysicalPlanner` falls back to the original left-deep join order
+/// instead of paying for the search.
+pub const MAX_DP_RELATIONS: usize = 12;
+
+/// One base relation being ordered by `JoinOrderSearch`: its already-lowered
+/// scan plan (so a leaf cost/cardinality can be read off it) and the column
+/// names it's known to provide, so a join condition can be resolved to the
+/// relation(s) it connects.
+pub struct JoinRelation {
+    pub plan: PhysicalPlan,
+    pub columns: HashSet<String>,
+}
+
+/// One join predicate from the original chain plus a flat selectivity
+/// guess, so a parent join's output cardinality can be derived from its
+/// inputs once the DP decides which two sides it joins. It carries no join
+/// type: the DP only ever runs over a pure `Inner` chain, and that is
+/// checked by the caller, not here.
+pub struct JoinEdge {
+    pub condition: FilterIntent,
+    pub selectivity: f64,
+}
+
+impl JoinEdge {
+    pub fn new(condition: FilterIntent) -> Self {
+        let selectivity = Self::selectivity_of(&condition);
+        Self { condition, selectivity }
+    }
+
+    /// A flat selectivity guess per predicate shape: this planner has no
+    /// column statistics to derive a real one from, so an equality (the
+    /// common join-key case) is assumed more selective than a range
+    /// comparison, `AND`-combined predicates multiply, and an unconstrained
+    /// condition (a cross product) doesn't reduce cardinality at all.
+    fn selectivity_of(condition: &FilterIntent) -> f64 {
+        match condition {
+            FilterIntent::Always => 1.0,
+            FilterIntent::Never => 0.0,
+            FilterIntent::Comparison {
+                op: ComparisonOp::Equal,
+                ..
+            } => 0.1,
+            FilterIntent::Comparison { .. } => 0.3,
+            FilterIntent::Logical {
+                op: LogicalOp::And,
+                operands,
+            } => operands.iter().map(Self::selectivity_of).product(),
+            FilterIntent::Logical {
+                op: LogicalOp::Or,
+                operands,
+            } => 1.0 - operands.iter().map(|o| 1.0 - Self::selectivity_of(o)).product::<f64>(),
+            FilterIntent::Logical {
+                op: LogicalOp::Not,
+                operands,
+            } => 1.0 - operands.first().map(Self::selectivity_of).unwrap_or(1.0),
+        }
+    }
+}
+
+/// A `JoinEdge` resolved to the pair of relation indices (into the slice
+/// passed to `JoinOrderSearch::search`) its condition actually connects.
+struct ResolvedEdge {
+    left: usize,
+    right: usize,
+    condition: FilterIntent,
+    selectivity: f64,
+}
+
+/// One entry of the DP memo: the cheapest plan found so far covering a
+/// given bitmask of relations, its accumulated cost, and its estimated
+/// output cardinality (used to cost whichever join covers it next).
+struct MemoEntry {
+    plan: PhysicalPlan,
+    cost: f64,
+    cardinality: f64,
+}
+
+/// A Selinger-style bottom-up join-order search: `best[mask]` is the
+/// cheapest plan (plus its estimated cardinality) joining exactly the
+/// relations in bitmask `mask`, found by trying every way to split `mask`
+/// into two smaller, already-solved halves and keeping the minimum over
+/// both a `HashJoin` and a `NestedLoopJoin` candidate.
+pub struct JoinOrderSearch {
+    estimator: CostEstimator,
+}
+
+impl JoinOrderSearch {
+    pub fn new() -> Self {
+        Self {
+            estimator: CostEstimator::new(),
+        }
+    }
+
+    /// Finds the cheapest way to join `relations` under `edges`, or `None`
+    /// if there are fewer than two relations or more than
+    /// `MAX_DP_RELATIONS` of them (the caller should fall back to its
+    /// existing left-deep order in either case).
+    pub fn search(self, relations: Vec<JoinRelation>, edges: &[JoinEdge]) -> Option<PhysicalPlan> {
+        let n = relations.len();
+        if !(2..=MAX_DP_RELATIONS).contains(&n) {
+            return None;
+        }
+
+        let resolved_edges = Self::resolve_edges(&relations, edges);
+
+        let mut memo: HashMap<u64, MemoEntry> = HashMap::new();
+        for (i, relation) in relations.into_iter().enumerate() {
+            let cost = self.estimator.estimate(&relation.plan).total();
+            let cardinality = CostEstimator::base_cardinality(&relation.plan);
+            memo.insert(
+                1u64 << i,
+                MemoEntry {
+                    plan: relation.plan,
+                    cost,
+                    cardinality,
+                },
+            );
+        }
+
+        for mask in Self::masks_by_popcount(n) {
+            if mask.count_ones() < 2 {
+                continue;
+            }
+            if let Some(entry) = Self::best_for(mask, &resolved_edges, &memo) {
+                memo.insert(mask, entry);
+            }
+        }
+
+        let full_mask = (1u64 << n) - 1;
+        memo.remove(&full_mask).map(|entry| entry.plan)
+    }
+
+    /// Every non-empty subset of `0..n`'s bits, ordered by ascending
+    /// popcount so `search` only ever looks up already-solved (strictly
+    /// smaller) subsets when it processes a larger one.
+    fn masks_by_popcount(n: usize) -> Vec<u64> {
+        let mut masks: Vec<u64> = (1u64..(1u64 << n)).collect();
+        masks.sort_by_key(|mask| mask.count_ones());
+        masks
+    }
+
+    /// Resolves each `JoinEdge`'s condition to the pair of relation indices
+    /// it references, by matching the columns the condition mentions
+    /// against each relation's known column set; conditions that resolve
+    /// to anything other than exactly two relations (ambiguous, or
+    /// referencing columns this planner can't attribute to a relation) are
+    /// dropped, which only costs the DP the ability to treat them as a
+    /// connecting edge — they're never required for correctness.
+    fn resolve_edges(relations: &[JoinRelation], edges: &[JoinEdge]) -> Vec<ResolvedEdge> {
+        let mut resolved = Vec::new();
+
+        for edge in edges {
+            let mut referenced = HashSet::new();
+            Self::condition_columns(&edge.condition, &mut referenced);
+
+            let touched: Vec<usize> = relations
+                .iter()
+                .enumerate()
+                .filter(|(_, relation)| referenced.iter().any(|column| relation.columns.contains(column)))
+                .map(|(i, _)| i)
+                .collect();
+
+            if let [left, right] = touched[..] {
+                resolved.push(ResolvedEdge {
+                    left,
+                    right,
+                    condition: edge.condition.clone(),
+                    selectivity: edge.selectivity,
+                });
+            }
+        }
+
+        resolved
+    }
+
+    fn condition_columns(condition: &FilterIntent, columns: &mut HashSet<String>) {
+        match condition {
+            FilterIntent::Always | FilterIntent::Never => {}
+            FilterIntent::Comparison { left, right, .. } => {
+                Self::expression_columns(left, columns);
+                Self::expression_columns(right, columns);
+            }
+            FilterIntent::Logical { operands, .. } => {
+                for operand in operands {
+                    Self::condition_columns(operand, columns);
+                }
+            }
+        }
+    }
+
+    fn expression_columns(expr: &ExpressionIntent, columns: &mut HashSet<String>) {
+        match expr {
+            ExpressionIntent::Column(name) => {
+                columns.insert(name.clone());
+            }
+            ExpressionIntent::QualifiedColumn { column, .. } => {
+                columns.insert(column.clone());
+            }
+            ExpressionIntent::Constant(_) | ExpressionIntent::Placeholder(_) => {}
+            ExpressionIntent::Arithmetic { left, right, .. } => {
+                Self::expression_columns(left, columns);
+                Self::expression_columns(right, columns);
+            }
+            ExpressionIntent::Function { args, .. } => {
+                for arg in args {
+                    Self::expression_columns(arg, columns);
+                }
+            }
+            ExpressionIntent::Cast { inner, .. } => Self::expression_columns(inner, columns),
+        }
+    }
+
+    /// The cheapest candidate covering `mask`, built from every way to
+    /// split it into two smaller, already-solved halves. A split with no
+    /// connecting edge is only considered if no split of `mask` has one —
+    /// that's what makes a cross product a last resort rather than just
+    /// another option the DP might prefer for being numerically cheaper.
+    fn best_for(mask: u64, edges: &[ResolvedEdge], memo: &HashMap<u64, MemoEntry>) -> Option<MemoEntry> {
+        match Self::best_over_splits(mask, edges, memo, true) {
+            Some(entry) => Some(entry),
+            None => Self::best_over_splits(mask, edges, memo, false),
+        }
+    }
+
+    fn best_over_splits(
+        mask: u64,
+        edges: &[ResolvedEdge],
+        memo: &HashMap<u64, MemoEntry>,
+        require_edge: bool,
+    ) -> Option<MemoEntry> {
+        let mut best: Option<MemoEntry> = None;
+
+        let mut sub = mask;
+        loop {
+            sub = sub.wrapping_sub(1) & mask;
+            if sub == 0 {
+                break;
+            }
+
+            let s1 = sub;
+            let s2 = mask & !s1;
+            if s1 > s2 {
+                // Each unordered split considered once.
+                continue;
+            }
+
+            let left = match memo.get(&s1) {
+                Some(entry) => entry,
+                None => continue,
+            };
+            let right = match memo.get(&s2) {
+                Some(entry) => entry,
+                None => continue,
+            };
+
+            let connecting = Self::connecting_edges(edges, s1, s2);
+            if require_edge && connecting.is_empty() {
+                continue;
+            }
+
+            let (condition, selectivity) = Self::combine_edges(&connecting);
+
+            for candidate in Self::candidates(left, right, &condition, selectivity) {
+                if best.as_ref().map_or(true, |current| candidate.cost < current.cost) {
+                    best = Some(candidate);
+                }
+            }
+        }
+
+        best
+    }
+
+    fn connecting_edges<'e>(edges: &'e [ResolvedEdge], s1: u64, s2: u64) -> Vec<&'e ResolvedEdge> {
+        edges
+            .iter()
+            .filter(|edge| {
+                let (left, right) = (1u64 << edge.left, 1u64 << edge.right);
+                (left & s1 != 0 && right & s2 != 0) || (left & s2 != 0 && right & s1 != 0)
+            })
+            .collect()
+    }
+
+    /// ANDs every connecting edge's condition together (so all of them are
+    /// actually checked) and multiplies their selectivities; an empty slice
+    /// means no connecting edge was found at all, i.e. a cross product.
+    fn combine_edges(edges: &[&ResolvedEdge]) -> (FilterIntent, f64) {
+        match edges {
+            [] => (FilterIntent::Always, 1.0),
+            [single] => (single.condition.clone(), single.selectivity),
+            _ => {
+                let condition = FilterIntent::Logical {
+                    op: LogicalOp::And,
+                    operands: edges.iter().map(|edge| edge.condition.clone()).collect(),
+                };
+                let selectivity = edges.iter().map(|edge| edge.selectivity).product();
+                (condition, selectivity)
+            }
+        }
+    }
+
+    /// A `HashJoin` and a `NestedLoopJoin` candidate over `left`/`right`,
+    /// costed from their accumulated costs and cardinalities rather than by
+    /// re-walking the composed tree: a hash join pays to materialize a
+    /// table over both sides once, while a nested loop pays to rescan the
+    /// inner side once per outer row, so it scales with `left.cardinality`
+    /// instead.
+    fn candidates(left: &MemoEntry, right: &MemoEntry, condition: &FilterIntent, selectivity: f64) -> Vec<MemoEntry> {
+        let cardinality = (left.cardinality * right.cardinality * selectivity).max(1.0);
+
+        let hash_cost = left.cost
+            + right.cost
+            + (left.cardinality + right.cardinality) * CPU_TUPLE_COST
+            + cardinality * CPU_OPERATOR_COST;
+        let nested_loop_cost = left.cost + left.cardinality * right.cost + cardinality * CPU_OPERATOR_COST;
+
+        vec![
+            MemoEntry {
+                plan: PhysicalPlan::HashJoin {
+                    join_type: JoinType::Inner,
+                    left: Box::new(left.plan.clone()),
+                    right: Box::new(right.plan.clone()),
+                    condition: condition.clone(),
+                },
+                cost: hash_cost,
+                cardinality,
+            },
+            MemoEntry {
+                plan: PhysicalPlan::NestedLoopJoin {
+                    join_type: JoinType::Inner,
+                    left: Box::new(left.plan.clone()),
+                    right: Box::new(right.plan.clone()),
+                    condition: condition.clone(),
+                },
+                cost: nested_loop_cost,
+                cardinality,
+            },
+        ]
+    }
+}
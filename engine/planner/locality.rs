@@ -1,4 +1,5 @@
-use crate::planner::physical::PhysicalPlan;
+use crate::language::intent::{ComparisonOp, ExpressionIntent, FilterIntent};
+use crate::planner::physical::{Partitioning, PhysicalPlan};
 use crate::sharding::keyspace::ShardId;
 use std::collections::HashSet;
 
@@ -23,6 +24,12 @@ impl LocalityAnalyzer {
             },
             PhysicalPlan::Filter { input, .. } => self.analyze(input),
             PhysicalPlan::Project { input, .. } => self.analyze(input),
+            PhysicalPlan::Exchange { input, .. } => {
+                let mut info = self.analyze(input);
+                info.is_local = true;
+                info.requires_shuffle = false;
+                info
+            }
             PhysicalPlan::HashJoin { left, right, .. } => {
                 let left_info = self.analyze(left);
                 let right_info = self.analyze(right);
@@ -38,6 +45,15 @@ impl LocalityAnalyzer {
                     requires_shuffle,
                 }
             }
+            PhysicalPlan::HashAggregate { input, .. } => {
+                let input_info = self.analyze(input);
+
+                LocalityInfo {
+                    shards: input_info.shards.clone(),
+                    is_local: input_info.is_local,
+                    requires_shuffle: !input_info.is_local && input_info.shards.len() > 1,
+                }
+            }
             _ => LocalityInfo {
                 shards: HashSet::new(),
                 is_local: true,
@@ -46,7 +62,131 @@ impl LocalityAnalyzer {
         }
     }
 
-    fn get_table_shards(&self, _table: &str) -> HashSet<ShardId> {
+    /// Rewrites `plan` so that every operator whose inputs `requires_shuffle`
+    /// is preceded by an `Exchange` that repartitions tuples by the
+    /// operator's join/group keys. Leaf scans are left untouched; the shards
+    /// they are pinned to are the ones `get_table_shards` reports, looked up
+    /// by `analyze` while planning. Only `Filter`/`Project`/`HashJoin`/
+    /// `HashAggregate` are descended into; any other node is returned as is,
+    /// together with everything below it.
+    pub fn plan_fragments(&self, plan: &PhysicalPlan) -> PhysicalPlan {
+        match plan {
+            PhysicalPlan::Filter { predicate, input } => PhysicalPlan::Filter {
+                predicate: predicate.clone(),
+                input: Box::new(self.plan_fragments(input)),
+            },
+            PhysicalPlan::Project { columns, input } => PhysicalPlan::Project {
+                columns: columns.clone(),
+                input: Box::new(self.plan_fragments(input)),
+            },
+            PhysicalPlan::HashJoin {
+                join_type,
+                left,
+                right,
+                condition,
+            } => {
+                let left_info = self.analyze(left);
+                let right_info = self.analyze(right);
+                let left_plan = self.plan_fragments(left);
+                let right_plan = self.plan_fragments(right);
+
+                if !self.are_colocated(&left_info.shards, &right_info.shards) {
+                    let keys = self.equi_join_keys(condition);
+                    PhysicalPlan::HashJoin {
+                        join_type: join_type.clone(),
+                        left: Box::new(self.exchange(left_plan, &keys.0)),
+                        right: Box::new(self.exchange(right_plan, &keys.1)),
+                        condition: condition.clone(),
+                    }
+                } else {
+                    PhysicalPlan::HashJoin {
+                        join_type: join_type.clone(),
+                        left: Box::new(left_plan),
+                        right: Box::new(right_plan),
+                        condition: condition.clone(),
+                    }
+                }
+            }
+            PhysicalPlan::HashAggregate {
+                group_by,
+                aggregates,
+                input,
+            } => {
+                let input_info = self.analyze(input);
+                let input_plan = self.plan_fragments(input);
+
+                if !input_info.is_local && input_info.shards.len() > 1 && !group_by.is_empty() {
+                    let keys = group_by
+                        .iter()
+                        .filter_map(Self::column_name)
+                        .collect::<Vec<_>>();
+
+                    PhysicalPlan::HashAggregate {
+                        group_by: group_by.clone(),
+                        aggregates: aggregates.clone(),
+                        input: Box::new(self.exchange(input_plan, &keys)),
+                    }
+                } else {
+                    PhysicalPlan::HashAggregate {
+                        group_by: group_by.clone(),
+                        aggregates: aggregates.clone(),
+                        input: Box::new(input_plan),
+                    }
+                }
+            }
+            other => other.clone(),
+        }
+    }
+
+    fn exchange(&self, input: PhysicalPlan, keys: &[String]) -> PhysicalPlan {
+        if keys.is_empty() {
+            PhysicalPlan::Exchange {
+                partitioning: Partitioning::Single,
+                input: Box::new(input),
+            }
+        } else {
+            PhysicalPlan::Exchange {
+                partitioning: Partitioning::HashPartition(keys.to_vec()),
+                input: Box::new(input),
+            }
+        }
+    }
+
+    /// Pulls the equi-join column names for each side out of a join
+    /// condition, e.g. `lhs.a = rhs.b` yields `(["a"], ["b"])`.
+    fn equi_join_keys(&self, condition: &FilterIntent) -> (Vec<String>, Vec<String>) {
+        match condition {
+            FilterIntent::Comparison {
+                op: ComparisonOp::Equal,
+                left,
+                right,
+            } => match (Self::column_name(left), Self::column_name(right)) {
+                (Some(l), Some(r)) => (vec![l], vec![r]),
+                _ => (vec![], vec![]),
+            },
+            FilterIntent::Logical { operands, .. } => {
+                let mut left_keys = Vec::new();
+                let mut right_keys = Vec::new();
+                for operand in operands {
+                    let (l, r) = self.equi_join_keys(operand);
+                    left_keys.extend(l);
+                    right_keys.extend(r);
+                }
+                (left_keys, right_keys)
+            }
+            _ => (vec![], vec![]),
+        }
+    }
+
+    fn column_name(expr: &ExpressionIntent) -> Option<String> {
+        match expr {
+            ExpressionIntent::Column(name) => Some(name.clone()),
+            ExpressionIntent::QualifiedColumn { column, .. } => Some(column.clone()),
+            _ => None,
+        }
+    }
+
+    pub fn get_table_shards(&self, _table: &str) -> HashSet<ShardId> {
         let mut shards = HashSet::new();
         for i in 0..16 {
             shards.insert(ShardId(i));
@@ -1,15 +1,92 @@
-use crate::language::ast::Statement;
+use crate::language::ast::{ColumnDefinition, Statement};
+use crate::language::catalog::Catalog;
 use crate::language::intent::*;
 use crate::language::semantic::SemanticAnalyzer;
 use crate::language::JoinType;
+use crate::planner::optimizer::Optimizer;
 use anyhow::Result;
 use serde::{Deserialize, Serialize};
 
+/// One side of a `KeyRange`: a bound value plus whether it's inclusive.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Bound {
+    pub value: ConstantValue,
+    pub inclusive: bool,
+}
+
+/// An optional lower and/or upper bound on `column`, built by
+/// `Optimizer::push_down_range_predicates` from `col <op> const` conjuncts so
+/// a scan can skip rows outside the range instead of reading everything and
+/// re-checking the predicate per row.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct KeyRange {
+    pub column: String,
+    pub lower: Option<Bound>,
+    pub upper: Option<Bound>,
+}
+
+impl KeyRange {
+    /// The `col <op> const` comparison(s) this range is equivalent to (ANDed
+    /// together if both bounds are set), so a scan without a real index
+    /// structure to seek on can filter its rows by evaluating this exactly
+    /// like any other pushed-down predicate.
+    pub fn to_filter(&self) -> FilterIntent {
+        let mut comparisons = Vec::new();
+
+        if let Some(lower) = &self.lower {
+            let op = if lower.inclusive {
+                ComparisonOp::GreaterThanOrEqual
+            } else {
+                ComparisonOp::GreaterThan
+            };
+            comparisons.push(FilterIntent::Comparison {
+                op,
+                left: ExpressionIntent::Column(self.column.clone()),
+                right: ExpressionIntent::Constant(lower.value.clone()),
+            });
+        }
+
+        if let Some(upper) = &self.upper {
+            let op = if upper.inclusive {
+                ComparisonOp::LessThanOrEqual
+            } else {
+                ComparisonOp::LessThan
+            };
+            comparisons.push(FilterIntent::Comparison {
+                op,
+                left: ExpressionIntent::Column(self.column.clone()),
+                right: ExpressionIntent::Constant(upper.value.clone()),
+            });
+        }
+
+        match comparisons.len() {
+            0 => FilterIntent::Always,
+            1 => comparisons.pop().expect("just checked len == 1"),
+            _ => FilterIntent::Logical {
+                op: LogicalOp::And,
+                operands: comparisons,
+            },
+        }
+    }
+}
+
+/// What `Optimizer::push_down_range_predicates` could (and couldn't) fold
+/// into a `Scan`: `key_range` narrows the rows actually read, while
+/// `residual` is whatever conjunct(s) it couldn't express as a range and
+/// must still be checked row-by-row via a `Filter` above the scan.
+#[derive(Debug, Clone, Serialize, Deserialize, Default)]
+pub struct ScanOptions {
+    pub key_range: Option<KeyRange>,
+    pub residual: Option<FilterIntent>,
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub enum LogicalPlan {
     Scan {
         table: String,
         columns: Vec<String>,
+        time_travel: Option<TimeTravelIntent>,
+        options: ScanOptions,
     },
     Filter {
         predicate: FilterIntent,
@@ -39,6 +116,9 @@ pub enum LogicalPlan {
         offset: usize,
         input: Box<LogicalPlan>,
     },
+    Distinct {
+        input: Box<LogicalPlan>,
+    },
     Insert {
         table: String,
         columns: Vec<String>,
@@ -53,44 +133,76 @@ pub enum LogicalPlan {
         table: String,
         filter: Option<FilterIntent>,
     },
+    CreateTable {
+        name: String,
+        columns: Vec<ColumnDefinition>,
+    },
+    CreateIndex {
+        name: String,
+        table: String,
+        columns: Vec<String>,
+    },
+    DropIndex {
+        name: String,
+    },
+    CreatePolicy {
+        policy_name: String,
+        table: String,
+        roles: Vec<String>,
+        filter: FilterIntent,
+    },
+    DropPolicy {
+        policy_name: String,
+        table: String,
+    },
 }
 
 pub struct LogicalPlanner {
     semantic_analyzer: SemanticAnalyzer,
+    catalog: Catalog,
 }
 
 impl LogicalPlanner {
-    pub fn new() -> Self {
+    pub fn new(catalog: Catalog) -> Self {
         Self {
-            semantic_analyzer: SemanticAnalyzer::new(),
+            semantic_analyzer: SemanticAnalyzer::new(catalog.clone()),
+            catalog,
         }
     }
 
     pub fn plan(&self, statement: &Statement) -> Result<LogicalPlan> {
         let intent = self.semantic_analyzer.analyze(statement)?;
-        self.intent_to_plan(&intent)
+        let plan = self.intent_to_plan(&intent)?;
+        Optimizer::new(self.catalog.clone()).optimize(plan)
     }
 
     fn intent_to_plan(&self, intent: &Intent) -> Result<LogicalPlan> {
         match intent {
             Intent::Retrieve {
                 columns,
+                distinct,
                 source,
                 filter,
                 aggregates,
+                grouping,
+                having,
                 ordering,
                 limit,
-                ..
+                time_travel,
             } => {
                 let mut plan = LogicalPlan::Scan {
                     table: source.primary.clone(),
                     columns: self.extract_column_names(columns),
+                    time_travel: time_travel.clone(),
+                    options: ScanOptions::default(),
                 };
 
                 for join in &source.joins {
                     let right = LogicalPlan::Scan {
                         table: join.table.clone(),
                         columns: vec![],
+                        time_travel: time_travel.clone(),
+                        options: ScanOptions::default(),
                     };
 
                     plan = LogicalPlan::Join {
@@ -110,17 +222,28 @@ impl LogicalPlanner {
 
                 if !aggregates.is_empty() {
                     plan = LogicalPlan::Aggregate {
-                        group_by: vec![],
+                        group_by: grouping.clone(),
                         aggregates: aggregates.clone(),
                         input: Box::new(plan),
                     };
                 }
 
+                if let Some(having_intent) = having {
+                    plan = LogicalPlan::Filter {
+                        predicate: having_intent.clone(),
+                        input: Box::new(plan),
+                    };
+                }
+
                 plan = LogicalPlan::Project {
                     columns: columns.clone(),
                     input: Box::new(plan),
                 };
 
+                if *distinct {
+                    plan = LogicalPlan::Distinct { input: Box::new(plan) };
+                }
+
                 if !ordering.is_empty() {
                     plan = LogicalPlan::Sort {
                         order_by: ordering.clone(),
@@ -158,6 +281,36 @@ impl LogicalPlanner {
                     filter: filter.clone(),
                 }),
             },
+            Intent::Schema {
+                operation: SchemaIntent::CreateTable { name, columns },
+            } => Ok(LogicalPlan::CreateTable {
+                name: name.clone(),
+                columns: columns.clone(),
+            }),
+            Intent::Schema {
+                operation: SchemaIntent::CreateIndex { name, table, columns },
+            } => Ok(LogicalPlan::CreateIndex {
+                name: name.clone(),
+                table: table.clone(),
+                columns: columns.clone(),
+            }),
+            Intent::Schema {
+                operation: SchemaIntent::DropIndex { name },
+            } => Ok(LogicalPlan::DropIndex { name: name.clone() }),
+            Intent::Schema {
+                operation: SchemaIntent::CreatePolicy { policy_name, table, roles, filter },
+            } => Ok(LogicalPlan::CreatePolicy {
+                policy_name: policy_name.clone(),
+                table: table.clone(),
+                roles: roles.clone(),
+                filter: filter.clone(),
+            }),
+            Intent::Schema {
+                operation: SchemaIntent::DropPolicy { policy_name, table },
+            } => Ok(LogicalPlan::DropPolicy {
+                policy_name: policy_name.clone(),
+                table: table.clone(),
+            }),
             _ => anyhow::bail!("Unsupported intent type"),
         }
     }
@@ -172,4 +325,4 @@ impl LogicalPlanner {
             })
             .collect()
     }
-                  }
+}
@@ -3,9 +3,15 @@ pub mod physical;
 pub mod optimizer;
 pub mod cost;
 pub mod locality;
+pub mod join_order;
+pub mod statistics;
+pub mod describe;
 
 pub use logical::*;
 pub use physical::*;
 pub use optimizer::*;
 pub use cost::*;
 pub use locality::*;
+pub use join_order::*;
+pub use statistics::*;
+pub use describe::*;
@@ -1,16 +1,23 @@
+use crate::language::catalog::Catalog;
 use crate::language::intent::*;
-use crate::planner::logical::LogicalPlan;
+use crate::language::JoinType;
+use crate::planner::logical::{Bound, KeyRange, LogicalPlan};
 use anyhow::Result;
+use std::cmp::Ordering;
+use std::collections::HashSet;
 
-pub struct Optimizer;
+pub struct Optimizer {
+    catalog: Catalog,
+}
 
 impl Optimizer {
-    pub fn new() -> Self {
-        Self
+    pub fn new(catalog: Catalog) -> Self {
+        Self { catalog }
     }
 
     pub fn optimize(&self, plan: LogicalPlan) -> Result<LogicalPlan> {
         let plan = self.push_down_filters(plan)?;
+        let plan = self.push_down_range_predicates(plan)?;
         let plan = self.push_down_projections(plan)?;
         let plan = self.fold_constants(plan)?;
         Ok(plan)
@@ -28,16 +35,13 @@ impl Optimizer {
                     } => {
                         let optimized_left = self.push_down_filters(*left)?;
                         let optimized_right = self.push_down_filters(*right)?;
-
-                        Ok(LogicalPlan::Filter {
+                        Ok(Self::push_filter_into_join(
                             predicate,
-                            input: Box::new(LogicalPlan::Join {
-                                join_type,
-                                left: Box::new(optimized_left),
-                                right: Box::new(optimized_right),
-                                condition,
-                            }),
-                        })
+                            join_type,
+                            optimized_left,
+                            optimized_right,
+                            condition,
+                        ))
                     }
                     _ => {
                         let optimized_input = self.push_down_filters(*input)?;
@@ -74,23 +78,606 @@ impl Optimizer {
         }
     }
 
+    /// Splits `predicate` on top-level AND into per-side conjuncts (pushed
+    /// below `left`/`right` as their own `Filter`) and whatever's left over
+    /// (kept as a `Filter` above the join). Only done for `Inner` joins:
+    /// pushing a predicate below an outer join that doesn't filter on the
+    /// preserved side can change which rows get null-padded, so outer joins
+    /// keep the whole predicate above the join exactly as before.
+    fn push_filter_into_join(
+        predicate: FilterIntent,
+        join_type: JoinType,
+        left: LogicalPlan,
+        right: LogicalPlan,
+        condition: FilterIntent,
+    ) -> LogicalPlan {
+        if !matches!(join_type, JoinType::Inner) {
+            return LogicalPlan::Filter {
+                predicate,
+                input: Box::new(LogicalPlan::Join {
+                    join_type,
+                    left: Box::new(left),
+                    right: Box::new(right),
+                    condition,
+                }),
+            };
+        }
+
+        let left_columns = Self::columns_of(&left);
+        let right_columns = Self::columns_of(&right);
+
+        let mut left_conjuncts = Vec::new();
+        let mut right_conjuncts = Vec::new();
+        let mut residual_conjuncts = Vec::new();
+
+        for conjunct in Self::split_conjuncts(predicate) {
+            let mut referenced = HashSet::new();
+            Self::filter_columns(&conjunct, &mut referenced);
+
+            if !referenced.is_empty() && referenced.is_subset(&left_columns) {
+                left_conjuncts.push(conjunct);
+            } else if !referenced.is_empty() && referenced.is_subset(&right_columns) {
+                right_conjuncts.push(conjunct);
+            } else {
+                residual_conjuncts.push(conjunct);
+            }
+        }
+
+        let new_left = match Self::conjunction(left_conjuncts) {
+            Some(pred) => LogicalPlan::Filter { predicate: pred, input: Box::new(left) },
+            None => left,
+        };
+        let new_right = match Self::conjunction(right_conjuncts) {
+            Some(pred) => LogicalPlan::Filter { predicate: pred, input: Box::new(right) },
+            None => right,
+        };
+
+        let join = LogicalPlan::Join {
+            join_type,
+            left: Box::new(new_left),
+            right: Box::new(new_right),
+            condition,
+        };
+
+        match Self::conjunction(residual_conjuncts) {
+            Some(pred) => LogicalPlan::Filter {
+                predicate: pred,
+                input: Box::new(join),
+            },
+            None => join,
+        }
+    }
+
+    /// Walks the plan looking for a `Filter` sitting directly over a `Scan`
+    /// and tries to fold its AND-connected conjuncts into a `KeyRange` on
+    /// the scan's key column (its primary key, or failing that its first
+    /// indexed column). Only `col <op> const` comparisons on that column are
+    /// convertible; everything else (OR branches, other columns,
+    /// expressions) is left in `residual` and re-emitted as a `Filter` above
+    /// the scan so it's still checked. This is deliberately conservative
+    /// about disjunctions: an OR conjunct is never split apart, so it either
+    /// fails to convert as a whole (common case, stays in `residual`) rather
+    /// than risk pushing down a range that's too wide.
+    fn push_down_range_predicates(&self, plan: LogicalPlan) -> Result<LogicalPlan> {
+        match plan {
+            LogicalPlan::Filter { predicate, input } => {
+                let input = self.push_down_range_predicates(*input)?;
+
+                let (table, columns, time_travel, mut options) = match input {
+                    LogicalPlan::Scan { table, columns, time_travel, options } => (table, columns, time_travel, options),
+                    other => {
+                        return Ok(LogicalPlan::Filter {
+                            predicate,
+                            input: Box::new(other),
+                        });
+                    }
+                };
+
+                let key_column = match self.key_column(&table) {
+                    Some(key_column) => key_column,
+                    None => {
+                        return Ok(LogicalPlan::Filter {
+                            predicate,
+                            input: Box::new(LogicalPlan::Scan { table, columns, time_travel, options }),
+                        });
+                    }
+                };
+
+                let mut range = options.key_range.take().unwrap_or_else(|| KeyRange {
+                    column: key_column.clone(),
+                    lower: None,
+                    upper: None,
+                });
+
+                let mut residual_conjuncts = Vec::new();
+                for conjunct in Self::split_conjuncts(predicate) {
+                    if Self::try_push_conjunct(&conjunct, &key_column, &mut range) {
+                        continue;
+                    }
+                    residual_conjuncts.push(conjunct);
+                }
+
+                options.key_range = Some(range);
+
+                let mut residual = Self::conjunction(residual_conjuncts);
+                if let Some(existing) = options.residual.take() {
+                    residual = match residual {
+                        Some(pred) => Self::conjunction(vec![existing, pred]),
+                        None => Some(existing),
+                    };
+                }
+                options.residual = residual.clone();
+
+                let scan = LogicalPlan::Scan { table, columns, time_travel, options };
+                Ok(match residual {
+                    Some(predicate) => LogicalPlan::Filter {
+                        predicate,
+                        input: Box::new(scan),
+                    },
+                    None => scan,
+                })
+            }
+            LogicalPlan::Project { columns, input } => Ok(LogicalPlan::Project {
+                columns,
+                input: Box::new(self.push_down_range_predicates(*input)?),
+            }),
+            LogicalPlan::Join {
+                join_type,
+                left,
+                right,
+                condition,
+            } => Ok(LogicalPlan::Join {
+                join_type,
+                left: Box::new(self.push_down_range_predicates(*left)?),
+                right: Box::new(self.push_down_range_predicates(*right)?),
+                condition,
+            }),
+            LogicalPlan::Aggregate { group_by, aggregates, input } => Ok(LogicalPlan::Aggregate {
+                group_by,
+                aggregates,
+                input: Box::new(self.push_down_range_predicates(*input)?),
+            }),
+            LogicalPlan::Sort { order_by, input } => Ok(LogicalPlan::Sort {
+                order_by,
+                input: Box::new(self.push_down_range_predicates(*input)?),
+            }),
+            LogicalPlan::Limit { count, offset, input } => Ok(LogicalPlan::Limit {
+                count,
+                offset,
+                input: Box::new(self.push_down_range_predicates(*input)?),
+            }),
+            other => Ok(other),
+        }
+    }
+
+    /// The column a `KeyRange` should be built on for `table`: its primary
+    /// key, or failing that the first column covered by any registered
+    /// index. `None` if the table is unknown to the catalog or has neither,
+    /// meaning nothing can be pushed down for it.
+    fn key_column(&self, table: &str) -> Option<String> {
+        let schema = self.catalog.table(table)?;
+        if let Some(pk) = schema.columns.iter().find(|c| c.primary_key) {
+            return Some(pk.name.clone());
+        }
+        schema.indexes.first().cloned()
+    }
+
+    /// Tries to fold a single conjunct into `range`. Succeeds only for a
+    /// direct `column <op> const` (or `const <op> column`) comparison on
+    /// `key_column` whose constant is comparable to whatever bound is
+    /// already there; anything else (a different column, an expression, an
+    /// OR/NOT, or an incomparable constant type) is left alone and the
+    /// conjunct must stay in `residual`.
+    fn try_push_conjunct(conjunct: &FilterIntent, key_column: &str, range: &mut KeyRange) -> bool {
+        let (op, left, right) = match conjunct {
+            FilterIntent::Comparison { op, left, right } => (op, left, right),
+            _ => return false,
+        };
+
+        let (column, op, value) = match (Self::column_of(left), Self::constant_of(right)) {
+            (Some(column), Some(value)) => (column, op.clone(), value),
+            _ => match (Self::constant_of(left), Self::column_of(right)) {
+                (Some(value), Some(column)) => (column, Self::flip(op), value),
+                _ => return false,
+            },
+        };
+
+        if column != key_column {
+            return false;
+        }
+
+        Self::combine_into_range(range, &op, value)
+    }
+
+    fn column_of(expr: &ExpressionIntent) -> Option<String> {
+        match expr {
+            ExpressionIntent::Column(name) => Some(name.clone()),
+            ExpressionIntent::QualifiedColumn { column, .. } => Some(column.clone()),
+            _ => None,
+        }
+    }
+
+    fn constant_of(expr: &ExpressionIntent) -> Option<ConstantValue> {
+        match expr {
+            ExpressionIntent::Constant(value) => Some(value.clone()),
+            _ => None,
+        }
+    }
+
+    fn flip(op: &ComparisonOp) -> ComparisonOp {
+        match op {
+            ComparisonOp::LessThan => ComparisonOp::GreaterThan,
+            ComparisonOp::LessThanOrEqual => ComparisonOp::GreaterThanOrEqual,
+            ComparisonOp::GreaterThan => ComparisonOp::LessThan,
+            ComparisonOp::GreaterThanOrEqual => ComparisonOp::LessThanOrEqual,
+            ComparisonOp::Equal => ComparisonOp::Equal,
+            ComparisonOp::NotEqual => ComparisonOp::NotEqual,
+        }
+    }
+
+    /// Same-variant ordering for `ConstantValue`: `Integer`/`Float`/`String`
+    /// compare within their own variant, everything else (including any
+    /// mismatched pair, or `Null`/`Boolean`) is treated as incomparable so
+    /// the caller leaves the conjunct in `residual` instead of guessing.
+    fn compare_constants(a: &ConstantValue, b: &ConstantValue) -> Option<Ordering> {
+        match (a, b) {
+            (ConstantValue::Integer(a), ConstantValue::Integer(b)) => Some(a.cmp(b)),
+            (ConstantValue::Float(a), ConstantValue::Float(b)) => a.partial_cmp(b),
+            (ConstantValue::String(a), ConstantValue::String(b)) => Some(a.cmp(b)),
+            _ => None,
+        }
+    }
+
+    fn combine_into_range(range: &mut KeyRange, op: &ComparisonOp, value: ConstantValue) -> bool {
+        match op {
+            ComparisonOp::Equal => {
+                let lower = Self::tighten(range.lower.clone(), Bound { value: value.clone(), inclusive: true }, true);
+                let upper = Self::tighten(range.upper.clone(), Bound { value, inclusive: true }, false);
+                match (lower, upper) {
+                    (Some(lower), Some(upper)) => {
+                        range.lower = Some(lower);
+                        range.upper = Some(upper);
+                        true
+                    }
+                    _ => false,
+                }
+            }
+            ComparisonOp::GreaterThan => Self::merge_lower(range, Bound { value, inclusive: false }),
+            ComparisonOp::GreaterThanOrEqual => Self::merge_lower(range, Bound { value, inclusive: true }),
+            ComparisonOp::LessThan => Self::merge_upper(range, Bound { value, inclusive: false }),
+            ComparisonOp::LessThanOrEqual => Self::merge_upper(range, Bound { value, inclusive: true }),
+            ComparisonOp::NotEqual => false,
+        }
+    }
+
+    fn merge_lower(range: &mut KeyRange, candidate: Bound) -> bool {
+        match Self::tighten(range.lower.clone(), candidate, true) {
+            Some(bound) => {
+                range.lower = Some(bound);
+                true
+            }
+            None => false,
+        }
+    }
+
+    fn merge_upper(range: &mut KeyRange, candidate: Bound) -> bool {
+        match Self::tighten(range.upper.clone(), candidate, false) {
+            Some(bound) => {
+                range.upper = Some(bound);
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Keeps whichever of `existing`/`candidate` is tighter — the larger one
+    /// for a lower bound, the smaller one for an upper bound (`keep_greater`
+    /// selects which) — or `None` if the two bounds' values can't be
+    /// compared at all, signalling the caller should give up on pushing this
+    /// conjunct down.
+    fn tighten(existing: Option<Bound>, candidate: Bound, keep_greater: bool) -> Option<Bound> {
+        let existing = match existing {
+            Some(existing) => existing,
+            None => return Some(candidate),
+        };
+
+        let ordering = Self::compare_constants(&candidate.value, &existing.value)?;
+        let candidate_is_tighter = match ordering {
+            Ordering::Equal => !candidate.inclusive && existing.inclusive,
+            Ordering::Greater => keep_greater,
+            Ordering::Less => !keep_greater,
+        };
+
+        Some(if candidate_is_tighter { candidate } else { existing })
+    }
+
+    /// Splits a predicate into its top-level AND conjuncts; anything that
+    /// isn't itself a top-level AND is a single conjunct.
+    fn split_conjuncts(predicate: FilterIntent) -> Vec<FilterIntent> {
+        match predicate {
+            FilterIntent::Logical {
+                op: LogicalOp::And,
+                operands,
+            } => operands.into_iter().flat_map(Self::split_conjuncts).collect(),
+            other => vec![other],
+        }
+    }
+
+    /// Inverse of `split_conjuncts`: reassembles conjuncts into a single
+    /// predicate, or `None` if there's nothing left to filter on.
+    fn conjunction(mut conjuncts: Vec<FilterIntent>) -> Option<FilterIntent> {
+        match conjuncts.len() {
+            0 => None,
+            1 => conjuncts.pop(),
+            _ => Some(FilterIntent::Logical {
+                op: LogicalOp::And,
+                operands: conjuncts,
+            }),
+        }
+    }
+
+    /// Every column supplied by `plan`, found by walking down to its
+    /// `Scan` leaves. Used to decide which side of a join a filter conjunct
+    /// can be pushed to.
+    fn columns_of(plan: &LogicalPlan) -> HashSet<String> {
+        let mut columns = HashSet::new();
+        Self::collect_columns(plan, &mut columns);
+        columns
+    }
+
+    fn collect_columns(plan: &LogicalPlan, columns: &mut HashSet<String>) {
+        match plan {
+            LogicalPlan::Scan { columns: cols, .. } => {
+                columns.extend(cols.iter().cloned());
+            }
+            LogicalPlan::Filter { input, .. }
+            | LogicalPlan::Project { input, .. }
+            | LogicalPlan::Aggregate { input, .. }
+            | LogicalPlan::Sort { input, .. }
+            | LogicalPlan::Limit { input, .. } => Self::collect_columns(input, columns),
+            LogicalPlan::Join { left, right, .. } => {
+                Self::collect_columns(left, columns);
+                Self::collect_columns(right, columns);
+            }
+            _ => {}
+        }
+    }
+
+    fn filter_columns(filter: &FilterIntent, columns: &mut HashSet<String>) {
+        match filter {
+            FilterIntent::Always | FilterIntent::Never => {}
+            FilterIntent::Comparison { left, right, .. } => {
+                Self::expression_columns(left, columns);
+                Self::expression_columns(right, columns);
+            }
+            FilterIntent::Logical { operands, .. } => {
+                for operand in operands {
+                    Self::filter_columns(operand, columns);
+                }
+            }
+        }
+    }
+
+    fn expression_columns(expr: &ExpressionIntent, columns: &mut HashSet<String>) {
+        match expr {
+            ExpressionIntent::Column(name) => {
+                columns.insert(name.clone());
+            }
+            ExpressionIntent::QualifiedColumn { column, .. } => {
+                columns.insert(column.clone());
+            }
+            ExpressionIntent::Constant(_) | ExpressionIntent::Placeholder(_) => {}
+            ExpressionIntent::Arithmetic { left, right, .. } => {
+                Self::expression_columns(left, columns);
+                Self::expression_columns(right, columns);
+            }
+            ExpressionIntent::Function { args, .. } => {
+                for arg in args {
+                    Self::expression_columns(arg, columns);
+                }
+            }
+            ExpressionIntent::Cast { inner, .. } => Self::expression_columns(inner, columns),
+        }
+    }
+
     fn push_down_projections(&self, plan: LogicalPlan) -> Result<LogicalPlan> {
+        Ok(Self::prune_columns(plan, None))
+    }
+
+    /// Top-down column pruning: `required` is the set of columns something
+    /// above `plan` actually needs out of it, or `None` if nothing above has
+    /// narrowed that down yet (so `plan` is free to keep whatever columns it
+    /// already carries). `Project`/`Aggregate` redefine the required set for
+    /// their own `input` from their own expressions, ignoring what's asked
+    /// of them from above, since anything above them can only reference the
+    /// columns they themselves expose.
+    fn prune_columns(plan: LogicalPlan, required: Option<&HashSet<String>>) -> LogicalPlan {
         match plan {
+            LogicalPlan::Scan { table, columns, time_travel, options } => {
+                let required = match required {
+                    Some(cols) if !cols.contains("*") => cols,
+                    _ => return LogicalPlan::Scan { table, columns, time_travel, options },
+                };
+
+                // Keep the caller's column order where it already lists a
+                // required column, then append any required column it
+                // didn't have yet (e.g. a join's inner scan, which starts
+                // with no columns at all and is filled in purely from what
+                // conditions/projections above it turn out to need).
+                let mut pruned: Vec<String> = columns.into_iter().filter(|c| required.contains(c)).collect();
+                for column in required {
+                    if !pruned.contains(column) {
+                        pruned.push(column.clone());
+                    }
+                }
+
+                LogicalPlan::Scan { table, columns: pruned, time_travel, options }
+            }
             LogicalPlan::Project { columns, input } => {
-                let optimized_input = self.push_down_projections(*input)?;
-                Ok(LogicalPlan::Project {
+                let input_columns = Self::columns_of(&input);
+                let columns = Self::expand_wildcard_columns(columns, &input_columns);
+
+                let mut needed = HashSet::new();
+                for column in &columns {
+                    Self::column_intent_columns(column, &mut needed);
+                }
+
+                let pruned_input = Self::prune_columns(*input, Self::non_empty(needed).as_ref());
+                LogicalPlan::Project {
                     columns,
-                    input: Box::new(optimized_input),
-                })
+                    input: Box::new(pruned_input),
+                }
             }
             LogicalPlan::Filter { predicate, input } => {
-                let optimized_input = self.push_down_projections(*input)?;
-                Ok(LogicalPlan::Filter {
+                let mut needed = required.cloned().unwrap_or_default();
+                Self::filter_columns(&predicate, &mut needed);
+
+                let pruned_input = Self::prune_columns(*input, Self::non_empty(needed).as_ref());
+                LogicalPlan::Filter {
                     predicate,
-                    input: Box::new(optimized_input),
-                })
+                    input: Box::new(pruned_input),
+                }
             }
-            _ => Ok(plan),
+            LogicalPlan::Join {
+                join_type,
+                left,
+                right,
+                condition,
+            } => {
+                let mut condition_columns = HashSet::new();
+                Self::filter_columns(&condition, &mut condition_columns);
+
+                let left_columns = Self::columns_of(&left);
+                let right_columns = Self::columns_of(&right);
+
+                let left_required = Self::side_required(required, &condition_columns, &left_columns);
+                let right_required = Self::side_required(required, &condition_columns, &right_columns);
+
+                let pruned_left = Self::prune_columns(*left, left_required.as_ref());
+                let pruned_right = Self::prune_columns(*right, right_required.as_ref());
+
+                LogicalPlan::Join {
+                    join_type,
+                    left: Box::new(pruned_left),
+                    right: Box::new(pruned_right),
+                    condition,
+                }
+            }
+            LogicalPlan::Aggregate {
+                group_by,
+                aggregates,
+                input,
+            } => {
+                let mut needed = HashSet::new();
+                for expr in &group_by {
+                    Self::expression_columns(expr, &mut needed);
+                }
+                for aggregate in &aggregates {
+                    Self::expression_columns(&aggregate.argument, &mut needed);
+                }
+
+                let pruned_input = Self::prune_columns(*input, Self::non_empty(needed).as_ref());
+                LogicalPlan::Aggregate {
+                    group_by,
+                    aggregates,
+                    input: Box::new(pruned_input),
+                }
+            }
+            LogicalPlan::Sort { order_by, input } => {
+                let mut needed = required.cloned().unwrap_or_default();
+                for order in &order_by {
+                    Self::expression_columns(&order.expr, &mut needed);
+                }
+
+                let pruned_input = Self::prune_columns(*input, Self::non_empty(needed).as_ref());
+                LogicalPlan::Sort {
+                    order_by,
+                    input: Box::new(pruned_input),
+                }
+            }
+            LogicalPlan::Limit { count, offset, input } => {
+                let pruned_input = Self::prune_columns(*input, required);
+                LogicalPlan::Limit {
+                    count,
+                    offset,
+                    input: Box::new(pruned_input),
+                }
+            }
+            other => other,
+        }
+    }
+
+    /// What a join child actually needs from `required`/`condition_columns`:
+    /// whichever of those columns it can supply, since the other side can't
+    /// possibly provide them. `None` (rather than an empty set) when that
+    /// comes out empty, so an unconstrained child isn't pruned down to zero
+    /// columns just because this particular join didn't reference it.
+    fn side_required(
+        required: Option<&HashSet<String>>,
+        condition_columns: &HashSet<String>,
+        side_columns: &HashSet<String>,
+    ) -> Option<HashSet<String>> {
+        let mut needed: HashSet<String> = condition_columns.intersection(side_columns).cloned().collect();
+        if let Some(required) = required {
+            needed.extend(required.intersection(side_columns).cloned());
+        }
+
+        Self::non_empty(needed)
+    }
+
+    /// Columns a node can't actually prune its child down to zero of still
+    /// count as "nothing is known yet" rather than "keep nothing": an empty
+    /// required set almost never reflects a genuine "this needs no columns
+    /// at all", so it's treated the same as `None` everywhere in this pass.
+    fn non_empty(columns: HashSet<String>) -> Option<HashSet<String>> {
+        if columns.is_empty() {
+            None
+        } else {
+            Some(columns)
+        }
+    }
+
+    /// Expands a `Project { All }` entry into an explicit `Named` column per
+    /// entry of `input_columns`, so pruning can keep proceeding above a
+    /// `SELECT *` instead of treating it as an opaque "needs everything".
+    fn expand_wildcard_columns(columns: Vec<ColumnIntent>, input_columns: &HashSet<String>) -> Vec<ColumnIntent> {
+        // Nothing below has a known column set to expand against (e.g. a
+        // `SELECT *` whose scan hasn't had its own columns filled in by
+        // anything yet) — leave `All` alone rather than silently dropping
+        // it and projecting zero columns.
+        if input_columns.is_empty() {
+            return columns;
+        }
+
+        let mut sorted_input: Vec<&String> = input_columns.iter().collect();
+        sorted_input.sort();
+
+        let mut expanded = Vec::with_capacity(columns.len());
+        for column in columns {
+            match column {
+                ColumnIntent::All => {
+                    for name in &sorted_input {
+                        expanded.push(ColumnIntent::Named((*name).clone()));
+                    }
+                }
+                other => expanded.push(other),
+            }
+        }
+        expanded
+    }
+
+    fn column_intent_columns(column: &ColumnIntent, columns: &mut HashSet<String>) {
+        match column {
+            ColumnIntent::All => {
+                columns.insert("*".to_string());
+            }
+            ColumnIntent::Named(name) => {
+                columns.insert(name.clone());
+            }
+            ColumnIntent::Qualified { column, .. } => {
+                columns.insert(column.clone());
+            }
+            ColumnIntent::Expression { expr, .. } => Self::expression_columns(expr, columns),
         }
     }
 
@@ -113,4 +700,4 @@ impl Optimizer {
             _ => Ok(plan),
         }
     }
-                          }
+}
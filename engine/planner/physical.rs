@@ -1,19 +1,37 @@
-use crate::ffi::storage::StorageEngine;
+use crate::language::ast::ColumnDefinition;
+use crate::language::catalog::Catalog;
 use crate::language::intent::*;
-use crate::planner::logical::LogicalPlan;
+use crate::language::JoinType;
+use crate::planner::join_order::{JoinEdge, JoinOrderSearch, JoinRelation};
+use crate::planner::logical::{KeyRange, LogicalPlan};
+use crate::storage::{StorageBackend, TableProvider};
 use anyhow::Result;
+use futures::future::BoxFuture;
+use futures::FutureExt;
 use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub enum Partitioning {
+    Single,
+    HashPartition(Vec<String>),
+}
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub enum PhysicalPlan {
     SeqScan {
         table: String,
         columns: Vec<String>,
+        time_travel: Option<TimeTravelIntent>,
+        key_range: Option<KeyRange>,
     },
     IndexScan {
         table: String,
         index: String,
         columns: Vec<String>,
+        predicate: Option<FilterIntent>,
+        time_travel: Option<TimeTravelIntent>,
+        key_range: Option<KeyRange>,
     },
     Filter {
         predicate: FilterIntent,
@@ -35,11 +53,27 @@ pub enum PhysicalPlan {
         right: Box<PhysicalPlan>,
         condition: FilterIntent,
     },
+    /// A join where the inner side is a `Scan` with a catalog-registered
+    /// index on the equi-join column: rather than building a hash table over
+    /// the inner side, the outer side is streamed and each row's join key is
+    /// looked up in the index directly.
+    IndexSemiJoin {
+        join_type: JoinType,
+        outer: Box<PhysicalPlan>,
+        inner_table: String,
+        inner_index: String,
+        inner_columns: Vec<String>,
+        condition: FilterIntent,
+    },
     HashAggregate {
         group_by: Vec<ExpressionIntent>,
         aggregates: Vec<AggregateIntent>,
         input: Box<PhysicalPlan>,
     },
+    Exchange {
+        partitioning: Partitioning,
+        input: Box<PhysicalPlan>,
+    },
     Sort {
         order_by: Vec<OrderIntent>,
         input: Box<PhysicalPlan>,
@@ -49,6 +83,9 @@ pub enum PhysicalPlan {
         offset: usize,
         input: Box<PhysicalPlan>,
     },
+    Distinct {
+        input: Box<PhysicalPlan>,
+    },
     Insert {
         table: String,
         columns: Vec<String>,
@@ -63,109 +100,430 @@ pub enum PhysicalPlan {
         table: String,
         filter: Option<FilterIntent>,
     },
+    CreateTable {
+        name: String,
+        columns: Vec<ColumnDefinition>,
+    },
+    CreateIndex {
+        name: String,
+        table: String,
+        columns: Vec<String>,
+    },
+    DropIndex {
+        name: String,
+    },
+    CreatePolicy {
+        policy_name: String,
+        table: String,
+        roles: Vec<String>,
+        filter: FilterIntent,
+    },
+    DropPolicy {
+        policy_name: String,
+        table: String,
+    },
 }
 
 pub struct PhysicalPlanner<'a> {
-    storage: &'a StorageEngine,
+    storage: &'a dyn StorageBackend,
+    catalog: Catalog,
 }
 
 impl<'a> PhysicalPlanner<'a> {
-    pub fn new(storage: &'a StorageEngine) -> Self {
-        Self { storage }
+    pub fn new(storage: &'a dyn StorageBackend, catalog: Catalog) -> Self {
+        Self { storage, catalog }
+    }
+
+    /// Builds the single-node operator tree for `logical_plan` and, where
+    /// `LocalityAnalyzer` finds an operator whose inputs aren't colocated,
+    /// inserts the `Exchange` fragments needed to run it across shards.
+    ///
+    /// Lowering a `Scan` awaits `TableProvider::scan` to consult storage for
+    /// index availability and cardinality, so this is `async` rather than
+    /// blocking on the storage layer.
+    pub async fn plan(&self, logical_plan: &LogicalPlan) -> Result<PhysicalPlan> {
+        let plan = self.plan_local(logical_plan).await?;
+        let analyzer = crate::planner::locality::LocalityAnalyzer::new();
+        Ok(analyzer.plan_fragments(&plan))
     }
 
-    pub fn plan(&self, logical_plan: &LogicalPlan) -> Result<PhysicalPlan> {
-        match logical_plan {
-            LogicalPlan::Scan { table, columns } => {
-                Ok(PhysicalPlan::SeqScan {
+    fn plan_local<'b>(&'b self, logical_plan: &'b LogicalPlan) -> BoxFuture<'b, Result<PhysicalPlan>> {
+        async move {
+            match logical_plan {
+                LogicalPlan::Scan { table, columns, time_travel, options } => {
+                    let stats = self.storage.scan(table, columns).await?;
+
+                    if let Some(column) = columns.iter().find(|c| stats.has_index(c)) {
+                        Ok(PhysicalPlan::IndexScan {
+                            table: table.clone(),
+                            index: column.clone(),
+                            columns: columns.clone(),
+                            predicate: None,
+                            time_travel: time_travel.clone(),
+                            key_range: options.key_range.clone(),
+                        })
+                    } else {
+                        Ok(PhysicalPlan::SeqScan {
+                            table: table.clone(),
+                            columns: columns.clone(),
+                            time_travel: time_travel.clone(),
+                            key_range: options.key_range.clone(),
+                        })
+                    }
+                }
+                LogicalPlan::Filter { predicate, input } => {
+                    if let Some(plan) = self.try_index_scan(predicate, input) {
+                        return Ok(PhysicalPlan::Filter {
+                            predicate: predicate.clone(),
+                            input: Box::new(plan),
+                        });
+                    }
+
+                    Ok(PhysicalPlan::Filter {
+                        predicate: predicate.clone(),
+                        input: Box::new(self.plan_local(input).await?),
+                    })
+                }
+                LogicalPlan::Project { columns, input } => {
+                    Ok(PhysicalPlan::Project {
+                        columns: columns.clone(),
+                        input: Box::new(self.plan_local(input).await?),
+                    })
+                }
+                LogicalPlan::Join {
+                    join_type,
+                    left,
+                    right,
+                    condition,
+                } => {
+                    if let Some(plan) = self.plan_join_order(join_type, left, right, condition).await? {
+                        return Ok(plan);
+                    }
+
+                    if let Some(inner) = self.indexed_inner_side(join_type, right, condition) {
+                        let (inner_table, inner_index, inner_columns) = inner;
+                        let outer_plan = self.plan_local(left).await?;
+
+                        return Ok(PhysicalPlan::IndexSemiJoin {
+                            join_type: join_type.clone(),
+                            outer: Box::new(outer_plan),
+                            inner_table,
+                            inner_index,
+                            inner_columns,
+                            condition: condition.clone(),
+                        });
+                    }
+
+                    let left_plan = self.plan_local(left).await?;
+                    let right_plan = self.plan_local(right).await?;
+
+                    Ok(PhysicalPlan::HashJoin {
+                        join_type: join_type.clone(),
+                        left: Box::new(left_plan),
+                        right: Box::new(right_plan),
+                        condition: condition.clone(),
+                    })
+                }
+                LogicalPlan::Aggregate {
+                    group_by,
+                    aggregates,
+                    input,
+                } => {
+                    Ok(PhysicalPlan::HashAggregate {
+                        group_by: group_by.clone(),
+                        aggregates: aggregates.clone(),
+                        input: Box::new(self.plan_local(input).await?),
+                    })
+                }
+                LogicalPlan::Sort { order_by, input } => {
+                    Ok(PhysicalPlan::Sort {
+                        order_by: order_by.clone(),
+                        input: Box::new(self.plan_local(input).await?),
+                    })
+                }
+                LogicalPlan::Limit {
+                    count,
+                    offset,
+                    input,
+                } => {
+                    Ok(PhysicalPlan::Limit {
+                        count: *count,
+                        offset: *offset,
+                        input: Box::new(self.plan_local(input).await?),
+                    })
+                }
+                LogicalPlan::Distinct { input } => {
+                    Ok(PhysicalPlan::Distinct {
+                        input: Box::new(self.plan_local(input).await?),
+                    })
+                }
+                LogicalPlan::Insert {
+                    table,
+                    columns,
+                    values,
+                } => {
+                    Ok(PhysicalPlan::Insert {
+                        table: table.clone(),
+                        columns: columns.clone(),
+                        values: values.clone(),
+                    })
+                }
+                LogicalPlan::Update {
+                    table,
+                    assignments,
+                    filter,
+                } => {
+                    Ok(PhysicalPlan::Update {
+                        table: table.clone(),
+                        assignments: assignments.clone(),
+                        filter: filter.clone(),
+                    })
+                }
+                LogicalPlan::Delete { table, filter } => {
+                    Ok(PhysicalPlan::Delete {
+                        table: table.clone(),
+                        filter: filter.clone(),
+                    })
+                }
+                LogicalPlan::CreateTable { name, columns } => {
+                    Ok(PhysicalPlan::CreateTable {
+                        name: name.clone(),
+                        columns: columns.clone(),
+                    })
+                }
+                LogicalPlan::CreateIndex { name, table, columns } => {
+                    Ok(PhysicalPlan::CreateIndex {
+                        name: name.clone(),
+                        table: table.clone(),
+                        columns: columns.clone(),
+                    })
+                }
+                LogicalPlan::DropIndex { name } => Ok(PhysicalPlan::DropIndex { name: name.clone() }),
+                LogicalPlan::CreatePolicy { policy_name, table, roles, filter } => {
+                    Ok(PhysicalPlan::CreatePolicy {
+                        policy_name: policy_name.clone(),
+                        table: table.clone(),
+                        roles: roles.clone(),
+                        filter: filter.clone(),
+                    })
+                }
+                LogicalPlan::DropPolicy { policy_name, table } => Ok(PhysicalPlan::DropPolicy {
+                    policy_name: policy_name.clone(),
                     table: table.clone(),
-                    columns: columns.clone(),
-                })
-            }
-            LogicalPlan::Filter { predicate, input } => {
-                Ok(PhysicalPlan::Filter {
-                    predicate: predicate.clone(),
-                    input: Box::new(self.plan(input)?),
-                })
+                }),
             }
-            LogicalPlan::Project { columns, input } => {
-                Ok(PhysicalPlan::Project {
-                    columns: columns.clone(),
-                    input: Box::new(self.plan(input)?),
-                })
+        }
+        .boxed()
+    }
+
+    /// Rewrites `Filter { col = const } over Scan` into `IndexScan` when the
+    /// catalog has a usable index on `col`, pushing `predicate` down to the
+    /// scan while the caller keeps wrapping the original `Filter` around it
+    /// (cheap to re-check, and correct even if the index selection below
+    /// were ever wrong).
+    fn try_index_scan(&self, predicate: &FilterIntent, input: &LogicalPlan) -> Option<PhysicalPlan> {
+        let (table, columns, time_travel, options) = match input {
+            LogicalPlan::Scan { table, columns, time_travel, options } => (table, columns, time_travel, options),
+            _ => return None,
+        };
+
+        let column = Self::equality_column(predicate)?;
+        let index = self.catalog.index_on_column(table, &column)?;
+
+        Some(PhysicalPlan::IndexScan {
+            table: table.clone(),
+            index: index.name.clone(),
+            columns: columns.clone(),
+            predicate: Some(predicate.clone()),
+            time_travel: time_travel.clone(),
+            key_range: options.key_range.clone(),
+        })
+    }
+
+    /// Re-orders a chain of `Inner` joins with `JoinOrderSearch` instead of
+    /// lowering it left-deep: flattens the chain into its base relations,
+    /// lowers each one (so the search has a real scan plan/cost to work
+    /// from), resolves each condition to the relation pair it connects, and
+    /// hands all of that to the DP. Returns `None` (falling back to the
+    /// caller's existing left-deep lowering, including the `IndexSemiJoin`
+    /// rewrite) whenever the chain isn't a pure `Inner` chain, has fewer
+    /// than three relations (reordering two relations is a no-op), or has
+    /// more than `join_order::MAX_DP_RELATIONS` of them.
+    async fn plan_join_order(
+        &self,
+        join_type: &JoinType,
+        left: &LogicalPlan,
+        right: &LogicalPlan,
+        condition: &FilterIntent,
+    ) -> Result<Option<PhysicalPlan>> {
+        let (relations, conditions) = match Self::flatten_inner_chain(join_type, left, right, condition) {
+            Some((relations, conditions)) if relations.len() >= 3 => (relations, conditions),
+            _ => return Ok(None),
+        };
+
+        let mut join_relations = Vec::with_capacity(relations.len());
+        for relation in relations {
+            let plan = self.plan_local(relation).await?;
+            let columns = self.relation_columns(relation);
+            join_relations.push(JoinRelation { plan, columns });
+        }
+
+        let edges: Vec<JoinEdge> = conditions.into_iter().map(JoinEdge::new).collect();
+
+        Ok(JoinOrderSearch::new().search(join_relations, &edges))
+    }
+
+    /// Flattens a left-deep chain of `Inner` joins (as built by
+    /// `LogicalPlanner::intent_to_plan`) into its base relations, in their
+    /// original left-to-right order, and the conditions connecting them.
+    /// Returns `None` for anything that isn't a pure `Inner`-join chain,
+    /// since reordering an outer join would change which rows it pads with
+    /// nulls.
+    fn flatten_inner_chain<'p>(
+        join_type: &JoinType,
+        left: &'p LogicalPlan,
+        right: &'p LogicalPlan,
+        condition: &FilterIntent,
+    ) -> Option<(Vec<&'p LogicalPlan>, Vec<FilterIntent>)> {
+        if !matches!(join_type, JoinType::Inner) {
+            return None;
+        }
+
+        let mut relations = Vec::new();
+        let mut conditions = vec![condition.clone()];
+
+        let mut current = left;
+        loop {
+            match current {
+                LogicalPlan::Join {
+                    join_type,
+                    left,
+                    right,
+                    condition,
+                } => {
+                    if !matches!(join_type, JoinType::Inner) {
+                        return None;
+                    }
+                    conditions.push(condition.clone());
+                    relations.push(right.as_ref());
+                    current = left.as_ref();
+                }
+                other => {
+                    relations.push(other);
+                    break;
+                }
             }
-            LogicalPlan::Join {
-                join_type,
+        }
+
+        relations.reverse();
+        conditions.reverse();
+        relations.push(right);
+
+        Some((relations, conditions))
+    }
+
+    /// The column names `relation` is known to provide, used to resolve
+    /// which relation(s) a join condition references. Only a bare `Scan`
+    /// has a catalog schema to consult; anything else (there is none in
+    /// practice, since `intent_to_plan` only ever puts `Scan`s at join
+    /// leaves) resolves to an empty set, which just costs that relation's
+    /// edges the ability to connect to it.
+    fn relation_columns(&self, relation: &LogicalPlan) -> HashSet<String> {
+        match relation {
+            LogicalPlan::Scan { table, .. } => self
+                .catalog
+                .table(table)
+                .map(|schema| schema.columns.iter().map(|column| column.name.clone()).collect())
+                .unwrap_or_default(),
+            _ => HashSet::new(),
+        }
+    }
+
+    /// If `right` is a bare `Scan` with a catalog-registered index on the
+    /// equi-join column `condition` ties it to, returns that table/index/
+    /// column set so the `Join` can be lowered to an `IndexSemiJoin`
+    /// instead of a `HashJoin`.
+    fn indexed_inner_side(
+        &self,
+        join_type: &JoinType,
+        right: &LogicalPlan,
+        condition: &FilterIntent,
+    ) -> Option<(String, String, Vec<String>)> {
+        if !matches!(join_type, JoinType::Inner | JoinType::Left) {
+            return None;
+        }
+
+        let (inner_table, inner_columns) = match right {
+            LogicalPlan::Scan { table, columns, .. } => (table, columns),
+            _ => return None,
+        };
+
+        let (left_expr, right_expr) = Self::equi_join_pair(condition)?;
+        let inner_column = Self::resolve_inner_column(&right_expr, inner_table)
+            .or_else(|| Self::resolve_inner_column(&left_expr, inner_table))?;
+
+        let index = self.catalog.index_on_column(inner_table, &inner_column)?;
+
+        Some((inner_table.clone(), index.name.clone(), inner_columns.clone()))
+    }
+
+    /// Extracts `column` from a top-level `col = const` comparison; returns
+    /// `None` for anything more elaborate (`AND`-chains, ranges, `OR`),
+    /// which fall back to a full scan/hash join.
+    fn equality_column(predicate: &FilterIntent) -> Option<String> {
+        match predicate {
+            FilterIntent::Comparison {
+                op: ComparisonOp::Equal,
                 left,
                 right,
-                condition,
-            } => {
-                let left_plan = self.plan(left)?;
-                let right_plan = self.plan(right)?;
-
-                Ok(PhysicalPlan::HashJoin {
-                    join_type: join_type.clone(),
-                    left: Box::new(left_plan),
-                    right: Box::new(right_plan),
-                    condition: condition.clone(),
-                })
-            }
-            LogicalPlan::Aggregate {
-                group_by,
-                aggregates,
-                input,
-            } => {
-                Ok(PhysicalPlan::HashAggregate {
-                    group_by: group_by.clone(),
-                    aggregates: aggregates.clone(),
-                    input: Box::new(self.plan(input)?),
-                })
-            }
-            LogicalPlan::Sort { order_by, input } => {
-                Ok(PhysicalPlan::Sort {
-                    order_by: order_by.clone(),
-                    input: Box::new(self.plan(input)?),
-                })
-            }
-            LogicalPlan::Limit {
-                count,
-                offset,
-                input,
-            } => {
-                Ok(PhysicalPlan::Limit {
-                    count: *count,
-                    offset: *offset,
-                    input: Box::new(self.plan(input)?),
-                })
-            }
-            LogicalPlan::Insert {
-                table,
-                columns,
-                values,
-            } => {
-                Ok(PhysicalPlan::Insert {
-                    table: table.clone(),
-                    columns: columns.clone(),
-                    values: values.clone(),
-                })
-            }
-            LogicalPlan::Update {
-                table,
-                assignments,
-                filter,
-            } => {
-                Ok(PhysicalPlan::Update {
-                    table: table.clone(),
-                    assignments: assignments.clone(),
-                    filter: filter.clone(),
-                })
+            } => match (left, right) {
+                (column, ExpressionIntent::Constant(_)) | (ExpressionIntent::Constant(_), column) => {
+                    Self::column_name(column)
+                }
+                _ => None,
+            },
+            _ => None,
+        }
+    }
+
+    /// The two sides of a top-level `col = col` equi-join comparison.
+    fn equi_join_pair(condition: &FilterIntent) -> Option<(ExpressionIntent, ExpressionIntent)> {
+        match condition {
+            FilterIntent::Comparison {
+                op: ComparisonOp::Equal,
+                left,
+                right,
+            } if Self::column_name(left).is_some() && Self::column_name(right).is_some() => {
+                Some((left.clone(), right.clone()))
             }
-            LogicalPlan::Delete { table, filter } => {
-                Ok(PhysicalPlan::Delete {
-                    table: table.clone(),
-                    filter: filter.clone(),
-                })
+            _ => None,
+        }
+    }
+
+    fn column_name(expr: &ExpressionIntent) -> Option<String> {
+        match expr {
+            ExpressionIntent::Column(name) => Some(name.clone()),
+            ExpressionIntent::QualifiedColumn { column, .. } => Some(column.clone()),
+            _ => None,
+        }
+    }
+
+    /// Resolves `expr` to a column name belonging to `inner_table`: a
+    /// qualified reference (`inner_table.col`) is trusted outright, while a
+    /// bare column name is accepted too since `LogicalPlan::Join` doesn't
+    /// retain per-column table provenance — the caller only acts on the
+    /// result if the catalog actually has an index for it.
+    fn resolve_inner_column(expr: &ExpressionIntent, inner_table: &str) -> Option<String> {
+        match expr {
+            ExpressionIntent::QualifiedColumn { table, column } => {
+                if table == inner_table {
+                    Some(column.clone())
+                } else {
+                    None
+                }
             }
+            ExpressionIntent::Column(name) => Some(name.clone()),
+            _ => None,
         }
     }
-                  }
+}
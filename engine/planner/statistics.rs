@@ -0,0 +1,223 @@
+use crate::language::intent::{ComparisonOp, ConstantValue, ExpressionIntent, FilterIntent, LogicalOp};
+use std::collections::HashMap;
+
+/// The selectivity `Statistics::selectivity` falls back to for any
+/// comparison over a column it has no `ColumnStats` for (range comparisons
+/// included), and for one that doesn't compare exactly one column.
+pub const DEFAULT_SELECTIVITY: f64 = 0.1;
+
+/// The selectivity `Statistics::selectivity` falls back to for a range
+/// predicate over a column whose `ColumnStats` have no (or an empty)
+/// histogram, or whose bound isn't a numeric constant.
+pub const DEFAULT_RANGE_SELECTIVITY: f64 = 0.3;
+
+/// An equi-width histogram over a column's observed values, used to derive
+/// a range predicate's selectivity from the fraction of buckets it
+/// actually overlaps rather than a flat guess.
+#[derive(Debug, Clone)]
+pub struct Histogram {
+    pub min: f64,
+    pub max: f64,
+    pub bucket_counts: Vec<u64>,
+}
+
+impl Histogram {
+    pub fn new(min: f64, max: f64, bucket_counts: Vec<u64>) -> Self {
+        Self { min, max, bucket_counts }
+    }
+
+    fn bucket_width(&self) -> f64 {
+        if self.bucket_counts.is_empty() || self.max <= self.min {
+            0.0
+        } else {
+            (self.max - self.min) / self.bucket_counts.len() as f64
+        }
+    }
+
+    /// The fraction of this column's rows estimated to fall within
+    /// `[lower, upper]` (either bound `None` meaning unbounded on that
+    /// side), found by summing each overlapping bucket's count scaled by
+    /// how much of that bucket's width the range actually covers.
+    pub fn range_fraction(&self, lower: Option<f64>, upper: Option<f64>) -> f64 {
+        let total: u64 = self.bucket_counts.iter().sum();
+        let width = self.bucket_width();
+        if total == 0 || width <= 0.0 {
+            return DEFAULT_RANGE_SELECTIVITY;
+        }
+
+        let lower = lower.unwrap_or(self.min).max(self.min);
+        let upper = upper.unwrap_or(self.max).min(self.max);
+        if upper < lower {
+            return 0.0;
+        }
+
+        let mut covered = 0.0;
+        for (i, &count) in self.bucket_counts.iter().enumerate() {
+            let bucket_lo = self.min + width * i as f64;
+            let bucket_hi = bucket_lo + width;
+            let overlap = (bucket_hi.min(upper) - bucket_lo.max(lower)).max(0.0);
+            if overlap > 0.0 {
+                covered += count as f64 * (overlap / width);
+            }
+        }
+
+        (covered / total as f64).clamp(0.0, 1.0)
+    }
+}
+
+/// Per-column statistics `Statistics::selectivity` consults: `distinct_values`
+/// drives equality/inequality selectivity (`1/distinct_values`), and
+/// `histogram`, when present, drives range selectivity.
+#[derive(Debug, Clone, Default)]
+pub struct ColumnStats {
+    pub distinct_values: u64,
+    pub histogram: Option<Histogram>,
+}
+
+/// Row/column statistics for one table, as `Statistics::analyze` would
+/// record them after scanning the table's actual data.
+#[derive(Debug, Clone, Default)]
+pub struct TableStats {
+    pub row_count: u64,
+    pub columns: HashMap<String, ColumnStats>,
+}
+
+/// The statistics `CostEstimator` consults for cardinality estimation.
+/// Tables with no entry here (the common case until `analyze` has run for
+/// them) make every lookup return `None`, so callers fall back to
+/// `CostEstimator`'s flat defaults — an un-analyzed table never produces a
+/// worse plan than it would have before `Statistics` existed.
+#[derive(Debug, Clone, Default)]
+pub struct Statistics {
+    tables: HashMap<String, TableStats>,
+}
+
+impl Statistics {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Registers/refreshes the statistics for `table`, as if `ANALYZE table`
+    /// had just been run against it.
+    pub fn analyze(&mut self, table: &str, stats: TableStats) {
+        self.tables.insert(table.to_string(), stats);
+    }
+
+    pub fn row_count(&self, table: &str) -> Option<f64> {
+        self.tables.get(table).map(|stats| stats.row_count as f64)
+    }
+
+    pub fn column(&self, table: &str, column: &str) -> Option<&ColumnStats> {
+        self.tables.get(table).and_then(|stats| stats.columns.get(column))
+    }
+
+    /// The fraction of `table`'s rows `predicate` is expected to keep:
+    /// equality predicates use `1/distinct_values`, range predicates use
+    /// the column's histogram, conjunctions multiply their operands'
+    /// selectivities, and disjunctions combine them by inclusion-exclusion
+    /// (`1 - product of (1 - selectivity)`). Falls back to
+    /// `DEFAULT_SELECTIVITY`/`DEFAULT_RANGE_SELECTIVITY` for anything this
+    /// has no column stats for.
+    pub fn selectivity(&self, table: &str, predicate: &FilterIntent) -> f64 {
+        match predicate {
+            FilterIntent::Always => 1.0,
+            FilterIntent::Never => 0.0,
+            FilterIntent::Comparison { op, left, right } => self.comparison_selectivity(table, op, left, right),
+            FilterIntent::Logical {
+                op: LogicalOp::And,
+                operands,
+            } => operands.iter().map(|operand| self.selectivity(table, operand)).product(),
+            FilterIntent::Logical {
+                op: LogicalOp::Or,
+                operands,
+            } => {
+                1.0 - operands
+                    .iter()
+                    .map(|operand| 1.0 - self.selectivity(table, operand))
+                    .product::<f64>()
+            }
+            FilterIntent::Logical {
+                op: LogicalOp::Not,
+                operands,
+            } => 1.0 - operands.first().map(|operand| self.selectivity(table, operand)).unwrap_or(1.0),
+        }
+    }
+
+    fn comparison_selectivity(
+        &self,
+        table: &str,
+        op: &ComparisonOp,
+        left: &ExpressionIntent,
+        right: &ExpressionIntent,
+    ) -> f64 {
+        let (column, value) = match (Self::column_name(left), Self::column_name(right)) {
+            (Some(column), None) => (column, Self::constant_f64(right)),
+            (None, Some(column)) => (column, Self::constant_f64(left)),
+            _ => return DEFAULT_SELECTIVITY,
+        };
+
+        let stats = match self.column(table, &column) {
+            Some(stats) => stats,
+            None => return DEFAULT_SELECTIVITY,
+        };
+
+        let equality_selectivity = if stats.distinct_values == 0 {
+            DEFAULT_SELECTIVITY
+        } else {
+            1.0 / stats.distinct_values as f64
+        };
+
+        match op {
+            ComparisonOp::Equal => equality_selectivity,
+            ComparisonOp::NotEqual => 1.0 - equality_selectivity,
+            ComparisonOp::LessThan | ComparisonOp::LessThanOrEqual => match (&stats.histogram, value) {
+                (Some(histogram), Some(value)) => histogram.range_fraction(None, Some(value)),
+                _ => DEFAULT_RANGE_SELECTIVITY,
+            },
+            ComparisonOp::GreaterThan | ComparisonOp::GreaterThanOrEqual => match (&stats.histogram, value) {
+                (Some(histogram), Some(value)) => histogram.range_fraction(Some(value), None),
+                _ => DEFAULT_RANGE_SELECTIVITY,
+            },
+        }
+    }
+
+    fn column_name(expr: &ExpressionIntent) -> Option<String> {
+        match expr {
+            ExpressionIntent::Column(name) => Some(name.clone()),
+            ExpressionIntent::QualifiedColumn { column, .. } => Some(column.clone()),
+            _ => None,
+        }
+    }
+
+    fn constant_f64(expr: &ExpressionIntent) -> Option<f64> {
+        match expr {
+            ExpressionIntent::Constant(ConstantValue::Integer(value)) => Some(*value as f64),
+            ExpressionIntent::Constant(ConstantValue::Float(value)) => Some(*value),
+            _ => None,
+        }
+    }
+
+    /// The number of distinct combinations `group_by` is expected to
+    /// produce over `table`: the product of each groupable column's
+    /// `distinct_values` (capped at `row_count`, since a join or filter
+    /// upstream can't manufacture more distinct groups than the table
+    /// actually has rows), or `None` if any column lacks stats.
+    pub fn group_cardinality(&self, table: &str, group_by: &[ExpressionIntent]) -> Option<f64> {
+        if group_by.is_empty() {
+            return Some(1.0);
+        }
+
+        let row_count = self.row_count(table)?;
+        let mut cardinality = 1.0;
+        for expr in group_by {
+            let column = Self::column_name(expr)?;
+            let stats = self.column(table, &column)?;
+            if stats.distinct_values == 0 {
+                return None;
+            }
+            cardinality *= stats.distinct_values as f64;
+        }
+
+        Some(cardinality.min(row_count))
+    }
+}
@@ -1,64 +1,211 @@
-use anyhow::Result;
+use anyhow::{Context, Result};
+use argon2::password_hash::rand_core::{OsRng, RngCore};
+use argon2::password_hash::{PasswordHash, PasswordHasher, PasswordVerifier, SaltString};
+use argon2::{Algorithm, Argon2, Params, Version};
+use hmac::{Hmac, Mac};
+use serde::{Deserialize, Serialize};
 use sha2::{Digest, Sha256};
+use std::collections::HashMap;
+use std::future::Future;
+use std::pin::Pin;
+
+/// RFC 5802 defaults: 16-byte salt, 4096 PBKDF2 rounds.
+const SCRAM_SALT_LEN: usize = 16;
+const SCRAM_ITERATIONS: u32 = 4096;
+
+/// The stored-key/server-key pair a SCRAM-SHA-256 exchange proves knowledge
+/// of, so the plaintext password itself never has to cross the wire. Only
+/// derivable from a plaintext password at the moment it's set, so providers
+/// that never see one (`StaticFileAuthProvider`'s pre-hashed entries,
+/// `LdapAuthProvider`'s directory bind) can't produce this — see
+/// `AuthProvider::scram_credential`.
+#[derive(Debug, Clone)]
+pub struct ScramCredential {
+    pub salt: Vec<u8>,
+    pub iterations: u32,
+    pub stored_key: [u8; 32],
+    pub server_key: [u8; 32],
+}
+
+impl ScramCredential {
+    fn derive(password: &str) -> Self {
+        let mut salt = vec![0u8; SCRAM_SALT_LEN];
+        OsRng.fill_bytes(&mut salt);
+
+        let mut salted_password = [0u8; 32];
+        pbkdf2::pbkdf2_hmac::<Sha256>(password.as_bytes(), &salt, SCRAM_ITERATIONS, &mut salted_password);
+
+        let client_key = hmac_sha256(&salted_password, b"Client Key");
+        let stored_key = sha256(&client_key);
+        let server_key = hmac_sha256(&salted_password, b"Server Key");
+
+        Self {
+            salt,
+            iterations: SCRAM_ITERATIONS,
+            stored_key,
+            server_key,
+        }
+    }
+}
+
+pub(crate) fn hmac_sha256(key: &[u8], data: &[u8]) -> [u8; 32] {
+    let mut mac = Hmac::<Sha256>::new_from_slice(key).expect("HMAC accepts a key of any length");
+    mac.update(data);
+    mac.finalize().into_bytes().into()
+}
+
+pub(crate) fn sha256(data: &[u8]) -> [u8; 32] {
+    let mut hasher = Sha256::new();
+    hasher.update(data);
+    hasher.finalize().into()
+}
+
+/// Tuned for an interactive login path rather than maximum hardness: ~19 MiB
+/// of memory, 2 iterations, single-threaded — the same parameters Aerogramme
+/// uses for its own user profiles.
+const ARGON2_MEMORY_COST_KIB: u32 = 19 * 1024;
+const ARGON2_ITERATIONS: u32 = 2;
+const ARGON2_PARALLELISM: u32 = 1;
+
+fn argon2id() -> Argon2<'static> {
+    let params = Params::new(ARGON2_MEMORY_COST_KIB, ARGON2_ITERATIONS, ARGON2_PARALLELISM, None)
+        .expect("hardcoded Argon2id parameters are always valid");
+    Argon2::new(Algorithm::Argon2id, Version::V0x13, params)
+}
+
+#[derive(Debug, Clone)]
+enum StoredHash {
+    /// A PHC-format Argon2id hash, e.g. `$argon2id$v=19$m=19456,t=2,p=1$<salt>$<hash>`.
+    Argon2id(String),
+    /// A pre-migration unsalted SHA-256 digest, kept only long enough to be
+    /// verified once and replaced by `InMemoryAuthProvider::authenticate`.
+    LegacySha256(Vec<u8>),
+}
 
 #[derive(Debug, Clone)]
 pub struct Credentials {
     pub username: String,
-    pub password_hash: Vec<u8>,
+    hash: StoredHash,
+    /// Only set when this `Credentials` was built from a plaintext password
+    /// on this process (`new`); see `ScramCredential`'s doc comment.
+    scram: Option<ScramCredential>,
 }
 
 impl Credentials {
     pub fn new(username: String, password: &str) -> Self {
-        let mut hasher = Sha256::new();
-        hasher.update(password.as_bytes());
-        let password_hash = hasher.finalize().to_vec();
+        Self {
+            username,
+            hash: StoredHash::Argon2id(Self::hash_argon2id(password)),
+            scram: Some(ScramCredential::derive(password)),
+        }
+    }
+
+    /// Wraps an already-computed PHC-format Argon2id hash, for callers
+    /// loading credentials that were hashed ahead of time (e.g. a static
+    /// users file) rather than from a plaintext password on this process.
+    pub fn from_argon2id_phc(username: String, phc: String) -> Self {
+        Self {
+            username,
+            hash: StoredHash::Argon2id(phc),
+            scram: None,
+        }
+    }
 
+    /// Wraps a digest produced by the old unsalted-SHA-256 scheme, for
+    /// callers restoring credentials written before this migration.
+    /// `InMemoryAuthProvider::authenticate` replaces it with a salted
+    /// Argon2id hash the next time this user logs in successfully.
+    pub fn from_legacy_sha256(username: String, digest: Vec<u8>) -> Self {
         Self {
             username,
-            password_hash,
+            hash: StoredHash::LegacySha256(digest),
+            scram: None,
         }
     }
 
+    fn hash_argon2id(password: &str) -> String {
+        let salt = SaltString::generate(&mut OsRng);
+        argon2id()
+            .hash_password(password.as_bytes(), &salt)
+            .expect("hashing a password never fails for valid UTF-8 input")
+            .to_string()
+    }
+
     pub fn verify(&self, password: &str) -> bool {
-        let mut hasher = Sha256::new();
-        hasher.update(password.as_bytes());
-        let hash = hasher.finalize().to_vec();
+        match &self.hash {
+            StoredHash::Argon2id(phc) => PasswordHash::new(phc)
+                .ok()
+                .map(|parsed| argon2id().verify_password(password.as_bytes(), &parsed).is_ok())
+                .unwrap_or(false),
+            StoredHash::LegacySha256(digest) => {
+                let mut hasher = Sha256::new();
+                hasher.update(password.as_bytes());
+                hasher.finalize().as_slice() == digest.as_slice()
+            }
+        }
+    }
 
-        self.password_hash == hash
+    /// True once `verify` has succeeded against a `LegacySha256` hash, so
+    /// the caller knows to call `rehash` and persist the upgraded hash.
+    pub fn needs_rehash(&self) -> bool {
+        matches!(self.hash, StoredHash::LegacySha256(_))
+    }
+
+    pub fn rehash(&mut self, password: &str) {
+        self.hash = StoredHash::Argon2id(Self::hash_argon2id(password));
+        self.scram = Some(ScramCredential::derive(password));
+    }
+
+    pub fn scram_credential(&self) -> Option<&ScramCredential> {
+        self.scram.as_ref()
     }
 }
 
-pub struct AuthManager {
+/// The authenticated principal handed back by an `AuthProvider`. Kept
+/// separate from `Credentials` since providers like LDAP never see (or
+/// store) a password hash at all.
+#[derive(Debug, Clone)]
+pub struct Identity {
+    pub username: String,
+}
+
+type AuthFuture<'a, T> = Pin<Box<dyn Future<Output = Result<T>> + Send + 'a>>;
+
+/// A source of user identities an `AuthManager` can authenticate and look up
+/// against, mirroring `storage::TableProvider`'s boxed-future shape so
+/// implementations backed by blocking I/O (file reads, LDAP binds) don't
+/// need an extra async-trait dependency just to implement this.
+pub trait AuthProvider: Send + Sync {
+    fn authenticate<'a>(&'a self, username: &'a str, password: &'a str) -> AuthFuture<'a, Identity>;
+    fn lookup<'a>(&'a self, username: &'a str) -> AuthFuture<'a, Identity>;
+
+    /// Returns the SCRAM-SHA-256 credential for `username`, if this provider
+    /// can produce one. Defaults to `None`; only `InMemoryAuthProvider`
+    /// overrides it, since it's the only provider that ever sees a plaintext
+    /// password to derive one from.
+    fn scram_credential<'a>(&'a self, _username: &'a str) -> AuthFuture<'a, Option<ScramCredential>> {
+        Box::pin(async move { Ok(None) })
+    }
+}
+
+/// The current behavior: an in-process `DashMap` of `Credentials`, seeded
+/// with a baked-in `admin/admin` user.
+pub struct InMemoryAuthProvider {
     users: dashmap::DashMap<String, Credentials>,
 }
 
-impl AuthManager {
+impl InMemoryAuthProvider {
     pub fn new() -> Self {
-        let manager = Self {
+        let provider = Self {
             users: dashmap::DashMap::new(),
         };
 
-        manager.users.insert(
+        provider.users.insert(
             "admin".to_string(),
             Credentials::new("admin".to_string(), "admin"),
         );
 
-        manager
-    }
-
-    pub fn authenticate(&self, username: &str, password: &str) -> Result<()> {
-        let entry = self.users.get(username);
-
-        match entry {
-            Some(creds) => {
-                if creds.verify(password) {
-                    Ok(())
-                } else {
-                    anyhow::bail!("Invalid password")
-                }
-            }
-            None => anyhow::bail!("User not found"),
-        }
+        provider
     }
 
     pub fn add_user(&self, username: String, password: &str) -> Result<()> {
@@ -71,3 +218,297 @@ impl AuthManager {
         Ok(())
     }
 }
+
+impl AuthProvider for InMemoryAuthProvider {
+    fn authenticate<'a>(&'a self, username: &'a str, password: &'a str) -> AuthFuture<'a, Identity> {
+        Box::pin(async move {
+            let mut entry = match self.users.get_mut(username) {
+                Some(entry) => entry,
+                None => anyhow::bail!("User not found"),
+            };
+
+            if !entry.verify(password) {
+                anyhow::bail!("Invalid password");
+            }
+
+            if entry.needs_rehash() {
+                entry.rehash(password);
+            }
+
+            Ok(Identity {
+                username: username.to_string(),
+            })
+        })
+    }
+
+    fn lookup<'a>(&'a self, username: &'a str) -> AuthFuture<'a, Identity> {
+        Box::pin(async move {
+            self.users
+                .get(username)
+                .map(|_| Identity {
+                    username: username.to_string(),
+                })
+                .ok_or_else(|| anyhow::anyhow!("User not found"))
+        })
+    }
+
+    fn scram_credential<'a>(&'a self, username: &'a str) -> AuthFuture<'a, Option<ScramCredential>> {
+        Box::pin(async move {
+            Ok(self
+                .users
+                .get(username)
+                .and_then(|entry| entry.scram_credential().cloned()))
+        })
+    }
+}
+
+#[derive(Debug, Deserialize)]
+struct StaticUserEntry {
+    username: String,
+    argon2_hash: String,
+}
+
+#[derive(Debug, Default, Deserialize)]
+struct StaticUsersFile {
+    #[serde(default)]
+    users: Vec<StaticUserEntry>,
+}
+
+/// Loads a fixed set of users and their pre-computed Argon2id hashes from a
+/// TOML or JSON file at startup. Read-only: unlike `InMemoryAuthProvider`,
+/// there's no in-process `add_user`, since the file on disk is the source
+/// of truth and this provider never writes back to it.
+pub struct StaticFileAuthProvider {
+    users: HashMap<String, Credentials>,
+}
+
+impl StaticFileAuthProvider {
+    pub fn from_path(path: &str) -> Result<Self> {
+        let contents = std::fs::read_to_string(path)
+            .with_context(|| format!("Failed to read auth users file: {}", path))?;
+
+        let file: StaticUsersFile = if path.ends_with(".json") {
+            serde_json::from_str(&contents)
+                .with_context(|| format!("Failed to parse auth users file: {}", path))?
+        } else {
+            toml::from_str(&contents)
+                .with_context(|| format!("Failed to parse auth users file: {}", path))?
+        };
+
+        let users = file
+            .users
+            .into_iter()
+            .map(|entry| {
+                let credentials =
+                    Credentials::from_argon2id_phc(entry.username.clone(), entry.argon2_hash);
+                (entry.username, credentials)
+            })
+            .collect();
+
+        Ok(Self { users })
+    }
+}
+
+impl AuthProvider for StaticFileAuthProvider {
+    fn authenticate<'a>(&'a self, username: &'a str, password: &'a str) -> AuthFuture<'a, Identity> {
+        Box::pin(async move {
+            let credentials = self
+                .users
+                .get(username)
+                .ok_or_else(|| anyhow::anyhow!("User not found"))?;
+
+            if !credentials.verify(password) {
+                anyhow::bail!("Invalid password");
+            }
+
+            Ok(Identity {
+                username: username.to_string(),
+            })
+        })
+    }
+
+    fn lookup<'a>(&'a self, username: &'a str) -> AuthFuture<'a, Identity> {
+        Box::pin(async move {
+            self.users
+                .get(username)
+                .map(|_| Identity {
+                    username: username.to_string(),
+                })
+                .ok_or_else(|| anyhow::anyhow!("User not found"))
+        })
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct LdapAuthProviderConfig {
+    pub url: String,
+    pub search_base: String,
+    pub search_filter: String,
+    pub service_bind_dn: Option<String>,
+    pub service_bind_password: Option<String>,
+}
+
+/// Authenticates against an existing directory: binds (optionally as a
+/// service account) to search for the user's DN, then rebinds as that DN
+/// with the supplied password to verify it. The directory never discloses
+/// a password hash to us; a successful rebind *is* the proof.
+pub struct LdapAuthProvider {
+    config: LdapAuthProviderConfig,
+}
+
+impl LdapAuthProvider {
+    pub fn new(config: LdapAuthProviderConfig) -> Self {
+        Self { config }
+    }
+
+    async fn resolve_dn(&self, username: &str) -> Result<String> {
+        let (conn, mut ldap) = ldap3::LdapConnAsync::new(&self.config.url).await?;
+        ldap3::drive!(conn);
+
+        if let (Some(bind_dn), Some(bind_password)) =
+            (&self.config.service_bind_dn, &self.config.service_bind_password)
+        {
+            ldap.simple_bind(bind_dn, bind_password)
+                .await?
+                .success()
+                .context("LDAP service bind failed")?;
+        }
+
+        // `username` is attacker-controlled input landing inside an LDAP
+        // search filter; without RFC 4515 escaping a value like
+        // `*)(uid=*))(|(uid=*` can rewrite the filter's boolean structure
+        // (classic LDAP injection) rather than just matching a username.
+        let filter = self
+            .config
+            .search_filter
+            .replace("{username}", &ldap3::ldap_escape(username));
+        let (results, _) = ldap
+            .search(&self.config.search_base, ldap3::Scope::Subtree, &filter, vec!["dn"])
+            .await?
+            .success()?;
+
+        let entry = results
+            .into_iter()
+            .next()
+            .ok_or_else(|| anyhow::anyhow!("User not found in directory: {}", username))?;
+
+        let _ = ldap.unbind().await;
+
+        Ok(ldap3::SearchEntry::construct(entry).dn)
+    }
+}
+
+impl AuthProvider for LdapAuthProvider {
+    fn authenticate<'a>(&'a self, username: &'a str, password: &'a str) -> AuthFuture<'a, Identity> {
+        Box::pin(async move {
+            let dn = self.resolve_dn(username).await?;
+
+            let (conn, mut ldap) = ldap3::LdapConnAsync::new(&self.config.url).await?;
+            ldap3::drive!(conn);
+
+            ldap.simple_bind(&dn, password)
+                .await?
+                .success()
+                .context("LDAP bind failed: invalid credentials")?;
+            let _ = ldap.unbind().await;
+
+            Ok(Identity {
+                username: username.to_string(),
+            })
+        })
+    }
+
+    fn lookup<'a>(&'a self, username: &'a str) -> AuthFuture<'a, Identity> {
+        Box::pin(async move {
+            self.resolve_dn(username).await?;
+            Ok(Identity {
+                username: username.to_string(),
+            })
+        })
+    }
+}
+
+/// Selects which `AuthProvider` backs an `AuthManager`. Set via the `auth`
+/// section of the node config, so operators can back MinSQL auth with an
+/// existing directory without a rebuild.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "provider", rename_all = "kebab-case")]
+pub enum AuthProviderConfig {
+    Memory,
+    StaticFile {
+        path: String,
+    },
+    Ldap {
+        url: String,
+        search_base: String,
+        #[serde(default = "default_ldap_search_filter")]
+        search_filter: String,
+        #[serde(default)]
+        service_bind_dn: Option<String>,
+        #[serde(default)]
+        service_bind_password: Option<String>,
+    },
+}
+
+impl Default for AuthProviderConfig {
+    fn default() -> Self {
+        AuthProviderConfig::Memory
+    }
+}
+
+fn default_ldap_search_filter() -> String {
+    "(uid={username})".to_string()
+}
+
+pub struct AuthManager {
+    provider: Box<dyn AuthProvider>,
+}
+
+impl AuthManager {
+    pub fn new() -> Self {
+        Self {
+            provider: Box::new(InMemoryAuthProvider::new()),
+        }
+    }
+
+    pub fn with_provider(provider: Box<dyn AuthProvider>) -> Self {
+        Self { provider }
+    }
+
+    pub fn from_config(config: &AuthProviderConfig) -> Result<Self> {
+        let provider: Box<dyn AuthProvider> = match config {
+            AuthProviderConfig::Memory => Box::new(InMemoryAuthProvider::new()),
+            AuthProviderConfig::StaticFile { path } => Box::new(StaticFileAuthProvider::from_path(path)?),
+            AuthProviderConfig::Ldap {
+                url,
+                search_base,
+                search_filter,
+                service_bind_dn,
+                service_bind_password,
+            } => Box::new(LdapAuthProvider::new(LdapAuthProviderConfig {
+                url: url.clone(),
+                search_base: search_base.clone(),
+                search_filter: search_filter.clone(),
+                service_bind_dn: service_bind_dn.clone(),
+                service_bind_password: service_bind_password.clone(),
+            })),
+        };
+
+        Ok(Self { provider })
+    }
+
+    pub async fn authenticate(&self, username: &str, password: &str) -> Result<Identity> {
+        self.provider.authenticate(username, password).await
+    }
+
+    pub async fn lookup(&self, username: &str) -> Result<Identity> {
+        self.provider.lookup(username).await
+    }
+
+    pub async fn scram_credential(&self, username: &str) -> Result<ScramCredential> {
+        self.provider
+            .scram_credential(username)
+            .await?
+            .ok_or_else(|| anyhow::anyhow!("SCRAM-SHA-256 is not available for user: {}", username))
+    }
+}
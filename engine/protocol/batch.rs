@@ -0,0 +1,33 @@
+use crate::replication::consensus::BatchItemResult;
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub enum BatchOp {
+    Query,
+    Execute,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BatchItem {
+    pub op: BatchOp,
+    pub sql: String,
+}
+
+/// Payload of a `MessageType::Batch` frame: an ordered list of `query`/
+/// `execute` items to run as one round-trip instead of one `Frame` per
+/// statement.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BatchRequest {
+    pub items: Vec<BatchItem>,
+    /// `true` aborts the remainder of the batch after the first item fails,
+    /// reporting every later item as skipped rather than attempting it;
+    /// `false` runs every item regardless of earlier failures.
+    pub stop_on_error: bool,
+}
+
+/// Payload of the `MessageType::BatchResponse` frame sent in reply: one
+/// result per `BatchRequest` item, in the same order.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BatchResponse {
+    pub results: Vec<BatchItemResult>,
+}
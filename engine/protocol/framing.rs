@@ -1,8 +1,21 @@
+use crate::execution::tuple::Tuple;
 use anyhow::{Context, Result};
 use bytes::{Buf, BufMut, BytesMut};
+use serde::{Deserialize, Serialize};
 use tokio::io::{AsyncReadExt, AsyncWriteExt};
 use tokio::net::TcpStream;
 
+/// Rows per `ResultChunk` frame in `FrameStream::write_result`. Bounds a
+/// single frame's payload by row count, not by bytes: a chunk of rows wide
+/// enough to exceed `MAX_FRAME_LEN` still goes out as one frame, which
+/// `Frame::read_from` then rejects.
+const RESULT_CHUNK_ROWS: usize = 1000;
+
+/// Per-frame size cap, kept regardless of streaming — it's DoS protection
+/// against a single oversized frame, not a limit on a whole result, which
+/// `FrameStream` instead bounds by spreading rows across many frames.
+const MAX_FRAME_LEN: u32 = 100 * 1024 * 1024;
+
 #[derive(Debug, Clone, PartialEq)]
 pub enum MessageType {
     Query = 1,
@@ -10,6 +23,22 @@ pub enum MessageType {
     Error = 3,
     Execute = 4,
     ExecuteResponse = 5,
+    RequestVote = 6,
+    RequestVoteResponse = 7,
+    AppendEntries = 8,
+    AppendEntriesResponse = 9,
+    AuthStart = 10,
+    AuthChallenge = 11,
+    AuthResponse = 12,
+    AuthResult = 13,
+    Batch = 14,
+    BatchResponse = 15,
+    /// One bounded batch of a streamed query result's rows. Zero or more of
+    /// these precede a terminating `ResultEnd`.
+    ResultChunk = 16,
+    /// Terminates a `ResultChunk` sequence, carrying the total row count and
+    /// completion status (`ResultEndPayload`) rather than any row data.
+    ResultEnd = 17,
 }
 
 impl MessageType {
@@ -20,6 +49,18 @@ impl MessageType {
             3 => Ok(MessageType::Error),
             4 => Ok(MessageType::Execute),
             5 => Ok(MessageType::ExecuteResponse),
+            6 => Ok(MessageType::RequestVote),
+            7 => Ok(MessageType::RequestVoteResponse),
+            8 => Ok(MessageType::AppendEntries),
+            9 => Ok(MessageType::AppendEntriesResponse),
+            10 => Ok(MessageType::AuthStart),
+            11 => Ok(MessageType::AuthChallenge),
+            12 => Ok(MessageType::AuthResponse),
+            13 => Ok(MessageType::AuthResult),
+            14 => Ok(MessageType::Batch),
+            15 => Ok(MessageType::BatchResponse),
+            16 => Ok(MessageType::ResultChunk),
+            17 => Ok(MessageType::ResultEnd),
             _ => anyhow::bail!("Unknown message type: {}", val),
         }
     }
@@ -31,6 +72,18 @@ impl MessageType {
             MessageType::Error => 3,
             MessageType::Execute => 4,
             MessageType::ExecuteResponse => 5,
+            MessageType::RequestVote => 6,
+            MessageType::RequestVoteResponse => 7,
+            MessageType::AppendEntries => 8,
+            MessageType::AppendEntriesResponse => 9,
+            MessageType::AuthStart => 10,
+            MessageType::AuthChallenge => 11,
+            MessageType::AuthResponse => 12,
+            MessageType::AuthResult => 13,
+            MessageType::Batch => 14,
+            MessageType::BatchResponse => 15,
+            MessageType::ResultChunk => 16,
+            MessageType::ResultEnd => 17,
         }
     }
 }
@@ -59,8 +112,8 @@ impl Frame {
 
     pub async fn read_from(stream: &mut TcpStream) -> Result<Self> {
         let length = stream.read_u32().await.context("Failed to read frame length")?;
-        
-        if length == 0 || length > 100 * 1024 * 1024 {
+
+        if length == 0 || length > MAX_FRAME_LEN {
             anyhow::bail!("Invalid frame length: {}", length);
         }
 
@@ -83,3 +136,87 @@ impl Frame {
         Ok(())
     }
 }
+
+/// The payload of a terminating `ResultEnd` frame: how many rows the whole
+/// streamed result carried and whether it completed or failed partway
+/// through.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ResultEndPayload {
+    pub row_count: u64,
+    pub error: Option<String>,
+}
+
+/// Streams a query result as a sequence of bounded `ResultChunk` frames
+/// followed by a `ResultEnd`, so a huge result set never has to be buffered
+/// whole on the wire the way a single `QueryResponse` frame would force it
+/// to be. Wraps the same `TcpStream` `Frame::read_from`/`write_to` use.
+pub struct FrameStream<'a> {
+    stream: &'a mut TcpStream,
+}
+
+impl<'a> FrameStream<'a> {
+    pub fn new(stream: &'a mut TcpStream) -> Self {
+        Self { stream }
+    }
+
+    /// Writes `rows` as `ResultChunk` frames of `RESULT_CHUNK_ROWS` rows
+    /// each, then a `ResultEnd` carrying the total count.
+    pub async fn write_result(&mut self, rows: &[Tuple]) -> Result<()> {
+        for batch in rows.chunks(RESULT_CHUNK_ROWS) {
+            let payload = serde_json::to_vec(batch).context("Failed to serialize result chunk")?;
+            Frame::new(MessageType::ResultChunk, payload)
+                .write_to(self.stream)
+                .await?;
+        }
+
+        self.write_end(rows.len() as u64, None).await
+    }
+
+    /// Terminates the stream early with an error: any chunks already sent
+    /// stay valid, but `error` tells the reader the result is incomplete.
+    pub async fn write_error(&mut self, rows_sent: u64, error: String) -> Result<()> {
+        self.write_end(rows_sent, Some(error)).await
+    }
+
+    async fn write_end(&mut self, row_count: u64, error: Option<String>) -> Result<()> {
+        let payload = serde_json::to_vec(&ResultEndPayload { row_count, error })
+            .context("Failed to serialize result end")?;
+        Frame::new(MessageType::ResultEnd, payload).write_to(self.stream).await
+    }
+
+    /// Pulls the next chunk of rows, pull-based like `execution::ExecNode`:
+    /// `Ok(Some(rows))` for a `ResultChunk`, `Ok(None)` once the terminating
+    /// `ResultEnd` has been consumed (bubbling up its `error`, if any), and
+    /// `Err` for anything else on the wire where a result frame was
+    /// expected.
+    pub async fn next_chunk(&mut self) -> Result<Option<Vec<Tuple>>> {
+        let frame = Frame::read_from(self.stream).await?;
+
+        match frame.message_type {
+            MessageType::ResultChunk => {
+                let rows: Vec<Tuple> =
+                    serde_json::from_slice(&frame.payload).context("Failed to parse result chunk")?;
+                Ok(Some(rows))
+            }
+            MessageType::ResultEnd => {
+                let end: ResultEndPayload =
+                    serde_json::from_slice(&frame.payload).context("Failed to parse result end")?;
+                if let Some(error) = end.error {
+                    anyhow::bail!("Streamed result ended with an error: {}", error);
+                }
+                Ok(None)
+            }
+            other => anyhow::bail!("Unexpected message type in result stream: {:?}", other),
+        }
+    }
+
+    /// Drains the whole stream into one `Vec`, for callers that don't need
+    /// incremental processing but still want the wire-level chunking.
+    pub async fn collect(&mut self) -> Result<Vec<Tuple>> {
+        let mut rows = Vec::new();
+        while let Some(chunk) = self.next_chunk().await? {
+            rows.extend(chunk);
+        }
+        Ok(rows)
+    }
+}
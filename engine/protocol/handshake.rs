@@ -104,6 +104,30 @@ impl HandshakeResponse {
     }
 }
 
+/// Client-side counterpart to `perform_handshake`: dials `addr`, sends a
+/// handshake request identifying this side as `client_name`, and returns the
+/// connected stream once the server's response has been read. Used for
+/// inter-node RPCs (e.g. Raft's `RequestVote`/`AppendEntries`) that reuse the
+/// same connection/handshake protocol query clients speak.
+pub async fn open(addr: &str, client_name: String) -> Result<(TcpStream, HandshakeResponse)> {
+    let mut stream = TcpStream::connect(addr)
+        .await
+        .with_context(|| format!("failed to connect to {}", addr))?;
+
+    let request = HandshakeRequest::new(client_name);
+    stream.write_all(&request.encode()).await?;
+
+    let mut buf = vec![0u8; 1024];
+    let n = stream.read(&mut buf).await.context("Failed to read handshake response")?;
+
+    if n == 0 {
+        anyhow::bail!("Connection closed during handshake");
+    }
+
+    let response = HandshakeResponse::decode(&buf[..n])?;
+    Ok((stream, response))
+}
+
 pub async fn perform_handshake(stream: &mut TcpStream, node_id: u32) -> Result<HandshakeRequest> {
     let mut buf = vec![0u8; 1024];
     let n = stream.read(&mut buf).await.context("Failed to read handshake")?;
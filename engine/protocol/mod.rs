@@ -1,9 +1,13 @@
 pub mod handshake;
 pub mod framing;
 pub mod auth;
+pub mod sasl;
+pub mod batch;
 pub mod server;
 
 pub use handshake::*;
 pub use framing::*;
 pub use auth::*;
+pub use sasl::*;
+pub use batch::*;
 pub use server::*;
@@ -0,0 +1,254 @@
+use crate::protocol::auth::{hmac_sha256, sha256, AuthManager, Identity};
+use crate::protocol::framing::{Frame, MessageType};
+use anyhow::{Context, Result};
+use base64::Engine;
+use rand::distributions::Alphanumeric;
+use rand::Rng;
+use serde::{Deserialize, Serialize};
+use tokio::net::TcpStream;
+
+/// Mechanisms advertised during the handshake, in server preference order.
+pub const MECHANISMS: &[&str] = &["SCRAM-SHA-256", "PLAIN", "LOGIN"];
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct AuthStartMessage {
+    pub mechanism: String,
+    #[serde(default)]
+    pub initial_response: String,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct AuthChallengeMessage {
+    pub data: String,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct AuthResponseMessage {
+    pub data: String,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct AuthResultMessage {
+    pub success: bool,
+    pub username: Option<String>,
+    /// The SCRAM server-final message (`v=<signature>`), when the
+    /// negotiated mechanism produces one.
+    pub data: Option<String>,
+    pub error: Option<String>,
+}
+
+/// Drives one SASL exchange to completion: reads the client's `AuthStart`,
+/// negotiates the mechanism it named, and writes the final `AuthResult`.
+/// Returns the authenticated `Identity` on success, or the error that was
+/// also reported to the client on failure.
+pub async fn negotiate(stream: &mut TcpStream, auth: &AuthManager) -> Result<Identity> {
+    let frame = Frame::read_from(stream).await?;
+    if frame.message_type != MessageType::AuthStart {
+        anyhow::bail!(
+            "Expected AuthStart to begin the SASL exchange, got {:?}",
+            frame.message_type
+        );
+    }
+    let start: AuthStartMessage = serde_json::from_slice(&frame.payload)?;
+
+    let outcome = match start.mechanism.as_str() {
+        "PLAIN" => plain(auth, &start.initial_response)
+            .await
+            .map(|identity| (identity, None)),
+        "LOGIN" => login(stream, auth).await.map(|identity| (identity, None)),
+        "SCRAM-SHA-256" => scram_sha256(stream, auth, &start.initial_response).await,
+        other => Err(anyhow::anyhow!("Unsupported SASL mechanism: {}", other)),
+    };
+
+    match outcome {
+        Ok((identity, data)) => {
+            let result = AuthResultMessage {
+                success: true,
+                username: Some(identity.username.clone()),
+                data,
+                error: None,
+            };
+            Frame::new(MessageType::AuthResult, serde_json::to_vec(&result)?)
+                .write_to(stream)
+                .await?;
+            Ok(identity)
+        }
+        Err(e) => {
+            let result = AuthResultMessage {
+                success: false,
+                username: None,
+                data: None,
+                error: Some(e.to_string()),
+            };
+            Frame::new(MessageType::AuthResult, serde_json::to_vec(&result)?)
+                .write_to(stream)
+                .await?;
+            Err(e)
+        }
+    }
+}
+
+/// RFC 4616: a single message of `[authzid] NUL authcid NUL passwd`. No
+/// further round trip is needed, so the whole thing rides in `AuthStart`.
+async fn plain(auth: &AuthManager, initial_response: &str) -> Result<Identity> {
+    let parts: Vec<&str> = initial_response.split('\0').collect();
+    let (authcid, password) = match parts.as_slice() {
+        [_authzid, authcid, password] => (*authcid, *password),
+        [authcid, password] => (*authcid, *password),
+        _ => anyhow::bail!("Malformed PLAIN response"),
+    };
+
+    auth.authenticate(authcid, password).await
+}
+
+/// The classic two-challenge LOGIN mechanism: the server prompts for a
+/// username, then a password, each as its own `AuthChallenge`/`AuthResponse`
+/// round trip.
+async fn login(stream: &mut TcpStream, auth: &AuthManager) -> Result<Identity> {
+    let username = challenge_and_read(stream, "Username:").await?;
+    let password = challenge_and_read(stream, "Password:").await?;
+    auth.authenticate(&username, &password).await
+}
+
+async fn challenge_and_read(stream: &mut TcpStream, prompt: &str) -> Result<String> {
+    let challenge = AuthChallengeMessage {
+        data: prompt.to_string(),
+    };
+    Frame::new(MessageType::AuthChallenge, serde_json::to_vec(&challenge)?)
+        .write_to(stream)
+        .await?;
+
+    let frame = Frame::read_from(stream).await?;
+    if frame.message_type != MessageType::AuthResponse {
+        anyhow::bail!("Expected AuthResponse, got {:?}", frame.message_type);
+    }
+
+    let response: AuthResponseMessage = serde_json::from_slice(&frame.payload)?;
+    Ok(response.data)
+}
+
+/// RFC 5802's client-first/server-first/client-final/server-final dance.
+/// This protocol has no TLS-layer channel binding to negotiate, so the gs2
+/// header is always the no-binding `n,,` and the client's bare first
+/// message (without that header) is what arrives as `AuthStart`'s
+/// `initial_response`.
+async fn scram_sha256(
+    stream: &mut TcpStream,
+    auth: &AuthManager,
+    client_first_bare: &str,
+) -> Result<(Identity, Option<String>)> {
+    let (username, client_nonce) = parse_client_first(client_first_bare)?;
+
+    let credential = auth
+        .scram_credential(&username)
+        .await
+        .context("SCRAM-SHA-256 is not available for this user")?;
+
+    let server_nonce = format!("{}{}", client_nonce, random_nonce_suffix());
+    let salt_b64 = base64::engine::general_purpose::STANDARD.encode(&credential.salt);
+    let server_first = format!("r={},s={},i={}", server_nonce, salt_b64, credential.iterations);
+
+    Frame::new(
+        MessageType::AuthChallenge,
+        serde_json::to_vec(&AuthChallengeMessage {
+            data: server_first.clone(),
+        })?,
+    )
+    .write_to(stream)
+    .await?;
+
+    let frame = Frame::read_from(stream).await?;
+    if frame.message_type != MessageType::AuthResponse {
+        anyhow::bail!("Expected AuthResponse, got {:?}", frame.message_type);
+    }
+    let response: AuthResponseMessage = serde_json::from_slice(&frame.payload)?;
+    let (channel_binding, nonce, proof_b64) = parse_client_final(&response.data)?;
+
+    if nonce != server_nonce {
+        anyhow::bail!("SCRAM nonce mismatch");
+    }
+    if channel_binding != base64::engine::general_purpose::STANDARD.encode("n,,") {
+        anyhow::bail!("SCRAM channel binding mismatch");
+    }
+
+    let client_final_without_proof = format!("c={},r={}", channel_binding, nonce);
+    let auth_message = format!(
+        "{},{},{}",
+        client_first_bare, server_first, client_final_without_proof
+    );
+
+    let client_proof = base64::engine::general_purpose::STANDARD
+        .decode(&proof_b64)
+        .context("Invalid base64 in SCRAM client proof")?;
+    let client_signature = hmac_sha256(&credential.stored_key, auth_message.as_bytes());
+
+    if client_proof.len() != client_signature.len() {
+        anyhow::bail!("Invalid password");
+    }
+    let recovered_client_key: Vec<u8> = client_proof
+        .iter()
+        .zip(client_signature.iter())
+        .map(|(p, s)| p ^ s)
+        .collect();
+
+    if sha256(&recovered_client_key) != credential.stored_key {
+        anyhow::bail!("Invalid password");
+    }
+
+    let server_signature = hmac_sha256(&credential.server_key, auth_message.as_bytes());
+    let server_final = format!(
+        "v={}",
+        base64::engine::general_purpose::STANDARD.encode(server_signature)
+    );
+
+    Ok((Identity { username }, Some(server_final)))
+}
+
+fn random_nonce_suffix() -> String {
+    rand::thread_rng()
+        .sample_iter(&Alphanumeric)
+        .take(24)
+        .map(char::from)
+        .collect()
+}
+
+fn parse_client_first(msg: &str) -> Result<(String, String)> {
+    let mut username = None;
+    let mut nonce = None;
+
+    for part in msg.split(',') {
+        if let Some(rest) = part.strip_prefix("n=") {
+            username = Some(rest.to_string());
+        } else if let Some(rest) = part.strip_prefix("r=") {
+            nonce = Some(rest.to_string());
+        }
+    }
+
+    Ok((
+        username.ok_or_else(|| anyhow::anyhow!("SCRAM client-first is missing the username"))?,
+        nonce.ok_or_else(|| anyhow::anyhow!("SCRAM client-first is missing the nonce"))?,
+    ))
+}
+
+fn parse_client_final(msg: &str) -> Result<(String, String, String)> {
+    let mut channel_binding = None;
+    let mut nonce = None;
+    let mut proof = None;
+
+    for part in msg.split(',') {
+        if let Some(rest) = part.strip_prefix("c=") {
+            channel_binding = Some(rest.to_string());
+        } else if let Some(rest) = part.strip_prefix("r=") {
+            nonce = Some(rest.to_string());
+        } else if let Some(rest) = part.strip_prefix("p=") {
+            proof = Some(rest.to_string());
+        }
+    }
+
+    Ok((
+        channel_binding
+            .ok_or_else(|| anyhow::anyhow!("SCRAM client-final is missing the channel binding"))?,
+        nonce.ok_or_else(|| anyhow::anyhow!("SCRAM client-final is missing the nonce"))?,
+        proof.ok_or_else(|| anyhow::anyhow!("SCRAM client-final is missing the proof"))?,
+    ))
+}
@@ -1,41 +1,101 @@
-use crate::ffi::storage::StorageEngine;
+use crate::execution::sandbox::{QueryLimits, Sandbox};
+use crate::language::catalog::Catalog;
 use crate::language::parser::Parser;
 use crate::planner::logical::LogicalPlanner;
 use crate::planner::physical::PhysicalPlanner;
 use crate::execution::engine::ExecutionEngine;
-use crate::replication::consensus::RaftNode;
+use crate::replication::consensus::{AppendEntriesRequest, BatchItemResult, RaftNode, RequestVoteRequest};
 use crate::telemetry::metrics::MetricsRegistry;
-use crate::protocol::{handshake, Frame, MessageType};
+use crate::protocol::auth::AuthManager;
+use crate::protocol::{handshake, sasl, BatchOp, BatchRequest, BatchResponse, Frame, MessageType};
+use crate::security::audit_log::AuditLogger;
+use crate::storage::StorageBackend;
 use anyhow::Result;
+use std::collections::HashMap;
+use std::net::SocketAddr;
+use std::sync::atomic::{AtomicU64, Ordering};
 use std::sync::Arc;
 use tokio::net::{TcpListener, TcpStream};
+use tokio::sync::RwLock;
+
+/// Tracks the sandbox of every in-flight query by a server-assigned id, so
+/// an admin interface can look one up and call `Sandbox::cancel` on it
+/// without having to thread a handle through the connection that started
+/// the query.
+#[derive(Default)]
+struct QueryRegistry {
+    next_id: AtomicU64,
+    sandboxes: RwLock<HashMap<u64, Sandbox>>,
+}
+
+impl QueryRegistry {
+    async fn register(&self, sandbox: Sandbox) -> u64 {
+        let id = self.next_id.fetch_add(1, Ordering::Relaxed);
+        self.sandboxes.write().await.insert(id, sandbox);
+        id
+    }
+
+    async fn complete(&self, id: u64) {
+        self.sandboxes.write().await.remove(&id);
+    }
+
+    /// Cancels the query `id` if it's still running. Returns `false` if it
+    /// already finished (or never existed), which an admin caller can treat
+    /// as "nothing to do" rather than an error.
+    pub async fn cancel(&self, id: u64) -> bool {
+        match self.sandboxes.read().await.get(&id) {
+            Some(sandbox) => {
+                sandbox.cancel();
+                true
+            }
+            None => false,
+        }
+    }
+}
 
 pub struct Server {
     port: u16,
-    storage: Arc<StorageEngine>,
+    storage: Arc<dyn StorageBackend>,
     raft_node: Arc<RaftNode>,
     metrics: Arc<MetricsRegistry>,
+    catalog: Arc<RwLock<Catalog>>,
+    queries: Arc<QueryRegistry>,
+    auth: Arc<AuthManager>,
+    audit: Arc<AuditLogger>,
 }
 
 impl Server {
     pub fn new(
         port: u16,
-        storage: Arc<StorageEngine>,
+        storage: Arc<dyn StorageBackend>,
         raft_node: Arc<RaftNode>,
         metrics: Arc<MetricsRegistry>,
+        catalog: Arc<RwLock<Catalog>>,
+        auth: Arc<AuthManager>,
     ) -> Result<Self> {
         Ok(Self {
             port,
             storage,
             raft_node,
             metrics,
+            catalog,
+            queries: Arc::new(QueryRegistry::default()),
+            auth,
+            audit: Arc::new(AuditLogger::new()),
         })
     }
 
+    /// Cancels an in-flight query by the id `Server` assigned it. Intended
+    /// for an admin interface; the protocol clients speak today has no wire
+    /// message for this yet.
+    pub async fn cancel_query(&self, id: u64) -> bool {
+        self.queries.cancel(id).await
+    }
+
     pub async fn serve(self) -> Result<()> {
         let addr = format!("0.0.0.0:{}", self.port);
         let listener = TcpListener::bind(&addr).await?;
-        
+
         tracing::info!("Server listening on {}", addr);
 
         loop {
@@ -45,9 +105,17 @@ impl Server {
             let storage = self.storage.clone();
             let raft_node = self.raft_node.clone();
             let metrics = self.metrics.clone();
+            let catalog = self.catalog.clone();
+            let queries = self.queries.clone();
+            let auth = self.auth.clone();
+            let audit = self.audit.clone();
 
             tokio::spawn(async move {
-                if let Err(e) = handle_connection(stream, storage, raft_node, metrics).await {
+                if let Err(e) = handle_connection(
+                    stream, storage, raft_node, metrics, catalog, queries, auth, audit, peer_addr,
+                )
+                .await
+                {
                     tracing::error!("Connection error: {}", e);
                 }
             });
@@ -57,22 +125,59 @@ impl Server {
 
 async fn handle_connection(
     mut stream: TcpStream,
-    storage: Arc<StorageEngine>,
+    storage: Arc<dyn StorageBackend>,
     raft_node: Arc<RaftNode>,
     metrics: Arc<MetricsRegistry>,
+    catalog: Arc<RwLock<Catalog>>,
+    queries: Arc<QueryRegistry>,
+    auth: Arc<AuthManager>,
+    audit: Arc<AuditLogger>,
+    peer_addr: SocketAddr,
 ) -> Result<()> {
     let _handshake_req = handshake::perform_handshake(&mut stream, raft_node.node_id()).await?;
 
+    let identity = match sasl::negotiate(&mut stream, &auth).await {
+        Ok(identity) => {
+            let _ = audit
+                .log_authentication(identity.username.clone(), true, Some(peer_addr.to_string()))
+                .await;
+            identity
+        }
+        Err(e) => {
+            let _ = audit
+                .log_authentication(String::new(), false, Some(peer_addr.to_string()))
+                .await;
+            return Err(e);
+        }
+    };
+
     loop {
         let frame = Frame::read_from(&mut stream).await?;
 
         match frame.message_type {
             MessageType::Query => {
                 let query_text = String::from_utf8(frame.payload)?;
-                
+
                 metrics.increment_queries();
 
-                let response = match execute_query(&query_text, &storage).await {
+                let sandbox = Sandbox::new(QueryLimits::default());
+                let query_id = queries.register(sandbox.clone()).await;
+
+                let started_at = std::time::Instant::now();
+                let result = execute_query(&query_text, &storage, &catalog, sandbox, &identity.username).await;
+                metrics.record_query_duration(started_at.elapsed().as_secs_f64());
+                queries.complete(query_id).await;
+
+                let _ = audit
+                    .log_query(
+                        identity.username.clone(),
+                        query_text,
+                        result.is_ok(),
+                        result.as_ref().err().map(|e| e.to_string()),
+                    )
+                    .await;
+
+                let response = match result {
                     Ok(result) => {
                         Frame::new(MessageType::QueryResponse, serde_json::to_vec(&result)?)
                     }
@@ -85,10 +190,21 @@ async fn handle_connection(
             }
             MessageType::Execute => {
                 let statement = String::from_utf8(frame.payload)?;
-                
+
                 metrics.increment_executions();
 
-                let response = match execute_statement(&statement, &storage, &raft_node).await {
+                let result = execute_statement(&statement, &storage, &raft_node).await;
+
+                let _ = audit
+                    .log_query(
+                        identity.username.clone(),
+                        statement,
+                        result.is_ok(),
+                        result.as_ref().err().map(|e| e.to_string()),
+                    )
+                    .await;
+
+                let response = match result {
                     Ok(()) => {
                         Frame::new(MessageType::ExecuteResponse, b"OK".to_vec())
                     }
@@ -99,6 +215,37 @@ async fn handle_connection(
 
                 response.write_to(&mut stream).await?;
             }
+            MessageType::Batch => {
+                let request: BatchRequest = serde_json::from_slice(&frame.payload)?;
+
+                let results = execute_batch(
+                    &request,
+                    &storage,
+                    &catalog,
+                    &raft_node,
+                    &audit,
+                    &identity.username,
+                )
+                .await;
+
+                let response = Frame::new(
+                    MessageType::BatchResponse,
+                    serde_json::to_vec(&BatchResponse { results })?,
+                );
+                response.write_to(&mut stream).await?;
+            }
+            MessageType::RequestVote => {
+                let request: RequestVoteRequest = serde_json::from_slice(&frame.payload)?;
+                let raft_response = raft_node.handle_request_vote(request).await;
+                let response = Frame::new(MessageType::RequestVoteResponse, serde_json::to_vec(&raft_response)?);
+                response.write_to(&mut stream).await?;
+            }
+            MessageType::AppendEntries => {
+                let request: AppendEntriesRequest = serde_json::from_slice(&frame.payload)?;
+                let raft_response = raft_node.handle_append_entries(request).await;
+                let response = Frame::new(MessageType::AppendEntriesResponse, serde_json::to_vec(&raft_response)?);
+                response.write_to(&mut stream).await?;
+            }
             _ => {
                 tracing::warn!("Unexpected message type: {:?}", frame.message_type);
             }
@@ -106,18 +253,24 @@ async fn handle_connection(
     }
 }
 
-async fn execute_query(query_text: &str, storage: &StorageEngine) -> Result<serde_json::Value> {
+async fn execute_query(
+    query_text: &str,
+    storage: &dyn StorageBackend,
+    catalog: &Arc<RwLock<Catalog>>,
+    sandbox: Sandbox,
+    role: &str,
+) -> Result<serde_json::Value> {
     let parser = Parser::new();
     let ast = parser.parse(query_text)?;
 
-    let logical_planner = LogicalPlanner::new();
+    let logical_planner = LogicalPlanner::new(catalog.read().await.clone());
     let logical_plan = logical_planner.plan(&ast)?;
 
-    let physical_planner = PhysicalPlanner::new(storage);
-    let physical_plan = physical_planner.plan(&logical_plan)?;
+    let physical_planner = PhysicalPlanner::new(storage, catalog.read().await.clone());
+    let physical_plan = physical_planner.plan(&logical_plan).await?;
 
-    let mut execution_engine = ExecutionEngine::new(storage);
-    let results = execution_engine.execute(physical_plan).await?;
+    let mut execution_engine = ExecutionEngine::new(storage, catalog.clone()).with_role(role);
+    let results = execution_engine.execute_cancellable(physical_plan, sandbox).await?;
 
     Ok(serde_json::json!({
         "rows": results,
@@ -126,7 +279,7 @@ async fn execute_query(query_text: &str, storage: &StorageEngine) -> Result<serd
 
 async fn execute_statement(
     statement: &str,
-    storage: &StorageEngine,
+    storage: &dyn StorageBackend,
     raft_node: &RaftNode,
 ) -> Result<()> {
     let parser = Parser::new();
@@ -135,4 +288,95 @@ async fn execute_statement(
     raft_node.propose_command(statement.as_bytes().to_vec()).await?;
 
     Ok(())
-              }
+}
+
+/// Runs a `BatchRequest`'s items against `storage`/`raft_node` in order,
+/// auditing each as it completes. `query` items run immediately against
+/// local storage since they aren't replicated; consecutive `execute` items
+/// are coalesced into a single `RaftNode::propose_batch` call so they commit
+/// as one replicated log entry instead of one Raft round-trip per statement.
+/// `stop_on_error` short-circuits the remainder of the batch, reporting every
+/// item after the first failure as skipped rather than attempted.
+async fn execute_batch(
+    request: &BatchRequest,
+    storage: &Arc<dyn StorageBackend>,
+    catalog: &Arc<RwLock<Catalog>>,
+    raft_node: &Arc<RaftNode>,
+    audit: &Arc<AuditLogger>,
+    user: &str,
+) -> Vec<BatchItemResult> {
+    const SKIPPED: &str = "skipped: earlier batch item failed";
+
+    let mut results = Vec::with_capacity(request.items.len());
+    let mut failed = false;
+    let mut i = 0;
+
+    while i < request.items.len() {
+        if failed && request.stop_on_error {
+            let _ = audit
+                .log_query(user.to_string(), request.items[i].sql.clone(), false, Some(SKIPPED.to_string()))
+                .await;
+            results.push(BatchItemResult { success: false, result: None, error: Some(SKIPPED.to_string()) });
+            i += 1;
+            continue;
+        }
+
+        match request.items[i].op {
+            BatchOp::Query => {
+                let sandbox = Sandbox::new(QueryLimits::default());
+                let outcome = execute_query(&request.items[i].sql, storage, catalog, sandbox, user).await;
+
+                let _ = audit
+                    .log_query(
+                        user.to_string(),
+                        request.items[i].sql.clone(),
+                        outcome.is_ok(),
+                        outcome.as_ref().err().map(|e| e.to_string()),
+                    )
+                    .await;
+
+                results.push(match outcome {
+                    Ok(value) => BatchItemResult { success: true, result: Some(value), error: None },
+                    Err(e) => {
+                        failed = true;
+                        BatchItemResult { success: false, result: None, error: Some(e.to_string()) }
+                    }
+                });
+
+                i += 1;
+            }
+            BatchOp::Execute => {
+                let mut statements = Vec::new();
+                while i < request.items.len() && matches!(request.items[i].op, BatchOp::Execute) {
+                    statements.push(request.items[i].sql.clone());
+                    i += 1;
+                }
+
+                match raft_node.propose_batch(statements.clone(), request.stop_on_error).await {
+                    Ok(group_results) => {
+                        for (statement, result) in statements.iter().zip(group_results.iter()) {
+                            let _ = audit
+                                .log_query(user.to_string(), statement.clone(), result.success, result.error.clone())
+                                .await;
+                            if !result.success {
+                                failed = true;
+                            }
+                        }
+                        results.extend(group_results);
+                    }
+                    Err(e) => {
+                        failed = true;
+                        for statement in &statements {
+                            let _ = audit
+                                .log_query(user.to_string(), statement.clone(), false, Some(e.to_string()))
+                                .await;
+                            results.push(BatchItemResult { success: false, result: None, error: Some(e.to_string()) });
+                        }
+                    }
+                }
+            }
+        }
+    }
+
+    results
+}
@@ -1,26 +1,185 @@
-use crate::ffi::storage::StorageEngine;
-use anyhow::Result;
+use crate::execution::engine::ExecutionEngine;
+use crate::language::catalog::Catalog;
+use crate::language::parser::Parser;
+use crate::planner::logical::LogicalPlanner;
+use crate::planner::physical::PhysicalPlanner;
+use crate::protocol::framing::{Frame, MessageType};
+use crate::protocol::handshake;
+use crate::replication::log::{LogEntry, LogEntryType, ReplicationLog};
+use crate::storage::StorageBackend;
+use anyhow::{bail, Context, Result};
+use rand::Rng;
+use serde::{Deserialize, Serialize};
+use serde::de::DeserializeOwned;
+use std::collections::HashMap;
 use std::sync::Arc;
-use tokio::sync::mpsc;
+use std::time::{Duration, Instant};
+use tokio::sync::{oneshot, Mutex, RwLock};
+
+/// The system table Raft log entries are persisted to. Reusing
+/// `StorageBackend::insert_row` (which flushes the WAL on every call) gives
+/// the log the same crash-recoverable durability as an ordinary table write,
+/// without needing a lower-level raw-log-append primitive storage backends
+/// don't expose.
+const RAFT_LOG_TABLE: &str = "__raft_log";
+
+const TICK_INTERVAL: Duration = Duration::from_millis(100);
+const ELECTION_TIMEOUT_MIN_MS: u64 = 300;
+const ELECTION_TIMEOUT_MAX_MS: u64 = 600;
+const RPC_TIMEOUT: Duration = Duration::from_millis(250);
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Role {
+    Follower,
+    Candidate,
+    Leader,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RequestVoteRequest {
+    pub term: u64,
+    pub candidate_id: u32,
+    pub last_log_index: u64,
+    pub last_log_term: u64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RequestVoteResponse {
+    pub term: u64,
+    pub vote_granted: bool,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AppendEntriesRequest {
+    pub term: u64,
+    pub leader_id: u32,
+    pub prev_log_index: u64,
+    pub prev_log_term: u64,
+    pub entries: Vec<LogEntry>,
+    pub leader_commit: u64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AppendEntriesResponse {
+    pub term: u64,
+    pub success: bool,
+}
+
+/// The outcome of one statement within a `propose_batch` call, reported back
+/// to the proposer (and, for client-submitted batches, on to
+/// `protocol::batch::BatchResponse`) in the same order the statements were
+/// given.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BatchItemResult {
+    pub success: bool,
+    pub result: Option<serde_json::Value>,
+    pub error: Option<String>,
+}
+
+/// The log-entry encoding for `LogEntryType::BatchWrite`: an ordered list of
+/// statements applied together as one unit, plus whether a failure partway
+/// through should abort the rest (`stop_on_error`).
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct BatchCommand {
+    statements: Vec<String>,
+    stop_on_error: bool,
+}
+
+/// What `apply_entry` produced, so `apply_committed` knows which waiter map
+/// to resolve and with what.
+enum ApplyOutcome {
+    Single,
+    Batch(Vec<BatchItemResult>),
+}
+
+/// Everything about a node's Raft role that changes as terms, elections, and
+/// replication progress. Held behind a single `Mutex` rather than split
+/// across several locks, since almost every transition (stepping down,
+/// becoming leader, committing an entry) touches more than one field at
+/// once.
+struct RaftState {
+    role: Role,
+    current_term: u64,
+    voted_for: Option<u32>,
+    leader_id: Option<u32>,
+    log: ReplicationLog,
+    commit_index: u64,
+    last_applied: u64,
+    /// Leader-only: next log index to send each peer, keyed by peer address.
+    next_index: HashMap<String, u64>,
+    /// Leader-only: highest log index known to be replicated to each peer.
+    match_index: HashMap<String, u64>,
+    last_heartbeat: Instant,
+    election_timeout: Duration,
+}
+
+impl RaftState {
+    fn new() -> Self {
+        Self {
+            role: Role::Follower,
+            current_term: 0,
+            voted_for: None,
+            leader_id: None,
+            log: ReplicationLog::new(),
+            commit_index: 0,
+            last_applied: 0,
+            next_index: HashMap::new(),
+            match_index: HashMap::new(),
+            last_heartbeat: Instant::now(),
+            election_timeout: random_election_timeout(),
+        }
+    }
+
+    fn step_down(&mut self, term: u64) {
+        self.role = Role::Follower;
+        self.current_term = term;
+        self.voted_for = None;
+        self.leader_id = None;
+    }
+}
+
+fn random_election_timeout() -> Duration {
+    let millis = rand::thread_rng().gen_range(ELECTION_TIMEOUT_MIN_MS..=ELECTION_TIMEOUT_MAX_MS);
+    Duration::from_millis(millis)
+}
 
 pub struct RaftNode {
     node_id: u32,
     peers: Vec<String>,
-    storage: Arc<StorageEngine>,
-    command_tx: mpsc::Sender<Vec<u8>>,
-    command_rx: Arc<tokio::sync::Mutex<mpsc::Receiver<Vec<u8>>>>,
+    storage: Arc<dyn StorageBackend>,
+    catalog: Arc<RwLock<Catalog>>,
+    state: Mutex<RaftState>,
+    /// Resolved once `propose_command`'s entry reaches `last_applied`, so the
+    /// proposer can block on commit instead of firing-and-forgetting.
+    commit_waiters: Mutex<HashMap<u64, oneshot::Sender<()>>>,
+    /// Same idea as `commit_waiters`, but for `propose_batch`'s entries,
+    /// which resolve with each statement's `BatchItemResult` rather than `()`.
+    batch_waiters: Mutex<HashMap<u64, oneshot::Sender<Vec<BatchItemResult>>>>,
 }
 
 impl RaftNode {
-    pub fn new(node_id: u32, peers: Vec<String>, storage: Arc<StorageEngine>) -> Result<Self> {
-        let (command_tx, command_rx) = mpsc::channel(1000);
+    pub fn new(
+        node_id: u32,
+        peers: Vec<String>,
+        storage: Arc<dyn StorageBackend>,
+        catalog: Arc<RwLock<Catalog>>,
+    ) -> Result<Self> {
+        let schema = serde_json::json!({
+            "index": { "name": "index", "type": "Integer", "nullable": false, "primary_key": true },
+            "term": { "name": "term", "type": "Integer", "nullable": false, "primary_key": false },
+            "entry_type": { "name": "entry_type", "type": "String", "nullable": false, "primary_key": false },
+            "data": { "name": "data", "type": "String", "nullable": true, "primary_key": false },
+        });
+        storage.create_table(RAFT_LOG_TABLE, &serde_json::to_string_pretty(&schema)?)?;
 
         Ok(Self {
             node_id,
             peers,
             storage,
-            command_tx,
-            command_rx: Arc::new(tokio::sync::Mutex::new(command_rx)),
+            catalog,
+            state: Mutex::new(RaftState::new()),
+            commit_waiters: Mutex::new(HashMap::new()),
+            batch_waiters: Mutex::new(HashMap::new()),
         })
     }
 
@@ -28,24 +187,501 @@ impl RaftNode {
         self.node_id
     }
 
+    /// Appends `command` to the log if this node is currently the leader,
+    /// replicates it to a majority of `peers`, and returns only once it has
+    /// actually been committed and applied. Fails immediately if this node
+    /// isn't the leader, so callers can forward the write elsewhere instead
+    /// of waiting on a node that will never commit it.
     pub async fn propose_command(&self, command: Vec<u8>) -> Result<()> {
-        self.command_tx.send(command).await?;
-        Ok(())
+        let (index, receiver) = {
+            let mut state = self.state.lock().await;
+
+            if state.role != Role::Leader {
+                bail!(
+                    "node {} is not the Raft leader (current leader: {:?})",
+                    self.node_id,
+                    state.leader_id
+                );
+            }
+
+            let entry = LogEntry {
+                term: state.current_term,
+                index: state.log.last_index() + 1,
+                entry_type: LogEntryType::Write,
+                data: command,
+            };
+            let index = entry.index;
+
+            self.persist_entry(&entry)?;
+            state.log.append(entry);
+
+            let (sender, receiver) = oneshot::channel();
+            self.commit_waiters.lock().await.insert(index, sender);
+
+            (index, receiver)
+        };
+
+        self.replicate_to_peers().await;
+
+        receiver
+            .await
+            .with_context(|| format!("commit notification for log index {} was dropped", index))
+    }
+
+    /// Like `propose_command`, but appends `statements` as a single
+    /// `BatchWrite` entry so they commit as one replicated log entry instead
+    /// of one entry (and one Raft round-trip) per statement, and are applied
+    /// one after another, in order, when that entry is applied; a statement
+    /// that fails does not undo the ones applied before it. Returns one
+    /// `BatchItemResult` per statement, in order;
+    /// if `stop_on_error` is set, a failed statement causes every statement
+    /// after it to be recorded as skipped rather than attempted.
+    pub async fn propose_batch(
+        &self,
+        statements: Vec<String>,
+        stop_on_error: bool,
+    ) -> Result<Vec<BatchItemResult>> {
+        let (index, receiver) = {
+            let mut state = self.state.lock().await;
+
+            if state.role != Role::Leader {
+                bail!(
+                    "node {} is not the Raft leader (current leader: {:?})",
+                    self.node_id,
+                    state.leader_id
+                );
+            }
+
+            let command = BatchCommand { statements, stop_on_error };
+            let entry = LogEntry {
+                term: state.current_term,
+                index: state.log.last_index() + 1,
+                entry_type: LogEntryType::BatchWrite,
+                data: serde_json::to_vec(&command)?,
+            };
+            let index = entry.index;
+
+            self.persist_entry(&entry)?;
+            state.log.append(entry);
+
+            let (sender, receiver) = oneshot::channel();
+            self.batch_waiters.lock().await.insert(index, sender);
+
+            (index, receiver)
+        };
+
+        self.replicate_to_peers().await;
+
+        receiver
+            .await
+            .with_context(|| format!("commit notification for log index {} was dropped", index))
     }
 
+    /// Drives elections, heartbeats, and committed-entry application. Never
+    /// returns in normal operation; `Lifecycle` races it against the server
+    /// and metrics loops and treats its exit as fatal.
     pub async fn run(self: Arc<Self>) -> Result<()> {
         loop {
-            let mut rx = self.command_rx.lock().await;
+            tokio::time::sleep(TICK_INTERVAL).await;
+
+            let (is_leader, should_start_election) = {
+                let state = self.state.lock().await;
+                let is_leader = state.role == Role::Leader;
+                let election_timed_out =
+                    !is_leader && state.last_heartbeat.elapsed() >= state.election_timeout;
+                (is_leader, election_timed_out)
+            };
+
+            if is_leader {
+                self.replicate_to_peers().await;
+            } else if should_start_election {
+                self.start_election().await;
+            }
+
+            if let Err(e) = self.apply_committed().await {
+                tracing::error!("node {} failed to apply committed entries: {}", self.node_id, e);
+            }
+        }
+    }
+
+    async fn start_election(&self) {
+        let (term, last_log_index, last_log_term) = {
+            let mut state = self.state.lock().await;
+            state.role = Role::Candidate;
+            state.current_term += 1;
+            state.voted_for = Some(self.node_id);
+            state.leader_id = None;
+            state.last_heartbeat = Instant::now();
+            state.election_timeout = random_election_timeout();
+            (state.current_term, state.log.last_index(), state.log.last_term())
+        };
+
+        tracing::info!("node {} starting election for term {}", self.node_id, term);
+
+        // Quorum is over the whole cluster (self + peers), not just `peers`:
+        // `self.peers.len() / 2 + 1` only happens to match that when
+        // `peers.len()` is even, and undercounts it by one otherwise (e.g. a
+        // 2-node cluster has `peers.len() == 1`, so the old formula let a
+        // candidate declare itself leader off its own self-vote with zero
+        // peers contacted).
+        let majority = (self.peers.len() + 1) / 2 + 1;
+        let mut votes = 1; // a candidate always votes for itself
 
-            if let Some(_command) = rx.recv().await {
-                drop(rx);
+        if votes < majority {
+            let request = RequestVoteRequest {
+                term,
+                candidate_id: self.node_id,
+                last_log_index,
+                last_log_term,
+            };
+
+            let responses = futures::future::join_all(
+                self.peers.iter().map(|peer| self.send_request_vote(peer, request.clone())),
+            )
+            .await;
+
+            for response in responses.into_iter().flatten() {
+                if response.term > term {
+                    let mut state = self.state.lock().await;
+                    if response.term > state.current_term {
+                        state.step_down(response.term);
+                    }
+                    return;
+                }
+                if response.vote_granted {
+                    votes += 1;
+                }
+            }
+        }
+
+        let mut state = self.state.lock().await;
+        if state.role != Role::Candidate || state.current_term != term {
+            return; // a higher term arrived while we were out canvassing votes
+        }
+
+        if votes >= majority {
+            state.role = Role::Leader;
+            state.leader_id = Some(self.node_id);
+            let next = state.log.last_index() + 1;
+            for peer in &self.peers {
+                state.next_index.insert(peer.clone(), next);
+                state.match_index.insert(peer.clone(), 0);
+            }
+            tracing::info!("node {} became leader for term {}", self.node_id, term);
+            drop(state);
+            self.replicate_to_peers().await;
+        } else {
+            tracing::info!("node {} lost election for term {} ({}/{} votes)", self.node_id, term, votes, majority);
+        }
+    }
+
+    pub async fn handle_request_vote(&self, request: RequestVoteRequest) -> RequestVoteResponse {
+        let mut state = self.state.lock().await;
+
+        if request.term > state.current_term {
+            state.step_down(request.term);
+        }
+
+        let log_ok = request.last_log_term > state.log.last_term()
+            || (request.last_log_term == state.log.last_term()
+                && request.last_log_index >= state.log.last_index());
+
+        let can_vote = state.voted_for.is_none() || state.voted_for == Some(request.candidate_id);
+        let vote_granted = request.term >= state.current_term && log_ok && can_vote;
+
+        if vote_granted {
+            state.voted_for = Some(request.candidate_id);
+            state.last_heartbeat = Instant::now();
+        }
+
+        RequestVoteResponse { term: state.current_term, vote_granted }
+    }
+
+    /// Sends `AppendEntries` (carrying whatever unreplicated suffix of the
+    /// log each peer is missing) to every peer. Used both to replicate newly
+    /// proposed entries and, when the log has nothing new, as the heartbeat
+    /// that keeps followers from starting an election.
+    async fn replicate_to_peers(&self) {
+        futures::future::join_all(self.peers.iter().map(|peer| self.replicate_to_peer(peer))).await;
+    }
+
+    async fn replicate_to_peer(&self, peer: &str) {
+        let request = {
+            let state = self.state.lock().await;
+            if state.role != Role::Leader {
+                return;
+            }
+
+            let next_index = *state.next_index.get(peer).unwrap_or(&(state.log.last_index() + 1));
+            let prev_log_index = next_index.saturating_sub(1);
+            let prev_log_term = if prev_log_index == 0 {
+                0
             } else {
-                break;
+                state.log.get(prev_log_index).map(|e| e.term).unwrap_or(0)
+            };
+
+            let entries: Vec<LogEntry> = (next_index..=state.log.last_index())
+                .filter_map(|raft_index| state.log.get(raft_index).cloned())
+                .collect();
+
+            AppendEntriesRequest {
+                term: state.current_term,
+                leader_id: self.node_id,
+                prev_log_index,
+                prev_log_term,
+                entries,
+                leader_commit: state.commit_index,
+            }
+        };
+
+        let sent_through = request.prev_log_index + request.entries.len() as u64;
+        let term = request.term;
+
+        let response = match self.call(peer, MessageType::AppendEntries, &request).await {
+            Ok(response) => response,
+            Err(e) => {
+                tracing::debug!("append_entries to {} failed: {}", peer, e);
+                return;
+            }
+        };
+
+        let mut state = self.state.lock().await;
+
+        if response.term > state.current_term {
+            state.step_down(response.term);
+            return;
+        }
+        if state.role != Role::Leader || state.current_term != term {
+            return;
+        }
+
+        if response.success {
+            state.match_index.insert(peer.to_string(), sent_through);
+            state.next_index.insert(peer.to_string(), sent_through + 1);
+            self.advance_commit_index(&mut state);
+        } else {
+            let next = state.next_index.entry(peer.to_string()).or_insert(1);
+            *next = next.saturating_sub(1).max(1);
+        }
+    }
+
+    pub async fn handle_append_entries(&self, request: AppendEntriesRequest) -> AppendEntriesResponse {
+        let mut state = self.state.lock().await;
+
+        if request.term < state.current_term {
+            return AppendEntriesResponse { term: state.current_term, success: false };
+        }
+
+        if request.term > state.current_term {
+            state.step_down(request.term);
+        }
+        state.role = Role::Follower;
+        state.leader_id = Some(request.leader_id);
+        state.last_heartbeat = Instant::now();
+
+        let log_ok = request.prev_log_index == 0
+            || state.log.get(request.prev_log_index).map(|e| e.term) == Some(request.prev_log_term);
+
+        if !log_ok {
+            return AppendEntriesResponse { term: state.current_term, success: false };
+        }
+
+        state.log.truncate(request.prev_log_index);
+        for entry in request.entries {
+            if let Err(e) = self.persist_entry(&entry) {
+                tracing::error!("node {} failed to persist replicated entry: {}", self.node_id, e);
+                return AppendEntriesResponse { term: state.current_term, success: false };
             }
+            state.log.append(entry);
+        }
 
-            tokio::time::sleep(tokio::time::Duration::from_millis(100)).await;
+        if request.leader_commit > state.commit_index {
+            state.commit_index = request.leader_commit.min(state.log.last_index());
+            state.log.commit(state.commit_index);
+        }
+
+        AppendEntriesResponse { term: state.current_term, success: true }
+    }
+
+    /// Raft §5.4.2: a leader only advances its commit index over entries
+    /// from its own current term, so a majority-replicated entry from an
+    /// earlier term can't be "indirectly" committed by a later-term entry
+    /// replicating on top of it.
+    fn advance_commit_index(&self, state: &mut RaftState) {
+        // See the matching comment in `start_election`: quorum is over the
+        // whole cluster (self + peers), not just `peers`.
+        let majority = (self.peers.len() + 1) / 2 + 1;
+
+        for index in (state.commit_index + 1)..=state.log.last_index() {
+            let replicated = 1 + self
+                .peers
+                .iter()
+                .filter(|peer| *state.match_index.get(*peer).unwrap_or(&0) >= index)
+                .count();
+
+            if replicated >= majority && state.log.get(index).map(|e| e.term) == Some(state.current_term) {
+                state.commit_index = index;
+                state.log.commit(index);
+            }
+        }
+    }
+
+    async fn apply_committed(&self) -> Result<()> {
+        let pending: Vec<LogEntry> = {
+            let state = self.state.lock().await;
+            if state.last_applied >= state.commit_index {
+                return Ok(());
+            }
+            ((state.last_applied + 1)..=state.commit_index)
+                .filter_map(|index| state.log.get(index).cloned())
+                .collect()
+        };
+
+        for entry in pending {
+            let index = entry.index;
+            let outcome = self.apply_entry(&entry).await?;
+
+            let mut state = self.state.lock().await;
+            state.last_applied = index;
+            state.log.apply(index);
+            drop(state);
+
+            match outcome {
+                ApplyOutcome::Single => {
+                    if let Some(waiter) = self.commit_waiters.lock().await.remove(&index) {
+                        let _ = waiter.send(());
+                    }
+                }
+                ApplyOutcome::Batch(results) => {
+                    if let Some(waiter) = self.batch_waiters.lock().await.remove(&index) {
+                        let _ = waiter.send(results);
+                    }
+                }
+            }
         }
 
         Ok(())
     }
+
+    async fn apply_entry(&self, entry: &LogEntry) -> Result<ApplyOutcome> {
+        match entry.entry_type {
+            LogEntryType::Write => {
+                let statement = String::from_utf8(entry.data.clone())
+                    .context("committed log entry was not valid UTF-8")?;
+
+                tracing::debug!("node {} applying committed entry {}: {}", self.node_id, entry.index, statement);
+
+                self.apply_statement(&statement).await?;
+                Ok(ApplyOutcome::Single)
+            }
+            LogEntryType::BatchWrite => {
+                let command: BatchCommand = serde_json::from_slice(&entry.data)
+                    .context("committed batch entry was not a valid BatchCommand")?;
+
+                tracing::debug!(
+                    "node {} applying committed batch entry {} ({} statements)",
+                    self.node_id,
+                    entry.index,
+                    command.statements.len()
+                );
+
+                let mut results = Vec::with_capacity(command.statements.len());
+                let mut failed = false;
+
+                for statement in &command.statements {
+                    if failed && command.stop_on_error {
+                        results.push(BatchItemResult {
+                            success: false,
+                            result: None,
+                            error: Some("skipped: earlier batch item failed".to_string()),
+                        });
+                        continue;
+                    }
+
+                    match self.apply_statement(statement).await {
+                        Ok(()) => results.push(BatchItemResult { success: true, result: None, error: None }),
+                        Err(e) => {
+                            failed = true;
+                            results.push(BatchItemResult { success: false, result: None, error: Some(e.to_string()) });
+                        }
+                    }
+                }
+
+                Ok(ApplyOutcome::Batch(results))
+            }
+            LogEntryType::Config | LogEntryType::Snapshot => {
+                tracing::debug!(
+                    "node {} skipping apply of {:?} entry {}",
+                    self.node_id,
+                    entry.entry_type,
+                    entry.index
+                );
+                Ok(ApplyOutcome::Single)
+            }
+        }
+    }
+
+    /// Parses, plans, and executes a single statement against local storage.
+    /// Shared by `LogEntryType::Write` (one statement per entry) and
+    /// `LogEntryType::BatchWrite` (many statements per entry, one call per
+    /// statement).
+    async fn apply_statement(&self, statement: &str) -> Result<()> {
+        let parser = Parser::new();
+        let ast = parser.parse(statement)?;
+
+        let logical_planner = LogicalPlanner::new(self.catalog.read().await.clone());
+        let logical_plan = logical_planner.plan(&ast)?;
+
+        let physical_planner = PhysicalPlanner::new(self.storage.as_ref(), self.catalog.read().await.clone());
+        let physical_plan = physical_planner.plan(&logical_plan).await?;
+
+        let mut engine = ExecutionEngine::new(self.storage.as_ref(), self.catalog.clone());
+        engine.execute(physical_plan).await?;
+
+        Ok(())
+    }
+
+    /// Persists `entry` as a row in `__raft_log` before it's considered part
+    /// of the in-memory log, so a crash right after `append` still leaves it
+    /// recoverable from storage. `insert_row` already flushes the WAL on
+    /// every call, so no separate flush is needed here.
+    fn persist_entry(&self, entry: &LogEntry) -> Result<()> {
+        let payload = serde_json::to_vec(entry)?;
+        self.storage.insert_row(RAFT_LOG_TABLE, &payload)?;
+        Ok(())
+    }
+
+    async fn send_request_vote(&self, peer: &str, request: RequestVoteRequest) -> Option<RequestVoteResponse> {
+        match self.call(peer, MessageType::RequestVote, &request).await {
+            Ok(response) => Some(response),
+            Err(e) => {
+                tracing::debug!("request_vote to {} failed: {}", peer, e);
+                None
+            }
+        }
+    }
+
+    /// Opens a fresh connection to `peer`, performs the same handshake a
+    /// query client would, and sends a single framed request/response pair.
+    /// Raft RPCs don't reuse connections across calls: peers come and go
+    /// far less often than the per-call cost of a handshake matters here.
+    async fn call<Req: Serialize, Resp: DeserializeOwned>(
+        &self,
+        peer: &str,
+        message_type: MessageType,
+        request: &Req,
+    ) -> Result<Resp> {
+        tokio::time::timeout(RPC_TIMEOUT, async move {
+            let (mut stream, _) = handshake::open(peer, format!("raft-node-{}", self.node_id)).await?;
+
+            let payload = serde_json::to_vec(request)?;
+            Frame::new(message_type, payload).write_to(&mut stream).await?;
+
+            let frame = Frame::read_from(&mut stream).await?;
+            let response = serde_json::from_slice(&frame.payload)?;
+            Ok(response)
+        })
+        .await
+        .context("Raft RPC timed out")?
+    }
 }
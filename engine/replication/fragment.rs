@@ -0,0 +1,102 @@
+use crate::execution::engine::ExecutionEngine;
+use crate::execution::tuple::Tuple;
+use crate::language::catalog::Catalog;
+use crate::planner::physical::PhysicalPlan;
+use crate::sharding::keyspace::ShardId;
+use crate::storage::StorageBackend;
+use anyhow::Result;
+use futures::future::BoxFuture;
+use futures::FutureExt;
+use serde::{Deserialize, Serialize};
+use std::sync::Arc;
+use tokio::sync::RwLock;
+
+/// A `PhysicalPlan` subtree pinned to a single shard, serialized so it can be
+/// shipped to whichever node currently owns that shard.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PlanFragment {
+    pub shard_id: ShardId,
+    pub plan: PhysicalPlan,
+}
+
+impl PlanFragment {
+    pub fn new(shard_id: ShardId, plan: PhysicalPlan) -> Self {
+        Self { shard_id, plan }
+    }
+
+    pub fn serialize(&self) -> Result<String> {
+        Ok(serde_json::to_string(self)?)
+    }
+
+    pub fn deserialize(payload: &str) -> Result<Self> {
+        Ok(serde_json::from_str(payload)?)
+    }
+}
+
+/// Ships a serialized `PlanFragment` to the node owning `shard_id` and
+/// collects the resulting tuple batch. Implementations hide whatever
+/// transport (in-process, network RPC, ...) is actually used to reach the
+/// shard owner.
+pub trait ShardTransport: Send + Sync {
+    fn dispatch<'a>(
+        &'a self,
+        shard_id: ShardId,
+        fragment: PlanFragment,
+    ) -> BoxFuture<'a, Result<Vec<Tuple>>>;
+}
+
+/// Executes every fragment against the local `StorageBackend`. This is the
+/// transport used when the coordinator and every shard happen to live in the
+/// same process (e.g. tests, or a single-node deployment); a real cluster
+/// would replace it with a transport that dials the peer address owning the
+/// shard and runs the fragment through a `ShardWorker` there.
+pub struct LocalShardTransport<'a> {
+    storage: &'a dyn StorageBackend,
+    catalog: Arc<RwLock<Catalog>>,
+}
+
+impl<'a> LocalShardTransport<'a> {
+    pub fn new(storage: &'a dyn StorageBackend, catalog: Arc<RwLock<Catalog>>) -> Self {
+        Self { storage, catalog }
+    }
+}
+
+impl<'a> ShardTransport for LocalShardTransport<'a> {
+    fn dispatch<'b>(
+        &'b self,
+        shard_id: ShardId,
+        fragment: PlanFragment,
+    ) -> BoxFuture<'b, Result<Vec<Tuple>>> {
+        async move {
+            tracing::debug!("dispatching fragment to shard {:?}", shard_id);
+            let worker = ShardWorker::new(self.storage, self.catalog.clone());
+            worker.execute(fragment).await
+        }
+        .boxed()
+    }
+}
+
+/// Runs on the node that owns a shard: deserializes an incoming fragment and
+/// executes it against the local store, returning the resulting tuples for
+/// the coordinator to merge.
+pub struct ShardWorker<'a> {
+    storage: &'a dyn StorageBackend,
+    catalog: Arc<RwLock<Catalog>>,
+}
+
+impl<'a> ShardWorker<'a> {
+    pub fn new(storage: &'a dyn StorageBackend, catalog: Arc<RwLock<Catalog>>) -> Self {
+        Self { storage, catalog }
+    }
+
+    pub async fn execute(&self, fragment: PlanFragment) -> Result<Vec<Tuple>> {
+        let mut engine = ExecutionEngine::new(self.storage, self.catalog.clone());
+        engine.execute(fragment.plan).await
+    }
+
+    pub async fn execute_payload(&self, payload: &str) -> Result<String> {
+        let fragment = PlanFragment::deserialize(payload)?;
+        let tuples = self.execute(fragment).await?;
+        Ok(serde_json::to_string(&tuples)?)
+    }
+}
@@ -11,12 +11,35 @@ pub struct LogEntry {
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub enum LogEntryType {
     Write,
+    /// An ordered group of `Write`-equivalent statements that commit and
+    /// apply as a single log entry rather than one entry each, so a client
+    /// batch of `execute` items replicates atomically.
+    BatchWrite,
     Config,
     Snapshot,
 }
 
+/// A point the log has been compacted up to: everything at or before
+/// `last_included_index` has been folded into `data` (an opaque state
+/// machine snapshot `ReplicationLog` doesn't interpret) and dropped from
+/// the in-memory entry list.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Snapshot {
+    pub last_included_index: u64,
+    pub last_included_term: u64,
+    pub data: Vec<u8>,
+}
+
+/// A Raft log. Indices passed in and out of `get`/`append`/`truncate`/
+/// `last_index`/`last_term` are logical 1-based Raft log indices, not raw
+/// `Vec` offsets: `base_index` (0 until something is compacted) is the
+/// index of the most recent snapshot point, so `entries[0]` holds logical
+/// index `base_index + 1`.
 pub struct ReplicationLog {
     entries: Vec<LogEntry>,
+    base_index: u64,
+    last_included_term: u64,
+    snapshot: Option<Snapshot>,
     commit_index: u64,
     last_applied: u64,
 }
@@ -25,6 +48,9 @@ impl ReplicationLog {
     pub fn new() -> Self {
         Self {
             entries: Vec::new(),
+            base_index: 0,
+            last_included_term: 0,
+            snapshot: None,
             commit_index: 0,
             last_applied: 0,
         }
@@ -34,16 +60,26 @@ impl ReplicationLog {
         self.entries.push(entry);
     }
 
+    /// `index` is a logical 1-based log index. Returns `None` both past the
+    /// end of the log and for any index at or below `base_index` — the
+    /// latter has been compacted away, so callers that need its term should
+    /// fall back to `last_included_term`/`snapshot` instead.
     pub fn get(&self, index: u64) -> Option<&LogEntry> {
-        self.entries.get(index as usize)
+        if index <= self.base_index {
+            return None;
+        }
+        self.entries.get((index - self.base_index - 1) as usize)
     }
 
     pub fn last_index(&self) -> u64 {
-        self.entries.len() as u64
+        self.base_index + self.entries.len() as u64
     }
 
+    /// Falls back to `last_included_term` when the tail is empty, which
+    /// happens both right after `install_snapshot`/`compact` truncate every
+    /// entry and, in the steady state, whenever the log is otherwise empty.
     pub fn last_term(&self) -> u64 {
-        self.entries.last().map(|e| e.term).unwrap_or(0)
+        self.entries.last().map(|e| e.term).unwrap_or(self.last_included_term)
     }
 
     pub fn commit(&mut self, index: u64) {
@@ -54,7 +90,74 @@ impl ReplicationLog {
         self.last_applied = index;
     }
 
+    /// `from_index` is a logical index: entries after `from_index` are
+    /// discarded, keeping the common prefix up to and including it. A
+    /// `from_index` at or below `base_index` — the conflicting
+    /// entries have already been compacted away — just empties the
+    /// (uncompacted) tail.
     pub fn truncate(&mut self, from_index: u64) {
-        self.entries.truncate(from_index as usize);
+        let keep = from_index.saturating_sub(self.base_index) as usize;
+        self.entries.truncate(keep);
+    }
+
+    /// Every uncompacted entry in the log, in append order. Used by
+    /// consumers (e.g. CDC's change log) that replay the whole history
+    /// rather than walking it by Raft log index.
+    pub fn entries(&self) -> &[LogEntry] {
+        &self.entries
+    }
+
+    /// The index of the most recent snapshot point, or `0` if the log has
+    /// never been compacted.
+    pub fn base_index(&self) -> u64 {
+        self.base_index
+    }
+
+    pub fn snapshot(&self) -> Option<&Snapshot> {
+        self.snapshot.as_ref()
+    }
+
+    /// Installs a snapshot received from a peer (or restored from disk),
+    /// discarding every entry it covers. Entries past `last_included_index`
+    /// that are still present are kept, matching Raft's InstallSnapshot
+    /// rule that a follower never throws away log it already has beyond the
+    /// snapshot point; if the snapshot covers the whole log (or more), the
+    /// log is emptied instead. A snapshot at or behind the current
+    /// `base_index` is a no-op — it can't un-compact anything we've already
+    /// moved past.
+    pub fn install_snapshot(&mut self, snapshot: Snapshot) {
+        if snapshot.last_included_index <= self.base_index {
+            return;
+        }
+
+        let covered = (snapshot.last_included_index - self.base_index) as usize;
+        if covered >= self.entries.len() {
+            self.entries.clear();
+        } else {
+            self.entries.drain(0..covered);
+        }
+
+        self.base_index = snapshot.last_included_index;
+        self.last_included_term = snapshot.last_included_term;
+        self.commit_index = self.commit_index.max(self.base_index);
+        self.last_applied = self.last_applied.max(self.base_index);
+        self.snapshot = Some(snapshot);
+    }
+
+    /// Compacts the log up to and including `up_to_index`, dropping those
+    /// entries from memory. `up_to_index` must name an entry still present
+    /// in the log (it has to be committed and applied already — the caller
+    /// is responsible for only compacting up to `last_applied`); anything
+    /// else is a no-op rather than a partial/incorrect compaction.
+    pub fn compact(&mut self, up_to_index: u64) {
+        let last_included_term = match self.get(up_to_index) {
+            Some(entry) => entry.term,
+            None => return,
+        };
+
+        let drop_count = (up_to_index - self.base_index) as usize;
+        self.entries.drain(0..drop_count);
+        self.base_index = up_to_index;
+        self.last_included_term = last_included_term;
     }
 }
@@ -1,7 +1,9 @@
 pub mod consensus;
+pub mod fragment;
 pub mod log;
 pub mod state_sync;
 
 pub use consensus::*;
+pub use fragment::*;
 pub use log::*;
 pub use state_sync::*;
@@ -1,9 +1,15 @@
-use anyhow::Result;
+use anyhow::{Context, Result};
 use chrono::{DateTime, Utc};
+use hmac::{Hmac, Mac};
 use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
 use std::sync::Arc;
 use tokio::sync::Mutex;
 
+/// The `prev_hash` of the first entry in the chain — there's nothing before
+/// it to link to.
+const GENESIS_HASH: [u8; 32] = [0u8; 32];
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub enum AuditEventType {
     QueryExecution,
@@ -25,11 +31,66 @@ pub struct AuditEvent {
     pub success: bool,
     pub error_message: Option<String>,
     pub ip_address: Option<String>,
+    /// The preceding entry's `entry_hash` (or `GENESIS_HASH` for the first
+    /// entry), forming a hash chain: editing or deleting any entry changes
+    /// its `entry_hash`, which breaks every `prev_hash` link after it.
+    pub prev_hash: [u8; 32],
+    /// `SHA256(prev_hash || canonical_encoding_of(self without the hashes))`,
+    /// computed once by `AuditLogger::log_event` and never recomputed except
+    /// by `verify_chain`.
+    pub entry_hash: [u8; 32],
+}
+
+/// The subset of `AuditEvent` that feeds `entry_hash`, serialized in this
+/// struct's declared field order (excluding both hash fields; `prev_hash`
+/// is fed to the hasher ahead of this encoding) so hashing
+/// is reproducible regardless of how `AuditEvent`'s own field order might
+/// change in the future.
+#[derive(Serialize)]
+struct CanonicalAuditEvent<'a> {
+    event_id: u64,
+    event_type: &'a AuditEventType,
+    timestamp: DateTime<Utc>,
+    user: &'a str,
+    query: &'a Option<String>,
+    table: &'a Option<String>,
+    success: bool,
+    error_message: &'a Option<String>,
+    ip_address: &'a Option<String>,
+}
+
+fn to_hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|b| format!("{:02x}", b)).collect()
+}
+
+fn entry_hash(event: &AuditEvent) -> Result<[u8; 32]> {
+    let canonical = CanonicalAuditEvent {
+        event_id: event.event_id,
+        event_type: &event.event_type,
+        timestamp: event.timestamp,
+        user: &event.user,
+        query: &event.query,
+        table: &event.table,
+        success: event.success,
+        error_message: &event.error_message,
+        ip_address: &event.ip_address,
+    };
+
+    let encoded =
+        serde_json::to_vec(&canonical).context("failed to canonically encode audit event")?;
+
+    let mut hasher = Sha256::new();
+    hasher.update(event.prev_hash);
+    hasher.update(&encoded);
+    Ok(hasher.finalize().into())
 }
 
 pub struct AuditLogger {
     events: Arc<Mutex<Vec<AuditEvent>>>,
     next_event_id: Arc<Mutex<u64>>,
+    /// The most recently appended entry's `entry_hash`, i.e. the tip of the
+    /// chain; the next `log_event` call links off of this.
+    chain_head: Arc<Mutex<[u8; 32]>>,
 }
 
 impl AuditLogger {
@@ -37,6 +98,7 @@ impl AuditLogger {
         Self {
             events: Arc::new(Mutex::new(Vec::new())),
             next_event_id: Arc::new(Mutex::new(1)),
+            chain_head: Arc::new(Mutex::new(GENESIS_HASH)),
         }
     }
 
@@ -46,6 +108,12 @@ impl AuditLogger {
         *next_id += 1;
         drop(next_id);
 
+        let mut chain_head = self.chain_head.lock().await;
+        event.prev_hash = *chain_head;
+        event.entry_hash = entry_hash(&event)?;
+        *chain_head = event.entry_hash;
+        drop(chain_head);
+
         let mut events = self.events.lock().await;
         events.push(event.clone());
 
@@ -60,6 +128,38 @@ impl AuditLogger {
         Ok(())
     }
 
+    /// Recomputes every entry's `entry_hash` from its recorded contents and
+    /// checks it against both the stored value and the preceding entry's
+    /// `entry_hash`, failing with an error that names the index of the
+    /// first entry where either check fails. A passing `events` slice with no external write access
+    /// guarantees no entry was edited, reordered, or dropped after logging.
+    pub async fn verify_chain(&self) -> Result<()> {
+        let events = self.events.lock().await;
+        let mut expected_prev = GENESIS_HASH;
+
+        for (index, event) in events.iter().enumerate() {
+            if event.prev_hash != expected_prev {
+                anyhow::bail!(
+                    "audit chain broken at index {} (event_id={}): prev_hash does not match the preceding entry",
+                    index,
+                    event.event_id
+                );
+            }
+
+            if entry_hash(event)? != event.entry_hash {
+                anyhow::bail!(
+                    "audit chain broken at index {} (event_id={}): entry_hash does not match the entry's recorded contents",
+                    index,
+                    event.event_id
+                );
+            }
+
+            expected_prev = event.entry_hash;
+        }
+
+        Ok(())
+    }
+
     pub async fn log_query(
         &self,
         user: String,
@@ -77,6 +177,8 @@ impl AuditLogger {
             success,
             error_message: error,
             ip_address: None,
+            prev_hash: GENESIS_HASH,
+            entry_hash: GENESIS_HASH,
         };
 
         self.log_event(event).await
@@ -98,6 +200,8 @@ impl AuditLogger {
             success,
             error_message: None,
             ip_address,
+            prev_hash: GENESIS_HASH,
+            entry_hash: GENESIS_HASH,
         };
 
         self.log_event(event).await
@@ -119,6 +223,8 @@ impl AuditLogger {
             success: true,
             error_message: None,
             ip_address: None,
+            prev_hash: GENESIS_HASH,
+            entry_hash: GENESIS_HASH,
         };
 
         self.log_event(event).await
@@ -188,4 +294,60 @@ impl AuditLogger {
             _ => anyhow::bail!("Unsupported format: {}", format),
         }
     }
+
+    /// Like `export_logs`, but appends the chain head hash covering every
+    /// entry in the export, optionally HMAC-SHA256-signed with
+    /// `signing_key`, so a verifier holding only the exported file (not this
+    /// process's in-memory log) can confirm `verify_chain` would have
+    /// passed and that the export itself wasn't truncated or edited after
+    /// the fact.
+    pub async fn export_logs_signed(
+        &self,
+        format: &str,
+        signing_key: Option<&[u8]>,
+    ) -> Result<String> {
+        let events = self.events.lock().await;
+        let chain_head = *self.chain_head.lock().await;
+
+        let signature = signing_key.map(|key| {
+            let mut mac = Hmac::<Sha256>::new_from_slice(key)
+                .expect("HMAC accepts a key of any length");
+            mac.update(&chain_head);
+            to_hex(&mac.finalize().into_bytes())
+        });
+
+        match format {
+            "json" => {
+                let payload = serde_json::json!({
+                    "events": &*events,
+                    "chain_head": to_hex(&chain_head),
+                    "signature": signature,
+                });
+                Ok(serde_json::to_string_pretty(&payload)?)
+            }
+            "csv" => {
+                let mut csv =
+                    String::from("event_id,event_type,timestamp,user,success,prev_hash,entry_hash\n");
+                for event in events.iter() {
+                    csv.push_str(&format!(
+                        "{},{:?},{},{},{},{},{}\n",
+                        event.event_id,
+                        event.event_type,
+                        event.timestamp,
+                        event.user,
+                        event.success,
+                        to_hex(&event.prev_hash),
+                        to_hex(&event.entry_hash)
+                    ));
+                }
+                csv.push_str(&format!("# chain_head={}", to_hex(&chain_head)));
+                if let Some(sig) = &signature {
+                    csv.push_str(&format!(" signature={}", sig));
+                }
+                csv.push('\n');
+                Ok(csv)
+            }
+            _ => anyhow::bail!("Unsupported format: {}", format),
+        }
+    }
 }
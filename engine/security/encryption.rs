@@ -1,6 +1,30 @@
+use aes_gcm::aead::rand_core::{OsRng, RngCore};
+use aes_gcm::aead::{Aead, KeyInit};
+use aes_gcm::{Aes256Gcm, Key, Nonce};
 use anyhow::Result;
-use sha2::{Digest, Sha256};
+use argon2::password_hash::{PasswordHash, PasswordHasher, PasswordVerifier, SaltString};
+use argon2::{Algorithm, Argon2, Params, Version};
+use hkdf::Hkdf;
+use sha2::Sha256;
 
+const NONCE_LEN: usize = 12;
+
+/// Tuned the same as `protocol::auth`'s login-path Argon2id: ~19 MiB of
+/// memory, 2 iterations, single-threaded.
+const ARGON2_MEMORY_COST_KIB: u32 = 19 * 1024;
+const ARGON2_ITERATIONS: u32 = 2;
+const ARGON2_PARALLELISM: u32 = 1;
+
+fn argon2id() -> Argon2<'static> {
+    let params = Params::new(ARGON2_MEMORY_COST_KIB, ARGON2_ITERATIONS, ARGON2_PARALLELISM, None)
+        .expect("hardcoded Argon2id parameters are always valid");
+    Argon2::new(Algorithm::Argon2id, Version::V0x13, params)
+}
+
+/// AES-256-GCM at rest, with per-use subkeys derived from the master key via
+/// HKDF-SHA256 rather than using the master key directly — so a key never
+/// gets reused across two different purposes (bulk "at rest" encryption vs.
+/// a specific column).
 pub struct EncryptionManager {
     master_key: Vec<u8>,
 }
@@ -10,50 +34,94 @@ impl EncryptionManager {
         Self { master_key }
     }
 
-    pub fn encrypt_at_rest(&self, data: &[u8]) -> Result<Vec<u8>> {
-        let mut encrypted = Vec::with_capacity(data.len());
-        
-        for (i, &byte) in data.iter().enumerate() {
-            let key_byte = self.master_key[i % self.master_key.len()];
-            encrypted.push(byte ^ key_byte);
+    /// Derives a 32-byte AES-256-GCM key from the master key via
+    /// HKDF-SHA256, with `info` binding the key to its purpose (e.g. a
+    /// column name) so two different `info`s never collide on the same key.
+    fn derive_key(&self, info: &[u8]) -> Key<Aes256Gcm> {
+        let hkdf = Hkdf::<Sha256>::new(None, &self.master_key);
+        let mut okm = [0u8; 32];
+        hkdf.expand(info, &mut okm)
+            .expect("32 bytes is a valid HKDF-SHA256 output length");
+        okm.into()
+    }
+
+    /// Encrypts `data` under the key derived for `info`, returning `nonce ||
+    /// ciphertext || tag` so `decrypt` has everything it needs to recover
+    /// the plaintext and verify integrity from the output alone.
+    fn encrypt(&self, info: &[u8], data: &[u8]) -> Result<Vec<u8>> {
+        let cipher = Aes256Gcm::new(&self.derive_key(info));
+
+        let mut nonce_bytes = [0u8; NONCE_LEN];
+        OsRng.fill_bytes(&mut nonce_bytes);
+        let nonce = Nonce::from_slice(&nonce_bytes);
+
+        let ciphertext = cipher
+            .encrypt(nonce, data)
+            .map_err(|_| anyhow::anyhow!("encryption failed"))?;
+
+        let mut out = Vec::with_capacity(NONCE_LEN + ciphertext.len());
+        out.extend_from_slice(&nonce_bytes);
+        out.extend_from_slice(&ciphertext);
+        Ok(out)
+    }
+
+    /// Splits `nonce || ciphertext || tag` back apart and verifies the tag,
+    /// returning an error on any mismatch (wrong key, wrong `info`, or
+    /// tampered ciphertext) rather than silently returning garbage.
+    fn decrypt(&self, info: &[u8], encrypted: &[u8]) -> Result<Vec<u8>> {
+        if encrypted.len() < NONCE_LEN {
+            anyhow::bail!("ciphertext shorter than the nonce prefix");
         }
 
-        Ok(encrypted)
+        let (nonce_bytes, ciphertext) = encrypted.split_at(NONCE_LEN);
+        let nonce = Nonce::from_slice(nonce_bytes);
+
+        let cipher = Aes256Gcm::new(&self.derive_key(info));
+        cipher
+            .decrypt(nonce, ciphertext)
+            .map_err(|_| anyhow::anyhow!("decryption failed: authentication tag mismatch"))
+    }
+
+    pub fn encrypt_at_rest(&self, data: &[u8]) -> Result<Vec<u8>> {
+        self.encrypt(b"at-rest", data)
     }
 
     pub fn decrypt_at_rest(&self, encrypted: &[u8]) -> Result<Vec<u8>> {
-        self.encrypt_at_rest(encrypted)
+        self.decrypt(b"at-rest", encrypted)
     }
 
     pub fn encrypt_column(&self, column_name: &str, data: &[u8]) -> Result<Vec<u8>> {
-        let mut hasher = Sha256::new();
-        hasher.update(column_name.as_bytes());
-        hasher.update(&self.master_key);
-        let column_key = hasher.finalize();
-
-        let mut encrypted = Vec::with_capacity(data.len());
-        
-        for (i, &byte) in data.iter().enumerate() {
-            let key_byte = column_key[i % column_key.len()];
-            encrypted.push(byte ^ key_byte);
-        }
-
-        Ok(encrypted)
+        self.encrypt(column_name.as_bytes(), data)
     }
 
     pub fn decrypt_column(&self, column_name: &str, encrypted: &[u8]) -> Result<Vec<u8>> {
-        self.encrypt_column(column_name, encrypted)
+        self.decrypt(column_name.as_bytes(), encrypted)
     }
 
+    /// Hashes with Argon2id under a fresh random salt, returning the
+    /// PHC-format string (the same representation `protocol::auth::Credentials`
+    /// stores) as bytes.
     pub fn hash_password(&self, password: &str) -> Vec<u8> {
-        let mut hasher = Sha256::new();
-        hasher.update(password.as_bytes());
-        hasher.update(&self.master_key);
-        hasher.finalize().to_vec()
+        let salt = SaltString::generate(&mut OsRng);
+        argon2id()
+            .hash_password(password.as_bytes(), &salt)
+            .expect("hashing a password never fails for valid UTF-8 input")
+            .to_string()
+            .into_bytes()
     }
 
+    /// Constant-time: `PasswordVerifier::verify_password` compares the
+    /// recomputed hash to the stored one without short-circuiting on the
+    /// first differing byte.
     pub fn verify_password(&self, password: &str, hash: &[u8]) -> bool {
-        let computed_hash = self.hash_password(password);
-        computed_hash == hash
+        let phc = match std::str::from_utf8(hash) {
+            Ok(phc) => phc,
+            Err(_) => return false,
+        };
+
+        PasswordHash::new(phc)
+            .ok()
+            .map(|parsed| argon2id().verify_password(password.as_bytes(), &parsed).is_ok())
+            .unwrap_or(false)
     }
 }
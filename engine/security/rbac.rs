@@ -17,10 +17,71 @@ pub enum Permission {
     RevokePermission,
 }
 
+/// How a `PermRule`'s `resource` compares against the resource path being
+/// checked, from narrowest to widest.
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum MatchMode {
+    /// Matches only the exact resource path, e.g. `public.users`.
+    Base,
+    /// Matches any path exactly one segment below the rule's path, e.g.
+    /// `public.users` matches `public.users.email` but not `public.users`
+    /// itself or `public.users.email.domain`.
+    Children,
+    /// Matches the rule's path and everything beneath it, including the
+    /// path itself.
+    Subtree,
+}
+
+/// A single grant: `permission` on every resource path that `mode` matches
+/// against `resource`. `*` names the root of the resource tree, so a
+/// `Subtree` rule on `*` matches every path.
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub struct PermRule {
+    pub permission: Permission,
+    pub resource: String,
+    pub mode: MatchMode,
+}
+
+impl PermRule {
+    pub fn new(permission: Permission, resource: impl Into<String>, mode: MatchMode) -> Self {
+        Self {
+            permission,
+            resource: resource.into(),
+            mode,
+        }
+    }
+
+    /// Splits a resource path into its dotted segments, with `*` (the root)
+    /// as the empty segment list so every mode's prefix comparison treats it
+    /// as "above" any concrete path.
+    fn segments(resource: &str) -> Vec<&str> {
+        if resource == "*" {
+            Vec::new()
+        } else {
+            resource.split('.').collect()
+        }
+    }
+
+    fn matches(&self, permission: &Permission, resource_path: &str) -> bool {
+        if self.permission != *permission {
+            return false;
+        }
+
+        let rule = Self::segments(&self.resource);
+        let requested = Self::segments(resource_path);
+
+        match self.mode {
+            MatchMode::Base => rule == requested,
+            MatchMode::Children => requested.len() == rule.len() + 1 && requested.starts_with(rule.as_slice()),
+            MatchMode::Subtree => requested.starts_with(rule.as_slice()),
+        }
+    }
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Role {
     pub name: String,
-    pub permissions: HashSet<Permission>,
+    pub rules: Vec<PermRule>,
     pub inherits_from: Vec<String>,
 }
 
@@ -46,43 +107,46 @@ impl RBACManager {
         manager
     }
 
+    /// A rule granting `permission` over the whole resource tree, used by
+    /// the default roles so `admin`/`readonly`/`readwrite` keep working
+    /// exactly as before now that permissions are resource-scoped.
+    fn subtree_rule(permission: Permission) -> PermRule {
+        PermRule::new(permission, "*", MatchMode::Subtree)
+    }
+
     fn create_default_roles(&mut self) {
         let admin_role = Role {
             name: "admin".to_string(),
-            permissions: vec![
-                Permission::Select,
-                Permission::Insert,
-                Permission::Update,
-                Permission::Delete,
-                Permission::CreateTable,
-                Permission::DropTable,
-                Permission::CreateIndex,
-                Permission::DropIndex,
-                Permission::CreateUser,
-                Permission::GrantPermission,
-                Permission::RevokePermission,
-            ]
-            .into_iter()
-            .collect(),
+            rules: vec![
+                Self::subtree_rule(Permission::Select),
+                Self::subtree_rule(Permission::Insert),
+                Self::subtree_rule(Permission::Update),
+                Self::subtree_rule(Permission::Delete),
+                Self::subtree_rule(Permission::CreateTable),
+                Self::subtree_rule(Permission::DropTable),
+                Self::subtree_rule(Permission::CreateIndex),
+                Self::subtree_rule(Permission::DropIndex),
+                Self::subtree_rule(Permission::CreateUser),
+                Self::subtree_rule(Permission::GrantPermission),
+                Self::subtree_rule(Permission::RevokePermission),
+            ],
             inherits_from: Vec::new(),
         };
 
         let readonly_role = Role {
             name: "readonly".to_string(),
-            permissions: vec![Permission::Select].into_iter().collect(),
+            rules: vec![Self::subtree_rule(Permission::Select)],
             inherits_from: Vec::new(),
         };
 
         let readwrite_role = Role {
             name: "readwrite".to_string(),
-            permissions: vec![
-                Permission::Select,
-                Permission::Insert,
-                Permission::Update,
-                Permission::Delete,
-            ]
-            .into_iter()
-            .collect(),
+            rules: vec![
+                Self::subtree_rule(Permission::Select),
+                Self::subtree_rule(Permission::Insert),
+                Self::subtree_rule(Permission::Update),
+                Self::subtree_rule(Permission::Delete),
+            ],
             inherits_from: Vec::new(),
         };
 
@@ -91,14 +155,14 @@ impl RBACManager {
         self.roles.insert("readwrite".to_string(), readwrite_role);
     }
 
-    pub fn create_role(&mut self, name: String, permissions: HashSet<Permission>) -> Result<()> {
+    pub fn create_role(&mut self, name: String, rules: Vec<PermRule>) -> Result<()> {
         if self.roles.contains_key(&name) {
             anyhow::bail!("Role already exists: {}", name);
         }
 
         let role = Role {
             name: name.clone(),
-            permissions,
+            rules,
             inherits_from: Vec::new(),
         };
 
@@ -106,23 +170,25 @@ impl RBACManager {
         Ok(())
     }
 
-    pub fn grant_permission(&mut self, role_name: &str, permission: Permission) -> Result<()> {
+    pub fn grant_permission(&mut self, role_name: &str, rule: PermRule) -> Result<()> {
         let role = self
             .roles
             .get_mut(role_name)
             .ok_or_else(|| anyhow::anyhow!("Role not found: {}", role_name))?;
 
-        role.permissions.insert(permission);
+        if !role.rules.contains(&rule) {
+            role.rules.push(rule);
+        }
         Ok(())
     }
 
-    pub fn revoke_permission(&mut self, role_name: &str, permission: &Permission) -> Result<()> {
+    pub fn revoke_permission(&mut self, role_name: &str, rule: &PermRule) -> Result<()> {
         let role = self
             .roles
             .get_mut(role_name)
             .ok_or_else(|| anyhow::anyhow!("Role not found: {}", role_name))?;
 
-        role.permissions.remove(permission);
+        role.rules.retain(|r| r != rule);
         Ok(())
     }
 
@@ -172,7 +238,7 @@ impl RBACManager {
         Ok(())
     }
 
-    pub fn check_permission(&self, username: &str, permission: &Permission) -> bool {
+    pub fn check_permission(&self, username: &str, permission: &Permission, resource_path: &str) -> bool {
         let user = match self.users.get(username) {
             Some(u) => u,
             None => return false,
@@ -180,7 +246,7 @@ impl RBACManager {
 
         for role_name in &user.roles {
             if let Some(role) = self.roles.get(role_name) {
-                if self.role_has_permission(role, permission) {
+                if self.role_has_permission(role, permission, resource_path) {
                     return true;
                 }
             }
@@ -189,20 +255,12 @@ impl RBACManager {
         false
     }
 
-    fn role_has_permission(&self, role: &Role, permission: &Permission) -> bool {
-        if role.permissions.contains(permission) {
-            return true;
-        }
-
-        for inherited_role_name in &role.inherits_from {
-            if let Some(inherited_role) = self.roles.get(inherited_role_name) {
-                if self.role_has_permission(inherited_role, permission) {
-                    return true;
-                }
-            }
-        }
+    fn role_has_permission(&self, role: &Role, permission: &Permission, resource_path: &str) -> bool {
+        let mut visited = HashSet::new();
+        let mut rules = HashSet::new();
+        self.collect_role_permissions(role, &mut rules, &mut visited);
 
-        false
+        rules.iter().any(|rule| rule.matches(permission, resource_path))
     }
 
     pub fn list_users(&self) -> Vec<String> {
@@ -213,26 +271,34 @@ impl RBACManager {
         self.roles.keys().cloned().collect()
     }
 
-    pub fn get_user_permissions(&self, username: &str) -> HashSet<Permission> {
-        let mut permissions = HashSet::new();
+    pub fn get_user_permissions(&self, username: &str) -> HashSet<PermRule> {
+        let mut rules = HashSet::new();
 
         if let Some(user) = self.users.get(username) {
             for role_name in &user.roles {
                 if let Some(role) = self.roles.get(role_name) {
-                    self.collect_role_permissions(role, &mut permissions);
+                    let mut visited = HashSet::new();
+                    self.collect_role_permissions(role, &mut rules, &mut visited);
                 }
             }
         }
 
-        permissions
+        rules
     }
 
-    fn collect_role_permissions(&self, role: &Role, permissions: &mut HashSet<Permission>) {
-        permissions.extend(role.permissions.iter().cloned());
+    /// Tallies `role`'s own rules plus every rule inherited up the
+    /// `inherits_from` tree into `rules`, tracking `visited` role names so a
+    /// cycle in the inheritance graph can't recurse forever.
+    fn collect_role_permissions(&self, role: &Role, rules: &mut HashSet<PermRule>, visited: &mut HashSet<String>) {
+        if !visited.insert(role.name.clone()) {
+            return;
+        }
+
+        rules.extend(role.rules.iter().cloned());
 
         for inherited_role_name in &role.inherits_from {
             if let Some(inherited_role) = self.roles.get(inherited_role_name) {
-                self.collect_role_permissions(inherited_role, permissions);
+                self.collect_role_permissions(inherited_role, rules, visited);
             }
         }
     }
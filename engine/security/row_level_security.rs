@@ -1,8 +1,10 @@
+use crate::execution::expression::ExpressionEvaluator;
 use crate::execution::tuple::Tuple;
 use crate::language::intent::FilterIntent;
 use anyhow::Result;
 use std::collections::HashMap;
 
+#[derive(Debug, Clone)]
 pub struct RowLevelSecurityPolicy {
     pub table: String,
     pub policy_name: String,
@@ -10,15 +12,14 @@ pub struct RowLevelSecurityPolicy {
     pub roles: Vec<String>,
 }
 
+#[derive(Debug, Clone, Default)]
 pub struct RLSManager {
     policies: HashMap<String, Vec<RowLevelSecurityPolicy>>,
 }
 
 impl RLSManager {
     pub fn new() -> Self {
-        Self {
-            policies: HashMap::new(),
-        }
+        Self::default()
     }
 
     pub fn add_policy(&mut self, policy: RowLevelSecurityPolicy) {
@@ -69,8 +70,14 @@ impl RLSManager {
         Ok(filtered)
     }
 
-    fn evaluate_policy_filter(&self, _filter: &FilterIntent, _tuple: &Tuple) -> bool {
-        true
+    /// Evaluates a policy's `USING` predicate against a row, same as a
+    /// `WHERE` clause would: a row survives only if the predicate evaluates
+    /// to true. A predicate that errors (e.g. a type mismatch) fails closed,
+    /// since a broken policy should hide rows rather than leak them.
+    fn evaluate_policy_filter(&self, filter: &FilterIntent, tuple: &Tuple) -> bool {
+        ExpressionEvaluator::new()
+            .evaluate_filter(filter, tuple)
+            .unwrap_or(false)
     }
 
     pub fn list_policies(&self, table: &str) -> Vec<String> {
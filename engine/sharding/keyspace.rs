@@ -1,9 +1,15 @@
-use blake3::Hash;
+use anyhow::Result;
 use serde::{Deserialize, Serialize};
+use std::cmp::Ordering;
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord, Serialize, Deserialize)]
 pub struct ShardId(pub u32);
 
+/// A half-open `[start, end)` span of key bytes owned by `shard_id`. An
+/// empty `start` stands for "no lower bound" (the span begins at the very
+/// first key) and an empty `end` stands for "no upper bound" (the span
+/// never ends) rather than literal zero-length bounds, since lexicographic
+/// comparison alone can't express either infinity.
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct KeyRange {
     pub start: Vec<u8>,
@@ -11,12 +17,48 @@ pub struct KeyRange {
     pub shard_id: ShardId,
 }
 
+impl KeyRange {
+    fn contains(&self, key: &[u8]) -> bool {
+        let after_start = self.start.is_empty() || key >= self.start.as_slice();
+        let before_end = self.end.is_empty() || key < self.end.as_slice();
+        after_start && before_end
+    }
+
+    /// Whether this range shares any keys with the query span
+    /// `[query_start, query_end)`, where `None` on either side means that
+    /// side of the query is unbounded.
+    fn overlaps(&self, query_start: Option<&[u8]>, query_end: Option<&[u8]>) -> bool {
+        let starts_before_query_end = match query_end {
+            Some(query_end) => self.start.is_empty() || self.start.as_slice() < query_end,
+            None => true,
+        };
+        let ends_after_query_start = match query_start {
+            Some(query_start) => self.end.is_empty() || query_start < self.end.as_slice(),
+            None => true,
+        };
+        starts_before_query_end && ends_after_query_start
+    }
+}
+
+/// Whether `Keyspace::lookup` distributes keys by range or by hash.
+/// `Hash` spreads keys uniformly but destroys key locality (a scan over a
+/// contiguous key range has to fan out to every shard); `Range` preserves
+/// locality at the cost of needing rebalancing as key distribution shifts.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum KeyspaceMode {
+    Hash,
+    Range,
+}
+
 pub struct Keyspace {
     pub ranges: Vec<KeyRange>,
     pub num_shards: usize,
+    mode: KeyspaceMode,
 }
 
 impl Keyspace {
+    /// Uniform hash-partitioned keyspace: `ranges` carry no real bounds, so
+    /// `lookup` falls back to `blake3(key) % num_shards`.
     pub fn new(num_shards: usize) -> Self {
         let mut ranges = Vec::new();
 
@@ -28,17 +70,157 @@ impl Keyspace {
             });
         }
 
-        Self { ranges, num_shards }
+        Self {
+            ranges,
+            num_shards,
+            mode: KeyspaceMode::Hash,
+        }
+    }
+
+    /// Range-partitioned keyspace: `splits` are the boundary keys between
+    /// shards, turned into `len(splits) + 1` contiguous `[start, end)`
+    /// spans ordered by `start`, shard `0` through `len(splits)`.
+    pub fn new_ranges(mut splits: Vec<Vec<u8>>) -> Self {
+        splits.sort();
+        splits.dedup();
+
+        let mut ranges = Vec::with_capacity(splits.len() + 1);
+        let mut start: Vec<u8> = Vec::new();
+
+        for (i, split) in splits.iter().enumerate() {
+            ranges.push(KeyRange {
+                start: start.clone(),
+                end: split.clone(),
+                shard_id: ShardId(i as u32),
+            });
+            start = split.clone();
+        }
+
+        ranges.push(KeyRange {
+            start,
+            end: Vec::new(),
+            shard_id: ShardId(splits.len() as u32),
+        });
+
+        let num_shards = ranges.len();
+        Self {
+            ranges,
+            num_shards,
+            mode: KeyspaceMode::Range,
+        }
     }
 
     pub fn lookup(&self, key: &[u8]) -> ShardId {
-        let hash = blake3::hash(key);
-        let hash_bytes = hash.as_bytes();
-        let hash_u64 = u64::from_le_bytes(hash_bytes[0..8].try_into().unwrap());
-        ShardId((hash_u64 % self.num_shards as u64) as u32)
+        match self.mode {
+            KeyspaceMode::Hash => {
+                let hash = blake3::hash(key);
+                let hash_bytes = hash.as_bytes();
+                let hash_u64 = u64::from_le_bytes(hash_bytes[0..8].try_into().unwrap());
+                ShardId((hash_u64 % self.num_shards as u64) as u32)
+            }
+            KeyspaceMode::Range => {
+                // `ranges` is sorted and contiguous, so binary search can
+                // walk straight to the span containing `key` instead of
+                // scanning every range.
+                let idx = self
+                    .ranges
+                    .binary_search_by(|range| {
+                        if !range.start.is_empty() && key < range.start.as_slice() {
+                            Ordering::Greater
+                        } else if !range.end.is_empty() && key >= range.end.as_slice() {
+                            Ordering::Less
+                        } else {
+                            Ordering::Equal
+                        }
+                    })
+                    .unwrap_or(0);
+                self.ranges[idx].shard_id
+            }
+        }
+    }
+
+    /// The shards overlapping the key span `[start, end)` (either bound
+    /// `None` for unbounded), for routing range scans. In `Hash` mode a
+    /// contiguous key range can land on any shard, so every shard is
+    /// returned.
+    pub fn lookup_range(&self, start: Option<&[u8]>, end: Option<&[u8]>) -> Vec<ShardId> {
+        match self.mode {
+            KeyspaceMode::Hash => self.ranges.iter().map(|r| r.shard_id).collect(),
+            KeyspaceMode::Range => self
+                .ranges
+                .iter()
+                .filter(|r| r.overlaps(start, end))
+                .map(|r| r.shard_id)
+                .collect(),
+        }
     }
 
     pub fn get_shard_range(&self, shard_id: ShardId) -> Option<&KeyRange> {
         self.ranges.iter().find(|r| r.shard_id == shard_id)
     }
-      }
+
+    /// Splits the range owned by `shard_id` at `split_at` into two
+    /// contiguous spans, the second handed to a newly minted shard. Only
+    /// meaningful in `Range` mode, since `Hash` mode's ranges carry no real
+    /// bounds to split.
+    pub fn split_range(&mut self, shard_id: ShardId, split_at: Vec<u8>) -> Result<ShardId> {
+        if self.mode != KeyspaceMode::Range {
+            anyhow::bail!("split_range requires a range-partitioned keyspace");
+        }
+
+        let index = self
+            .ranges
+            .iter()
+            .position(|r| r.shard_id == shard_id)
+            .ok_or_else(|| anyhow::anyhow!("Unknown shard: {:?}", shard_id))?;
+
+        let range = &self.ranges[index];
+        if !range.contains(&split_at) || range.start == split_at {
+            anyhow::bail!("split point must fall strictly inside the shard's range");
+        }
+
+        let new_shard_id = ShardId(self.ranges.iter().map(|r| r.shard_id.0 + 1).max().unwrap_or(0));
+        let new_range = KeyRange {
+            start: split_at.clone(),
+            end: range.end.clone(),
+            shard_id: new_shard_id,
+        };
+
+        self.ranges[index].end = split_at;
+        self.ranges.insert(index + 1, new_range);
+        self.num_shards += 1;
+
+        Ok(new_shard_id)
+    }
+
+    /// Merges the adjacent ranges owned by `left` and `right` (in range
+    /// order) back into a single range under `left`'s shard id, the inverse
+    /// of `split_range`.
+    pub fn merge_ranges(&mut self, left: ShardId, right: ShardId) -> Result<()> {
+        if self.mode != KeyspaceMode::Range {
+            anyhow::bail!("merge_ranges requires a range-partitioned keyspace");
+        }
+
+        let left_index = self
+            .ranges
+            .iter()
+            .position(|r| r.shard_id == left)
+            .ok_or_else(|| anyhow::anyhow!("Unknown shard: {:?}", left))?;
+        let right_index = self
+            .ranges
+            .iter()
+            .position(|r| r.shard_id == right)
+            .ok_or_else(|| anyhow::anyhow!("Unknown shard: {:?}", right))?;
+
+        if right_index != left_index + 1 {
+            anyhow::bail!("merge_ranges requires two adjacent shards");
+        }
+
+        let merged_end = self.ranges[right_index].end.clone();
+        self.ranges[left_index].end = merged_end;
+        self.ranges.remove(right_index);
+        self.num_shards -= 1;
+
+        Ok(())
+    }
+}
@@ -1,5 +1,5 @@
+use crate::language::intent::{ComparisonOp, ConstantValue, ExpressionIntent, FilterIntent, Intent, LogicalOp};
 use crate::sharding::keyspace::{Keyspace, ShardId};
-use crate::language::intent::Intent;
 use std::collections::HashMap;
 
 pub struct Router {
@@ -36,8 +36,132 @@ impl Router {
         }
     }
 
-    pub fn route(&self, _intent: &Intent) -> Vec<ShardId> {
-        self.shard_map.keys().copied().collect()
+    /// Builds a router over a range-partitioned keyspace (see
+    /// `Keyspace::new_ranges`), preserving key locality for point/range
+    /// predicates instead of spreading every key uniformly by hash.
+    pub fn new_ranged(splits: Vec<Vec<u8>>) -> Self {
+        let keyspace = Keyspace::new_ranges(splits);
+        let mut shard_map = HashMap::new();
+
+        for i in 0..keyspace.num_shards {
+            shard_map.insert(
+                ShardId(i as u32),
+                ShardInfo {
+                    shard_id: ShardId(i as u32),
+                    node_id: (i % 3) as u32,
+                    is_primary: true,
+                },
+            );
+        }
+
+        Self {
+            keyspace,
+            shard_map,
+        }
+    }
+
+    /// The minimal set of shards that could hold rows matching `intent`:
+    /// narrowed from its key-column predicates when they bound the key to a
+    /// point or range, falling back to every shard when the predicate can't
+    /// be narrowed (no filter, an `OR`/`NOT`-shaped filter, or a predicate
+    /// not anchored to a constant).
+    pub fn route(&self, intent: &Intent) -> Vec<ShardId> {
+        let filter = match intent {
+            Intent::Retrieve { filter: Some(filter), .. } => filter,
+            Intent::Mutate { filter: Some(filter), .. } => filter,
+            _ => return self.all_shards(),
+        };
+
+        let (lower, upper) = Self::key_bounds(filter);
+        if lower.is_none() && upper.is_none() {
+            return self.all_shards();
+        }
+
+        self.keyspace.lookup_range(lower.as_deref(), upper.as_deref())
+    }
+
+    /// Walks `filter` for `column <op> constant` comparisons (in either
+    /// order) and combines them into a single `[lower, upper)` bound on the
+    /// key. Only `AND`-connected conjuncts are safe to combine this way: an
+    /// `OR` or `NOT` could match rows outside any bound inferred from one
+    /// branch, so those fall back to no bound at all.
+    fn key_bounds(filter: &FilterIntent) -> (Option<Vec<u8>>, Option<Vec<u8>>) {
+        match filter {
+            FilterIntent::Always | FilterIntent::Never => (None, None),
+            FilterIntent::Comparison { op, left, right } => Self::bounds_from_comparison(op, left, right),
+            FilterIntent::Logical { op: LogicalOp::And, operands } => {
+                let mut lower = None;
+                let mut upper = None;
+                for operand in operands {
+                    let (operand_lower, operand_upper) = Self::key_bounds(operand);
+                    lower = Self::tighter_lower(lower, operand_lower);
+                    upper = Self::tighter_upper(upper, operand_upper);
+                }
+                (lower, upper)
+            }
+            FilterIntent::Logical { .. } => (None, None),
+        }
+    }
+
+    fn bounds_from_comparison(
+        op: &ComparisonOp,
+        left: &ExpressionIntent,
+        right: &ExpressionIntent,
+    ) -> (Option<Vec<u8>>, Option<Vec<u8>>) {
+        let (op, constant) = match (left, right) {
+            (ExpressionIntent::Column(_), ExpressionIntent::Constant(value)) => (op.clone(), value),
+            (ExpressionIntent::Constant(value), ExpressionIntent::Column(_)) => (Self::flip(op), value),
+            _ => return (None, None),
+        };
+
+        let bytes = Self::constant_bytes(constant);
+        match op {
+            ComparisonOp::Equal => (Some(bytes.clone()), Some(Self::immediate_successor(bytes))),
+            ComparisonOp::LessThan => (None, Some(bytes)),
+            ComparisonOp::LessThanOrEqual => (None, Some(Self::immediate_successor(bytes))),
+            ComparisonOp::GreaterThan => (Some(Self::immediate_successor(bytes)), None),
+            ComparisonOp::GreaterThanOrEqual => (Some(bytes), None),
+            ComparisonOp::NotEqual => (None, None),
+        }
+    }
+
+    fn flip(op: &ComparisonOp) -> ComparisonOp {
+        match op {
+            ComparisonOp::LessThan => ComparisonOp::GreaterThan,
+            ComparisonOp::LessThanOrEqual => ComparisonOp::GreaterThanOrEqual,
+            ComparisonOp::GreaterThan => ComparisonOp::LessThan,
+            ComparisonOp::GreaterThanOrEqual => ComparisonOp::LessThanOrEqual,
+            ComparisonOp::Equal => ComparisonOp::Equal,
+            ComparisonOp::NotEqual => ComparisonOp::NotEqual,
+        }
+    }
+
+    /// The lexicographically smallest byte string strictly greater than
+    /// `bytes`, used to turn an inclusive bound into the equivalent
+    /// exclusive one for a half-open `[start, end)` range.
+    fn immediate_successor(mut bytes: Vec<u8>) -> Vec<u8> {
+        bytes.push(0);
+        bytes
+    }
+
+    fn constant_bytes(value: &ConstantValue) -> Vec<u8> {
+        format!("{:?}", value).into_bytes()
+    }
+
+    fn tighter_lower(a: Option<Vec<u8>>, b: Option<Vec<u8>>) -> Option<Vec<u8>> {
+        match (a, b) {
+            (Some(a), Some(b)) => Some(if a >= b { a } else { b }),
+            (Some(a), None) | (None, Some(a)) => Some(a),
+            (None, None) => None,
+        }
+    }
+
+    fn tighter_upper(a: Option<Vec<u8>>, b: Option<Vec<u8>>) -> Option<Vec<u8>> {
+        match (a, b) {
+            (Some(a), Some(b)) => Some(if a <= b { a } else { b }),
+            (Some(a), None) | (None, Some(a)) => Some(a),
+            (None, None) => None,
+        }
     }
 
     pub fn route_key(&self, key: &[u8]) -> ShardId {
@@ -0,0 +1,51 @@
+use anyhow::Result;
+use std::collections::HashMap;
+use std::future::Future;
+use std::pin::Pin;
+
+#[derive(Debug, Clone, Default)]
+pub struct ColumnStatistics {
+    pub min: Option<String>,
+    pub max: Option<String>,
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct TableStatistics {
+    pub row_count: u64,
+    pub columns: HashMap<String, ColumnStatistics>,
+    pub indexes: Vec<String>,
+}
+
+impl TableStatistics {
+    pub fn has_index(&self, column: &str) -> bool {
+        self.indexes.iter().any(|idx| idx == column)
+    }
+}
+
+/// `TableProvider` is the planner-facing view of a storage backend: given a
+/// table and the columns a query needs, it reports row counts, per-column
+/// min/max bounds, and which columns have a usable index, without the
+/// planner blocking on the storage layer while it gathers that metadata.
+pub trait TableProvider {
+    fn scan(
+        &self,
+        table: &str,
+        columns: &[String],
+    ) -> Pin<Box<dyn Future<Output = Result<TableStatistics>> + Send + '_>>;
+}
+
+/// The storage surface the query executor and the replicated log depend on.
+/// `ffi::storage::StorageEngine` (the native library backend) and
+/// `storage::memory::InMemoryStorageBackend` (a pure-Rust backend with no
+/// native dependency, for tests and embedded/single-node use) both implement
+/// this, so callers hold an `Arc<dyn StorageBackend>` rather than naming a
+/// concrete engine.
+pub trait StorageBackend: TableProvider + Send + Sync {
+    fn create_table(&self, table_name: &str, schema: &str) -> Result<()>;
+    fn insert_row(&self, table_name: &str, data: &[u8]) -> Result<u64>;
+    fn update_rows(&self, table_name: &str, predicate: &str, data: &[u8]) -> Result<usize>;
+    fn delete_rows(&self, table_name: &str, predicate: &str) -> Result<usize>;
+    fn checkpoint(&self) -> Result<()>;
+    fn wal_flush(&self) -> Result<()>;
+    fn recover(&self) -> Result<()>;
+}
@@ -0,0 +1,173 @@
+use crate::storage::backend::{StorageBackend, TableProvider, TableStatistics};
+use anyhow::{Context, Result};
+use dashmap::DashMap;
+use std::collections::BTreeMap;
+use std::future::Future;
+use std::pin::Pin;
+use std::sync::Mutex;
+
+/// One mutation appended to `InMemoryStorageBackend`'s WAL. There's no
+/// separate durable log to recover from on restart (everything lives in the
+/// process), so this only exists to bound how long `checkpoint` lets it grow
+/// before discarding it, the same "flush then checkpoint" lifecycle
+/// `ffi::storage::StorageEngine` follows against the native WAL.
+#[derive(Debug, Clone)]
+enum WalRecord {
+    Insert { table: String, row_id: u64 },
+    Update { table: String, count: usize },
+    Delete { table: String, count: usize },
+}
+
+#[derive(Default)]
+struct Table {
+    rows: BTreeMap<u64, Vec<u8>>,
+    next_row_id: u64,
+}
+
+/// A pure-Rust `StorageBackend` with no native dependency: a `BTreeMap` of
+/// rows per table plus a `Vec` WAL, all held in memory. Intended for tests
+/// and single-node deployments that don't want to link the C storage
+/// library, not as a durability story — nothing here survives a restart.
+///
+/// Rows are matched against `predicate` during `update_rows`/`delete_rows`
+/// by the query executor already handing opaque, engine-specific predicate
+/// strings to storage (see `execution::engine::ExecutionEngine`, which
+/// `Debug`-formats the filter for the native engine to interpret). This
+/// backend has no native interpreter to hand that string to, so it treats
+/// every row in the table as matching, same as `TableProvider::scan`'s
+/// placeholder statistics stand in for a real catalog query.
+pub struct InMemoryStorageBackend {
+    tables: DashMap<String, Table>,
+    wal: Mutex<Vec<WalRecord>>,
+}
+
+impl InMemoryStorageBackend {
+    pub fn new() -> Self {
+        Self {
+            tables: DashMap::new(),
+            wal: Mutex::new(Vec::new()),
+        }
+    }
+
+    fn record(&self, entry: WalRecord) {
+        self.wal.lock().unwrap().push(entry);
+    }
+}
+
+impl Default for InMemoryStorageBackend {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl StorageBackend for InMemoryStorageBackend {
+    fn create_table(&self, table_name: &str, schema: &str) -> Result<()> {
+        tracing::debug!(
+            "in-memory: creating table '{}' with schema: {}",
+            table_name,
+            schema
+        );
+        self.tables.insert(
+            table_name.to_string(),
+            Table {
+                rows: BTreeMap::new(),
+                next_row_id: 1,
+            },
+        );
+        Ok(())
+    }
+
+    fn insert_row(&self, table_name: &str, data: &[u8]) -> Result<u64> {
+        let mut table = self
+            .tables
+            .get_mut(table_name)
+            .with_context(|| format!("Table '{}' does not exist", table_name))?;
+
+        let row_id = table.next_row_id;
+        table.next_row_id += 1;
+        table.rows.insert(row_id, data.to_vec());
+
+        self.record(WalRecord::Insert {
+            table: table_name.to_string(),
+            row_id,
+        });
+
+        Ok(row_id)
+    }
+
+    fn update_rows(&self, table_name: &str, _predicate: &str, data: &[u8]) -> Result<usize> {
+        let mut table = self
+            .tables
+            .get_mut(table_name)
+            .with_context(|| format!("Table '{}' does not exist", table_name))?;
+
+        for row in table.rows.values_mut() {
+            *row = data.to_vec();
+        }
+        let count = table.rows.len();
+
+        self.record(WalRecord::Update {
+            table: table_name.to_string(),
+            count,
+        });
+
+        Ok(count)
+    }
+
+    fn delete_rows(&self, table_name: &str, _predicate: &str) -> Result<usize> {
+        let mut table = self
+            .tables
+            .get_mut(table_name)
+            .with_context(|| format!("Table '{}' does not exist", table_name))?;
+
+        let count = table.rows.len();
+        table.rows.clear();
+
+        self.record(WalRecord::Delete {
+            table: table_name.to_string(),
+            count,
+        });
+
+        Ok(count)
+    }
+
+    fn checkpoint(&self) -> Result<()> {
+        self.wal.lock().unwrap().clear();
+        Ok(())
+    }
+
+    fn wal_flush(&self) -> Result<()> {
+        Ok(())
+    }
+
+    fn recover(&self) -> Result<()> {
+        Ok(())
+    }
+}
+
+impl TableProvider for InMemoryStorageBackend {
+    fn scan(
+        &self,
+        table: &str,
+        columns: &[String],
+    ) -> Pin<Box<dyn Future<Output = Result<TableStatistics>> + Send + '_>> {
+        let table = table.to_string();
+        let columns = columns.to_vec();
+
+        Box::pin(async move {
+            let row_count = self.tables.get(&table).map(|t| t.rows.len() as u64).unwrap_or(0);
+
+            tracing::debug!(
+                "in-memory: gathering statistics for table '{}' ({} columns requested)",
+                table,
+                columns.len()
+            );
+
+            Ok(TableStatistics {
+                row_count,
+                columns: std::collections::HashMap::new(),
+                indexes: Vec::new(),
+            })
+        })
+    }
+}
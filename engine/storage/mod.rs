@@ -0,0 +1,5 @@
+pub mod backend;
+pub mod memory;
+
+pub use backend::*;
+pub use memory::*;
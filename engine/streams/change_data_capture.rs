@@ -1,4 +1,6 @@
 use crate::execution::tuple::Tuple;
+use crate::monitoring::metrics::StreamingMetrics;
+use crate::replication::log::{LogEntry, LogEntryType, ReplicationLog};
 use anyhow::Result;
 use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
@@ -6,6 +8,8 @@ use std::collections::HashMap;
 use std::sync::Arc;
 use tokio::sync::{mpsc, RwLock};
 
+const CDC_CHANNEL_CAPACITY: usize = 1000;
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub enum ChangeType {
     Insert,
@@ -30,25 +34,47 @@ pub struct CDCSubscription {
     pub tables: Vec<String>,
     pub operations: Vec<ChangeType>,
     pub filter: Option<String>,
+    /// Resumes a previously disconnected consumer: on `subscribe`, every
+    /// persisted change with `change_id > start_after` is replayed before
+    /// the subscription switches to the live feed, giving exactly-once
+    /// catch-up instead of a gap.
+    pub start_after: Option<u64>,
 }
 
 pub struct ChangeDataCapture {
     subscribers: Arc<RwLock<HashMap<String, mpsc::Sender<ChangeEvent>>>>,
     subscriptions: Arc<RwLock<HashMap<String, CDCSubscription>>>,
     next_change_id: Arc<RwLock<u64>>,
+    log: Arc<RwLock<ReplicationLog>>,
+    metrics: Arc<StreamingMetrics>,
 }
 
 impl ChangeDataCapture {
-    pub fn new() -> Self {
+    pub fn new(metrics: Arc<StreamingMetrics>) -> Self {
         Self {
             subscribers: Arc::new(RwLock::new(HashMap::new())),
             subscriptions: Arc::new(RwLock::new(HashMap::new())),
             next_change_id: Arc::new(RwLock::new(1)),
+            log: Arc::new(RwLock::new(ReplicationLog::new())),
+            metrics,
         }
     }
 
     pub async fn subscribe(&self, subscription: CDCSubscription) -> Result<mpsc::Receiver<ChangeEvent>> {
-        let (tx, rx) = mpsc::channel(1000);
+        let (tx, rx) = mpsc::channel(CDC_CHANNEL_CAPACITY);
+
+        if let Some(start_after) = subscription.start_after {
+            let backlog = self.replay(None, None).await;
+            for event in backlog.into_iter().filter(|e| e.change_id > start_after) {
+                if !subscription.tables.contains(&event.table) {
+                    continue;
+                }
+                if !Self::matches_operation(&subscription.operations, &event.change_type) {
+                    continue;
+                }
+                tx.send(event).await.ok();
+            }
+        }
 
         self.subscribers.write().await.insert(subscription.id.clone(), tx);
         self.subscriptions.write().await.insert(subscription.id.clone(), subscription);
@@ -85,6 +111,18 @@ impl ChangeDataCapture {
             transaction_id,
         };
 
+        self.metrics
+            .changes_emitted
+            .increment(&format!("{}:{:?}", table, change_type));
+
+        let entry = LogEntry {
+            term: 0,
+            index: change_id,
+            entry_type: LogEntryType::Write,
+            data: serde_json::to_vec(&event)?,
+        };
+        self.log.write().await.append(entry);
+
         let subscriptions = self.subscriptions.read().await;
         let subscribers = self.subscribers.read().await;
 
@@ -98,7 +136,16 @@ impl ChangeDataCapture {
             }
 
             if let Some(tx) = subscribers.get(sub_id) {
-                tx.send(event.clone()).await.ok();
+                // Backpressure on this channel is the subscriber falling
+                // behind; `capacity()` reports the permits still free, so
+                // `CDC_CHANNEL_CAPACITY - capacity()` is how many buffered
+                // events it hasn't drained yet.
+                let lag = CDC_CHANNEL_CAPACITY as i64 - tx.capacity() as i64;
+                self.metrics.set_subscriber_lag(sub_id, lag);
+
+                if tx.send(event.clone()).await.is_err() {
+                    self.metrics.subscriber_send_failures.increment(sub_id);
+                }
             }
         }
 
@@ -113,13 +160,29 @@ impl ChangeDataCapture {
         operations.iter().any(|op| std::mem::discriminant(op) == std::mem::discriminant(change_type))
     }
 
+    /// Replays every persisted `ChangeEvent` matching `table`/`since`, in
+    /// the order they were originally emitted.
+    async fn replay(&self, table: Option<String>, since: Option<DateTime<Utc>>) -> Vec<ChangeEvent> {
+        self.log
+            .read()
+            .await
+            .entries()
+            .iter()
+            .filter_map(|entry| serde_json::from_slice::<ChangeEvent>(&entry.data).ok())
+            .filter(|event| table.as_ref().is_none_or(|t| &event.table == t))
+            .filter(|event| since.is_none_or(|s| event.timestamp > s))
+            .collect()
+    }
+
     pub async fn get_change_log(
         &self,
         table: Option<String>,
         since: Option<DateTime<Utc>>,
         limit: usize,
     ) -> Vec<ChangeEvent> {
-        Vec::new()
+        let mut events = self.replay(table, since).await;
+        events.truncate(limit);
+        events
     }
 
     pub async fn export_changes(
@@ -127,9 +190,20 @@ impl ChangeDataCapture {
         format: &str,
         table: Option<String>,
     ) -> Result<String> {
+        let events = self.replay(table, None).await;
+
         match format {
-            "json" => Ok(serde_json::to_string_pretty(&Vec::<ChangeEvent>::new())?),
-            "csv" => Ok("change_id,change_type,table,timestamp\n".to_string()),
+            "json" => Ok(serde_json::to_string_pretty(&events)?),
+            "csv" => {
+                let mut out = String::from("change_id,change_type,table,timestamp\n");
+                for event in &events {
+                    out.push_str(&format!(
+                        "{},{:?},{},{}\n",
+                        event.change_id, event.change_type, event.table, event.timestamp
+                    ));
+                }
+                Ok(out)
+            }
             _ => anyhow::bail!("Unsupported format: {}", format),
         }
     }
@@ -1,6 +1,14 @@
-use crate::execution::tuple::Tuple;
+use crate::execution::expression::ExpressionEvaluator;
+use crate::execution::tuple::{Tuple, Value};
 use crate::language::ast::Statement;
+use crate::language::catalog::Catalog;
+use crate::monitoring::metrics::StreamingMetrics;
+use crate::planner::logical::LogicalPlanner;
+use crate::planner::physical::{PhysicalPlan, PhysicalPlanner};
+use crate::storage::StorageBackend;
+use crate::streams::pub_sub::PubSubBroker;
 use anyhow::Result;
+use chrono::{DateTime, TimeZone, Utc};
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 use std::sync::Arc;
@@ -14,6 +22,7 @@ pub struct ContinuousQuery {
     pub source_table: String,
     pub window_type: WindowType,
     pub window_size: std::time::Duration,
+    pub event_time_column: String,
     pub output_action: OutputAction,
 }
 
@@ -34,13 +43,26 @@ pub enum OutputAction {
 pub struct ContinuousQueryEngine {
     queries: Arc<RwLock<HashMap<String, ContinuousQuery>>>,
     data_streams: Arc<RwLock<HashMap<String, mpsc::Sender<Tuple>>>>,
+    metrics: Arc<StreamingMetrics>,
+    storage: Arc<dyn StorageBackend>,
+    pubsub: Arc<PubSubBroker>,
+    catalog: Arc<RwLock<Catalog>>,
 }
 
 impl ContinuousQueryEngine {
-    pub fn new() -> Self {
+    pub fn new(
+        metrics: Arc<StreamingMetrics>,
+        storage: Arc<dyn StorageBackend>,
+        pubsub: Arc<PubSubBroker>,
+        catalog: Arc<RwLock<Catalog>>,
+    ) -> Self {
         Self {
             queries: Arc::new(RwLock::new(HashMap::new())),
             data_streams: Arc::new(RwLock::new(HashMap::new())),
+            metrics,
+            storage,
+            pubsub,
+            catalog,
         }
     }
 
@@ -52,53 +74,278 @@ impl ContinuousQueryEngine {
 
         self.data_streams.write().await.insert(source_table.clone(), tx);
 
+        let logical_plan = LogicalPlanner::new(self.catalog.read().await.clone()).plan(&cq.query)?;
+        let plan = PhysicalPlanner::new(&self.storage, self.catalog.read().await.clone()).plan(&logical_plan).await?;
+
         let cq_clone = cq.clone();
+        let metrics = self.metrics.clone();
+        let storage = self.storage.clone();
+        let pubsub = self.pubsub.clone();
         tokio::spawn(async move {
-            let mut window_buffer = Vec::new();
-            let mut window_start = std::time::Instant::now();
+            let evaluator = ExpressionEvaluator::new();
+            let mut window_buffer: Vec<Tuple> = Vec::new();
+            let mut watermark: Option<DateTime<Utc>> = None;
+            let mut window_start: Option<DateTime<Utc>> = None;
+            let mut last_emit: Option<DateTime<Utc>> = None;
+            let mut last_tuple_time: Option<DateTime<Utc>> = None;
+
+            let window_size = chrono::Duration::from_std(cq_clone.window_size)
+                .unwrap_or_else(|_| chrono::Duration::zero());
 
             while let Some(tuple) = rx.recv().await {
-                window_buffer.push(tuple);
-
-                let elapsed = window_start.elapsed();
-                if elapsed >= cq_clone.window_size {
-                    Self::process_window(&cq_clone, &window_buffer).await;
-                    
-                    match cq_clone.window_type {
-                        WindowType::Tumbling => {
-                            window_buffer.clear();
-                            window_start = std::time::Instant::now();
+                let event_time = match Self::extract_event_time(&tuple, &cq_clone.event_time_column) {
+                    Some(t) => t,
+                    None => {
+                        tracing::warn!(
+                            "continuous query '{}': tuple missing event-time column '{}', dropping",
+                            cq_clone.name,
+                            cq_clone.event_time_column
+                        );
+                        continue;
+                    }
+                };
+
+                window_start.get_or_insert(event_time);
+                watermark = Some(watermark.map_or(event_time, |w| w.max(event_time)));
+                let watermark = watermark.unwrap();
+
+                match cq_clone.window_type {
+                    WindowType::Session => {
+                        if let Some(last) = last_tuple_time {
+                            if event_time - last > window_size {
+                                Self::flush_window(
+                                    &cq_clone,
+                                    &plan,
+                                    &evaluator,
+                                    std::mem::take(&mut window_buffer),
+                                    &metrics,
+                                    &storage,
+                                    &pubsub,
+                                )
+                                .await;
+                                window_start = Some(event_time);
+                            }
                         }
-                        WindowType::Sliding => {
-                            let slide_amount = cq_clone.window_size / 2;
-                            let cutoff = window_start + slide_amount;
-                            window_start = std::time::Instant::now();
+                        last_tuple_time = Some(event_time);
+                        window_buffer.push(tuple);
+                        metrics.buffered_tuples.add(1);
+                    }
+                    WindowType::Tumbling => {
+                        window_buffer.push(tuple);
+                        metrics.buffered_tuples.add(1);
+
+                        let start = window_start.unwrap();
+                        if watermark - start >= window_size {
+                            Self::flush_window(
+                                &cq_clone,
+                                &plan,
+                                &evaluator,
+                                std::mem::take(&mut window_buffer),
+                                &metrics,
+                                &storage,
+                                &pubsub,
+                            )
+                            .await;
+                            window_start = None;
                         }
-                        WindowType::Session => {
-                            window_buffer.clear();
-                            window_start = std::time::Instant::now();
+                    }
+                    WindowType::Sliding => {
+                        window_buffer.push(tuple);
+                        metrics.buffered_tuples.add(1);
+
+                        // Retain only tuples inside the trailing
+                        // `window_size`, so the buffer always reflects the
+                        // current overlapping window rather than growing
+                        // without bound.
+                        let cutoff = watermark - window_size;
+                        let before = window_buffer.len();
+                        window_buffer.retain(|t| {
+                            Self::extract_event_time(t, &cq_clone.event_time_column)
+                                .map_or(true, |t| t > cutoff)
+                        });
+                        metrics.buffered_tuples.add(window_buffer.len() as i64 - before as i64);
+
+                        let slide = window_size / 2;
+                        let due = last_emit.map_or(true, |last| watermark - last >= slide);
+                        if due {
+                            Self::flush_window(
+                                &cq_clone,
+                                &plan,
+                                &evaluator,
+                                window_buffer.clone(),
+                                &metrics,
+                                &storage,
+                                &pubsub,
+                            )
+                            .await;
+                            last_emit = Some(watermark);
                         }
                     }
                 }
             }
+
+            // Flush whatever is left once the source stream closes, so a
+            // query that stops receiving tuples doesn't silently drop its
+            // final partial window.
+            if !window_buffer.is_empty() {
+                Self::flush_window(&cq_clone, &plan, &evaluator, window_buffer, &metrics, &storage, &pubsub).await;
+            }
         });
 
         self.queries.write().await.insert(query_id, cq);
         Ok(())
     }
 
-    async fn process_window(cq: &ContinuousQuery, window: &[Tuple]) {
+    /// Reads a tuple's event-time column as either epoch-millis (`Integer`)
+    /// or an RFC3339 timestamp (`String`); any other representation or a
+    /// missing column means the tuple can't be placed in a window.
+    fn extract_event_time(tuple: &Tuple, column: &str) -> Option<DateTime<Utc>> {
+        match tuple.get(column)? {
+            Value::Integer(millis) => Utc.timestamp_millis_opt(*millis).single(),
+            Value::String(s) => DateTime::parse_from_rfc3339(s).ok().map(|dt| dt.with_timezone(&Utc)),
+            _ => None,
+        }
+    }
+
+    async fn flush_window(
+        cq: &ContinuousQuery,
+        plan: &PhysicalPlan,
+        evaluator: &ExpressionEvaluator,
+        window: Vec<Tuple>,
+        metrics: &Arc<StreamingMetrics>,
+        storage: &Arc<dyn StorageBackend>,
+        pubsub: &Arc<PubSubBroker>,
+    ) {
+        let process_start = std::time::Instant::now();
+        let window_len = window.len();
+
+        let rows = match Self::apply_plan(plan, window, evaluator) {
+            Ok(rows) => rows,
+            Err(e) => {
+                tracing::warn!("continuous query '{}': failed to apply plan: {}", cq.name, e);
+                return;
+            }
+        };
+
+        metrics.record_window_emitted(process_start.elapsed().as_secs_f64());
+        metrics.buffered_tuples.add(-(window_len as i64));
+
         tracing::info!(
-            "Processing window for continuous query '{}': {} tuples",
+            "continuous query '{}': window of {} input tuples produced {} output rows",
             cq.name,
-            window.len()
+            window_len,
+            rows.len()
         );
+
+        if let Err(e) = Self::route_output(&cq.output_action, rows, storage, pubsub).await {
+            tracing::warn!("continuous query '{}': failed to route output: {}", cq.name, e);
+        }
+    }
+
+    /// Runs `plan` over an explicit window buffer rather than against
+    /// storage: the query was planned once at registration against the
+    /// source table's schema, but each firing replaces the leaf scan with
+    /// the tuples actually collected in this window (`SeqScan`/`IndexScan`
+    /// only ever produce mock rows, so they can't be re-executed here).
+    fn apply_plan(plan: &PhysicalPlan, window: Vec<Tuple>, evaluator: &ExpressionEvaluator) -> Result<Vec<Tuple>> {
+        match plan {
+            PhysicalPlan::SeqScan { .. } | PhysicalPlan::IndexScan { .. } => Ok(window),
+            PhysicalPlan::Filter { predicate, input } => {
+                let tuples = Self::apply_plan(input, window, evaluator)?;
+                tuples
+                    .into_iter()
+                    .map(|t| Ok((t.clone(), evaluator.evaluate_filter(predicate, &t)?)))
+                    .collect::<Result<Vec<_>>>()
+                    .map(|rows| rows.into_iter().filter(|(_, keep)| *keep).map(|(t, _)| t).collect())
+            }
+            PhysicalPlan::Project { columns, input } => {
+                let tuples = Self::apply_plan(input, window, evaluator)?;
+                Ok(tuples
+                    .into_iter()
+                    .map(|tuple| {
+                        let mut projected = Tuple::new();
+                        for col_intent in columns {
+                            match col_intent {
+                                crate::language::intent::ColumnIntent::Named(name) => {
+                                    if let Some(val) = tuple.get(name) {
+                                        projected.insert(name.clone(), val.clone());
+                                    }
+                                }
+                                crate::language::intent::ColumnIntent::All => {
+                                    for (k, v) in &tuple.values {
+                                        projected.insert(k.clone(), v.clone());
+                                    }
+                                }
+                                _ => {}
+                            }
+                        }
+                        projected
+                    })
+                    .collect())
+            }
+            PhysicalPlan::HashAggregate {
+                group_by,
+                aggregates,
+                input,
+            } => {
+                let tuples = Self::apply_plan(input, window, evaluator)?;
+                let mut aggregate = crate::execution::operators::aggregate::HashAggregate::new(
+                    tuples,
+                    group_by.clone(),
+                    aggregates.clone(),
+                )?;
+                let mut results = Vec::new();
+                while let Some(tuple) = aggregate.next()? {
+                    results.push(tuple);
+                }
+                Ok(results)
+            }
+            PhysicalPlan::Limit { count, offset, input } => {
+                let tuples = Self::apply_plan(input, window, evaluator)?;
+                Ok(tuples.into_iter().skip(*offset).take(*count).collect())
+            }
+            other => anyhow::bail!("continuous query plan contains unsupported operator: {:?}", other),
+        }
+    }
+
+    async fn route_output(
+        action: &OutputAction,
+        rows: Vec<Tuple>,
+        storage: &Arc<dyn StorageBackend>,
+        pubsub: &Arc<PubSubBroker>,
+    ) -> Result<()> {
+        if rows.is_empty() {
+            return Ok(());
+        }
+
+        match action {
+            OutputAction::InsertInto(table) => {
+                for tuple in rows {
+                    let tuple_json = serde_json::to_string(&tuple)?;
+                    storage.insert_row(table, tuple_json.as_bytes())?;
+                }
+                storage.wal_flush()?;
+            }
+            OutputAction::Notify(channel) => {
+                let payload = serde_json::to_value(&rows)?;
+                pubsub.publish(channel, payload).await?;
+            }
+            OutputAction::Webhook(url) => {
+                // No HTTP client crate is in this tree's dependency set, so
+                // delivery is logged rather than faked; a real webhook
+                // dispatcher would POST `rows` as JSON to `url` here.
+                tracing::warn!("webhook output to '{}' is not yet implemented, dropping {} rows", url, rows.len());
+            }
+        }
+
+        Ok(())
     }
 
     pub async fn emit_to_stream(&self, table: &str, tuple: Tuple) -> Result<()> {
         let streams = self.data_streams.read().await;
-        
+
         if let Some(tx) = streams.get(table) {
+            self.metrics.tuples_ingested.increment(table);
             tx.send(tuple).await.ok();
         }
 
@@ -113,4 +360,4 @@ impl ContinuousQueryEngine {
     pub async fn list_continuous_queries(&self) -> Vec<ContinuousQuery> {
         self.queries.read().await.values().cloned().collect()
     }
-  }
+}
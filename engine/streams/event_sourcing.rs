@@ -1,10 +1,12 @@
+use crate::ffi::event_log::EventLogEngine;
 use anyhow::Result;
 use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
 use serde_json::Value;
 use std::collections::HashMap;
-use std::sync::Arc;
-use tokio::sync::RwLock;
+use std::fmt;
+use std::sync::{Arc, Mutex};
+use tokio::sync::{broadcast, RwLock};
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Event {
@@ -26,24 +28,335 @@ pub struct Aggregate {
     pub state: Value,
 }
 
+/// A condition on an aggregate's current version that `append_event` must
+/// satisfy before the event is recorded, checked atomically under the same
+/// write lock that applies the append.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Precondition {
+    /// Append regardless of the aggregate's current version.
+    Always,
+    /// The aggregate must not already exist (version `0`).
+    New,
+    /// The aggregate must already exist (version `> 0`).
+    Exists,
+    /// The aggregate's current version must match exactly.
+    ExpectedVersion(u64),
+}
+
+/// Where a `get_events` read should start.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Since {
+    BeginningOfStream,
+    Event(u64),
+}
+
+/// Returned when a `Precondition` doesn't hold for the aggregate's current
+/// version at append time.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PreconditionFailed {
+    pub expected: String,
+    pub actual: u64,
+}
+
+impl fmt::Display for PreconditionFailed {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "precondition failed: expected {}, found version {}",
+            self.expected, self.actual
+        )
+    }
+}
+
+impl std::error::Error for PreconditionFailed {}
+
+/// Folds an `Event` into an aggregate's materialized `state`, keyed by
+/// `aggregate_type` so `EventStore` can dispatch each event to the reducer
+/// that knows how to interpret it.
+pub trait Projector: Send + Sync {
+    fn aggregate_type(&self) -> &str;
+    fn apply(&self, state: Value, event: &Event) -> Value;
+}
+
+/// Default reducer: deep-merges `event.event_data` into `state`, recursing
+/// into matching nested objects, overwriting scalars, and replacing arrays
+/// wholesale rather than merging element-by-element.
+pub struct JsonMergeProjector {
+    aggregate_type: String,
+}
+
+impl JsonMergeProjector {
+    pub fn new(aggregate_type: impl Into<String>) -> Self {
+        Self {
+            aggregate_type: aggregate_type.into(),
+        }
+    }
+
+    fn merge(base: Value, incoming: Value) -> Value {
+        match (base, incoming) {
+            (Value::Object(mut base_map), Value::Object(incoming_map)) => {
+                for (key, incoming_value) in incoming_map {
+                    let merged = match base_map.remove(&key) {
+                        Some(existing) => Self::merge(existing, incoming_value),
+                        None => incoming_value,
+                    };
+                    base_map.insert(key, merged);
+                }
+                Value::Object(base_map)
+            }
+            (_, incoming) => incoming,
+        }
+    }
+}
+
+impl Projector for JsonMergeProjector {
+    fn aggregate_type(&self) -> &str {
+        &self.aggregate_type
+    }
+
+    fn apply(&self, state: Value, event: &Event) -> Value {
+        Self::merge(state, event.event_data.clone())
+    }
+}
+
+/// Where `EventStore` durably persists events and snapshots. Mirrors the
+/// `StorageBackend`/`InMemoryStorageBackend`/`StorageEngine` split in
+/// `storage::backend`: a pure-Rust implementation for tests and
+/// single-process use, and a native one backed by the same page manager and
+/// WAL the table storage layer uses. Kept synchronous like `StorageBackend`
+/// rather than `async fn` — every implementation either holds a plain
+/// `Mutex` or makes a blocking FFI call, neither of which benefits from
+/// `.await`.
+pub trait Backend: Send + Sync {
+    fn append(&self, event: &Event) -> Result<()>;
+    fn load_all(&self) -> Result<Vec<Event>>;
+    fn write_snapshot(&self, aggregate_id: &str, version: u64, state: &Value) -> Result<()>;
+    fn load_snapshot(&self, aggregate_id: &str) -> Result<Option<(u64, Value)>>;
+    /// Replaces the persisted event log with exactly `surviving`, discarding
+    /// everything else — the durable counterpart to `purge_old_events`
+    /// filtering a `Vec` in place.
+    fn compact(&self, surviving: &[Event]) -> Result<()>;
+}
+
+/// A `Backend` with no native dependency: events and snapshots held in
+/// memory behind a `Mutex`, same lifecycle tradeoff as
+/// `storage::memory::InMemoryStorageBackend` — nothing here survives a
+/// restart, which is exactly what makes it suitable for tests.
+#[derive(Default)]
+pub struct InMemoryBackend {
+    events: Mutex<Vec<Event>>,
+    snapshots: Mutex<HashMap<String, (u64, Value)>>,
+}
+
+impl InMemoryBackend {
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+impl Backend for InMemoryBackend {
+    fn append(&self, event: &Event) -> Result<()> {
+        self.events.lock().unwrap().push(event.clone());
+        Ok(())
+    }
+
+    fn load_all(&self) -> Result<Vec<Event>> {
+        Ok(self.events.lock().unwrap().clone())
+    }
+
+    fn write_snapshot(&self, aggregate_id: &str, version: u64, state: &Value) -> Result<()> {
+        self.snapshots
+            .lock()
+            .unwrap()
+            .insert(aggregate_id.to_string(), (version, state.clone()));
+        Ok(())
+    }
+
+    fn load_snapshot(&self, aggregate_id: &str) -> Result<Option<(u64, Value)>> {
+        Ok(self.snapshots.lock().unwrap().get(aggregate_id).cloned())
+    }
+
+    fn compact(&self, surviving: &[Event]) -> Result<()> {
+        *self.events.lock().unwrap() = surviving.to_vec();
+        Ok(())
+    }
+}
+
+/// A `Backend` that persists events and snapshots through the native event
+/// log (`ffi::event_log::EventLogEngine`), the event-sourcing counterpart to
+/// `ffi::storage::StorageEngine` for table storage: each event is a
+/// JSON-encoded record appended to a WAL segment, and `compact` rewrites
+/// that segment rather than editing it in place so a crash mid-compaction
+/// never leaves a torn log behind.
+pub struct WalBackend {
+    engine: EventLogEngine,
+}
+
+impl WalBackend {
+    pub fn open(data_dir: &str) -> Result<Self> {
+        Ok(Self {
+            engine: EventLogEngine::open(data_dir)?,
+        })
+    }
+}
+
+impl Backend for WalBackend {
+    fn append(&self, event: &Event) -> Result<()> {
+        self.engine.append(&serde_json::to_vec(event)?)
+    }
+
+    fn load_all(&self) -> Result<Vec<Event>> {
+        self.engine
+            .read_all()?
+            .iter()
+            .map(|record| Ok(serde_json::from_slice(record)?))
+            .collect()
+    }
+
+    fn write_snapshot(&self, aggregate_id: &str, version: u64, state: &Value) -> Result<()> {
+        let data = serde_json::to_vec(&(version, state))?;
+        self.engine.write_snapshot(aggregate_id, &data)
+    }
+
+    fn load_snapshot(&self, aggregate_id: &str) -> Result<Option<(u64, Value)>> {
+        match self.engine.read_snapshot(aggregate_id)? {
+            Some(data) => Ok(Some(serde_json::from_slice(&data)?)),
+            None => Ok(None),
+        }
+    }
+
+    fn compact(&self, surviving: &[Event]) -> Result<()> {
+        let records = surviving
+            .iter()
+            .map(serde_json::to_vec)
+            .collect::<std::result::Result<Vec<_>, _>>()?;
+        self.engine.compact(&records)
+    }
+}
+
+/// Buffered capacity of each aggregate type's broadcast channel. A
+/// subscriber that falls this far behind the live append rate sees its
+/// receiver return `Lagged` and has to catch up via a fresh `get_event_stream`
+/// read rather than the channel itself.
+const BROADCAST_CAPACITY: usize = 1024;
+
 pub struct EventStore {
-    events: Arc<RwLock<Vec<Event>>>,
+    backend: Arc<dyn Backend>,
     aggregates: Arc<RwLock<HashMap<String, Aggregate>>>,
-    snapshots: Arc<RwLock<HashMap<String, (u64, Value)>>>,
+    projectors: Arc<RwLock<HashMap<String, Box<dyn Projector>>>>,
+    broadcasters: Arc<RwLock<HashMap<String, broadcast::Sender<Event>>>>,
 }
 
 impl EventStore {
     pub fn new() -> Self {
+        Self::with_backend(Arc::new(InMemoryBackend::new()))
+    }
+
+    /// Opens a `WalBackend`-backed store rooted at `data_dir` and replays its
+    /// persisted events to rebuild `aggregates`' version numbers, so
+    /// `Precondition` checks against a freshly reopened store see the same
+    /// versions it had before the restart. Materialized `state` is left for
+    /// `rebuild_aggregate` to fold on demand, since projectors are
+    /// registered after construction and so aren't available yet here.
+    pub async fn open(data_dir: &str) -> Result<Self> {
+        let store = Self::with_backend(Arc::new(WalBackend::open(data_dir)?));
+        store.restore_versions().await?;
+        Ok(store)
+    }
+
+    fn with_backend(backend: Arc<dyn Backend>) -> Self {
         Self {
-            events: Arc::new(RwLock::new(Vec::new())),
+            backend,
             aggregates: Arc::new(RwLock::new(HashMap::new())),
-            snapshots: Arc::new(RwLock::new(HashMap::new())),
+            projectors: Arc::new(RwLock::new(HashMap::new())),
+            broadcasters: Arc::new(RwLock::new(HashMap::new())),
+        }
+    }
+
+    async fn restore_versions(&self) -> Result<()> {
+        let events = self.backend.load_all()?;
+        let mut aggregates = self.aggregates.write().await;
+
+        for event in events {
+            let aggregate = aggregates
+                .entry(event.aggregate_id.clone())
+                .or_insert_with(|| Aggregate {
+                    id: event.aggregate_id.clone(),
+                    aggregate_type: event.aggregate_type.clone(),
+                    version: 0,
+                    state: Value::Null,
+                });
+            aggregate.version = event.version;
         }
+
+        Ok(())
+    }
+
+    /// Registers `projector` to fold events whose `aggregate_type` matches
+    /// `projector.aggregate_type()`, replacing any projector previously
+    /// registered for that type.
+    pub async fn register_projector(&self, projector: Box<dyn Projector>) {
+        let mut projectors = self.projectors.write().await;
+        projectors.insert(projector.aggregate_type().to_string(), projector);
+    }
+
+    /// Subscribes to every event appended for `aggregate_type` from now on.
+    /// Lazily creates that type's broadcast channel on first subscription.
+    pub async fn subscribe(&self, aggregate_type: &str) -> broadcast::Receiver<Event> {
+        let mut broadcasters = self.broadcasters.write().await;
+        broadcasters
+            .entry(aggregate_type.to_string())
+            .or_insert_with(|| broadcast::channel(BROADCAST_CAPACITY).0)
+            .subscribe()
     }
 
-    pub async fn append_event(&self, event: Event) -> Result<()> {
+    pub async fn append_event(&self, mut event: Event, precondition: Precondition) -> Result<()> {
         let mut aggregates = self.aggregates.write().await;
 
+        let current_version = aggregates
+            .get(&event.aggregate_id)
+            .map(|a| a.version)
+            .unwrap_or(0);
+
+        match precondition {
+            Precondition::Always => {}
+            Precondition::New => {
+                if current_version > 0 {
+                    return Err(PreconditionFailed {
+                        expected: "no existing aggregate".to_string(),
+                        actual: current_version,
+                    }
+                    .into());
+                }
+            }
+            Precondition::Exists => {
+                if current_version == 0 {
+                    return Err(PreconditionFailed {
+                        expected: "an existing aggregate".to_string(),
+                        actual: current_version,
+                    }
+                    .into());
+                }
+            }
+            Precondition::ExpectedVersion(expected) => {
+                if current_version != expected {
+                    return Err(PreconditionFailed {
+                        expected: expected.to_string(),
+                        actual: current_version,
+                    }
+                    .into());
+                }
+            }
+        }
+
+        // The new version is derived here rather than trusted from the
+        // caller-supplied `event.version`: a `Precondition::ExpectedVersion`
+        // only validates `current_version`, it doesn't tie the appended
+        // event to `current_version + 1`, so a caller (or a retried/
+        // duplicated request) could otherwise desync the stored version from
+        // the actual number of appended events.
+        event.version = current_version + 1;
+
         let aggregate = aggregates
             .entry(event.aggregate_id.clone())
             .or_insert_with(|| Aggregate {
@@ -53,32 +366,54 @@ impl EventStore {
                 state: Value::Null,
             });
 
-        if event.version != aggregate.version + 1 {
-            anyhow::bail!(
-                "Version mismatch: expected {}, got {}",
-                aggregate.version + 1,
-                event.version
-            );
+        aggregate.version = event.version;
+
+        let projectors = self.projectors.read().await;
+        if let Some(projector) = projectors.get(&event.aggregate_type) {
+            let state = std::mem::take(&mut aggregate.state);
+            aggregate.state = projector.apply(state, &event);
         }
+        drop(projectors);
+        drop(aggregates);
 
-        aggregate.version = event.version;
+        self.backend.append(&event)?;
 
-        let mut events = self.events.write().await;
-        events.push(event);
+        let broadcasters = self.broadcasters.read().await;
+        if let Some(sender) = broadcasters.get(&event.aggregate_type) {
+            // No receivers is a normal, unsubscribed state, not an error.
+            let _ = sender.send(event);
+        }
 
         Ok(())
     }
 
-    pub async fn get_events(&self, aggregate_id: &str, from_version: Option<u64>) -> Vec<Event> {
-        let events = self.events.read().await;
+    /// Events for `aggregate_id` from `since` onward, oldest first, capped at
+    /// `max_count` entries (when given) so callers can page through a stream
+    /// deterministically.
+    pub async fn get_events(
+        &self,
+        aggregate_id: &str,
+        since: Since,
+        max_count: Option<usize>,
+    ) -> Vec<Event> {
+        let from_version = match since {
+            Since::BeginningOfStream => 0,
+            Since::Event(version) => version,
+        };
 
-        events
-            .iter()
-            .filter(|e| {
-                e.aggregate_id == aggregate_id && from_version.is_none_or(|v| e.version >= v)
-            })
-            .cloned()
-            .collect()
+        let events = self.backend.load_all().unwrap_or_else(|err| {
+            tracing::warn!("failed to load events from backend: {}", err);
+            Vec::new()
+        });
+
+        let matching = events
+            .into_iter()
+            .filter(|e| e.aggregate_id == aggregate_id && e.version >= from_version);
+
+        match max_count {
+            Some(max_count) => matching.take(max_count).collect(),
+            None => matching.collect(),
+        }
     }
 
     pub async fn get_aggregate_state(&self, aggregate_id: &str) -> Option<Aggregate> {
@@ -91,43 +426,57 @@ impl EventStore {
         version: u64,
         state: Value,
     ) -> Result<()> {
-        self.snapshots
-            .write()
-            .await
-            .insert(aggregate_id, (version, state));
-        Ok(())
+        self.backend.write_snapshot(&aggregate_id, version, &state)
     }
 
     pub async fn get_snapshot(&self, aggregate_id: &str) -> Option<(u64, Value)> {
-        self.snapshots.read().await.get(aggregate_id).cloned()
+        self.backend
+            .load_snapshot(aggregate_id)
+            .unwrap_or_else(|err| {
+                tracing::warn!(
+                    "failed to load snapshot for aggregate '{}': {}",
+                    aggregate_id,
+                    err
+                );
+                None
+            })
     }
 
     pub async fn rebuild_aggregate(&self, aggregate_id: &str) -> Result<Value> {
         if let Some((snapshot_version, snapshot_state)) = self.get_snapshot(aggregate_id).await {
             let events = self
-                .get_events(aggregate_id, Some(snapshot_version + 1))
+                .get_events(aggregate_id, Since::Event(snapshot_version + 1), None)
                 .await;
 
             let mut state = snapshot_state;
             for event in events {
-                state = self.apply_event(state, &event);
+                state = self.apply_event(state, &event).await;
             }
 
             Ok(state)
         } else {
-            let events = self.get_events(aggregate_id, None).await;
+            let events = self
+                .get_events(aggregate_id, Since::BeginningOfStream, None)
+                .await;
 
             let mut state = Value::Null;
             for event in events {
-                state = self.apply_event(state, &event);
+                state = self.apply_event(state, &event).await;
             }
 
             Ok(state)
         }
     }
 
-    fn apply_event(&self, state: Value, _event: &Event) -> Value {
-        state
+    /// Dispatches `event` through the projector registered for its
+    /// `aggregate_type`, or returns `state` unchanged when none is
+    /// registered.
+    async fn apply_event(&self, state: Value, event: &Event) -> Value {
+        let projectors = self.projectors.read().await;
+        match projectors.get(&event.aggregate_type) {
+            Some(projector) => projector.apply(state, event),
+            None => state,
+        }
     }
 
     pub async fn get_event_stream(
@@ -135,26 +484,30 @@ impl EventStore {
         aggregate_type: Option<String>,
         from_timestamp: Option<DateTime<Utc>>,
     ) -> Vec<Event> {
-        let events = self.events.read().await;
+        let events = self.backend.load_all().unwrap_or_else(|err| {
+            tracing::warn!("failed to load events from backend: {}", err);
+            Vec::new()
+        });
 
         events
-            .iter()
+            .into_iter()
             .filter(|e| {
                 aggregate_type
                     .as_ref()
                     .is_none_or(|t| &e.aggregate_type == t)
                     && from_timestamp.is_none_or(|ts| e.timestamp >= ts)
             })
-            .cloned()
             .collect()
     }
 
     pub async fn purge_old_events(&self, before: DateTime<Utc>) -> Result<usize> {
-        let mut events = self.events.write().await;
+        let events = self.backend.load_all()?;
         let original_len = events.len();
 
-        events.retain(|e| e.timestamp >= before);
+        let surviving: Vec<Event> = events.into_iter().filter(|e| e.timestamp >= before).collect();
+        let purged = original_len - surviving.len();
 
-        Ok(original_len - events.len())
+        self.backend.compact(&surviving)?;
+        Ok(purged)
     }
 }
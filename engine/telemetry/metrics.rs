@@ -1,11 +1,17 @@
+use crate::monitoring::metrics::Histogram;
+use anyhow::Result;
 use std::sync::atomic::{AtomicU64, Ordering};
-use tokio::time::{Duration, interval};
+use std::sync::Arc;
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::TcpListener;
+use tokio::time::{interval, Duration};
 
 pub struct MetricsRegistry {
     queries_executed: AtomicU64,
     statements_executed: AtomicU64,
     transactions_committed: AtomicU64,
     transactions_aborted: AtomicU64,
+    query_duration: Histogram,
 }
 
 impl MetricsRegistry {
@@ -15,6 +21,9 @@ impl MetricsRegistry {
             statements_executed: AtomicU64::new(0),
             transactions_committed: AtomicU64::new(0),
             transactions_aborted: AtomicU64::new(0),
+            query_duration: Histogram::new(vec![
+                0.001, 0.005, 0.01, 0.05, 0.1, 0.5, 1.0, 5.0, 10.0,
+            ]),
         }
     }
 
@@ -42,6 +51,12 @@ impl MetricsRegistry {
         self.statements_executed.load(Ordering::Relaxed)
     }
 
+    /// Records one query's end-to-end execution time, for the
+    /// `minsql_query_duration_seconds` histogram `render_prometheus` exposes.
+    pub fn record_query_duration(&self, seconds: f64) {
+        self.query_duration.observe(seconds);
+    }
+
     pub async fn report_loop(&self) {
         let mut ticker = interval(Duration::from_secs(60));
 
@@ -57,4 +72,96 @@ impl MetricsRegistry {
             );
         }
     }
+
+    /// Serializes every registered metric in Prometheus text exposition
+    /// format, for the admin HTTP endpoint `serve_admin_http` stands up.
+    pub fn render_prometheus(&self) -> String {
+        let mut out = String::new();
+
+        render_counter(
+            &mut out,
+            "minsql_queries_executed_total",
+            "Total queries executed",
+            self.queries_executed.load(Ordering::Relaxed),
+        );
+        render_counter(
+            &mut out,
+            "minsql_statements_executed_total",
+            "Total DDL/DML statements executed",
+            self.statements_executed.load(Ordering::Relaxed),
+        );
+        render_counter(
+            &mut out,
+            "minsql_transactions_committed_total",
+            "Total transactions committed",
+            self.transactions_committed.load(Ordering::Relaxed),
+        );
+        render_counter(
+            &mut out,
+            "minsql_transactions_aborted_total",
+            "Total transactions aborted",
+            self.transactions_aborted.load(Ordering::Relaxed),
+        );
+
+        self.query_duration.render(
+            "minsql_query_duration_seconds",
+            "Query execution latency",
+            &mut out,
+        );
+
+        out.push_str("# EOF\n");
+        out
+    }
+}
+
+fn render_counter(out: &mut String, name: &str, help: &str, value: u64) {
+    out.push_str(&format!(
+        "# HELP {name} {help}\n# TYPE {name} counter\n{name} {value}\n"
+    ));
+}
+
+/// Serves `GET /metrics` over a raw HTTP/1.1 response on `port`, separate
+/// from the `protocol::server::Server` TCP listener clients speak the wire
+/// protocol on, matching the admin-facing surface `monitoring::metrics`
+/// already exposes for the streaming subsystems.
+pub async fn serve_admin_http(port: u16, metrics: Arc<MetricsRegistry>) -> Result<()> {
+    let addr = format!("0.0.0.0:{}", port);
+    let listener = TcpListener::bind(&addr).await?;
+
+    tracing::info!("Admin metrics endpoint listening on {}", addr);
+
+    loop {
+        let (stream, _) = listener.accept().await?;
+        let metrics = metrics.clone();
+
+        tokio::spawn(async move {
+            if let Err(e) = handle_admin_request(stream, metrics).await {
+                tracing::warn!("Admin HTTP connection error: {}", e);
+            }
+        });
+    }
+}
+
+async fn handle_admin_request(
+    mut stream: tokio::net::TcpStream,
+    metrics: Arc<MetricsRegistry>,
+) -> Result<()> {
+    let mut buf = [0u8; 1024];
+    let n = stream.read(&mut buf).await?;
+    let request = String::from_utf8_lossy(&buf[..n]);
+    let path = request.split_whitespace().nth(1).unwrap_or("/");
+
+    let (status, content_type, body) = if path == "/metrics" {
+        ("200 OK", "text/plain; version=0.0.4", metrics.render_prometheus())
+    } else {
+        ("404 Not Found", "text/plain", "not found\n".to_string())
+    };
+
+    let response = format!(
+        "HTTP/1.1 {status}\r\nContent-Type: {content_type}\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{body}",
+        body.len()
+    );
+
+    stream.write_all(response.as_bytes()).await?;
+    Ok(())
 }
@@ -23,11 +23,33 @@ pub enum TransactionState {
     Aborted,
 }
 
+/// One write a transaction has made, in enough detail to undo it: the row's
+/// table and id, plus the bytes it held before this write (`None` if the
+/// write inserted the row, so undoing it deletes the row rather than
+/// restoring a prior version).
+#[derive(Debug, Clone)]
+pub struct WriteRecord {
+    pub table: String,
+    pub row_id: u64,
+    pub before: Option<Vec<u8>>,
+}
+
+/// A named mark in a transaction's `write_log`, created by `SAVEPOINT name`.
+/// `mark` is the log length at the moment the savepoint was taken, so
+/// rolling back to it means undoing everything recorded at or after that
+/// index.
+struct Savepoint {
+    name: String,
+    mark: usize,
+}
+
 pub struct Transaction {
     pub id: TransactionId,
     pub state: TransactionState,
     pub snapshot: Snapshot,
     pub logical_time: LogicalTime,
+    write_log: Vec<WriteRecord>,
+    savepoints: Vec<Savepoint>,
 }
 
 impl Transaction {
@@ -37,6 +59,8 @@ impl Transaction {
             state: TransactionState::Active,
             snapshot,
             logical_time,
+            write_log: Vec::new(),
+            savepoints: Vec::new(),
         }
     }
 
@@ -47,6 +71,54 @@ impl Transaction {
     pub fn abort(&mut self) {
         self.state = TransactionState::Aborted;
     }
+
+    /// Appends a write to the undo log, so a later `ROLLBACK TO SAVEPOINT`
+    /// taken before it can unwind it.
+    pub fn record_write(&mut self, table: String, row_id: u64, before: Option<Vec<u8>>) {
+        self.write_log.push(WriteRecord { table, row_id, before });
+    }
+
+    /// Marks the current position in the undo log under `name`. Re-using a
+    /// name destroys the earlier savepoint of the same name, matching
+    /// standard `SAVEPOINT` semantics.
+    pub fn create_savepoint(&mut self, name: String) {
+        self.savepoints.retain(|savepoint| savepoint.name != name);
+        self.savepoints.push(Savepoint {
+            name,
+            mark: self.write_log.len(),
+        });
+    }
+
+    /// Discards the named savepoint, and any savepoint established after it,
+    /// without undoing the writes they cover — those writes stay part of the
+    /// enclosing (sub)transaction.
+    pub fn release_savepoint(&mut self, name: &str) -> Result<()> {
+        let index = self.savepoint_index(name)?;
+        self.savepoints.truncate(index);
+        Ok(())
+    }
+
+    /// Undoes every write recorded since the named savepoint, returning them
+    /// in reverse (most-recent-first) order for the caller to apply against
+    /// storage. The savepoint itself stays open — `ROLLBACK TO` can target it
+    /// again — but any savepoint taken after it is discarded, since the
+    /// writes it marked no longer exist.
+    pub fn rollback_to_savepoint(&mut self, name: &str) -> Result<Vec<WriteRecord>> {
+        let index = self.savepoint_index(name)?;
+        let mark = self.savepoints[index].mark;
+        self.savepoints.truncate(index + 1);
+
+        let mut undone: Vec<WriteRecord> = self.write_log.drain(mark..).collect();
+        undone.reverse();
+        Ok(undone)
+    }
+
+    fn savepoint_index(&self, name: &str) -> Result<usize> {
+        self.savepoints
+            .iter()
+            .rposition(|savepoint| savepoint.name == name)
+            .ok_or_else(|| anyhow::anyhow!("Savepoint not found: {}", name))
+    }
 }
 
 pub struct TransactionManager {
@@ -103,4 +175,41 @@ impl TransactionManager {
             .map(|entry| entry.snapshot.clone())
             .ok_or_else(|| anyhow::anyhow!("Transaction not found"))
     }
+
+    pub fn record_write(&self, xid: TransactionId, table: String, row_id: u64, before: Option<Vec<u8>>) -> Result<()> {
+        let mut entry = self
+            .active_transactions
+            .get_mut(&xid)
+            .ok_or_else(|| anyhow::anyhow!("Transaction not found"))?;
+        entry.record_write(table, row_id, before);
+        Ok(())
+    }
+
+    pub fn create_savepoint(&self, xid: TransactionId, name: String) -> Result<()> {
+        let mut entry = self
+            .active_transactions
+            .get_mut(&xid)
+            .ok_or_else(|| anyhow::anyhow!("Transaction not found"))?;
+        entry.create_savepoint(name);
+        Ok(())
+    }
+
+    pub fn release_savepoint(&self, xid: TransactionId, name: &str) -> Result<()> {
+        let mut entry = self
+            .active_transactions
+            .get_mut(&xid)
+            .ok_or_else(|| anyhow::anyhow!("Transaction not found"))?;
+        entry.release_savepoint(name)
+    }
+
+    /// Unwinds the transaction to the named savepoint, returning the writes
+    /// that were undone (most-recent-first) so the caller can reverse them
+    /// against storage. The transaction itself stays active.
+    pub fn rollback_to_savepoint(&self, xid: TransactionId, name: &str) -> Result<Vec<WriteRecord>> {
+        let mut entry = self
+            .active_transactions
+            .get_mut(&xid)
+            .ok_or_else(|| anyhow::anyhow!("Transaction not found"))?;
+        entry.rollback_to_savepoint(name)
+    }
 }
@@ -49,4 +49,24 @@ impl Snapshot {
 
         true
     }
+
+    /// Time-based counterpart to `is_visible`, used for historical (`AT
+    /// TIMESTAMP`) snapshots rather than live MVCC reads: a row version is
+    /// visible here if it committed at or before this snapshot's
+    /// `logical_time` and, if it was ever deleted, the deletion happened
+    /// strictly after that time.
+    pub fn is_visible_at(&self, commit_time: LogicalTime, delete_time: Option<LogicalTime>) -> bool {
+        if Self::happens_after(commit_time, self.logical_time) {
+            return false;
+        }
+
+        match delete_time {
+            Some(delete_time) if !Self::happens_after(delete_time, self.logical_time) => false,
+            _ => true,
+        }
+    }
+
+    fn happens_after(a: LogicalTime, b: LogicalTime) -> bool {
+        (a.physical, a.logical) > (b.physical, b.logical)
+    }
 }
@@ -1,4 +1,5 @@
 use crate::determinism::clock::LogicalTime;
+use crate::language::intent::TimeTravelIntent;
 use crate::transactions::manager::TransactionId;
 use crate::transactions::snapshot::Snapshot;
 use anyhow::Result;
@@ -6,6 +7,32 @@ use chrono::{DateTime, Utc};
 
 pub struct TimeTravelManager;
 
+/// A resolved `AT TIMESTAMP ... [UNTIL TIMESTAMP ...]` bound for a scan.
+/// `snapshot` governs the upper edge of visibility via
+/// `Snapshot::is_visible_at`; `since`, populated only when the query gave an
+/// `UNTIL TIMESTAMP`, additionally excludes rows that had already committed
+/// before the `AT TIMESTAMP` bound, so `AT T1 UNTIL T2` reads the row
+/// versions committed between T1 and T2 (both inclusive) that were not yet
+/// deleted at T2; a version committed before T1, or deleted by T2, is not
+/// read.
+pub struct TimeTravelWindow {
+    pub snapshot: Snapshot,
+    pub since: Option<LogicalTime>,
+}
+
+impl TimeTravelWindow {
+    pub fn includes(&self, commit_time: LogicalTime, delete_time: Option<LogicalTime>) -> bool {
+        if !self.snapshot.is_visible_at(commit_time, delete_time) {
+            return false;
+        }
+
+        match self.since {
+            Some(since) => (commit_time.physical, commit_time.logical) >= (since.physical, since.logical),
+            None => true,
+        }
+    }
+}
+
 impl TimeTravelManager {
     pub fn new() -> Self {
         Self
@@ -31,4 +58,33 @@ impl TimeTravelManager {
             Vec::new(),
         )
     }
+
+    /// Resolves a parsed `TimeTravelIntent` (still holding the raw
+    /// `AT TIMESTAMP`/`UNTIL TIMESTAMP` strings from the query) into a
+    /// `TimeTravelWindow` a scan can filter row versions against.
+    pub fn resolve_window(&self, intent: &TimeTravelIntent) -> Result<TimeTravelWindow> {
+        let at_time = Self::parse_timestamp(&intent.at_time)?;
+
+        match &intent.until_time {
+            Some(until) => {
+                let until_time = Self::parse_timestamp(until)?;
+                let snapshot = self.create_historical_snapshot(until_time)?;
+                let since = LogicalTime {
+                    logical: 0,
+                    physical: at_time.timestamp_micros() as u64,
+                };
+                Ok(TimeTravelWindow { snapshot, since: Some(since) })
+            }
+            None => {
+                let snapshot = self.create_historical_snapshot(at_time)?;
+                Ok(TimeTravelWindow { snapshot, since: None })
+            }
+        }
+    }
+
+    fn parse_timestamp(value: &str) -> Result<DateTime<Utc>> {
+        DateTime::parse_from_rfc3339(value)
+            .map(|dt| dt.with_timezone(&Utc))
+            .map_err(|e| anyhow::anyhow!("Invalid time-travel timestamp '{}': {}", value, e))
+    }
 }